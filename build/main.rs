@@ -1,9 +1,9 @@
 use gl_generator::{Api, Fallbacks, Profile, Registry};
 use std::env;
 use std::fs::File;
-use std::io::Write;
 use std::path::Path;
 
+mod stubs;
 mod textures;
 
 fn main() {
@@ -12,15 +12,25 @@ fn main() {
 
     textures::build_texture_file(&mut File::create(&dest.join("textures.rs")).unwrap());
     println!("cargo:rerun-if-changed=build/main.rs");
+    println!("cargo:rerun-if-changed=build/stubs.rs");
 
-    let mut file_output = File::create(&dest.join("gl_bindings.rs")).unwrap();
-    generate_gl_bindings(&mut file_output);
+    let registry = gl_registry();
+
+    let mut file_output = File::create(dest.join("gl_bindings.rs")).unwrap();
+    registry.write_bindings(gl_generator::StructGenerator, &mut file_output).unwrap();
+
+    if env::var_os("CARGO_FEATURE_RECORDING").is_some() {
+        let mut file_output = File::create(dest.join("recording_stubs.rs")).unwrap();
+        stubs::build_recording_stubs(&registry, &mut file_output).unwrap();
+    }
+
+    if env::var_os("CARGO_FEATURE_TRACE").is_some() {
+        let mut file_output = File::create(dest.join("tracing_stubs.rs")).unwrap();
+        stubs::build_tracing_stubs(&registry, &mut file_output).unwrap();
+    }
 }
 
-fn generate_gl_bindings<W>(dest: &mut W)
-where
-    W: Write,
-{
+fn gl_registry() -> Registry {
     let gl_registry = Registry::new(
         Api::Gl,
         (4, 6),
@@ -50,11 +60,13 @@ where
             "GL_ARB_invalidate_subdata",
             "GL_ARB_multi_draw_indirect",
             "GL_ARB_occlusion_query",
+            "GL_ARB_parallel_shader_compile",
             "GL_ARB_pixel_buffer_object",
             "GL_ARB_robustness",
             "GL_ARB_seamless_cube_map",
             "GL_ARB_shader_image_load_store",
             "GL_ARB_shader_objects",
+            "GL_ARB_shading_language_include",
             "GL_ARB_texture_buffer_object",
             "GL_ARB_texture_float",
             "GL_ARB_texture_multisample",
@@ -87,6 +99,7 @@ where
             "GL_EXT_texture_sRGB",
             "GL_EXT_transform_feedback",
             "GL_GREMEDY_string_marker",
+            "GL_KHR_parallel_shader_compile",
             "GL_KHR_robustness",
             "GL_NVX_gpu_memory_info",
             "GL_NV_conditional_render",
@@ -112,6 +125,7 @@ where
             "GL_EXT_primitive_bounding_box",
             "GL_EXT_robustness",
             "GL_KHR_debug",
+            "GL_KHR_parallel_shader_compile",
             "GL_NV_copy_buffer",
             "GL_NV_framebuffer_multisample",
             "GL_NV_internalformat_sample_query",
@@ -128,7 +142,5 @@ where
         ],
     );
 
-    (gl_registry + gles_registry)
-        .write_bindings(gl_generator::StructGenerator, dest)
-        .unwrap();
+    gl_registry + gles_registry
 }
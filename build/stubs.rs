@@ -0,0 +1,97 @@
+use gl_generator::{Cmd, Registry};
+use std::io;
+use std::io::Write;
+
+/// Writes one stub entry point per OpenGL command, plus a `get_proc_address` function that
+/// returns them by symbol name.
+///
+/// Each stub forwards its name and arguments to `super::dispatch` and converts the reply to its
+/// return type. The content of the file is meant to be included in `backend::recording`.
+pub fn build_recording_stubs<W: Write>(registry: &Registry, dest: &mut W) -> io::Result<()> {
+    writeln!(dest, "mod __gl_imports {{ pub use std::os::raw; }}")?;
+    writeln!(dest, "use crate::gl::types;")?;
+    writeln!(dest, "use super::{{IntoArg, FromReply}};")?;
+    write_get_proc_address(registry, dest)?;
+
+    for cmd in &registry.cmds {
+        let symbol = format!("gl{}", cmd.proto.ident);
+        writeln!(dest, "#[allow(non_snake_case, clippy::unused_unit)]")?;
+        writeln!(dest, "extern \"system\" fn {}({}) -> {} {{", symbol, params(cmd), cmd.proto.ty)?;
+        writeln!(dest, "    FromReply::from_reply(super::dispatch(\"{}\", &[{}]))", symbol, args(cmd))?;
+        writeln!(dest, "}}")?;
+    }
+
+    Ok(())
+}
+
+/// Writes one stub entry point per OpenGL command, a `get_proc_address` function that returns
+/// them by symbol name, and an `invoke` function that calls a command by name.
+///
+/// Each stub passes its name and arguments to `super::trace` alongside a closure that calls the
+/// real command. The content of the file is meant to be included in `trace`.
+pub fn build_tracing_stubs<W: Write>(registry: &Registry, dest: &mut W) -> io::Result<()> {
+    writeln!(dest, "mod __gl_imports {{ pub use std::os::raw; }}")?;
+    writeln!(dest, "use crate::gl::types;")?;
+    writeln!(dest, "use crate::backend::recording::{{Arg, IntoArg}};")?;
+    writeln!(dest, "use super::{{FromValue, IntoReturn, Value}};")?;
+    write_get_proc_address(registry, dest)?;
+
+    for cmd in &registry.cmds {
+        let symbol = format!("gl{}", cmd.proto.ident);
+        let idents = cmd.params.iter().map(|p| p.ident.clone()).collect::<Vec<_>>().join(", ");
+        writeln!(dest, "#[allow(non_snake_case, clippy::unused_unit)]")?;
+        writeln!(dest, "extern \"system\" fn {}({}) -> {} {{", symbol, params(cmd), cmd.proto.ty)?;
+        writeln!(dest, "    super::trace(\"{}\", &[{}], |__gl| unsafe {{ __gl.{}({}) }})",
+                 symbol, args(cmd), cmd.proto.ident, idents)?;
+        writeln!(dest, "}}")?;
+    }
+
+    writeln!(dest, "#[allow(clippy::unit_arg)]")?;
+    writeln!(dest, "pub unsafe fn invoke(gl: &crate::gl::Gl, symbol: &str, args: &mut [Value]) \
+                    -> Option<Option<Arg>> {{")?;
+    writeln!(dest, "    match symbol {{")?;
+    for cmd in &registry.cmds {
+        let values = (0 .. cmd.params.len())
+            .map(|num| format!("FromValue::from_value(&mut args[{}])", num))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(dest, "        \"gl{}\" if args.len() == {} => \
+                        Some(IntoReturn::into_return(gl.{}({}))),",
+                 cmd.proto.ident, cmd.params.len(), cmd.proto.ident, values)?;
+    }
+    writeln!(dest, "        _ => None,")?;
+    writeln!(dest, "    }}")?;
+    writeln!(dest, "}}")?;
+
+    Ok(())
+}
+
+fn write_get_proc_address<W: Write>(registry: &Registry, dest: &mut W) -> io::Result<()> {
+    writeln!(dest, "pub fn get_proc_address(symbol: &str) -> *const __gl_imports::raw::c_void {{")?;
+    writeln!(dest, "    match symbol {{")?;
+    for cmd in &registry.cmds {
+        let symbol = format!("gl{}", cmd.proto.ident);
+        writeln!(dest, "        \"{0}\" => {0} as *const __gl_imports::raw::c_void,", symbol)?;
+    }
+    writeln!(dest, "        _ => std::ptr::null(),")?;
+    writeln!(dest, "    }}")?;
+    writeln!(dest, "}}")
+}
+
+/// Returns the parameters of a command with their types, for example `mode: types::GLenum`.
+fn params(cmd: &Cmd) -> String {
+    cmd.params
+        .iter()
+        .map(|param| format!("{}: {}", param.ident, param.ty))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the parameters of a command converted to `Arg`s.
+fn args(cmd: &Cmd) -> String {
+    cmd.params
+        .iter()
+        .map(|param| format!("IntoArg::into_arg({})", param.ident))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -309,6 +309,7 @@ fn build_texture<W: Write>(dest: &mut W, ty: TextureType, dimensions: TextureDim
             use crate::texture::any::{{self, TextureAny, TextureAnyLayer, TextureAnyMipmap}};
             use crate::texture::any::{{TextureAnyLayerMipmap, TextureAnyImage, Dimensions}};
             use crate::texture::bindless::{{ResidentTexture, BindlessTexturesNotSupportedError}};
+            use crate::transfer::Transfer;
             use crate::texture::get_format::{{InternalFormat, InternalFormatType, GetFormatError}};
             use crate::texture::pixel_buffer::PixelBuffer;
             use crate::texture::{{TextureCreationError, Texture1dDataSource, Texture2dDataSource}};
@@ -1066,6 +1067,16 @@ fn build_texture<W: Write>(dest: &mut W, ty: TextureType, dimensions: TextureDim
             }}
         "#)).unwrap();
 
+    // `into_transfer`
+    (write!(dest, r#"
+            /// Removes the texture from its context, so that it can be sent to another thread and
+            /// adopted by another context of the same share group. See the `transfer` module.
+            #[inline]
+            pub fn into_transfer(self) -> Transfer<{name}> {{
+                self.0.into_transfer().map({name})
+            }}
+        "#, name = name)).unwrap();
+
     // writing the layer & mipmap access functions
     if dimensions.is_array() {
         (write!(dest, r#"
@@ -46,4 +46,10 @@ fn main() {
 
     println!("{} context renderer: {}", api, display.get_opengl_renderer_string());
     println!("{} context vendor: {}", api, display.get_opengl_vendor_string());
+
+    let report = display.capabilities_report();
+    println!("{} supported extensions: {}", api, report.supported_extensions().count());
+    for (name, values) in &report.limits {
+        println!("{} {}: {:?}", api, name, values);
+    }
 }
@@ -34,7 +34,7 @@ fn main() {
                                                &[0u8, 1, 2]).unwrap();
 
     // loading SPIR-V module that contains fragment and vertex shader entry points both called "main"
-    let spirv = SpirvEntryPoint { binary: include_bytes!("shader.spv"), entry_point: "main" };
+    let spirv = SpirvEntryPoint::new(include_bytes!("shader.spv"), "main");
     let program = glium::Program::new(
         &display,
         ProgramCreationInput::SpirV(SpirvProgram::from_vs_and_fs(spirv, spirv))
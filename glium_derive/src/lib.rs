@@ -0,0 +1,291 @@
+/*!
+Derive macros for the `Vertex`, `Uniforms` and `UniformBlock` traits of glium.
+
+Enable the `derive` feature of glium instead of depending on this crate directly. The macros are
+then available as `glium::Vertex`, `glium::uniforms::Uniforms` and
+`glium::uniforms::UniformBlock`, next to the traits that they implement.
+
+The behavior of the macros can be customized with `#[glium(...)]` attributes on the struct and
+on its fields. Unknown options and invalid combinations are reported at compile time, with an
+error that points to the attribute.
+
+*/
+#![warn(missing_docs)]
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, Error};
+
+mod options;
+mod uniform_block;
+mod uniforms;
+mod vertex;
+
+/// Implements the `glium::vertex::Vertex` trait for a struct.
+///
+/// Each field of the struct is a vertex attribute, whose type must implement
+/// `glium::vertex::Attribute`. The struct must also implement `Copy`.
+///
+/// ## Example
+///
+/// ```ignore
+/// #[derive(Copy, Clone, glium::Vertex)]
+/// #[glium(divisor = 2)]
+/// struct Instance {
+///     #[glium(name = "i_position")]
+///     position: [f32; 3],
+///     #[glium(normalize, location = 3)]
+///     color: [u8; 4],
+///     #[glium(skip)]
+///     _padding: u32,
+/// }
+/// ```
+///
+/// ## Options of the struct
+///
+/// - `divisor = N`: when a buffer of this type is drawn with `per_instance`, each element is
+///   used for `N` consecutive instances instead of one.
+///
+/// ## Options of the fields
+///
+/// - `name = "..."`: the name of the attribute in the shader. The default is the name of the
+///   field. The fields of tuple structs must have a name.
+/// - `normalize`: integer values are normalized to the `[0, 1]` or `[-1, 1]` range.
+/// - `location = N`: the location of the attribute, when the shader declares it with
+///   `layout(location = N)`.
+/// - `flatten`: the type of the field implements `Vertex` as well, and its attributes are added
+///   to the ones of the struct.
+/// - `skip`: the field isn't an attribute.
+///
+/// Two attributes can't have the same name or the same location.
+#[proc_macro_derive(Vertex, attributes(glium))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex::expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Implements the `glium::uniforms::Uniforms` trait for a struct.
+///
+/// Each field of the struct is a uniform, whose type must implement
+/// `glium::uniforms::AsUniformValue`. The struct can then be passed to `Surface::draw` instead of
+/// the result of the `uniform!` macro.
+///
+/// ## Example
+///
+/// ```ignore
+/// #[derive(glium::uniforms::Uniforms)]
+/// struct Material<'a> {
+///     #[glium(name = "u_diffuse")]
+///     diffuse: &'a glium::Texture2d,
+///     shininess: f32,
+///     #[glium(flatten)]
+///     lights: Lights,
+/// }
+/// ```
+///
+/// ## Options of the fields
+///
+/// - `name = "..."`: the name of the uniform in the shader. The default is the name of the
+///   field. The fields of tuple structs must have a name.
+/// - `flatten`: the type of the field implements `Uniforms` as well, and its uniforms are added
+///   to the ones of the struct.
+/// - `skip`: the field isn't a uniform.
+///
+/// Two uniforms can't have the same name.
+#[proc_macro_derive(Uniforms, attributes(glium))]
+pub fn derive_uniforms(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    uniforms::expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Implements the `glium::uniforms::UniformBlock` trait for a struct.
+///
+/// Each field of the struct is a member of the block, whose type must implement
+/// `glium::uniforms::UniformBlock`. This includes other structs that derive `UniformBlock`,
+/// which correspond to nested structs in the shader.
+///
+/// The layout of the struct is compared with the one of the block when the buffer is bound,
+/// like with the `implement_uniform_block!` macro.
+///
+/// ## Example
+///
+/// ```ignore
+/// #[derive(Copy, Clone, glium::uniforms::UniformBlock)]
+/// #[repr(C)]
+/// #[glium(std140)]
+/// struct Light {
+///     position: [f32; 3],
+///     intensity: f32,
+///     color: [f32; 3],
+///     #[glium(skip)]
+///     _padding: f32,
+/// }
+/// ```
+///
+/// ## Options of the struct
+///
+/// - `std140`: the fields are checked at compile time against the rules of the `std140` layout.
+///   A field that isn't correctly aligned, for example a `[f32; 4]` that follows a single
+///   `f32`, is a compilation error. So is a field whose size differs from its `std140` size,
+///   for example a `[[f32; 3]; 3]` for a `mat3`, whose columns are 16 bytes apart, or a
+///   `[f32; 8]` for a `float[8]`, whose elements are 16 bytes apart. Use `#[repr(C)]` so that
+///   the fields are in the order of their declaration.
+///
+///   The check is opt-in, as blocks may use other layouts such as `std430`, and it can't be
+///   used on structs with generic parameters. Other structs are only compared with the layout
+///   of the block when the buffer is bound. The check uses `offset_of!` in a constant, which
+///   requires Rust 1.77 or later, while the other options work with older versions.
+///
+/// ## Options of the fields
+///
+/// - `name = "..."`: the name of the member in the shader. The default is the name of the
+///   field. The fields of tuple structs must have a name.
+/// - `skip`: the field isn't a member of the block, which is useful for padding.
+///
+/// Two members can't have the same name.
+#[proc_macro_derive(UniformBlock, attributes(glium))]
+pub fn derive_uniform_block(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    uniform_block::expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, DeriveInput};
+
+    use crate::{uniform_block, uniforms, vertex};
+
+    fn error(result: syn::Result<proc_macro2::TokenStream>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn vertex_bindings() {
+        let input: DeriveInput = parse_quote! {
+            #[glium(divisor = 3)]
+            struct Foo<T> {
+                #[glium(name = "a_position", location = 2)]
+                position: [f32; 2],
+                #[glium(normalize)]
+                color: [u8; 4],
+                #[glium(flatten)]
+                extra: T,
+                #[glium(skip)]
+                padding: u32,
+            }
+        };
+
+        let output = vertex::expand(&input).unwrap().to_string();
+        assert!(output.contains("\"a_position\""));
+        assert!(output.contains("T : :: glium :: vertex :: Vertex"));
+        assert!(output.contains("fn instance_divisor () -> u32 { 3 }"));
+        assert!(!output.contains("padding"));
+    }
+
+    #[test]
+    fn vertex_errors() {
+        let input: DeriveInput = parse_quote! {
+            struct Foo {
+                a: f32,
+                #[glium(name = "a")]
+                b: f32,
+            }
+        };
+        assert_eq!(error(vertex::expand(&input)), "the name `a` is used by several fields");
+
+        let input: DeriveInput = parse_quote! {
+            struct Foo {
+                #[glium(location = 1)]
+                a: f32,
+                #[glium(location = 1)]
+                b: f32,
+            }
+        };
+        assert_eq!(error(vertex::expand(&input)), "the location 1 is used by several fields");
+
+        let input: DeriveInput = parse_quote! {
+            #[glium(divisor = 0)]
+            struct Foo {
+                a: f32,
+            }
+        };
+        assert_eq!(error(vertex::expand(&input)), "the divisor can't be 0");
+
+        let input: DeriveInput = parse_quote! {
+            struct Foo(f32);
+        };
+        assert!(error(vertex::expand(&input)).contains("must be named"));
+
+        let input: DeriveInput = parse_quote! {
+            enum Foo { A }
+        };
+        assert_eq!(error(vertex::expand(&input)), "`#[derive(Vertex)]` can only be used on structs");
+    }
+
+    #[test]
+    fn unknown_options() {
+        let input: DeriveInput = parse_quote! {
+            struct Foo {
+                #[glium(normalize)]
+                a: f32,
+            }
+        };
+        assert_eq!(error(uniforms::expand(&input)),
+                   "unknown option for a field of `#[derive(Uniforms)]`, expected one of: \
+                    `name`, `skip`, `flatten`");
+
+        let input: DeriveInput = parse_quote! {
+            #[glium(std140)]
+            struct Foo {
+                a: f32,
+            }
+        };
+        assert_eq!(error(uniforms::expand(&input)),
+                   "unknown option for the struct of `#[derive(Uniforms)]`, it doesn't have any \
+                    option");
+
+        let input: DeriveInput = parse_quote! {
+            struct Foo {
+                #[glium(skip, name = "b")]
+                a: f32,
+            }
+        };
+        assert_eq!(error(uniforms::expand(&input)), "`skip` can't be combined with other options");
+    }
+
+    #[test]
+    fn uniform_block_std140() {
+        let input: DeriveInput = parse_quote! {
+            #[glium(std140)]
+            struct Foo {
+                a: f32,
+                #[glium(name = "c")]
+                b: [f32; 4],
+            }
+        };
+        let output = uniform_block::expand(&input).unwrap().to_string();
+        assert!(output.contains("const _ : () ="));
+        assert!(output.contains("the offset of the field `c` of `Foo`"));
+        assert!(output.contains("the size of the field `c` of `Foo`"));
+        assert!(output.contains("const STD140_SIZE"));
+
+        let input: DeriveInput = parse_quote! {
+            #[glium(std140)]
+            struct Foo {
+                a: f32,
+                b: [f32],
+            }
+        };
+        let output = uniform_block::expand(&input).unwrap().to_string();
+        assert!(output.contains("size_of :: < f32 > ()"));
+        assert!(!output.contains("size_of :: < [f32] > ()"));
+
+        let input: DeriveInput = parse_quote! {
+            #[glium(std140)]
+            struct Foo<T> {
+                a: T,
+            }
+        };
+        assert_eq!(error(uniform_block::expand(&input)),
+                   "`#[glium(std140)]` can't be used on structs with parameters");
+    }
+}
@@ -0,0 +1,218 @@
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Attribute, Data, DeriveInput, Error, Fields, Index, LitInt, LitStr, Member, Result};
+use syn::{Generics, Type, WherePredicate};
+
+/// Options of the struct itself, set with `#[glium(...)]` on the struct.
+#[derive(Default)]
+pub struct StructOptions {
+    /// `divisor = N`, only for `Vertex`.
+    pub divisor: Option<u32>,
+    /// `std140`, only for `UniformBlock`.
+    pub std140: bool,
+}
+
+/// Options of a field, set with `#[glium(...)]` on the field.
+#[derive(Default)]
+pub struct FieldOptions {
+    /// `name = "..."`, the name of the field in the shader.
+    pub name: Option<LitStr>,
+    /// `normalize`, only for `Vertex`.
+    pub normalize: bool,
+    /// `location = N`, only for `Vertex`.
+    pub location: Option<LitInt>,
+    /// `skip`, the field is ignored.
+    pub skip: bool,
+    /// `flatten`, the field is replaced with its own fields.
+    pub flatten: bool,
+}
+
+/// A field of the struct that the trait is derived for.
+pub struct Field<'a> {
+    pub member: Member,
+    pub ty: &'a Type,
+    pub options: FieldOptions,
+    span: Span,
+}
+
+impl<'a> Field<'a> {
+    /// Returns the name of the field in the shader, which is either the one given with
+    /// `#[glium(name = "...")]` or the name of the field in the struct.
+    pub fn name(&self) -> Result<LitStr> {
+        if let Some(ref name) = self.options.name {
+            return Ok(name.clone());
+        }
+
+        match self.member {
+            Member::Named(ref ident) => Ok(LitStr::new(&ident.unraw().to_string(), ident.span())),
+            Member::Unnamed(_) => Err(Error::new(self.span, "the fields of tuple structs must be \
+                                                              named with `#[glium(name = \"...\")]`")),
+        }
+    }
+
+    /// Adds a `where` predicate that requires the type of the field to implement a trait, if
+    /// the type depends on a type parameter of the struct.
+    ///
+    /// The other types are checked where the trait is used, because a predicate on a concrete
+    /// type that doesn't implement the trait would be reported on the derive instead of the field.
+    pub fn add_bound(&self, generics: &mut Generics, bound: proc_macro2::TokenStream) {
+        let parameters = generics.type_params().map(|param| param.ident.clone())
+                                 .collect::<Vec<_>>();
+
+        if !uses_parameters(self.ty.to_token_stream(), &parameters) {
+            return;
+        }
+
+        let ty = self.ty;
+        let predicate: WherePredicate = syn::parse_quote_spanned!(ty.span()=> #ty: #bound);
+        generics.make_where_clause().predicates.push(predicate);
+    }
+}
+
+/// Parses the `#[glium(...)]` attributes of the struct.
+pub fn struct_options(input: &DeriveInput, allowed: &[&str], derive: &str)
+                      -> Result<StructOptions>
+{
+    let mut options = StructOptions::default();
+
+    for attr in glium_attributes(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            match &check_allowed(&meta, allowed, "the struct", derive)?[..] {
+                "divisor" => {
+                    let divisor: LitInt = meta.value()?.parse()?;
+                    match divisor.base10_parse()? {
+                        0 => return Err(Error::new(divisor.span(), "the divisor can't be 0")),
+                        divisor => options.divisor = Some(divisor),
+                    }
+                },
+                "std140" => options.std140 = true,
+                _ => unreachable!(),
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+/// Returns the fields of the struct with their options. Returns an error if the input isn't a
+/// struct or if an option is invalid.
+pub fn fields<'a>(input: &'a DeriveInput, allowed: &[&str], derive: &str)
+                  -> Result<Vec<Field<'a>>>
+{
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => return Err(Error::new(input.ident.span(),
+                                   format!("`#[derive({})]` can only be used on structs", derive))),
+    };
+
+    let fields = match *fields {
+        Fields::Named(ref fields) => fields.named.iter().collect(),
+        Fields::Unnamed(ref fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    fields.into_iter().enumerate().map(|(index, field)| {
+        let member = match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index { index: index as u32, span: field.ty.span() }),
+        };
+
+        Ok(Field {
+            member,
+            ty: &field.ty,
+            options: field_options(&field.attrs, allowed, derive)?,
+            span: field.span(),
+        })
+    }).collect()
+}
+
+/// Returns an error if two fields have the same name in the shader.
+pub fn check_unique_names(fields: &[Field<'_>]) -> Result<()> {
+    let mut names: Vec<String> = Vec::new();
+
+    for field in fields.iter().filter(|field| !field.options.flatten) {
+        let name = field.name()?;
+
+        if names.contains(&name.value()) {
+            return Err(Error::new(name.span(), format!("the name `{}` is used by several fields",
+                                                       name.value())));
+        }
+
+        names.push(name.value());
+    }
+
+    Ok(())
+}
+
+fn field_options(attrs: &[Attribute], allowed: &[&str], derive: &str) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attr in glium_attributes(attrs) {
+        attr.parse_nested_meta(|meta| {
+            match &check_allowed(&meta, allowed, "a field", derive)?[..] {
+                "name" => options.name = Some(meta.value()?.parse()?),
+                "normalize" => options.normalize = true,
+                "location" => {
+                    let location: LitInt = meta.value()?.parse()?;
+                    location.base10_parse::<u16>()?;
+                    options.location = Some(location);
+                },
+                "skip" => options.skip = true,
+                "flatten" => options.flatten = true,
+                _ => unreachable!(),
+            }
+
+            Ok(())
+        })?;
+
+        let others = options.name.is_some() || options.normalize || options.location.is_some();
+
+        if options.skip && (others || options.flatten) {
+            return Err(Error::new(attr.span(), "`skip` can't be combined with other options"));
+        }
+
+        if options.flatten && others {
+            return Err(Error::new(attr.span(), "`flatten` can't be combined with other options"));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Returns true if some tokens contain one of the identifiers.
+fn uses_parameters(tokens: TokenStream, parameters: &[Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ref ident) => parameters.contains(ident),
+        TokenTree::Group(ref group) => uses_parameters(group.stream(), parameters),
+        _ => false,
+    })
+}
+
+fn glium_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("glium"))
+}
+
+/// Returns the name of the option, or an error if it isn't in `allowed`.
+fn check_allowed(meta: &syn::meta::ParseNestedMeta<'_>, allowed: &[&str], target: &str,
+                 derive: &str) -> Result<String>
+{
+    let option = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+
+    if allowed.contains(&&option[..]) {
+        return Ok(option);
+    }
+
+    let expected = if allowed.is_empty() {
+        "it doesn't have any option".to_owned()
+    } else {
+        format!("expected one of: {}", allowed.iter().map(|option| format!("`{}`", option))
+                                              .collect::<Vec<_>>().join(", "))
+    };
+
+    Err(meta.error(format!("unknown option for {} of `#[derive({})]`, {}", target, derive,
+                           expected)))
+}
@@ -0,0 +1,175 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Error, Result, Type};
+
+use crate::options;
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let struct_options = options::struct_options(input, &["std140"], "UniformBlock")?;
+    let fields = options::fields(input, &["name", "skip"], "UniformBlock")?;
+    let fields = fields.into_iter().filter(|field| !field.options.skip).collect::<Vec<_>>();
+
+    options::check_unique_names(&fields)?;
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+
+    let mut names = Vec::with_capacity(fields.len());
+    let mut checks = Vec::with_capacity(fields.len());
+    let mut members = Vec::with_capacity(fields.len());
+    let mut alignments = Vec::with_capacity(fields.len());
+    let mut sizes = Vec::with_capacity(fields.len());
+    let mut std140_checks = Vec::new();
+
+    for field in &fields {
+        field.add_bound(&mut generics, quote!(::glium::uniforms::UniformBlock));
+
+        let name = field.name()?;
+        let member = &field.member;
+        let ty = field.ty;
+
+        checks.push(quote_spanned! { ty.span()=>
+            let member = members.iter().find(|&&(ref name, _)| name == #name);
+            let member = match member {
+                ::std::option::Option::Some(&(_, ref member)) => member,
+                ::std::option::Option::None => return ::std::result::Result::Err(LayoutMismatchError::MissingField {
+                    name: #name.to_owned(),
+                }),
+            };
+
+            let offset = base_offset + ::glium::__glium_offset_of!(Self, #member);
+            if let ::std::result::Result::Err(err) =
+                <#ty as ::glium::uniforms::UniformBlock>::matches(member, offset)
+            {
+                return ::std::result::Result::Err(LayoutMismatchError::MemberMismatch {
+                    member: #name.to_owned(),
+                    err: ::std::boxed::Box::new(err),
+                });
+            }
+        });
+
+        members.push(quote_spanned! { ty.span()=>
+            (
+                #name.to_owned(),
+                <#ty as ::glium::uniforms::UniformBlock>::build_layout(
+                    base_offset + ::glium::__glium_offset_of!(Self, #member)
+                ),
+            )
+        });
+
+        alignments.push(quote!(<#ty as ::glium::uniforms::UniformBlock>::STD140_ALIGNMENT));
+        sizes.push(quote!((
+            <#ty as ::glium::uniforms::UniformBlock>::STD140_ALIGNMENT,
+            <#ty as ::glium::uniforms::UniformBlock>::STD140_SIZE,
+        )));
+
+        let message = format!("the offset of the field `{}` of `{}` isn't a multiple of its \
+                               alignment in the std140 layout, padding is missing before it",
+                              name.value(), ident).replace('{', "{{").replace('}', "}}");
+        std140_checks.push(quote_spanned! { ty.span()=>
+            if let ::std::option::Option::Some(alignment) =
+                <#ty as ::glium::uniforms::UniformBlock>::STD140_ALIGNMENT
+            {
+                if ::core::mem::offset_of!(#ident, #member) % alignment != 0 {
+                    ::core::panic!(#message);
+                }
+            }
+        });
+
+        // a dynamically-sized array doesn't have a size, but the stride of its elements does
+        let (sized_ty, std140_size) = match ty {
+            Type::Slice(slice) => {
+                let elem = &slice.elem;
+                (&**elem, quote! {
+                    ::glium::uniforms::__std140_array_size(
+                        <#elem as ::glium::uniforms::UniformBlock>::STD140_ALIGNMENT,
+                        <#elem as ::glium::uniforms::UniformBlock>::STD140_SIZE,
+                        1,
+                    )
+                })
+            },
+            _ => (ty, quote!(<#ty as ::glium::uniforms::UniformBlock>::STD140_SIZE)),
+        };
+
+        let message = format!("the size of the field `{}` of `{}` doesn't match its size in the \
+                               std140 layout, the elements of an array or the columns of a \
+                               matrix may need padding",
+                              name.value(), ident).replace('{', "{{").replace('}', "}}");
+        std140_checks.push(quote_spanned! { ty.span()=>
+            if let ::std::option::Option::Some(size) = #std140_size {
+                if ::core::mem::size_of::<#sized_ty>() != size {
+                    ::core::panic!(#message);
+                }
+            }
+        });
+
+        names.push(name);
+    }
+
+    // the offsets are checked in a constant, which can't depend on the parameters of the struct
+    let std140_check = if struct_options.std140 {
+        if !input.generics.params.is_empty() {
+            return Err(Error::new(input.generics.span(),
+                                  "`#[glium(std140)]` can't be used on structs with parameters"));
+        }
+
+        Some(quote! {
+            const _: () = {
+                #(#std140_checks)*
+            };
+        })
+    } else {
+        None
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::glium::uniforms::UniformBlock for #ident #ty_generics #where_clause {
+            fn matches(layout: &::glium::program::BlockLayout, base_offset: usize)
+                       -> ::std::result::Result<(), ::glium::uniforms::LayoutMismatchError>
+            {
+                use ::glium::program::BlockLayout;
+                use ::glium::uniforms::LayoutMismatchError;
+
+                let members = match *layout {
+                    BlockLayout::Struct { ref members } => members,
+                    _ => return ::std::result::Result::Err(LayoutMismatchError::LayoutMismatch {
+                        expected: layout.clone(),
+                        obtained: <Self as ::glium::uniforms::UniformBlock>::build_layout(base_offset),
+                    }),
+                };
+
+                // checking that each member exists in the input struct
+                for &(ref name, _) in members {
+                    if ![#(#names),*].contains(&&name[..]) {
+                        return ::std::result::Result::Err(LayoutMismatchError::MissingField {
+                            name: name.clone(),
+                        });
+                    }
+                }
+
+                // checking that each field of the input struct is correct in the reflection
+                #(#checks)*
+
+                ::std::result::Result::Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn build_layout(base_offset: usize) -> ::glium::program::BlockLayout {
+                ::glium::program::BlockLayout::Struct {
+                    members: ::std::vec![#(#members),*],
+                }
+            }
+
+            const STD140_ALIGNMENT: ::std::option::Option<usize> =
+                ::glium::uniforms::__std140_aggregate_alignment(&[#(#alignments),*]);
+
+            const STD140_SIZE: ::std::option::Option<usize> =
+                ::glium::uniforms::__std140_struct_size(&[#(#sizes),*]);
+        }
+
+        #std140_check
+    })
+}
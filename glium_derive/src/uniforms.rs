@@ -0,0 +1,51 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Result};
+
+use crate::options;
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    options::struct_options(input, &[], "Uniforms")?;
+    let fields = options::fields(input, &["name", "skip", "flatten"], "Uniforms")?;
+    let fields = fields.into_iter().filter(|field| !field.options.skip).collect::<Vec<_>>();
+
+    options::check_unique_names(&fields)?;
+
+    let mut generics = input.generics.clone();
+    let mut visits = Vec::with_capacity(fields.len());
+
+    for field in &fields {
+        let member = &field.member;
+
+        if field.options.flatten {
+            field.add_bound(&mut generics, quote!(::glium::uniforms::Uniforms));
+
+            visits.push(quote_spanned! { field.ty.span()=>
+                ::glium::uniforms::Uniforms::visit_values(&self.#member, &mut visit);
+            });
+
+        } else {
+            field.add_bound(&mut generics, quote!(::glium::uniforms::AsUniformValue));
+
+            let name = field.name()?;
+            visits.push(quote_spanned! { field.ty.span()=>
+                visit(#name, ::glium::uniforms::AsUniformValue::as_uniform_value(&self.#member));
+            });
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::glium::uniforms::Uniforms for #ident #ty_generics #where_clause {
+            #[allow(unused_mut, unused_variables)]
+            fn visit_values<'__glium, __GliumF>(&'__glium self, mut visit: __GliumF)
+                where __GliumF: ::core::ops::FnMut(&str, ::glium::uniforms::UniformValue<'__glium>)
+            {
+                #(#visits)*
+            }
+        }
+    })
+}
@@ -0,0 +1,94 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Error, Result};
+
+use crate::options;
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let struct_options = options::struct_options(input, &["divisor"], "Vertex")?;
+    let fields = options::fields(input, &["name", "normalize", "location", "skip", "flatten"],
+                                 "Vertex")?;
+    let fields = fields.into_iter().filter(|field| !field.options.skip).collect::<Vec<_>>();
+
+    options::check_unique_names(&fields)?;
+
+    let mut locations = Vec::new();
+    for location in fields.iter().filter_map(|field| field.options.location.as_ref()) {
+        let value = location.base10_parse::<i32>()?;
+
+        if locations.contains(&value) {
+            return Err(Error::new(location.span(),
+                                  format!("the location {} is used by several fields", value)));
+        }
+
+        locations.push(value);
+    }
+
+    let mut generics = input.generics.clone();
+    let mut bindings = Vec::with_capacity(fields.len());
+
+    for field in &fields {
+        let member = &field.member;
+        let ty = field.ty;
+
+        if field.options.flatten {
+            field.add_bound(&mut generics, quote!(::glium::vertex::Vertex));
+
+            bindings.push(quote_spanned! { ty.span()=>
+                let offset = ::glium::__glium_offset_of!(Self, #member);
+                for &(ref name, field_offset, location, ty, normalize) in
+                    <#ty as ::glium::vertex::Vertex>::build_bindings().iter()
+                {
+                    bindings.push((name.clone(), offset + field_offset, location, ty, normalize));
+                }
+            });
+
+        } else {
+            field.add_bound(&mut generics, quote!(::glium::vertex::Attribute));
+
+            let name = field.name()?;
+            let location = match field.options.location {
+                Some(ref location) => Literal::i32_unsuffixed(location.base10_parse()?),
+                None => Literal::i32_unsuffixed(-1),
+            };
+            let normalize = field.options.normalize;
+
+            bindings.push(quote_spanned! { ty.span()=>
+                bindings.push((
+                    ::std::borrow::Cow::Borrowed(#name),
+                    ::glium::__glium_offset_of!(Self, #member),
+                    #location,
+                    <#ty as ::glium::vertex::Attribute>::get_type(),
+                    #normalize,
+                ));
+            });
+        }
+    }
+
+    let instance_divisor = struct_options.divisor.map(|divisor| {
+        let divisor = Literal::u32_unsuffixed(divisor);
+        quote! {
+            #[inline]
+            fn instance_divisor() -> u32 {
+                #divisor
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::glium::vertex::Vertex for #ident #ty_generics #where_clause {
+            fn build_bindings() -> ::glium::vertex::VertexFormat {
+                #[allow(unused_mut)]
+                let mut bindings = ::std::vec::Vec::new();
+                #(#bindings)*
+                ::std::borrow::Cow::Owned(bindings)
+            }
+
+            #instance_divisor
+        }
+    })
+}
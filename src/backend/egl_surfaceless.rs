@@ -0,0 +1,634 @@
+#![cfg(feature = "egl_surfaceless")]
+/*!
+
+Backend implementation that renders offscreen through EGL, without any window system.
+
+The display is obtained with `EGL_MESA_platform_surfaceless`, which means that no X11 or Wayland
+server is required. This makes it possible to render and read back pixels on machines that only
+have a software rasterizer such as Mesa's llvmpipe, for example on a CI server.
+
+The default framebuffer is a pbuffer whose dimensions are chosen when creating the context. If
+the implementation doesn't provide any pbuffer configuration, the context is made current
+without any surface (`EGL_KHR_surfaceless_context`). In this situation the default framebuffer
+is incomplete and you must draw on textures or renderbuffers instead.
+
+# Features
+
+Only available if the 'egl_surfaceless' feature is enabled.
+
+*/
+use crate::backend::{self, Backend};
+use crate::context;
+use crate::debug;
+use crate::recovery::{self, RecoveryError};
+use crate::version::{Api, Version};
+use crate::{Frame, IncompatibleOpenGl, SwapBuffersError};
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ops::Deref;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::rc::Rc;
+
+use libloading::Library;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::os::raw::{c_char, c_void};
+
+    pub type EGLBoolean = u32;
+    pub type EGLenum = u32;
+    pub type EGLint = i32;
+    pub type EGLDisplay = *mut c_void;
+    pub type EGLConfig = *mut c_void;
+    pub type EGLContext = *mut c_void;
+    pub type EGLSurface = *mut c_void;
+
+    pub const NO_CONTEXT: EGLContext = 0 as EGLContext;
+    pub const NO_DISPLAY: EGLDisplay = 0 as EGLDisplay;
+    pub const NO_SURFACE: EGLSurface = 0 as EGLSurface;
+
+    pub const FALSE: EGLBoolean = 0;
+    pub const NONE: EGLint = 0x3038;
+    pub const EXTENSIONS: EGLint = 0x3055;
+
+    pub const ALPHA_SIZE: EGLint = 0x3021;
+    pub const BLUE_SIZE: EGLint = 0x3022;
+    pub const GREEN_SIZE: EGLint = 0x3023;
+    pub const RED_SIZE: EGLint = 0x3024;
+    pub const DEPTH_SIZE: EGLint = 0x3025;
+    pub const STENCIL_SIZE: EGLint = 0x3026;
+    pub const SURFACE_TYPE: EGLint = 0x3033;
+    pub const RENDERABLE_TYPE: EGLint = 0x3040;
+    pub const HEIGHT: EGLint = 0x3056;
+    pub const WIDTH: EGLint = 0x3057;
+
+    pub const PBUFFER_BIT: EGLint = 0x0001;
+    pub const OPENGL_ES2_BIT: EGLint = 0x0004;
+    pub const OPENGL_BIT: EGLint = 0x0008;
+    pub const OPENGL_ES3_BIT: EGLint = 0x0040;
+
+    pub const OPENGL_ES_API: EGLenum = 0x30A0;
+    pub const OPENGL_API: EGLenum = 0x30A2;
+
+    pub const CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+    pub const CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+    pub const CONTEXT_OPENGL_DEBUG: EGLint = 0x31B0;
+
+    pub const PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+    pub type GetProcAddress = unsafe extern "C" fn(*const c_char) -> *const c_void;
+    pub type GetPlatformDisplay = unsafe extern "C" fn(EGLenum, *mut c_void, *const c_void)
+                                                       -> EGLDisplay;
+    pub type Initialize = unsafe extern "C" fn(EGLDisplay, *mut EGLint, *mut EGLint) -> EGLBoolean;
+    pub type QueryString = unsafe extern "C" fn(EGLDisplay, EGLint) -> *const c_char;
+    pub type BindApi = unsafe extern "C" fn(EGLenum) -> EGLBoolean;
+    pub type ChooseConfig = unsafe extern "C" fn(EGLDisplay, *const EGLint, *mut EGLConfig, EGLint,
+                                                 *mut EGLint) -> EGLBoolean;
+    pub type CreateContext = unsafe extern "C" fn(EGLDisplay, EGLConfig, EGLContext,
+                                                  *const EGLint) -> EGLContext;
+    pub type CreatePbufferSurface = unsafe extern "C" fn(EGLDisplay, EGLConfig, *const EGLint)
+                                                         -> EGLSurface;
+    pub type MakeCurrent = unsafe extern "C" fn(EGLDisplay, EGLSurface, EGLSurface, EGLContext)
+                                                -> EGLBoolean;
+    pub type GetCurrentContext = unsafe extern "C" fn() -> EGLContext;
+    pub type DestroyContext = unsafe extern "C" fn(EGLDisplay, EGLContext) -> EGLBoolean;
+    pub type DestroySurface = unsafe extern "C" fn(EGLDisplay, EGLSurface) -> EGLBoolean;
+    pub type GetError = unsafe extern "C" fn() -> EGLint;
+}
+
+/// Error that can happen while creating an EGL surfaceless context.
+#[derive(Debug)]
+pub enum CreationError {
+    /// The EGL library couldn't be loaded.
+    LibraryNotFound(String),
+    /// The EGL library doesn't provide a function that is required.
+    MissingFunction(&'static str),
+    /// The implementation doesn't support `EGL_MESA_platform_surfaceless`.
+    PlatformNotSupported,
+    /// `eglInitialize` failed. Contains the value of `eglGetError`.
+    InitializationFailed(i32),
+    /// The requested API can't be bound.
+    ApiNotSupported,
+    /// No configuration matches the requested API.
+    NoAvailableConfig,
+    /// `eglCreateContext` failed. Contains the value of `eglGetError`.
+    ContextCreationFailed(i32),
+    /// `eglCreatePbufferSurface` failed. Contains the value of `eglGetError`.
+    SurfaceCreationFailed(i32),
+    /// `eglMakeCurrent` failed. Contains the value of `eglGetError`.
+    MakeCurrentFailed(i32),
+    /// The OpenGL implementation is too old.
+    IncompatibleOpenGl(IncompatibleOpenGl),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            CreationError::LibraryNotFound(err) =>
+                write!(fmt, "Couldn't load the EGL library: {}", err),
+            CreationError::MissingFunction(name) =>
+                write!(fmt, "The EGL library doesn't provide `{}`", name),
+            CreationError::PlatformNotSupported =>
+                fmt.write_str("The EGL implementation doesn't support EGL_MESA_platform_surfaceless"),
+            CreationError::InitializationFailed(err) =>
+                write!(fmt, "eglInitialize failed with error 0x{:x}", err),
+            CreationError::ApiNotSupported =>
+                fmt.write_str("The requested API is not supported by the EGL implementation"),
+            CreationError::NoAvailableConfig =>
+                fmt.write_str("No EGL configuration matches the requested API"),
+            CreationError::ContextCreationFailed(err) =>
+                write!(fmt, "eglCreateContext failed with error 0x{:x}", err),
+            CreationError::SurfaceCreationFailed(err) =>
+                write!(fmt, "eglCreatePbufferSurface failed with error 0x{:x}", err),
+            CreationError::MakeCurrentFailed(err) =>
+                write!(fmt, "eglMakeCurrent failed with error 0x{:x}", err),
+            CreationError::IncompatibleOpenGl(err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl Error for CreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            CreationError::IncompatibleOpenGl(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IncompatibleOpenGl> for CreationError {
+    #[inline]
+    fn from(err: IncompatibleOpenGl) -> CreationError {
+        CreationError::IncompatibleOpenGl(err)
+    }
+}
+
+/// Function pointers of the EGL library.
+struct Egl {
+    get_proc_address: ffi::GetProcAddress,
+    get_platform_display: ffi::GetPlatformDisplay,
+    initialize: ffi::Initialize,
+    query_string: ffi::QueryString,
+    bind_api: ffi::BindApi,
+    choose_config: ffi::ChooseConfig,
+    create_context: ffi::CreateContext,
+    create_pbuffer_surface: ffi::CreatePbufferSurface,
+    make_current: ffi::MakeCurrent,
+    get_current_context: ffi::GetCurrentContext,
+    destroy_context: ffi::DestroyContext,
+    destroy_surface: ffi::DestroySurface,
+    get_error: ffi::GetError,
+
+    // must be kept alive as long as the function pointers above are used
+    _library: Library,
+}
+
+impl Egl {
+    fn load() -> Result<Egl, CreationError> {
+        let library = unsafe { Library::new("libEGL.so.1") }
+            .or_else(|_| unsafe { Library::new("libEGL.so") })
+            .map_err(|err| CreationError::LibraryNotFound(err.to_string()))?;
+
+        macro_rules! load {
+            ($name:expr) => (
+                unsafe {
+                    *library.get(concat!($name, "\0").as_bytes())
+                            .map_err(|_| CreationError::MissingFunction($name))?
+                }
+            );
+        }
+
+        let get_proc_address: ffi::GetProcAddress = load!("eglGetProcAddress");
+
+        // `eglGetPlatformDisplay` is only available with EGL 1.5, so we fall back to the
+        // extension function
+        let get_platform_display = unsafe {
+            ["eglGetPlatformDisplay", "eglGetPlatformDisplayEXT"].iter().find_map(|name| {
+                let name = CString::new(*name).unwrap();
+                let ptr = get_proc_address(name.as_ptr());
+                if ptr.is_null() {
+                    None
+                } else {
+                    Some(std::mem::transmute::<*const c_void, ffi::GetPlatformDisplay>(ptr))
+                }
+            }).ok_or(CreationError::MissingFunction("eglGetPlatformDisplay"))?
+        };
+
+        Ok(Egl {
+            get_proc_address,
+            get_platform_display,
+            initialize: load!("eglInitialize"),
+            query_string: load!("eglQueryString"),
+            bind_api: load!("eglBindAPI"),
+            choose_config: load!("eglChooseConfig"),
+            create_context: load!("eglCreateContext"),
+            create_pbuffer_surface: load!("eglCreatePbufferSurface"),
+            make_current: load!("eglMakeCurrent"),
+            get_current_context: load!("eglGetCurrentContext"),
+            destroy_context: load!("eglDestroyContext"),
+            destroy_surface: load!("eglDestroySurface"),
+            get_error: load!("eglGetError"),
+            _library: library,
+        })
+    }
+}
+
+/// An EGL context and the surface it renders to.
+struct EglContext {
+    egl: Rc<Egl>,
+    display: ffi::EGLDisplay,
+    context: ffi::EGLContext,
+    /// `NO_SURFACE` if the implementation doesn't support pbuffers.
+    surface: ffi::EGLSurface,
+    /// The version that was requested when creating the context.
+    version: Option<Version>,
+    dimensions: (u32, u32),
+}
+
+impl EglContext {
+    /// Creates a new context. If `shared` is `Some`, the new context shares its objects with it.
+    fn new(egl: Rc<Egl>, dimensions: (u32, u32), version: Option<Version>, debug: bool,
+           shared: Option<ffi::EGLContext>) -> Result<EglContext, CreationError>
+    {
+        unsafe {
+            // the attributes list is null, as its type differs between `eglGetPlatformDisplay`
+            // and `eglGetPlatformDisplayEXT`
+            let display = (egl.get_platform_display)(ffi::PLATFORM_SURFACELESS_MESA,
+                                                     ptr::null_mut(), ptr::null());
+            if display == ffi::NO_DISPLAY {
+                return Err(CreationError::PlatformNotSupported);
+            }
+
+            let (mut major, mut minor) = (0, 0);
+            if (egl.initialize)(display, &mut major, &mut minor) == ffi::FALSE {
+                return Err(CreationError::InitializationFailed((egl.get_error)()));
+            }
+
+            let extensions = (egl.query_string)(display, ffi::EXTENSIONS);
+            let extensions = if extensions.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(extensions).to_string_lossy().into_owned()
+            };
+            let surfaceless_context = extensions.split(' ')
+                                                .any(|e| e == "EGL_KHR_surfaceless_context");
+
+            let api = version.map(|v| v.0).unwrap_or(Api::Gl);
+            let (egl_api, renderable) = match (api, version) {
+                (Api::Gl, _) => (ffi::OPENGL_API, ffi::OPENGL_BIT),
+                (Api::GlEs, Some(Version(_, major, _))) if major >= 3 =>
+                    (ffi::OPENGL_ES_API, ffi::OPENGL_ES3_BIT),
+                (Api::GlEs, _) => (ffi::OPENGL_ES_API, ffi::OPENGL_ES2_BIT),
+            };
+
+            if (egl.bind_api)(egl_api) == ffi::FALSE {
+                return Err(CreationError::ApiNotSupported);
+            }
+
+            // we first try to find a config that supports pbuffers, then fall back to any config
+            // if the implementation supports surfaceless contexts
+            let mut config = ptr::null_mut();
+            let mut surface_type = ffi::PBUFFER_BIT;
+            loop {
+                let attributes = [
+                    ffi::SURFACE_TYPE, surface_type,
+                    ffi::RENDERABLE_TYPE, renderable,
+                    ffi::RED_SIZE, 8,
+                    ffi::GREEN_SIZE, 8,
+                    ffi::BLUE_SIZE, 8,
+                    ffi::ALPHA_SIZE, 8,
+                    ffi::DEPTH_SIZE, 24,
+                    ffi::STENCIL_SIZE, 8,
+                    ffi::NONE,
+                ];
+
+                let mut num_configs = 0;
+                if (egl.choose_config)(display, attributes.as_ptr(), &mut config, 1,
+                                       &mut num_configs) != ffi::FALSE && num_configs >= 1
+                {
+                    break;
+                }
+
+                if surface_type == 0 || !surfaceless_context {
+                    return Err(CreationError::NoAvailableConfig);
+                }
+                surface_type = 0;
+            }
+
+            let mut attributes = Vec::with_capacity(7);
+            if let Some(Version(_, major, minor)) = version {
+                attributes.extend_from_slice(&[
+                    ffi::CONTEXT_MAJOR_VERSION, major as ffi::EGLint,
+                    ffi::CONTEXT_MINOR_VERSION, minor as ffi::EGLint,
+                ]);
+            }
+            if debug {
+                attributes.extend_from_slice(&[ffi::CONTEXT_OPENGL_DEBUG, 1]);
+            }
+            attributes.push(ffi::NONE);
+
+            let shared = shared.unwrap_or(ffi::NO_CONTEXT);
+            let context = (egl.create_context)(display, config, shared, attributes.as_ptr());
+            if context == ffi::NO_CONTEXT {
+                return Err(CreationError::ContextCreationFailed((egl.get_error)()));
+            }
+
+            // from now on, the context is destroyed by the destructor if an error happens
+            let mut context = EglContext {
+                egl,
+                display,
+                context,
+                surface: ffi::NO_SURFACE,
+                version,
+                dimensions,
+            };
+
+            if surface_type != 0 {
+                let attributes = [
+                    ffi::WIDTH, dimensions.0 as ffi::EGLint,
+                    ffi::HEIGHT, dimensions.1 as ffi::EGLint,
+                    ffi::NONE,
+                ];
+                context.surface = (context.egl.create_pbuffer_surface)(display, config,
+                                                                       attributes.as_ptr());
+                if context.surface == ffi::NO_SURFACE {
+                    return Err(CreationError::SurfaceCreationFailed((context.egl.get_error)()));
+                }
+            }
+
+            if (context.egl.make_current)(display, context.surface, context.surface,
+                                          context.context) == ffi::FALSE
+            {
+                return Err(CreationError::MakeCurrentFailed((context.egl.get_error)()));
+            }
+
+            Ok(context)
+        }
+    }
+}
+
+impl Drop for EglContext {
+    fn drop(&mut self) {
+        unsafe {
+            if (self.egl.get_current_context)() == self.context {
+                (self.egl.make_current)(self.display, ffi::NO_SURFACE, ffi::NO_SURFACE,
+                                        ffi::NO_CONTEXT);
+            }
+
+            if self.surface != ffi::NO_SURFACE {
+                (self.egl.destroy_surface)(self.display, self.surface);
+            }
+
+            // the display is not terminated, as it is shared with the other contexts of the
+            // process
+            (self.egl.destroy_context)(self.display, self.context);
+        }
+    }
+}
+
+/// An offscreen context that doesn't depend on any window system.
+pub struct EglSurfaceless {
+    context: Rc<context::Context>,
+    egl_context: RefCell<Rc<EglContext>>,
+    /// Whether the OpenGL context was created with the debug flag.
+    debug_context: bool,
+}
+
+/// Builds an `EglSurfaceless` whose context shares its objects with another one. Returned by
+/// `EglSurfaceless::shared_builder`.
+///
+/// Contrary to `EglSurfaceless`, the builder can be sent to another thread, for example in order
+/// to upload resources from a worker thread. See the `transfer` module.
+pub struct SharedBuilder {
+    /// The context to share the objects with.
+    shared: ffi::EGLContext,
+    share_group: context::ShareGroup,
+    dimensions: (u32, u32),
+    version: Option<Version>,
+    debug_context: bool,
+}
+
+// the EGL context is only used as a parameter of `eglCreateContext`, which is thread-safe
+unsafe impl Send for SharedBuilder {}
+
+impl SharedBuilder {
+    /// Builds the context.
+    ///
+    /// The `EglSurfaceless` that created the builder must still be alive.
+    #[inline]
+    pub fn build(self) -> Result<EglSurfaceless, CreationError> {
+        self.build_with_debug(Default::default())
+    }
+
+    /// The same as `build`, but allows for specifying debug callback behaviour.
+    ///
+    /// The OpenGL context is created with the debug flag if the one of the `EglSurfaceless` that
+    /// created the builder has it, and if `debug` is `DebugMessageOnError` or `Custom`.
+    pub fn build_with_debug(self, debug: debug::DebugCallbackBehavior)
+                            -> Result<EglSurfaceless, CreationError>
+    {
+        EglSurfaceless::build(self.dimensions, self.version, debug, self.debug_context, None,
+                              Some((self.shared, self.share_group)))
+    }
+}
+
+/// An implementation of the `Backend` trait for an EGL surfaceless context.
+pub struct EglSurfacelessBackend(Rc<EglContext>);
+
+unsafe impl Backend for EglSurfacelessBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        // pbuffers are single-buffered, so there is nothing to swap
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        let symbol = CString::new(symbol).unwrap();
+        (self.0.egl.get_proc_address)(symbol.as_ptr() as *const c_char)
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.0.dimensions
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        unsafe { (self.0.egl.get_current_context)() == self.0.context }
+    }
+
+    #[inline]
+    unsafe fn make_current(&self) {
+        let ctxt = &self.0;
+        let result = (ctxt.egl.make_current)(ctxt.display, ctxt.surface, ctxt.surface,
+                                             ctxt.context);
+        assert!(result != ffi::FALSE, "eglMakeCurrent failed with error 0x{:x}",
+                (ctxt.egl.get_error)());
+    }
+}
+
+impl Deref for EglSurfaceless {
+    type Target = context::Context;
+    #[inline]
+    fn deref(&self) -> &context::Context {
+        &self.context
+    }
+}
+
+impl backend::Facade for EglSurfaceless {
+    #[inline]
+    fn get_context(&self) -> &Rc<context::Context> {
+        &self.context
+    }
+}
+
+impl EglSurfaceless {
+    /// Creates a new offscreen context whose default framebuffer has the given dimensions.
+    ///
+    /// The latest version of desktop OpenGL supported by the implementation is used.
+    ///
+    /// Performs a compatibility check to make sure that all core elements of glium are supported
+    /// by the implementation.
+    #[inline]
+    pub fn new(dimensions: (u32, u32)) -> Result<EglSurfaceless, CreationError> {
+        EglSurfaceless::with_debug(dimensions, Default::default())
+    }
+
+    /// The same as the `new` constructor, but allows for specifying debug callback behaviour.
+    #[inline]
+    pub fn with_debug(dimensions: (u32, u32), debug: debug::DebugCallbackBehavior)
+                      -> Result<EglSurfaceless, CreationError>
+    {
+        EglSurfaceless::with_version(dimensions, None, debug)
+    }
+
+    /// The same as the `with_debug` constructor, but allows for requesting a specific API and
+    /// version. Passing `None` requests the latest version of desktop OpenGL.
+    pub fn with_version(dimensions: (u32, u32), version: Option<Version>,
+                        debug: debug::DebugCallbackBehavior)
+                        -> Result<EglSurfaceless, CreationError>
+    {
+        EglSurfaceless::build(dimensions, version, debug, false, None, None)
+    }
+
+    /// The same as the `with_version` constructor, but the OpenGL context is created with the
+    /// debug flag if `debug` is `DebugMessageOnError` or `Custom`. Debug contexts may be slower,
+    /// but produce more debug messages.
+    pub fn with_debug_context(dimensions: (u32, u32), version: Option<Version>,
+                              debug: debug::DebugCallbackBehavior)
+                              -> Result<EglSurfaceless, CreationError>
+    {
+        EglSurfaceless::build(dimensions, version, debug, true, None, None)
+    }
+
+    /// The same as the `with_debug` constructor, but glium only uses the version and the
+    /// extensions allowed by the mask. See `Context::with_capability_mask`.
+    ///
+    /// If the mask is for OpenGL ES, an OpenGL ES context of this version is created. Otherwise,
+    /// the latest version of desktop OpenGL is used.
+    pub fn with_capability_mask(dimensions: (u32, u32), mask: context::CapabilityMask,
+                                debug: debug::DebugCallbackBehavior)
+                                -> Result<EglSurfaceless, CreationError>
+    {
+        let version = match mask.version {
+            Version(Api::GlEs, _, _) => Some(mask.version),
+            Version(Api::Gl, _, _) => None,
+        };
+
+        EglSurfaceless::build(dimensions, version, debug, false, Some(mask), None)
+    }
+
+    fn build(dimensions: (u32, u32), version: Option<Version>,
+             debug: debug::DebugCallbackBehavior, debug_context: bool,
+             mask: Option<context::CapabilityMask>,
+             shared: Option<(ffi::EGLContext, context::ShareGroup)>)
+             -> Result<EglSurfaceless, CreationError>
+    {
+        let debug_context = debug_context && matches!(debug,
+            debug::DebugCallbackBehavior::DebugMessageOnError |
+            debug::DebugCallbackBehavior::Custom { .. });
+        let egl = Rc::new(Egl::load()?);
+        let egl_context = Rc::new(EglContext::new(egl, dimensions, version, debug_context,
+                                                  shared.map(|(context, _)| context))?);
+        let backend = EglSurfacelessBackend(egl_context.clone());
+        let context = match (mask, shared) {
+            (Some(mask), _) => unsafe {
+                context::Context::with_capability_mask(backend, true, debug, mask)
+            }?,
+            (None, Some((_, share_group))) => unsafe {
+                context::Context::new_shared(backend, true, debug, share_group)
+            }?,
+            (None, None) => unsafe { context::Context::new(backend, true, debug) }?,
+        };
+
+        Ok(EglSurfaceless {
+            context,
+            egl_context: RefCell::new(egl_context),
+            debug_context,
+        })
+    }
+
+    /// Replaces the EGL context with a new one that shares its objects with the old one.
+    ///
+    /// This is mostly useful in tests, in order to check that everything that isn't shared
+    /// between contexts (FBOs, VAOs, etc.) is correctly invalidated.
+    pub fn rebuild(&self) -> Result<(), CreationError> {
+        let new_context = {
+            let old = self.egl_context.borrow();
+            Rc::new(EglContext::new(old.egl.clone(), old.dimensions, old.version,
+                                    self.debug_context, Some(old.context))?)
+        };
+
+        let backend = EglSurfacelessBackend(new_context.clone());
+        unsafe { self.context.rebuild(backend) }?;
+        *self.egl_context.borrow_mut() = new_context;
+        Ok(())
+    }
+
+    /// Returns a builder for a context that shares its objects with this one, and that can be
+    /// built on another thread. See the `transfer` module.
+    pub fn shared_builder(&self) -> SharedBuilder {
+        let egl_context = self.egl_context.borrow();
+        SharedBuilder {
+            shared: egl_context.context,
+            share_group: self.context.share_group(),
+            dimensions: egl_context.dimensions,
+            version: egl_context.version,
+            debug_context: self.debug_context,
+        }
+    }
+
+    /// Replaces the lost EGL context with a new one that doesn't share any object with it, and
+    /// recreates the recoverable resources. See the `recovery` module.
+    pub fn recover(&self) -> Result<(), RecoveryError> {
+        let resources = recovery::release(&self.context)?;
+
+        let new_context = {
+            let old = self.egl_context.borrow();
+            EglContext::new(old.egl.clone(), old.dimensions, old.version, self.debug_context,
+                            None)
+                .map_err(|err| RecoveryError::ContextCreation(Box::new(err)))?
+        };
+        let new_context = Rc::new(new_context);
+
+        let backend = EglSurfacelessBackend(new_context.clone());
+        unsafe { recovery::reload(&self.context, &resources, backend) }?;
+        *self.egl_context.borrow_mut() = new_context;
+        Ok(())
+    }
+
+    /// Start drawing on the default framebuffer.
+    ///
+    /// This function returns a `Frame`, which can be used to draw on it. Finishing the `Frame`
+    /// doesn't do anything, as the default framebuffer is single-buffered.
+    #[inline]
+    pub fn draw(&self) -> Frame {
+        Frame::new(self.context.clone(), self.get_framebuffer_dimensions())
+    }
+}
@@ -105,18 +105,47 @@ impl Headless {
         Self::new_inner(context, debug, false)
     }
 
+    /// The same as the `with_debug` constructor, but the glutin context must share its objects
+    /// with the contexts of the given share group. It has typically been built with
+    /// `ContextBuilder::with_shared_lists`, then sent to another thread. See the `transfer` module.
+    ///
+    /// # Safety
+    ///
+    /// The glutin context must really share its objects with the contexts of the group.
+    pub unsafe fn new_shared<T: ContextCurrentState>(
+        context: glutin::Context<T>,
+        share_group: context::ShareGroup,
+        debug: debug::DebugCallbackBehavior,
+    ) -> Result<Self, IncompatibleOpenGl>
+    {
+        Self::build(context, debug, true, Some(share_group))
+    }
+
     fn new_inner<T: ContextCurrentState>(
         context: glutin::Context<T>,
         debug: debug::DebugCallbackBehavior,
         checked: bool,
     ) -> Result<Self, IncompatibleOpenGl>
     {
-        let context = unsafe {
-            context.treat_as_current()
-        };
+        unsafe { Self::build(context, debug, checked, None) }
+    }
+
+    unsafe fn build<T: ContextCurrentState>(
+        context: glutin::Context<T>,
+        debug: debug::DebugCallbackBehavior,
+        checked: bool,
+        share_group: Option<context::ShareGroup>,
+    ) -> Result<Self, IncompatibleOpenGl>
+    {
+        let context = context.treat_as_current();
         let glutin_context = Rc::new(RefCell::new(Takeable::new(context)));
         let glutin_backend = GlutinBackend(glutin_context.clone());
-        let context = unsafe { context::Context::new(glutin_backend, checked, debug) }?;
+        let context = match share_group {
+            Some(share_group) => {
+                context::Context::new_shared(glutin_backend, checked, debug, share_group)
+            },
+            None => context::Context::new(glutin_backend, checked, debug),
+        }?;
         Ok(Headless { context, glutin: glutin_context })
     }
 
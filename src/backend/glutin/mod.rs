@@ -17,6 +17,7 @@ use crate::backend::Backend;
 use crate::backend::Context;
 use crate::context;
 use crate::debug;
+use crate::recovery::{self, RecoveryError};
 use crate::glutin::{ContextCurrentState, PossiblyCurrent as Pc};
 use std::cell::{Cell, Ref, RefCell};
 use std::error::Error;
@@ -160,6 +161,33 @@ impl Display {
         Ok(())
     }
 
+    /// Replaces the Display's lost `WindowedContext` with one built from the given window and
+    /// context builders, and recreates the recoverable resources. See the `recovery` module.
+    ///
+    /// Contrary to `rebuild`, the new `WindowedContext` doesn't share its display lists with the
+    /// old one.
+    pub fn recover<T: ContextCurrentState>(
+        &self,
+        wb: glutin::window::WindowBuilder,
+        cb: glutin::ContextBuilder<'_, T>,
+        events_loop: &glutin::event_loop::EventLoopWindowTarget<()>,
+    ) -> Result<(), RecoveryError> {
+        let new_gl_window = cb.build_windowed(wb, events_loop)
+            .map_err(|err| RecoveryError::ContextCreation(Box::new(err)))?;
+        let new_gl_window = unsafe { new_gl_window.treat_as_current() };
+
+        let resources = recovery::release(&self.context)?;
+
+        // Replace the stored WindowedContext with the new one, which destroys the old one.
+        {
+            let mut gl_window = self.gl_window.borrow_mut();
+            Takeable::insert(&mut gl_window, new_gl_window);
+        }
+
+        let backend = GlutinBackend(self.gl_window.clone());
+        unsafe { recovery::reload(&self.context, &resources, backend) }
+    }
+
     /// Borrow the inner glutin WindowedContext.
     #[inline]
     pub fn gl_window(&self) -> Ref<'_, impl Deref<Target = glutin::WindowedContext<Pc>>> {
@@ -19,15 +19,22 @@ use std::os::raw::c_void;
 use crate::CapabilitiesSource;
 use crate::SwapBuffersError;
 
-use crate::context::Capabilities;
-use crate::context::ExtensionsList;
 use crate::version::Version;
 
 pub use crate::context::Context;
+pub use crate::context::{Capabilities, CapabilitiesReport, ExtensionsList, FormatInfos};
+pub use crate::context::CapabilityMask;
 pub use crate::context::ReleaseBehavior;
+pub use crate::context::ShareGroup;
+pub use crate::context::StateGroup;
+pub use crate::context::{LeakReport, LiveResource, ResourceKind, ResourceStats, ResourceUsage};
 
 #[cfg(feature = "glutin")]
 pub mod glutin;
+#[cfg(feature = "egl_surfaceless")]
+pub mod egl_surfaceless;
+#[cfg(feature = "recording")]
+pub mod recording;
 
 /// Trait for types that can be used as a backend for a glium context.
 ///
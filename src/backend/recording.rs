@@ -0,0 +1,865 @@
+/*!
+
+Backend that doesn't talk to any OpenGL implementation, but records the calls that glium makes.
+
+Every OpenGL function returned by `get_proc_address` is a stub that appends its name and its
+arguments to a trace, which can then be inspected with `RecordingBackend::calls`. This makes it
+possible to test code built on top of glium without a GPU, for example to check that a
+`Surface::draw` produced exactly the binds and state changes that you expect, or that redundant
+state changes are filtered out by glium's state cache.
+
+The stubs also emulate the small subset of a driver that glium relies on:
+
+ - `glGetString`, `glGetIntegerv` and the other `glGet*` functions answer from the
+   `DriverProfile` that was passed when creating the backend.
+ - `glGen*` and `glCreate*` return new object names.
+ - Buffers have real storage, so uploading, mapping and reading them back work as expected.
+ - Shaders always compile, programs always link and don't have any active uniform or attribute.
+   The compilation and the linking complete immediately.
+ - The binary of a program is the renderer string of the profile. `glProgramBinary` rejects the
+   binaries that don't match it, like a driver that has been updated.
+ - Framebuffers are always complete, fences are always signaled and queries always have
+   their result available.
+ - A simulated GPU clock advances by one microsecond with each call. `glQueryCounter` stores its
+   value in the query and `glGetInteger64v(GL_TIMESTAMP)` returns it, so timestamps measure the
+   number of calls that were made between them.
+ - Messages inserted with `glDebugMessageInsert` are sent to the debug callback, which makes it
+   possible to test the handling of the debug output.
+
+All the other functions do nothing and return zero.
+
+# Example
+
+```
+use std::rc::Rc;
+use glium::Surface;
+use glium::backend::Context;
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::debug::DebugCallbackBehavior;
+
+let backend = Rc::new(RecordingBackend::new(DriverProfile::default(), (800, 600)));
+let context = unsafe {
+    Context::new(backend.clone(), true, DebugCallbackBehavior::Ignore)
+}.unwrap();
+backend.clear_calls();
+
+let mut frame = glium::Frame::new(context.clone(), (800, 600));
+frame.clear_color(0.0, 0.0, 1.0, 1.0);
+frame.finish().unwrap();
+
+assert!(backend.calls().iter().any(|call| call.name == "glClearColor"));
+```
+
+# Features
+
+Only available if the 'recording' feature is enabled.
+
+*/
+use crate::backend::Backend;
+use crate::context::{CapabilitiesReport, REPORT_LIMITS};
+use crate::gl;
+use crate::version::{Api, Version};
+use crate::{Profile, SwapBuffersError};
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+/// Format of the program binaries.
+const PROGRAM_BINARY_FORMAT: u32 = 0x676c;
+
+#[allow(clippy::all)]
+mod stubs {
+    include!(concat!(env!("OUT_DIR"), "/recording_stubs.rs"));
+}
+
+thread_local! {
+    /// The driver of the recording backend that is current in this thread.
+    static CURRENT: RefCell<Option<Rc<Driver>>> = const { RefCell::new(None) };
+}
+
+/// Describes the OpenGL implementation that a `RecordingBackend` pretends to be.
+#[derive(Debug, Clone)]
+pub struct DriverProfile {
+    /// Version of OpenGL returned by `glGetString(GL_VERSION)`.
+    pub version: Version,
+
+    /// Value of `GL_CONTEXT_PROFILE_MASK`. Only queried by glium from OpenGL 3.2 onwards.
+    pub profile: Option<Profile>,
+
+    /// If true, `GL_CONTEXT_FLAG_DEBUG_BIT` is set in `GL_CONTEXT_FLAGS`.
+    pub debug: bool,
+
+    /// Value returned by `glGetString(GL_VENDOR)`.
+    pub vendor: String,
+
+    /// Value returned by `glGetString(GL_RENDERER)`.
+    pub renderer: String,
+
+    /// List of supported extensions, for example `GL_ARB_buffer_storage`.
+    pub extensions: Vec<String>,
+
+    /// Values returned by `glGetIntegerv` and the other `glGet*` functions, indexed by the
+    /// `GLenum` being queried. Indexed queries like `glGetIntegeri_v` look up the element at
+    /// the given index.
+    ///
+    /// Queries that aren't in this list and that can't be derived from the other fields leave
+    /// their output untouched.
+    pub limits: HashMap<u32, Vec<i64>>,
+}
+
+impl DriverProfile {
+    /// Builds a profile for the given version, with no extension and the minimum limits
+    /// required by the OpenGL 3.3 specifications.
+    pub fn new(version: Version) -> DriverProfile {
+        let limits = [
+            (gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, vec![48]),
+            (gl::MAX_TEXTURE_IMAGE_UNITS, vec![16]),
+            (gl::MAX_TEXTURE_SIZE, vec![1024]),
+            (gl::MAX_3D_TEXTURE_SIZE, vec![256]),
+            (gl::MAX_CUBE_MAP_TEXTURE_SIZE, vec![1024]),
+            (gl::MAX_ARRAY_TEXTURE_LAYERS, vec![256]),
+            (gl::MAX_TEXTURE_BUFFER_SIZE, vec![65536]),
+            (gl::MAX_RENDERBUFFER_SIZE, vec![1024]),
+            (gl::MAX_VIEWPORT_DIMS, vec![1024, 1024]),
+            (gl::MAX_DRAW_BUFFERS, vec![8]),
+            (gl::MAX_COLOR_ATTACHMENTS, vec![8]),
+            (gl::MAX_SAMPLES, vec![4]),
+            (gl::MAX_VERTEX_ATTRIBS, vec![16]),
+            (gl::MAX_CLIP_DISTANCES, vec![8]),
+            (gl::MAX_UNIFORM_BUFFER_BINDINGS, vec![36]),
+            (gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT, vec![256]),
+            (gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS, vec![4]),
+        ];
+
+        DriverProfile {
+            version,
+            profile: Some(Profile::Core),
+            debug: false,
+            vendor: "glium".to_owned(),
+            renderer: "recording backend".to_owned(),
+            extensions: Vec::new(),
+            limits: limits.iter().cloned().collect(),
+        }
+    }
+
+    /// Builds a profile that reproduces the environment described by a report returned by
+    /// `Context::capabilities_report`, for example a report collected from the machine of a user.
+    ///
+    /// The version, the profile, the vendor and renderer strings, the supported extensions and
+    /// the limits are reproduced. The robustness of the context and the properties of the
+    /// default framebuffer aren't.
+    pub fn from_report(report: &CapabilitiesReport) -> DriverProfile {
+        let mut profile = DriverProfile::new(report.version);
+        profile.profile = report.profile;
+        profile.debug = report.debug;
+        profile.vendor = report.vendor.clone();
+        profile.renderer = report.renderer.clone();
+        profile.extensions = report.supported_extensions().map(|e| e.to_owned()).collect();
+
+        for (name, values) in &report.limits {
+            if let Some(&(_, pname, _)) = REPORT_LIMITS.iter().find(|&&(n, _, _)| n == name) {
+                profile.limits.insert(pname, values.clone());
+            }
+        }
+
+        // without `GL_ARB_transform_feedback3`, glium determines the number of transform feedback
+        // buffers from the number of separate attributes
+        if report.version < Version(Api::Gl, 4, 0) &&
+           !profile.extensions.iter().any(|e| e == "GL_ARB_transform_feedback3")
+        {
+            if let Some(values) = report.limits.get("GL_MAX_TRANSFORM_FEEDBACK_BUFFERS") {
+                profile.limits.insert(gl::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS, values.clone());
+            }
+        }
+
+        profile
+    }
+}
+
+impl Default for DriverProfile {
+    /// Returns an OpenGL 3.3 core profile.
+    #[inline]
+    fn default() -> DriverProfile {
+        DriverProfile::new(Version(Api::Gl, 3, 3))
+    }
+}
+
+/// An OpenGL function call captured by a `RecordingBackend`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// Name of the function, for example `glDrawArrays`.
+    pub name: &'static str,
+
+    /// Arguments of the call, in the order of the function's declaration.
+    pub args: Vec<Arg>,
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}(", self.name)?;
+        for (num, arg) in self.args.iter().enumerate() {
+            if num != 0 {
+                write!(fmt, ", ")?;
+            }
+            write!(fmt, "{}", arg)?;
+        }
+        write!(fmt, ")")
+    }
+}
+
+/// Value of an argument of a recorded call.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Arg {
+    /// A signed integer, for example a `GLint` or a `GLsizei`.
+    Int(i64),
+
+    /// An unsigned integer. This includes `GLenum`s, `GLbitfield`s, `GLboolean`s and object
+    /// names.
+    UInt(u64),
+
+    /// A `GLfloat` or a `GLdouble`.
+    Float(f64),
+
+    /// The address of a pointer. The data it points to is only valid during the call and isn't
+    /// recorded.
+    Pointer(usize),
+}
+
+impl Arg {
+    /// Returns the value as an unsigned integer, reinterpreting signed integers and pointers.
+    /// Returns `None` for floats.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Arg::Int(val) => Some(val as u64),
+            Arg::UInt(val) => Some(val),
+            Arg::Float(_) => None,
+            Arg::Pointer(val) => Some(val as u64),
+        }
+    }
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Arg::Int(val) => write!(fmt, "{}", val),
+            Arg::UInt(val) => write!(fmt, "{:#x}", val),
+            Arg::Float(val) => write!(fmt, "{:?}", val),
+            Arg::Pointer(0) => write!(fmt, "NULL"),
+            Arg::Pointer(val) => write!(fmt, "{:#x}", val),
+        }
+    }
+}
+
+/// Backend whose OpenGL functions record their calls instead of executing them.
+///
+/// Wrap it in an `Rc` and keep a clone in order to inspect the calls after creating the
+/// `Context`.
+pub struct RecordingBackend {
+    driver: Rc<Driver>,
+    dimensions: (u32, u32),
+}
+
+impl RecordingBackend {
+    /// Builds a new backend that pretends to be the implementation described by `profile`,
+    /// with a default framebuffer of the given dimensions.
+    pub fn new(profile: DriverProfile, dimensions: (u32, u32)) -> RecordingBackend {
+        RecordingBackend {
+            driver: Rc::new(Driver {
+                calls: RefCell::new(Vec::new()),
+                state: RefCell::new(DriverState::new(profile)),
+            }),
+            dimensions,
+        }
+    }
+
+    /// Returns the calls recorded so far.
+    #[inline]
+    pub fn calls(&self) -> Vec<Call> {
+        self.driver.calls.borrow().clone()
+    }
+
+    /// Returns the calls recorded so far and clears the trace.
+    #[inline]
+    pub fn take_calls(&self) -> Vec<Call> {
+        mem::take(&mut *self.driver.calls.borrow_mut())
+    }
+
+    /// Clears the trace.
+    #[inline]
+    pub fn clear_calls(&self) {
+        self.driver.calls.borrow_mut().clear();
+    }
+}
+
+unsafe impl Backend for RecordingBackend {
+    #[inline]
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        stubs::get_proc_address(symbol)
+    }
+
+    #[inline]
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        CURRENT.with(|current| {
+            current.borrow().as_ref().is_some_and(|driver| Rc::ptr_eq(driver, &self.driver))
+        })
+    }
+
+    #[inline]
+    unsafe fn make_current(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.driver.clone()));
+    }
+}
+
+/// Called by all the stubs. Records the call in the current driver and returns the raw value
+/// that the stub must return.
+fn dispatch(name: &'static str, args: &[Arg]) -> u64 {
+    CURRENT.with(|current| {
+        match *current.borrow() {
+            Some(ref driver) => {
+                driver.calls.borrow_mut().push(Call { name, args: args.to_vec() });
+                let reply = unsafe { driver.state.borrow_mut().reply(name, args) };
+
+                // the callback is called without borrowing the state, as it is allowed to call
+                // OpenGL functions
+                if name.starts_with("glDebugMessageInsert") {
+                    let callback = driver.state.borrow().debug_callback;
+                    if let Some((callback, user_param)) = callback {
+                        unsafe { insert_debug_message(callback, user_param, args) };
+                    }
+                }
+
+                reply
+            },
+            None => 0,
+        }
+    })
+}
+
+/// Conversion from the arguments of a stub.
+pub(crate) trait IntoArg {
+    fn into_arg(self) -> Arg;
+}
+
+macro_rules! impl_into_arg {
+    ($variant:ident, $as:ty, $($ty:ty),+) => (
+        $(
+            impl IntoArg for $ty {
+                #[inline]
+                fn into_arg(self) -> Arg {
+                    Arg::$variant(self as $as)
+                }
+            }
+        )+
+    );
+}
+
+impl_into_arg!(Int, i64, i8, i16, i32, i64, isize);
+impl_into_arg!(UInt, u64, u8, u16, u32, u64, usize);
+impl_into_arg!(Float, f64, f32, f64);
+
+impl<T> IntoArg for *const T {
+    #[inline]
+    fn into_arg(self) -> Arg {
+        Arg::Pointer(self as usize)
+    }
+}
+
+impl<T> IntoArg for *mut T {
+    #[inline]
+    fn into_arg(self) -> Arg {
+        Arg::Pointer(self as usize)
+    }
+}
+
+// Debug callbacks are passed as `Option<extern "system" fn(...)>`.
+impl<T: Copy> IntoArg for Option<T> {
+    #[inline]
+    fn into_arg(self) -> Arg {
+        assert_eq!(mem::size_of::<T>(), mem::size_of::<usize>());
+        match self {
+            Some(f) => Arg::Pointer(unsafe { mem::transmute_copy::<T, usize>(&f) }),
+            None => Arg::Pointer(0),
+        }
+    }
+}
+
+/// Conversion to the return value of a stub.
+trait FromReply {
+    fn from_reply(reply: u64) -> Self;
+}
+
+impl FromReply for () {
+    #[inline]
+    fn from_reply(_: u64) {}
+}
+
+macro_rules! impl_from_reply {
+    ($($ty:ty),+) => (
+        $(
+            impl FromReply for $ty {
+                #[inline]
+                fn from_reply(reply: u64) -> $ty {
+                    reply as $ty
+                }
+            }
+        )+
+    );
+}
+
+impl_from_reply!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T> FromReply for *const T {
+    #[inline]
+    fn from_reply(reply: u64) -> *const T {
+        reply as usize as *const T
+    }
+}
+
+impl<T> FromReply for *mut T {
+    #[inline]
+    fn from_reply(reply: u64) -> *mut T {
+        reply as usize as *mut T
+    }
+}
+
+struct Driver {
+    calls: RefCell<Vec<Call>>,
+    state: RefCell<DriverState>,
+}
+
+/// The objects and values that the stubs need to remember between calls.
+struct DriverState {
+    profile: DriverProfile,
+
+    /// Values returned by `glGetString`.
+    strings: HashMap<u32, CString>,
+
+    /// Values returned by `glGetStringi(GL_EXTENSIONS, ...)`.
+    extensions: Vec<CString>,
+
+    /// The next name returned by `glGen*` and `glCreate*`.
+    next_name: u32,
+
+    /// Buffer bound to each target.
+    buffer_bindings: HashMap<u32, u32>,
+
+    /// Content of each buffer.
+    buffers: HashMap<u32, Vec<u8>>,
+
+    /// The function and the user parameter passed to `glDebugMessageCallback`.
+    debug_callback: Option<(usize, usize)>,
+
+    /// The simulated GPU clock, in nanoseconds.
+    clock: u64,
+
+    /// Timestamps recorded by `glQueryCounter`.
+    timestamps: HashMap<u32, u64>,
+
+    /// Programs whose binary has been rejected by `glProgramBinary`.
+    rejected_programs: HashSet<u32>,
+}
+
+impl DriverState {
+    fn new(profile: DriverProfile) -> DriverState {
+        let Version(api, major, minor) = profile.version;
+
+        let version = match api {
+            Api::Gl => format!("{}.{} glium", major, minor),
+            Api::GlEs => format!("OpenGL ES {}.{} glium", major, minor),
+        };
+
+        let glsl = crate::get_supported_glsl_version(&profile.version);
+        let glsl = match api {
+            Api::Gl => format!("{}.{}0", glsl.1, glsl.2),
+            Api::GlEs => format!("OpenGL ES GLSL ES {}.{}0", glsl.1, glsl.2),
+        };
+
+        let mut strings = HashMap::new();
+        strings.insert(gl::VERSION, CString::new(version).unwrap());
+        strings.insert(gl::SHADING_LANGUAGE_VERSION, CString::new(glsl).unwrap());
+        strings.insert(gl::VENDOR, CString::new(profile.vendor.clone()).unwrap());
+        strings.insert(gl::RENDERER, CString::new(profile.renderer.clone()).unwrap());
+        strings.insert(gl::EXTENSIONS, CString::new(profile.extensions.join(" ")).unwrap());
+
+        let extensions = profile.extensions.iter()
+                                           .map(|e| CString::new(e.clone()).unwrap())
+                                           .collect();
+
+        DriverState {
+            profile,
+            strings,
+            extensions,
+            next_name: 1,
+            buffer_bindings: HashMap::new(),
+            buffers: HashMap::new(),
+            debug_callback: None,
+            clock: 0,
+            timestamps: HashMap::new(),
+            rejected_programs: HashSet::new(),
+        }
+    }
+
+    /// Returns the values of a `glGet*` query.
+    fn query(&self, pname: u32) -> Vec<i64> {
+        if let Some(values) = self.profile.limits.get(&pname) {
+            return values.clone();
+        }
+
+        match pname {
+            gl::MAJOR_VERSION => vec![self.profile.version.1 as i64],
+            gl::MINOR_VERSION => vec![self.profile.version.2 as i64],
+            gl::NUM_EXTENSIONS => vec![self.extensions.len() as i64],
+            gl::CONTEXT_PROFILE_MASK => vec![match self.profile.profile {
+                Some(Profile::Core) => gl::CONTEXT_CORE_PROFILE_BIT as i64,
+                Some(Profile::Compatibility) => gl::CONTEXT_COMPATIBILITY_PROFILE_BIT as i64,
+                None => 0,
+            }],
+            gl::CONTEXT_FLAGS => vec![if self.profile.debug {
+                gl::CONTEXT_FLAG_DEBUG_BIT as i64
+            } else {
+                0
+            }],
+            gl::TIMESTAMP => vec![self.clock as i64],
+            gl::NUM_PROGRAM_BINARY_FORMATS => vec![1],
+            gl::PROGRAM_BINARY_FORMATS => vec![PROGRAM_BINARY_FORMAT as i64],
+            gl::ACTIVE_TEXTURE => vec![gl::TEXTURE0 as i64],
+            _ => Vec::new(),
+        }
+    }
+
+    fn gen_name(&mut self) -> u32 {
+        let name = self.next_name;
+        self.next_name += 1;
+        name
+    }
+
+    fn bound_buffer(&self, target: u64) -> u32 {
+        self.buffer_bindings.get(&(target as u32)).cloned().unwrap_or(0)
+    }
+
+    /// Returns the buffer designated by the first argument of a function, which is either a
+    /// buffer name for DSA functions or a bind point.
+    fn buffer_argument(&self, function: &str, arg: u64) -> u32 {
+        if function.contains("Named") {
+            arg as u32
+        } else {
+            self.bound_buffer(arg)
+        }
+    }
+
+    /// Returns the content of a buffer in the given range, or `None` if out of bounds.
+    fn buffer_range(&mut self, id: u32, offset: i64, size: i64) -> Option<&mut [u8]> {
+        if offset < 0 || size < 0 {
+            return None;
+        }
+
+        let content = self.buffers.get_mut(&id)?;
+        content.get_mut(offset as usize .. (offset + size) as usize)
+    }
+
+    /// Handles a call and returns the raw return value.
+    ///
+    /// # Safety
+    ///
+    /// The pointers in the arguments must follow the rules of the OpenGL specifications.
+    unsafe fn reply(&mut self, name: &str, args: &[Arg]) -> u64 {
+        let uint = |num: usize| args.get(num).and_then(Arg::as_u64).unwrap_or(0);
+        let int = |num: usize| uint(num) as i64;
+        let pointer = |num: usize| uint(num) as usize;
+
+        self.clock += 1000;
+
+        match name {
+            "glGetError" | "glGetGraphicsResetStatus" | "glGetGraphicsResetStatusARB" |
+            "glGetGraphicsResetStatusEXT" | "glGetGraphicsResetStatusKHR" => gl::NO_ERROR as u64,
+
+            "glGetString" => {
+                self.strings.get(&(uint(0) as u32)).map_or(0, |s| s.as_ptr() as u64)
+            },
+
+            "glGetStringi" if uint(0) as u32 == gl::EXTENSIONS => {
+                self.extensions.get(uint(1) as usize).map_or(0, |s| s.as_ptr() as u64)
+            },
+
+            "glGetIntegerv" | "glGetInteger64v" | "glGetBooleanv" | "glGetFloatv" |
+            "glGetDoublev" => {
+                let values = self.query(uint(0) as u32);
+                write_values(name, pointer(1), &values);
+                0
+            },
+
+            "glGetIntegeri_v" | "glGetInteger64i_v" | "glGetBooleani_v" | "glGetFloati_v" |
+            "glGetDoublei_v" => {
+                let values = self.query(uint(0) as u32);
+                if let Some(&value) = values.get(uint(1) as usize) {
+                    write_values(name, pointer(2), &[value]);
+                }
+                0
+            },
+
+            "glCreateShader" | "glCreateProgram" | "glCreateShaderObjectARB" |
+            "glCreateProgramObjectARB" | "glCreateShaderProgramv" | "glCreateShaderProgramEXT" |
+            "glGenLists" | "glFenceSync" | "glFenceSyncAPPLE" | "glGetTextureHandleARB" |
+            "glGetTextureSamplerHandleARB" | "glGetImageHandleARB" => self.gen_name() as u64,
+
+            _ if (name.starts_with("glGen") || name.starts_with("glCreate")) && args.len() == 2 &&
+                 matches!(args[1], Arg::Pointer(_)) =>
+            {
+                let output = pointer(1) as *mut u32;
+                for num in 0 .. int(0).max(0) as usize {
+                    *output.add(num) = self.gen_name();
+                }
+                0
+            },
+
+            "glGetShaderiv" | "glGetProgramiv" | "glGetProgramPipelineiv" |
+            "glGetObjectParameterivARB" => {
+                let value = match uint(1) as u32 {
+                    gl::LINK_STATUS if self.rejected_programs.contains(&(uint(0) as u32)) => {
+                        gl::FALSE as u32
+                    },
+                    gl::COMPILE_STATUS | gl::LINK_STATUS | gl::VALIDATE_STATUS |
+                    gl::COMPLETION_STATUS_KHR => gl::TRUE as u32,
+                    gl::PROGRAM_BINARY_LENGTH => self.program_binary().len() as u32,
+                    _ => 0,
+                };
+                *(pointer(2) as *mut i32) = value as i32;
+                0
+            },
+
+            "glGetProgramBinary" => {
+                let binary = self.program_binary();
+                let len = binary.len().min(int(1).max(0) as usize);
+                ptr::copy_nonoverlapping(binary.as_ptr(), pointer(4) as *mut u8, len);
+                if pointer(2) != 0 {
+                    *(pointer(2) as *mut i32) = len as i32;
+                }
+                *(pointer(3) as *mut u32) = PROGRAM_BINARY_FORMAT;
+                0
+            },
+
+            "glProgramBinary" => {
+                let binary = slice::from_raw_parts(pointer(2) as *const u8, int(3).max(0) as usize);
+                if uint(1) as u32 != PROGRAM_BINARY_FORMAT || binary != self.program_binary() {
+                    self.rejected_programs.insert(uint(0) as u32);
+                }
+                0
+            },
+
+            "glGetUniformLocation" | "glGetUniformLocationARB" | "glGetAttribLocation" |
+            "glGetAttribLocationARB" | "glGetFragDataLocation" | "glGetFragDataIndex" |
+            "glGetSubroutineUniformLocation" | "glGetSubroutineIndex" |
+            "glGetUniformBlockIndex" | "glGetProgramResourceIndex" |
+            "glGetProgramResourceLocation" => u64::MAX,
+
+            "glCheckFramebufferStatus" | "glCheckFramebufferStatusEXT" |
+            "glCheckNamedFramebufferStatus" | "glCheckNamedFramebufferStatusEXT" => {
+                gl::FRAMEBUFFER_COMPLETE as u64
+            },
+
+            "glClientWaitSync" | "glClientWaitSyncAPPLE" => gl::ALREADY_SIGNALED as u64,
+
+            "glQueryCounter" | "glQueryCounterEXT" => {
+                self.timestamps.insert(uint(0) as u32, self.clock);
+                0
+            },
+
+            _ if name.starts_with("glGetQueryObject") => {
+                let value = match uint(1) as u32 {
+                    gl::QUERY_RESULT_AVAILABLE => 1,
+                    gl::QUERY_RESULT => self.timestamps.get(&(uint(0) as u32)).cloned()
+                                                       .unwrap_or(0),
+                    _ => 0,
+                };
+                if name.contains("64") {
+                    *(pointer(2) as *mut u64) = value;
+                } else {
+                    *(pointer(2) as *mut u32) = value as u32;
+                }
+                0
+            },
+
+            "glBindBuffer" | "glBindBufferARB" => {
+                self.buffer_bindings.insert(uint(0) as u32, uint(1) as u32);
+                0
+            },
+
+            "glBindBufferBase" | "glBindBufferRange" | "glBindBufferBaseEXT" |
+            "glBindBufferRangeEXT" => {
+                self.buffer_bindings.insert(uint(0) as u32, uint(2) as u32);
+                0
+            },
+
+            "glDeleteBuffers" | "glDeleteBuffersARB" => {
+                let ids = pointer(1) as *const u32;
+                for num in 0 .. int(0).max(0) as usize {
+                    self.buffers.remove(&*ids.add(num));
+                }
+                0
+            },
+
+            "glBufferData" | "glBufferDataARB" | "glBufferStorage" | "glBufferStorageEXT" => {
+                let id = self.bound_buffer(uint(0));
+                self.set_buffer_data(id, int(1), pointer(2) as *const u8);
+                0
+            },
+
+            "glNamedBufferData" | "glNamedBufferDataEXT" | "glNamedBufferStorage" |
+            "glNamedBufferStorageEXT" => {
+                self.set_buffer_data(uint(0) as u32, int(1), pointer(2) as *const u8);
+                0
+            },
+
+            "glBufferSubData" | "glBufferSubDataARB" | "glNamedBufferSubData" |
+            "glNamedBufferSubDataEXT" => {
+                let id = self.buffer_argument(name, uint(0));
+                if let Some(content) = self.buffer_range(id, int(1), int(2)) {
+                    ptr::copy_nonoverlapping(pointer(3) as *const u8, content.as_mut_ptr(),
+                                             content.len());
+                }
+                0
+            },
+
+            "glGetBufferSubData" | "glGetBufferSubDataARB" | "glGetNamedBufferSubData" |
+            "glGetNamedBufferSubDataEXT" => {
+                let id = self.buffer_argument(name, uint(0));
+                if let Some(content) = self.buffer_range(id, int(1), int(2)) {
+                    ptr::copy_nonoverlapping(content.as_ptr(), pointer(3) as *mut u8,
+                                             content.len());
+                }
+                0
+            },
+
+            "glCopyBufferSubData" | "glCopyBufferSubDataNV" | "glCopyNamedBufferSubData" => {
+                let (read, write) = if name == "glCopyNamedBufferSubData" {
+                    (uint(0) as u32, uint(1) as u32)
+                } else {
+                    (self.bound_buffer(uint(0)), self.bound_buffer(uint(1)))
+                };
+
+                let data = self.buffer_range(read, int(2), int(4)).map(|c| c.to_vec());
+                if let (Some(data), Some(dest)) = (data, self.buffer_range(write, int(3), int(4))) {
+                    dest.copy_from_slice(&data);
+                }
+                0
+            },
+
+            "glGetBufferParameteriv" | "glGetBufferParameterivARB" |
+            "glGetNamedBufferParameteriv" | "glGetNamedBufferParameterivEXT" => {
+                let id = self.buffer_argument(name, uint(0));
+                if uint(1) as u32 == gl::BUFFER_SIZE {
+                    let size = self.buffers.get(&id).map_or(0, |c| c.len());
+                    *(pointer(2) as *mut i32) = size as i32;
+                }
+                0
+            },
+
+            "glMapBufferRange" | "glMapBufferRangeEXT" | "glMapNamedBufferRange" |
+            "glMapNamedBufferRangeEXT" => {
+                let id = self.buffer_argument(name, uint(0));
+                self.buffer_range(id, int(1), int(2)).map_or(0, |c| c.as_mut_ptr() as u64)
+            },
+
+            "glMapBuffer" | "glMapBufferARB" | "glMapBufferOES" | "glMapNamedBuffer" |
+            "glMapNamedBufferEXT" => {
+                let id = self.buffer_argument(name, uint(0));
+                self.buffers.get_mut(&id).map_or(0, |c| c.as_mut_ptr() as u64)
+            },
+
+            "glDebugMessageCallback" | "glDebugMessageCallbackKHR" |
+            "glDebugMessageCallbackARB" => {
+                self.debug_callback = match pointer(0) {
+                    0 => None,
+                    callback => Some((callback, pointer(1))),
+                };
+                0
+            },
+
+            "glUnmapBuffer" | "glUnmapBufferARB" | "glUnmapBufferOES" | "glUnmapNamedBuffer" |
+            "glUnmapNamedBufferEXT" => gl::TRUE as u64,
+
+            _ => 0,
+        }
+    }
+
+    /// Returns the binary that `glGetProgramBinary` returns for every program. `glProgramBinary`
+    /// rejects the binaries built by a driver with a different renderer string.
+    fn program_binary(&self) -> &[u8] {
+        self.profile.renderer.as_bytes()
+    }
+
+    /// Replaces the content of a buffer, copying `data` if it isn't null.
+    unsafe fn set_buffer_data(&mut self, id: u32, size: i64, data: *const u8) {
+        let mut content = vec![0; size.max(0) as usize];
+        if !data.is_null() {
+            ptr::copy_nonoverlapping(data, content.as_mut_ptr(), content.len());
+        }
+        self.buffers.insert(id, content);
+    }
+}
+
+/// Writes the result of a `glGet*` query with the type that corresponds to the function.
+unsafe fn write_values(function: &str, output: usize, values: &[i64]) {
+    for (num, &value) in values.iter().enumerate() {
+        if function.starts_with("glGetInteger64") {
+            *(output as *mut i64).add(num) = value;
+        } else if function.starts_with("glGetInteger") {
+            *(output as *mut i32).add(num) = value as i32;
+        } else if function.starts_with("glGetBoolean") {
+            *(output as *mut u8).add(num) = (value != 0) as u8;
+        } else if function.starts_with("glGetFloat") {
+            *(output as *mut f32).add(num) = value as f32;
+        } else if function.starts_with("glGetDouble") {
+            *(output as *mut f64).add(num) = value as f64;
+        }
+    }
+}
+
+/// Sends the message passed to `glDebugMessageInsert` to the debug callback.
+///
+/// # Safety
+///
+/// `callback` must be a `GLDEBUGPROC` and the arguments must follow the rules of the OpenGL
+/// specifications.
+unsafe fn insert_debug_message(callback: usize, user_param: usize, args: &[Arg]) {
+    let uint = |num: usize| args.get(num).and_then(Arg::as_u64).unwrap_or(0);
+
+    let message = uint(5) as *const u8;
+    let message = match uint(4) as i32 {
+        length if length < 0 => CStr::from_ptr(message as *const _).to_owned(),
+        length => {
+            let bytes = std::slice::from_raw_parts(message, length as usize);
+            match CString::new(bytes) {
+                Ok(message) => message,
+                Err(_) => return,
+            }
+        },
+    };
+
+    let callback: extern "system" fn(gl::types::GLenum, gl::types::GLenum, gl::types::GLuint,
+                                     gl::types::GLenum, gl::types::GLsizei,
+                                     *const gl::types::GLchar, *mut c_void)
+        = mem::transmute(callback);
+
+    callback(uint(0) as u32, uint(1) as u32, uint(2) as u32, uint(3) as u32,
+             message.as_bytes().len() as gl::types::GLsizei, message.as_ptr(),
+             user_param as *mut c_void);
+}
@@ -1,6 +1,7 @@
 use crate::backend::Facade;
 use crate::context::CommandContext;
 use crate::context::Context;
+use crate::context::ResourceKind;
 use crate::version::Version;
 use crate::CapabilitiesSource;
 use crate::ContextExt;
@@ -12,6 +13,7 @@ use std::cell::Cell;
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut, Range};
 use crate::GlObject;
+use crate::Handle;
 use crate::TransformFeedbackSessionExt;
 
 use crate::buffer::{Content, BufferType, BufferMode, BufferCreationError};
@@ -95,8 +97,26 @@ pub struct Alloc {
 
     /// ID of the draw call where the buffer was last written as an SSBO.
     latest_shader_write: Cell<u64>,
+
+    /// If false, the buffer has been created outside of glium and isn't deleted on drop.
+    owned: bool,
 }
 
+/// The parts of an `Alloc` that don't depend on its context. See `Alloc::into_raw`.
+pub struct RawAlloc {
+    id: gl::types::GLuint,
+    ty: BufferType,
+    size: usize,
+    persistent_mapping: Option<*mut raw::c_void>,
+    immutable: bool,
+    creation_mode: BufferMode,
+    created_with_buffer_storage: bool,
+    owned: bool,
+}
+
+// the persistent mapping is valid in all the contexts that share the buffer
+unsafe impl Send for RawAlloc {}
+
 impl Alloc {
     /// Builds a new buffer containing the given data. The size of the buffer is equal to the
     /// size of the data.
@@ -123,6 +143,7 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            owned: true,
         })
     }
 
@@ -147,6 +168,165 @@ impl Alloc {
             creation_mode: mode,
             mapped: Cell::new(false),
             latest_shader_write: Cell::new(0),
+            owned: true,
+        })
+    }
+
+    /// Removes the buffer from its context without destroying it, so that it can be adopted by
+    /// another context of the same share group with `from_raw`.
+    pub fn into_raw(self) -> RawAlloc {
+        {
+            let mut ctxt = self.context.make_current();
+            self.assert_unmapped(&mut ctxt);
+            self.assert_not_transform_feedback(&mut ctxt);
+            VertexAttributesSystem::purge_buffer(&mut ctxt, self.id);
+            if self.owned {
+                ctxt.resources.destroyed(ResourceKind::Buffer, Handle::Id(self.id));
+            }
+            unsafe { release_buffer(&mut ctxt, self.id) };
+        }
+
+        let raw = RawAlloc {
+            id: self.id,
+            ty: self.ty,
+            size: self.size,
+            persistent_mapping: self.persistent_mapping,
+            immutable: self.immutable,
+            creation_mode: self.creation_mode,
+            created_with_buffer_storage: self.created_with_buffer_storage,
+            owned: self.owned,
+        };
+
+        // skipping the destructor, which would delete the buffer
+        let this = mem::ManuallyDrop::new(self);
+        drop(unsafe { ptr::read(&this.context) });
+        raw
+    }
+
+    /// Adopts a buffer returned by `into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// The context of the facade must share its objects with the context that the buffer was
+    /// removed from.
+    pub unsafe fn from_raw<F>(facade: &F, raw: RawAlloc) -> Alloc where F: Facade + ?Sized {
+        if raw.owned {
+            let ctxt = facade.get_context().make_current();
+            ctxt.resources.created(ResourceKind::Buffer, Handle::Id(raw.id), raw.size);
+        }
+
+        Alloc {
+            context: facade.get_context().clone(),
+            id: raw.id,
+            ty: raw.ty,
+            size: raw.size,
+            persistent_mapping: raw.persistent_mapping,
+            immutable: raw.immutable,
+            created_with_buffer_storage: raw.created_with_buffer_storage,
+            creation_mode: raw.creation_mode,
+            mapped: Cell::new(false),
+            latest_shader_write: Cell::new(0),
+            owned: raw.owned,
+        }
+    }
+
+    /// Builds a buffer from an existing, externally created OpenGL buffer. The size and the
+    /// storage of the buffer are queried from the backend.
+    ///
+    /// If `owned` is true, the buffer is destroyed when the `Alloc` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a buffer of the context of the facade whose storage has been
+    /// allocated, and the buffer must not be mapped.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, ty: BufferType, owned: bool)
+                             -> Result<Alloc, BufferCreationError> where F: Facade + ?Sized
+    {
+        let mut ctxt = facade.get_context().make_current();
+
+        if !is_buffer_type_supported(&mut ctxt, ty) {
+            return Err(BufferCreationError::BufferTypeNotSupported);
+        }
+
+        let mut size = 0;
+        let mut usage = 0;
+        let mut immutable_storage = 0;
+        let mut storage_flags = 0;
+
+        let storage_supported = ctxt.version >= &Version(Api::Gl, 4, 4) ||
+                                ctxt.extensions.gl_arb_buffer_storage ||
+                                ctxt.extensions.gl_ext_buffer_storage;
+
+        if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+            ctxt.gl.GetNamedBufferParameteriv(id, gl::BUFFER_SIZE, &mut size);
+            ctxt.gl.GetNamedBufferParameteriv(id, gl::BUFFER_USAGE, &mut usage);
+            ctxt.gl.GetNamedBufferParameteriv(id, gl::BUFFER_IMMUTABLE_STORAGE,
+                                              &mut immutable_storage);
+            ctxt.gl.GetNamedBufferParameteriv(id, gl::BUFFER_STORAGE_FLAGS, &mut storage_flags);
+
+        } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                  ctxt.version >= &Version(Api::GlEs, 2, 0)
+        {
+            let bind = bind_buffer(&mut ctxt, id, ty);
+            ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_SIZE, &mut size);
+            ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_USAGE, &mut usage);
+            if storage_supported {
+                ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_IMMUTABLE_STORAGE,
+                                             &mut immutable_storage);
+                ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_STORAGE_FLAGS, &mut storage_flags);
+            }
+
+        } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+            let bind = bind_buffer(&mut ctxt, id, ty);
+            ctxt.gl.GetBufferParameterivARB(bind, gl::BUFFER_SIZE, &mut size);
+            ctxt.gl.GetBufferParameterivARB(bind, gl::BUFFER_USAGE, &mut usage);
+
+        } else {
+            unreachable!();
+        }
+
+        let size = size as usize;
+        let storage_flags = storage_flags as gl::types::GLbitfield;
+        let created_with_buffer_storage = immutable_storage != 0;
+
+        // buffers that can't be modified with `glBufferSubData` or mapped for reading and writing
+        // are treated like the ones created with `BufferMode::Immutable`
+        let immutable = created_with_buffer_storage &&
+                        (storage_flags & gl::DYNAMIC_STORAGE_BIT == 0 ||
+                         storage_flags & (gl::MAP_READ_BIT | gl::MAP_WRITE_BIT) !=
+                             gl::MAP_READ_BIT | gl::MAP_WRITE_BIT);
+
+        let creation_mode = if immutable {
+            BufferMode::Immutable
+        } else if created_with_buffer_storage {
+            if storage_flags & gl::CLIENT_STORAGE_BIT != 0 {
+                BufferMode::Dynamic
+            } else {
+                BufferMode::Default
+            }
+        } else {
+            match usage as gl::types::GLenum {
+                gl::STATIC_DRAW | gl::STATIC_READ | gl::STATIC_COPY => BufferMode::Default,
+                _ => BufferMode::Dynamic,
+            }
+        };
+
+        if owned {
+            ctxt.resources.created(ResourceKind::Buffer, Handle::Id(id), size);
+        }
+
+        Ok(Alloc {
+            context: facade.get_context().clone(),
+            id,
+            ty,
+            size,
+            persistent_mapping: None,
+            immutable,
+            created_with_buffer_storage,
+            creation_mode,
+            mapped: Cell::new(false),
+            latest_shader_write: Cell::new(0),
+            owned,
         })
     }
 
@@ -874,7 +1054,12 @@ impl Drop for Alloc {
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
             VertexAttributesSystem::purge_buffer(&mut ctxt, self.id);
-            destroy_buffer(&mut ctxt, self.id);
+
+            if self.owned {
+                destroy_buffer(&mut ctxt, self.id);
+            } else {
+                unbind_buffer(&mut ctxt, self.id);
+            }
         }
     }
 }
@@ -1304,6 +1489,8 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext<'_>, size: usiz
         None
     };
 
+    ctxt.resources.created(ResourceKind::Buffer, Handle::Id(id), size);
+
     Ok((id, immutable, created_with_buffer_storage, persistent_mapping))
 }
 
@@ -1484,7 +1671,7 @@ unsafe fn indexed_bind_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLui
 
     macro_rules! check {
         ($ctxt:expr, $input_id:expr, $input_ty:expr, $input_index:expr, $check:ident,
-         $state_var:ident, $max:ident) =>
+         $state_var:ident, $max:ident $(, $generic_state_var:ident)?) =>
         (
             if $input_ty == BufferType::$check {
                 let en = $input_ty.to_glenum();
@@ -1514,6 +1701,9 @@ unsafe fn indexed_bind_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLui
                     } else {
                         panic!("The backend doesn't support indexed buffer bind points");
                     }
+
+                    // binding to an indexed bind point also binds to the generic bind point
+                    $(ctxt.state.$generic_state_var = $input_id;)?
                 }
 
                 return;
@@ -1522,13 +1712,13 @@ unsafe fn indexed_bind_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLui
     }
 
     check!(ctxt, id, ty, index, UniformBuffer, indexed_uniform_buffer_bindings,
-           max_indexed_uniform_buffer);
+           max_indexed_uniform_buffer, uniform_buffer_binding);
     check!(ctxt, id, ty, index, TransformFeedbackBuffer, indexed_transform_feedback_buffer_bindings,
            max_indexed_transform_feedback_buffer);
     check!(ctxt, id, ty, index, AtomicCounterBuffer, indexed_atomic_counter_buffer_bindings,
-           max_indexed_atomic_counter_buffer);
+           max_indexed_atomic_counter_buffer, atomic_counter_buffer_binding);
     check!(ctxt, id, ty, index, ShaderStorageBuffer, indexed_shader_storage_buffer_bindings,
-           max_indexed_shader_storage_buffer);
+           max_indexed_shader_storage_buffer, shader_storage_buffer_binding);
 
     panic!();
 }
@@ -1619,9 +1809,26 @@ unsafe fn copy_buffer(ctxt: &mut CommandContext<'_>, source: gl::types::GLuint,
 
 /// Destroys a buffer.
 unsafe fn destroy_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLuint) {
+    ctxt.resources.destroyed(ResourceKind::Buffer, Handle::Id(id));
+
     // FIXME: uncomment this and move it from Buffer's destructor
     //self.context.vertex_array_objects.purge_buffer(&mut ctxt, id);
 
+    unbind_buffer(ctxt, id);
+
+    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+        ctxt.version >= &Version(Api::GlEs, 2, 0)
+    {
+        ctxt.gl.DeleteBuffers(1, [id].as_ptr());
+    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+        ctxt.gl.DeleteBuffersARB(1, [id].as_ptr());
+    } else {
+        unreachable!();
+    }
+}
+
+/// Removes a buffer from the bind points of the state cache.
+unsafe fn unbind_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLuint) {
     if ctxt.state.array_buffer_binding == id {
         ctxt.state.array_buffer_binding = 0;
     }
@@ -1694,16 +1901,76 @@ unsafe fn destroy_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLuint) {
             point.buffer = 0;
         }
     }
+}
 
-    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
-        ctxt.version >= &Version(Api::GlEs, 2, 0)
-    {
-        ctxt.gl.DeleteBuffers(1, [id].as_ptr());
-    } else if ctxt.extensions.gl_arb_vertex_buffer_object {
-        ctxt.gl.DeleteBuffersARB(1, [id].as_ptr());
-    } else {
-        unreachable!();
+/// Unbinds a buffer from the bind points where the state cache has it, both in the state cache
+/// and in the OpenGL context.
+///
+/// Contrary to `destroy_buffer`, the buffer isn't deleted and OpenGL doesn't unbind it
+/// automatically.
+unsafe fn release_buffer(ctxt: &mut CommandContext<'_>, id: gl::types::GLuint) {
+    macro_rules! indexed {
+        ($target:expr, $state_var:ident $(, $generic_state_var:ident)?) => (
+            for index in 0 .. ctxt.state.$state_var.len() {
+                if ctxt.state.$state_var[index].buffer != id {
+                    continue;
+                }
+
+                ctxt.state.$state_var[index] = Default::default();
+
+                if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                   ctxt.version >= &Version(Api::GlEs, 3, 0)
+                {
+                    ctxt.gl.BindBufferBase($target, index as gl::types::GLuint, 0);
+                } else if ctxt.extensions.gl_ext_transform_feedback {
+                    ctxt.gl.BindBufferBaseEXT($target, index as gl::types::GLuint, 0);
+                } else {
+                    unreachable!();
+                }
+
+                // unbinding from an indexed bind point also unbinds from the generic bind point
+                $(ctxt.state.$generic_state_var = 0;)?
+            }
+        );
     }
+
+    macro_rules! generic {
+        ($target:expr, $state_var:ident) => (
+            if ctxt.state.$state_var == id {
+                ctxt.state.$state_var = 0;
+
+                if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                   ctxt.version >= &Version(Api::GlEs, 2, 0)
+                {
+                    ctxt.gl.BindBuffer($target, 0);
+                } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                    ctxt.gl.BindBufferARB($target, 0);
+                } else {
+                    unreachable!();
+                }
+            }
+        );
+    }
+
+    indexed!(gl::UNIFORM_BUFFER, indexed_uniform_buffer_bindings, uniform_buffer_binding);
+    indexed!(gl::TRANSFORM_FEEDBACK_BUFFER, indexed_transform_feedback_buffer_bindings);
+    indexed!(gl::ATOMIC_COUNTER_BUFFER, indexed_atomic_counter_buffer_bindings,
+             atomic_counter_buffer_binding);
+    indexed!(gl::SHADER_STORAGE_BUFFER, indexed_shader_storage_buffer_bindings,
+             shader_storage_buffer_binding);
+
+    generic!(gl::ARRAY_BUFFER, array_buffer_binding);
+    generic!(gl::PIXEL_PACK_BUFFER, pixel_pack_buffer_binding);
+    generic!(gl::PIXEL_UNPACK_BUFFER, pixel_unpack_buffer_binding);
+    generic!(gl::UNIFORM_BUFFER, uniform_buffer_binding);
+    generic!(gl::COPY_READ_BUFFER, copy_read_buffer_binding);
+    generic!(gl::COPY_WRITE_BUFFER, copy_write_buffer_binding);
+    generic!(gl::DISPATCH_INDIRECT_BUFFER, dispatch_indirect_buffer_binding);
+    generic!(gl::DRAW_INDIRECT_BUFFER, draw_indirect_buffer_binding);
+    generic!(gl::QUERY_BUFFER, query_buffer_binding);
+    generic!(gl::TEXTURE_BUFFER, texture_buffer_binding);
+    generic!(gl::ATOMIC_COUNTER_BUFFER, atomic_counter_buffer_binding);
+    generic!(gl::SHADER_STORAGE_BUFFER, shader_storage_buffer_binding);
 }
 
 /// Flushes a range of a mapped buffer.
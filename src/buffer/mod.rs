@@ -189,6 +189,9 @@ pub enum BufferCreationError {
 
     /// This type of buffer is not supported.
     BufferTypeNotSupported,
+
+    /// The size of an existing buffer doesn't match the content type.
+    SizeMismatch,
 }
 
 impl fmt::Display for BufferCreationError {
@@ -196,6 +199,8 @@ impl fmt::Display for BufferCreationError {
         let desc = match self {
             BufferCreationError::OutOfMemory => "Not enough memory to create the buffer",
             BufferCreationError::BufferTypeNotSupported => "This type of buffer is not supported",
+            BufferCreationError::SizeMismatch =>
+                "The size of the buffer doesn't match the content type",
         };
         fmt.write_str(desc)
     }
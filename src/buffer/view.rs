@@ -6,6 +6,7 @@ use std::marker::PhantomData;
 
 use crate::texture::{PixelValue, Texture1dDataSink};
 use crate::gl;
+use crate::debug;
 
 use crate::backend::Facade;
 use crate::BufferExt;
@@ -30,6 +31,7 @@ use crate::buffer::alloc::WriteMapping;
 use crate::buffer::alloc::ReadError;
 use crate::buffer::alloc::CopyError;
 use crate::field::Field;
+use crate::transfer::Transfer;
 
 /// Represents a view of a buffer.
 pub struct Buffer<T: ?Sized> where T: Content {
@@ -82,12 +84,72 @@ impl<T: ?Sized> Buffer<T> where T: Content {
             })
     }
 
+    /// Builds a buffer from an existing, externally created OpenGL buffer. The size of the buffer
+    /// and the way its storage was allocated are queried from the backend.
+    ///
+    /// If `owned` is true, this buffer will take ownership of the OpenGL buffer and be
+    /// responsible for cleaning it up. Otherwise, the buffer must be cleaned up externally, but
+    /// only after this buffer's lifetime has ended. If an error is returned, the OpenGL buffer
+    /// is never destroyed.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a buffer of the facade's context (or of a context sharing its
+    /// objects) whose storage has been allocated, and the buffer must not be mapped.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, ty: BufferType,
+                             owned: bool) -> Result<Buffer<T>, BufferCreationError>
+                             where F: Facade + ?Sized
+    {
+        let alloc = Alloc::from_id(facade, id, ty, owned)?;
+
+        if !<T as Content>::is_size_suitable(alloc.get_size()) {
+            alloc.into_raw();
+            return Err(BufferCreationError::SizeMismatch);
+        }
+
+        Ok(Buffer {
+            alloc: Some(alloc),
+            fence: Some(Fences::new()),
+            marker: PhantomData,
+        })
+    }
+
     /// Returns the context corresponding to this buffer.
     #[inline]
     pub fn get_context(&self) -> &Rc<Context> {
         self.alloc.as_ref().unwrap().get_context()
     }
 
+    /// Removes the buffer from its context, so that it can be sent to another thread and adopted
+    /// by another context of the same share group. See the `transfer` module.
+    pub fn into_transfer(mut self) -> Transfer<Buffer<T>> where T: 'static {
+        let alloc = self.alloc.take().unwrap();
+        let mut fence = self.fence.take().unwrap();
+
+        let context = alloc.get_context().clone();
+        fence.clean(&mut context.make_current());
+        let raw = alloc.into_raw();
+
+        Transfer::new(&context, move |context| {
+            Buffer {
+                alloc: Some(unsafe { Alloc::from_raw(context, raw) }),
+                fence: Some(Fences::new()),
+                marker: PhantomData,
+            }
+        })
+    }
+
+    /// Attaches a label to the buffer object.
+    ///
+    /// Debugging tools like RenderDoc or apitrace and the messages of the debug output use the
+    /// label to designate the buffer. Does nothing if the backend doesn't support `GL_KHR_debug`.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        let alloc = self.alloc.as_ref().unwrap();
+        let mut ctxt = alloc.get_context().make_current();
+        debug::set_object_label(&mut ctxt, gl::BUFFER, alloc.get_id(), label);
+    }
+
     /// Returns the size in bytes of this buffer.
     #[inline]
     pub fn get_size(&self) -> usize {
@@ -13,10 +13,11 @@ use crate::gl;
 use crate::ToGlEnum;
 
 use crate::CapabilitiesSource;
-use crate::image_format::TextureFormat;
+use crate::image_format::{self, ClientFormat, TextureFormat};
 
 /// Describes the OpenGL context profile.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Profile {
     /// The context uses only future-compatible functions and definitions.
     Core,
@@ -148,8 +149,57 @@ pub struct FormatInfos {
     pub multisamples: Option<Vec<gl::types::GLint>>,
 }
 
+/// Describes what a texture format can be used for. Returned by `Context::get_format_support`.
+///
+/// When the backend supports `GL_ARB_internalformat_query2`, the values are queried from the
+/// driver. Otherwise they are deduced from the version and the extensions, and only report
+/// what is guaranteed to work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatSupport {
+    /// True if textures can be created with this format.
+    pub textures: bool,
+
+    /// True if render buffers can be created with this format.
+    pub render_buffers: bool,
+
+    /// True if textures of this format can be sampled with linear filtering.
+    pub filterable: bool,
+
+    /// True if the format can be attached to a framebuffer as a color attachment.
+    pub color_renderable: bool,
+
+    /// True if the format can be attached to a framebuffer as a depth attachment.
+    pub depth_renderable: bool,
+
+    /// True if the format can be attached to a framebuffer as a stencil attachment.
+    pub stencil_renderable: bool,
+
+    /// True if blending is supported when drawing to this format.
+    pub blendable: bool,
+
+    /// True if textures of this format can be bound to image units for load and store
+    /// operations.
+    pub image_load_store: bool,
+
+    /// True if the mipmaps of textures of this format can be generated automatically.
+    pub mipmap_generation: bool,
+
+    /// The client format that the implementation prefers for uploading data to textures of
+    /// this format, if known.
+    pub preferred_upload_format: Option<ClientFormat>,
+
+    /// True if the implementation prefers the data of `preferred_upload_format` with the red and
+    /// blue components swapped, like `GL_BGRA`. This is the order used by
+    /// `TextureAny::raw_upload_from_pixel_buffer_inverted`.
+    pub preferred_upload_inverted: bool,
+
+    /// True if the values have been queried from the driver.
+    pub queried: bool,
+}
+
 /// Defines what happens when you change the current context.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReleaseBehavior {
     /// Nothing is done when using another context.
     None,
@@ -762,3 +812,146 @@ pub fn get_internal_format(gl: &gl::Gl, version: &Version, extensions: &Extensio
         }
     }
 }
+
+/// Returns what a precise internal format can be used for.
+///
+/// *Safety*: the OpenGL context corresponding to `gl` must be current in the thread.
+pub unsafe fn get_format_support(gl: &gl::Gl, version: &Version, extensions: &ExtensionsList,
+                                 format: TextureFormat) -> FormatSupport
+{
+    // We create a dummy object to implement the `CapabilitiesSource` trait.
+    let dummy = {
+        struct DummyCaps<'a>(&'a Version, &'a ExtensionsList);
+        impl<'a> CapabilitiesSource for DummyCaps<'a> {
+            fn get_version(&self) -> &Version { self.0 }
+            fn get_extensions(&self) -> &ExtensionsList { self.1 }
+            fn get_capabilities(&self) -> &Capabilities { unreachable!() }
+        }
+        DummyCaps(version, extensions)
+    };
+
+    let internal = format.to_glenum();
+    let textures = format.is_supported_for_textures(&dummy);
+    let render_buffers = format.is_supported_for_renderbuffers(&dummy);
+
+    let (is_color, is_depth, is_stencil) = match format {
+        TextureFormat::CompressedFormat(_) | TextureFormat::CompressedSrgbFormat(_) =>
+            (false, false, false),
+        TextureFormat::DepthFormat(_) => (false, true, false),
+        TextureFormat::StencilFormat(_) => (false, false, true),
+        TextureFormat::DepthStencilFormat(_) => (false, true, true),
+        _ => (true, false, false),
+    };
+
+    let is_integral = matches!(format, TextureFormat::UncompressedIntegral(_) |
+                                       TextureFormat::UncompressedUnsigned(_));
+    let is_f32 = matches!(internal, gl::R32F | gl::RG32F | gl::RGB32F | gl::RGBA32F);
+    let is_f16 = matches!(internal, gl::R16F | gl::RG16F | gl::RGB16F | gl::RGBA16F);
+
+    // glium refuses to attach the formats that it doesn't consider renderable
+    let renderable = (textures || render_buffers) && format.is_renderable(&dummy);
+
+    if version >= &Version(Api::Gl, 4, 3) || extensions.gl_arb_internalformat_query2 {
+        let query = |target, pname| {
+            let mut value = 0;
+            gl.GetInternalformativ(target, internal, pname, 1, &mut value);
+            value as gl::types::GLenum
+        };
+
+        let textures = textures &&
+                       query(gl::TEXTURE_2D, gl::INTERNALFORMAT_SUPPORTED) == gl::TRUE as _;
+        let render_buffers = render_buffers &&
+                             query(gl::RENDERBUFFER, gl::INTERNALFORMAT_SUPPORTED) ==
+                                 gl::TRUE as _;
+        let target = if textures { gl::TEXTURE_2D } else { gl::RENDERBUFFER };
+        let renderable = renderable && (textures || render_buffers);
+
+        let color_renderable = renderable && is_color &&
+                               query(target, gl::COLOR_RENDERABLE) == gl::TRUE as _;
+
+        let preferred_upload = if textures {
+            image_format::client_format_from_glenums(query(gl::TEXTURE_2D,
+                                                           gl::TEXTURE_IMAGE_FORMAT),
+                                                     query(gl::TEXTURE_2D,
+                                                           gl::TEXTURE_IMAGE_TYPE))
+        } else {
+            None
+        };
+
+        return FormatSupport {
+            textures,
+            render_buffers,
+            filterable: textures && query(gl::TEXTURE_2D, gl::FILTER) != gl::NONE,
+            color_renderable,
+            depth_renderable: renderable && is_depth &&
+                              query(target, gl::DEPTH_RENDERABLE) == gl::TRUE as _,
+            stencil_renderable: renderable && is_stencil &&
+                                query(target, gl::STENCIL_RENDERABLE) == gl::TRUE as _,
+            blendable: color_renderable && !is_integral &&
+                       query(target, gl::FRAMEBUFFER_BLEND) != gl::NONE,
+            image_load_store: textures &&
+                              query(gl::TEXTURE_2D, gl::SHADER_IMAGE_LOAD) != gl::NONE &&
+                              query(gl::TEXTURE_2D, gl::SHADER_IMAGE_STORE) != gl::NONE,
+            mipmap_generation: textures && query(gl::TEXTURE_2D, gl::MIPMAP) == gl::TRUE as _,
+            preferred_upload_format: preferred_upload.map(|(format, _)| format),
+            preferred_upload_inverted: preferred_upload.is_some_and(|(_, inverted)| inverted),
+            queried: true,
+        };
+    }
+
+    // without `GL_ARB_internalformat_query2`, we only report what the specifications guarantee
+    let filterable = textures && match format {
+        TextureFormat::UncompressedFloat(_) if is_f32 => {
+            version.0 == Api::Gl || extensions.gl_oes_texture_float_linear
+        },
+        TextureFormat::UncompressedFloat(_) if is_f16 => {
+            version.0 == Api::Gl || version >= &Version(Api::GlEs, 3, 0) ||
+            extensions.gl_oes_texture_half_float_linear
+        },
+        TextureFormat::UncompressedFloat(_) | TextureFormat::Srgb(_) |
+        TextureFormat::CompressedFormat(_) | TextureFormat::CompressedSrgbFormat(_) => true,
+        TextureFormat::DepthFormat(_) | TextureFormat::DepthStencilFormat(_) => {
+            version.0 == Api::Gl
+        },
+        TextureFormat::UncompressedIntegral(_) | TextureFormat::UncompressedUnsigned(_) |
+        TextureFormat::StencilFormat(_) => false,
+    };
+
+    let color_renderable = renderable && is_color;
+
+    let image_load_store = textures && if version >= &Version(Api::Gl, 4, 2) ||
+                                          extensions.gl_arb_shader_image_load_store
+    {
+        matches!(internal, gl::RGBA32F | gl::RGBA16F | gl::RG32F | gl::RG16F |
+                           gl::R11F_G11F_B10F | gl::R32F | gl::R16F | gl::RGBA32UI |
+                           gl::RGBA16UI | gl::RGB10_A2UI | gl::RGBA8UI | gl::RG32UI |
+                           gl::RG16UI | gl::RG8UI | gl::R32UI | gl::R16UI | gl::R8UI |
+                           gl::RGBA32I | gl::RGBA16I | gl::RGBA8I | gl::RG32I | gl::RG16I |
+                           gl::RG8I | gl::R32I | gl::R16I | gl::R8I | gl::RGBA16 |
+                           gl::RGB10_A2 | gl::RGBA8 | gl::RG16 | gl::RG8 | gl::R16 | gl::R8 |
+                           gl::RGBA16_SNORM | gl::RGBA8_SNORM | gl::RG16_SNORM |
+                           gl::RG8_SNORM | gl::R16_SNORM | gl::R8_SNORM)
+    } else if version >= &Version(Api::GlEs, 3, 1) {
+        matches!(internal, gl::RGBA32F | gl::RGBA16F | gl::R32F | gl::RGBA32UI |
+                           gl::RGBA16UI | gl::RGBA8UI | gl::R32UI | gl::RGBA32I |
+                           gl::RGBA16I | gl::RGBA8I | gl::R32I | gl::RGBA8 | gl::RGBA8_SNORM)
+    } else {
+        false
+    };
+
+    FormatSupport {
+        textures,
+        render_buffers,
+        filterable,
+        color_renderable,
+        depth_renderable: renderable && is_depth,
+        stencil_renderable: renderable && is_stencil,
+        blendable: color_renderable && !is_integral &&
+                   (!is_f32 || version.0 == Api::Gl || extensions.gl_ext_float_blend),
+        image_load_store,
+        mipmap_generation: filterable && color_renderable,
+        preferred_upload_format: None,
+        preferred_upload_inverted: false,
+        queried: false,
+    }
+}
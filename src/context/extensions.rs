@@ -1,3 +1,4 @@
+use crate::context::CapabilityMask;
 use crate::gl;
 use crate::version::Api;
 use crate::version::Version;
@@ -9,11 +10,13 @@ macro_rules! extensions {
         #[derive(Debug, Clone, Copy)]
         pub struct ExtensionsList {
             $(
+                #[doc = concat!("True if `", $string, "` is supported.")]
                 pub $field: bool,
             )+
         }
 
-        /// Returns the list of extensions supported by the backend.
+        /// Returns the list of extensions supported by the backend. If a mask is passed, only
+        /// the extensions allowed by the mask are returned.
         ///
         /// The version must match the one of the backend.
         ///
@@ -24,7 +27,9 @@ macro_rules! extensions {
         /// Can panic if the version number doesn't match the backend, leading to unloaded functions
         /// being called.
         ///
-        pub unsafe fn get_extensions(gl: &gl::Gl, version: &Version) -> ExtensionsList {
+        pub unsafe fn get_extensions(gl: &gl::Gl, version: &Version, mask: Option<&CapabilityMask>)
+                                     -> ExtensionsList
+        {
             let strings = get_extensions_strings(gl, version);
 
             let mut extensions = ExtensionsList {
@@ -34,6 +39,10 @@ macro_rules! extensions {
             };
 
             for extension in strings.into_iter() {
+                if mask.is_some_and(|mask| !mask.allows(&extension)) {
+                    continue;
+                }
+
                 match &extension[..] {
                     $(
                         $string => extensions.$field = true,
@@ -44,6 +53,17 @@ macro_rules! extensions {
 
             extensions
         }
+
+        impl ExtensionsList {
+            /// Returns the name of every extension known to glium, and whether it is supported.
+            pub fn flags(&self) -> Vec<(&'static str, bool)> {
+                vec![
+                    $(
+                        ($string, self.$field),
+                    )+
+                ]
+            }
+        }
     }
 }
 
@@ -81,9 +101,11 @@ extensions! {
     "GL_ARB_gpu_shader_int64" => gl_arb_gpu_shader_int64,
     "GL_ARB_instanced_arrays" => gl_arb_instanced_arrays,
     "GL_ARB_internalformat_query" => gl_arb_internalformat_query,
+    "GL_ARB_internalformat_query2" => gl_arb_internalformat_query2,
     "GL_ARB_invalidate_subdata" => gl_arb_invalidate_subdata,
     "GL_ARB_occlusion_query" => gl_arb_occlusion_query,
     "GL_ARB_occlusion_query2" => gl_arb_occlusion_query2,
+    "GL_ARB_parallel_shader_compile" => gl_arb_parallel_shader_compile,
     "GL_ARB_pixel_buffer_object" => gl_arb_pixel_buffer_object,
     "GL_ARB_program_interface_query" => gl_arb_program_interface_query,
     "GL_ARB_query_buffer_object" => gl_arb_query_buffer_object,
@@ -94,11 +116,13 @@ extensions! {
     "GL_ARB_robust_buffer_access_behavior" => gl_arb_robust_buffer_access_behavior,
     "GL_ARB_sampler_objects" => gl_arb_sampler_objects,
     "GL_ARB_seamless_cube_map" => gl_arb_seamless_cube_map,
+    "GL_ARB_separate_shader_objects" => gl_arb_separate_shader_objects,
     "GL_ARB_shader_atomic_counters" => gl_arb_shader_atomic_counters,
     "GL_ARB_shader_image_load_store" => gl_arb_shader_image_load_store,
     "GL_ARB_shader_objects" => gl_arb_shader_objects,
     "GL_ARB_shader_storage_buffer_object" => gl_arb_shader_storage_buffer_object,
     "GL_ARB_shader_subroutine" => gl_arb_shader_subroutine,
+    "GL_ARB_shading_language_include" => gl_arb_shading_language_include,
     "GL_ARB_sync" => gl_arb_sync,
     "GL_ARB_tessellation_shader" => gl_arb_tessellation_shader,
     "GL_ARB_texture_buffer_object" => gl_arb_texture_buffer_object,
@@ -133,6 +157,7 @@ extensions! {
     "GL_EXT_memory_object" => gl_ext_memory_object,
     "GL_EXT_memory_object_fd" => gl_ext_memory_object_fd,
     "GL_EXT_disjoint_timer_query" => gl_ext_disjoint_timer_query,
+    "GL_EXT_float_blend" => gl_ext_float_blend,
     "GL_EXT_framebuffer_blit" => gl_ext_framebuffer_blit,
     "GL_EXT_framebuffer_object" => gl_ext_framebuffer_object,
     "GL_EXT_framebuffer_multisample" => gl_ext_framebuffer_multisample,
@@ -167,6 +192,7 @@ extensions! {
     "GL_GREMEDY_string_marker" => gl_gremedy_string_marker,
     "GL_KHR_debug" => gl_khr_debug,
     "GL_KHR_context_flush_control" => gl_khr_context_flush_control,
+    "GL_KHR_parallel_shader_compile" => gl_khr_parallel_shader_compile,
     "GL_KHR_robustness" => gl_khr_robustness,
     "GL_KHR_robust_buffer_access_behavior" => gl_khr_robust_buffer_access_behavior,
     "GL_NV_fbo_color_attachments" => gl_nv_fbo_color_attachments,
@@ -200,6 +226,8 @@ extensions! {
     "GL_OES_texture_3D" => gl_oes_texture_3d,
     "GL_OES_texture_buffer" => gl_oes_texture_buffer,
     "GL_OES_texture_cube_map_array" => gl_oes_texture_cube_map_array,
+    "GL_OES_texture_float_linear" => gl_oes_texture_float_linear,
+    "GL_OES_texture_half_float_linear" => gl_oes_texture_half_float_linear,
     "GL_OES_texture_stencil8" => gl_oes_texture_stencil8,
     "GL_OES_texture_storage_multisample_2d_array" => gl_oes_texture_storage_multisample_2d_array,
     "GL_OES_vertex_array_object" => gl_oes_vertex_array_object,
@@ -0,0 +1,401 @@
+//! Invalidation of the state cache, after OpenGL functions have been called behind glium's back.
+//!
+//! See `Context::invalidate_state` and `Context::exec_raw`.
+
+use crate::context::CommandContext;
+use crate::context::state::{IndexedBufferState, TextureUnitState};
+use crate::context::verify::{get_bool, get_floats, get_int, get_ints, get_uint, is_enabled};
+use crate::gl;
+use crate::version::Api;
+use crate::version::Version;
+use crate::Handle;
+
+/// Value of an object binding whose real value is unknown. This forces glium to bind its objects
+/// again, as no object has this name.
+const UNKNOWN_BINDING: gl::types::GLuint = gl::types::GLuint::MAX;
+
+/// A group of OpenGL states that are cached by glium. See `Context::invalidate_state_groups`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StateGroup {
+    /// The flags that are switched with `glEnable` and `glDisable`.
+    Capabilities,
+
+    /// The current program, the current program pipeline and the current vertex array object.
+    Program,
+
+    /// The buffers bound to the buffer targets, including the indexed ones.
+    Buffers,
+
+    /// The framebuffers and the renderbuffer that are bound.
+    Framebuffers,
+
+    /// The blending equations, functions and color, and the color write mask.
+    Blending,
+
+    /// The depth function, write mask and range, and the stencil functions, operations and write
+    /// masks.
+    DepthStencil,
+
+    /// The viewport, the scissor box, the width of lines, the size of points, the culled face,
+    /// the polygon mode and offset, the provoking vertex and the smoothing hints.
+    Rasterizer,
+
+    /// The active texture unit, and the textures and samplers bound to each texture unit.
+    Textures,
+
+    /// The values used by `glClear`.
+    Clear,
+
+    /// The alignments passed to `glPixelStore` and the color clamping of `glReadPixels`.
+    PixelStore,
+}
+
+impl StateGroup {
+    /// All the groups of states.
+    pub const ALL: &'static [StateGroup] = &[
+        StateGroup::Capabilities, StateGroup::Program, StateGroup::Buffers,
+        StateGroup::Framebuffers, StateGroup::Blending, StateGroup::DepthStencil,
+        StateGroup::Rasterizer, StateGroup::Textures, StateGroup::Clear, StateGroup::PixelStore,
+    ];
+}
+
+/// Updates the given groups of `ctxt.state` so that they match the state of the OpenGL context.
+///
+/// The values are queried with `glGet*` when possible. The values that can't be queried are
+/// replaced with values that force glium to set them again before using them.
+pub fn invalidate(ctxt: &mut CommandContext<'_>, groups: &[StateGroup]) {
+    let gl = ctxt.gl;
+    let version = ctxt.version;
+    let extensions = ctxt.extensions;
+    let capabilities = ctxt.capabilities;
+    let state = &mut *ctxt.state;
+
+    if state.lost_context {
+        return;
+    }
+
+    for group in groups {
+        unsafe {
+            match *group {
+                StateGroup::Capabilities => {
+                    state.enabled_blend = is_enabled(gl, gl::BLEND);
+                    state.enabled_cull_face = is_enabled(gl, gl::CULL_FACE);
+                    state.enabled_depth_test = is_enabled(gl, gl::DEPTH_TEST);
+                    state.enabled_dither = is_enabled(gl, gl::DITHER);
+                    state.enabled_polygon_offset_fill = is_enabled(gl, gl::POLYGON_OFFSET_FILL);
+                    state.enabled_sample_alpha_to_coverage =
+                        is_enabled(gl, gl::SAMPLE_ALPHA_TO_COVERAGE);
+                    state.enabled_sample_coverage = is_enabled(gl, gl::SAMPLE_COVERAGE);
+                    state.enabled_scissor_test = is_enabled(gl, gl::SCISSOR_TEST);
+                    state.enabled_stencil_test = is_enabled(gl, gl::STENCIL_TEST);
+
+                    if version.0 == Api::Gl {
+                        state.enabled_multisample = is_enabled(gl, gl::MULTISAMPLE);
+                        state.enabled_line_smooth = is_enabled(gl, gl::LINE_SMOOTH);
+                        state.enabled_polygon_smooth = is_enabled(gl, gl::POLYGON_SMOOTH);
+                        state.enabled_polygon_offset_line =
+                            is_enabled(gl, gl::POLYGON_OFFSET_LINE);
+                        state.enabled_polygon_offset_point =
+                            is_enabled(gl, gl::POLYGON_OFFSET_POINT);
+
+                        let max_clip_planes = get_int(gl, gl::MAX_CLIP_DISTANCES).clamp(0, 32);
+                        state.enabled_clip_planes = (0 .. max_clip_planes as u32)
+                            .filter(|&i| is_enabled(gl, gl::CLIP_DISTANCE0 + i))
+                            .fold(0, |mask, i| mask | (1 << i));
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 2) {
+                        state.enabled_program_point_size = is_enabled(gl, gl::PROGRAM_POINT_SIZE);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_depth_clamp ||
+                       extensions.gl_nv_depth_clamp
+                    {
+                        let depth_clamp = is_enabled(gl, gl::DEPTH_CLAMP);
+                        state.enabled_depth_clamp_near = depth_clamp;
+                        state.enabled_depth_clamp_far = depth_clamp;
+                    }
+
+                    if extensions.gl_amd_depth_clamp_separate {
+                        state.enabled_depth_clamp_near = is_enabled(gl, gl::DEPTH_CLAMP_NEAR_AMD);
+                        state.enabled_depth_clamp_far = is_enabled(gl, gl::DEPTH_CLAMP_FAR_AMD);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 0) ||
+                       extensions.gl_arb_framebuffer_srgb || extensions.gl_ext_framebuffer_srgb ||
+                       extensions.gl_ext_srgb_write_control
+                    {
+                        state.enabled_framebuffer_srgb = is_enabled(gl, gl::FRAMEBUFFER_SRGB);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 0) ||
+                       version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_ext_transform_feedback
+                    {
+                        state.enabled_rasterizer_discard = is_enabled(gl, gl::RASTERIZER_DISCARD);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 3) ||
+                       version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_arb_es3_compatibility
+                    {
+                        state.enabled_primitive_fixed_restart =
+                            is_enabled(gl, gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                    }
+                },
+
+                StateGroup::Program => {
+                    state.program = match state.program {
+                        Handle::Id(_) => Handle::Id(get_uint(gl, gl::CURRENT_PROGRAM)),
+                        Handle::Handle(_) => {
+                            Handle::Handle(gl.GetHandleARB(gl::PROGRAM_OBJECT_ARB))
+                        },
+                    };
+
+                    state.program_pipeline = if version >= &Version(Api::Gl, 4, 1) ||
+                                                version >= &Version(Api::GlEs, 3, 1) ||
+                                                extensions.gl_arb_separate_shader_objects
+                    {
+                        get_uint(gl, gl::PROGRAM_PIPELINE_BINDING)
+                    } else {
+                        0
+                    };
+
+                    state.vertex_array = if version >= &Version(Api::Gl, 3, 0) ||
+                                            version >= &Version(Api::GlEs, 3, 0) ||
+                                            extensions.gl_arb_vertex_array_object ||
+                                            extensions.gl_oes_vertex_array_object ||
+                                            extensions.gl_apple_vertex_array_object
+                    {
+                        get_uint(gl, gl::VERTEX_ARRAY_BINDING)
+                    } else {
+                        UNKNOWN_BINDING
+                    };
+                },
+
+                StateGroup::Buffers => {
+                    state.array_buffer_binding = get_uint(gl, gl::ARRAY_BUFFER_BINDING);
+                    state.pixel_pack_buffer_binding = UNKNOWN_BINDING;
+                    state.pixel_unpack_buffer_binding = UNKNOWN_BINDING;
+                    state.uniform_buffer_binding = UNKNOWN_BINDING;
+                    state.copy_read_buffer_binding = UNKNOWN_BINDING;
+                    state.copy_write_buffer_binding = UNKNOWN_BINDING;
+                    state.dispatch_indirect_buffer_binding = UNKNOWN_BINDING;
+                    state.draw_indirect_buffer_binding = UNKNOWN_BINDING;
+                    state.query_buffer_binding = UNKNOWN_BINDING;
+                    state.texture_buffer_binding = UNKNOWN_BINDING;
+                    state.atomic_counter_buffer_binding = UNKNOWN_BINDING;
+                    state.shader_storage_buffer_binding = UNKNOWN_BINDING;
+
+                    if version >= &Version(Api::Gl, 2, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_arb_pixel_buffer_object
+                    {
+                        state.pixel_pack_buffer_binding =
+                            get_uint(gl, gl::PIXEL_PACK_BUFFER_BINDING);
+                        state.pixel_unpack_buffer_binding =
+                            get_uint(gl, gl::PIXEL_UNPACK_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_arb_uniform_buffer_object
+                    {
+                        state.uniform_buffer_binding = get_uint(gl, gl::UNIFORM_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_arb_copy_buffer
+                    {
+                        state.copy_read_buffer_binding =
+                            get_uint(gl, gl::COPY_READ_BUFFER_BINDING);
+                        state.copy_write_buffer_binding =
+                            get_uint(gl, gl::COPY_WRITE_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 2) {
+                        state.texture_buffer_binding = get_uint(gl, gl::TEXTURE_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 0) || version >= &Version(Api::GlEs, 3, 1) {
+                        state.draw_indirect_buffer_binding =
+                            get_uint(gl, gl::DRAW_INDIRECT_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 2) || version >= &Version(Api::GlEs, 3, 1) ||
+                       extensions.gl_arb_shader_atomic_counters
+                    {
+                        state.atomic_counter_buffer_binding =
+                            get_uint(gl, gl::ATOMIC_COUNTER_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) ||
+                       extensions.gl_arb_compute_shader
+                    {
+                        state.dispatch_indirect_buffer_binding =
+                            get_uint(gl, gl::DISPATCH_INDIRECT_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) ||
+                       extensions.gl_arb_shader_storage_buffer_object
+                    {
+                        state.shader_storage_buffer_binding =
+                            get_uint(gl, gl::SHADER_STORAGE_BUFFER_BINDING);
+                    }
+
+                    if version >= &Version(Api::Gl, 4, 4) || extensions.gl_arb_query_buffer_object {
+                        state.query_buffer_binding = get_uint(gl, gl::QUERY_BUFFER_BINDING);
+                    }
+
+                    // the indexed bindings are forgotten rather than queried one by one
+                    for bindings in [&mut state.indexed_uniform_buffer_bindings[..],
+                                     &mut state.indexed_atomic_counter_buffer_bindings[..],
+                                     &mut state.indexed_shader_storage_buffer_bindings[..],
+                                     &mut state.indexed_transform_feedback_buffer_bindings[..]]
+                    {
+                        for binding in bindings {
+                            *binding = IndexedBufferState {
+                                buffer: UNKNOWN_BINDING,
+                                .. Default::default()
+                            };
+                        }
+                    }
+                },
+
+                StateGroup::Framebuffers => {
+                    if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+                       extensions.gl_arb_framebuffer_object
+                    {
+                        state.draw_framebuffer = get_uint(gl, gl::DRAW_FRAMEBUFFER_BINDING);
+                        state.read_framebuffer = get_uint(gl, gl::READ_FRAMEBUFFER_BINDING);
+                        state.renderbuffer = get_uint(gl, gl::RENDERBUFFER_BINDING);
+
+                    } else if version >= &Version(Api::GlEs, 2, 0) ||
+                              extensions.gl_ext_framebuffer_object
+                    {
+                        state.draw_framebuffer = get_uint(gl, gl::FRAMEBUFFER_BINDING);
+                        state.read_framebuffer = state.draw_framebuffer;
+                        state.renderbuffer = get_uint(gl, gl::RENDERBUFFER_BINDING);
+                    }
+
+                    state.default_framebuffer_read = None;
+                },
+
+                StateGroup::Blending => {
+                    state.blend_equation = (get_uint(gl, gl::BLEND_EQUATION_RGB),
+                                            get_uint(gl, gl::BLEND_EQUATION_ALPHA));
+                    state.blend_func = (get_uint(gl, gl::BLEND_SRC_RGB),
+                                        get_uint(gl, gl::BLEND_DST_RGB),
+                                        get_uint(gl, gl::BLEND_SRC_ALPHA),
+                                        get_uint(gl, gl::BLEND_DST_ALPHA));
+
+                    let [r, g, b, a] = get_floats::<4>(gl, gl::BLEND_COLOR);
+                    state.blend_color = (r, g, b, a);
+
+                    let mut mask = [0; 4];
+                    gl.GetBooleanv(gl::COLOR_WRITEMASK, mask.as_mut_ptr());
+                    state.color_mask = (mask[0], mask[1], mask[2], mask[3]);
+                },
+
+                StateGroup::DepthStencil => {
+                    state.depth_func = get_uint(gl, gl::DEPTH_FUNC);
+                    state.depth_mask = get_bool(gl, gl::DEPTH_WRITEMASK);
+
+                    let [near, far] = get_floats::<2>(gl, gl::DEPTH_RANGE);
+                    state.depth_range = (near, far);
+
+                    state.stencil_func_front = (get_uint(gl, gl::STENCIL_FUNC),
+                                                get_int(gl, gl::STENCIL_REF),
+                                                get_uint(gl, gl::STENCIL_VALUE_MASK));
+                    state.stencil_func_back = (get_uint(gl, gl::STENCIL_BACK_FUNC),
+                                               get_int(gl, gl::STENCIL_BACK_REF),
+                                               get_uint(gl, gl::STENCIL_BACK_VALUE_MASK));
+                    state.stencil_mask_front = get_uint(gl, gl::STENCIL_WRITEMASK);
+                    state.stencil_mask_back = get_uint(gl, gl::STENCIL_BACK_WRITEMASK);
+                    state.stencil_op_front = (get_uint(gl, gl::STENCIL_FAIL),
+                                              get_uint(gl, gl::STENCIL_PASS_DEPTH_FAIL),
+                                              get_uint(gl, gl::STENCIL_PASS_DEPTH_PASS));
+                    state.stencil_op_back = (get_uint(gl, gl::STENCIL_BACK_FAIL),
+                                             get_uint(gl, gl::STENCIL_BACK_PASS_DEPTH_FAIL),
+                                             get_uint(gl, gl::STENCIL_BACK_PASS_DEPTH_PASS));
+                },
+
+                StateGroup::Rasterizer => {
+                    let [x, y, width, height] = get_ints::<4>(gl, gl::VIEWPORT);
+                    state.viewport = Some((x, y, width, height));
+
+                    let [x, y, width, height] = get_ints::<4>(gl, gl::SCISSOR_BOX);
+                    state.scissor = Some((x, y, width, height));
+
+                    let [line_width] = get_floats::<1>(gl, gl::LINE_WIDTH);
+                    state.line_width = line_width;
+                    state.cull_face = get_uint(gl, gl::CULL_FACE_MODE);
+
+                    let [factor] = get_floats::<1>(gl, gl::POLYGON_OFFSET_FACTOR);
+                    let [units] = get_floats::<1>(gl, gl::POLYGON_OFFSET_UNITS);
+                    state.polygon_offset = (factor, units);
+
+                    if version.0 == Api::Gl {
+                        let [point_size] = get_floats::<1>(gl, gl::POINT_SIZE);
+                        state.point_size = point_size;
+                        state.smooth = (get_uint(gl, gl::LINE_SMOOTH_HINT),
+                                        get_uint(gl, gl::POLYGON_SMOOTH_HINT));
+
+                        // `GL_POLYGON_MODE` can't be queried with the core profile
+                        state.polygon_mode = 0;
+                    }
+
+                    if version >= &Version(Api::Gl, 3, 2) || extensions.gl_arb_provoking_vertex ||
+                       extensions.gl_ext_provoking_vertex
+                    {
+                        state.provoking_vertex = get_uint(gl, gl::PROVOKING_VERTEX);
+                    }
+                },
+
+                StateGroup::Textures => {
+                    state.active_texture =
+                        get_uint(gl, gl::ACTIVE_TEXTURE).wrapping_sub(gl::TEXTURE0);
+
+                    // the sampler bindings can only be forgotten if glium is allowed to change
+                    // them, otherwise glium keeps assuming that no sampler is bound
+                    let sampler = if version >= &Version(Api::Gl, 3, 3) ||
+                                     version >= &Version(Api::GlEs, 3, 0) ||
+                                     extensions.gl_arb_sampler_objects
+                    {
+                        UNKNOWN_BINDING
+                    } else {
+                        0
+                    };
+
+                    let unknown = TextureUnitState { texture: UNKNOWN_BINDING, sampler };
+                    let units = if sampler == UNKNOWN_BINDING {
+                        capabilities.max_combined_texture_image_units.max(0) as usize
+                    } else {
+                        state.texture_units.len()
+                    };
+                    let units = units.max(state.active_texture as usize + 1);
+
+                    state.texture_units.clear();
+                    state.texture_units.resize(units, unknown);
+                },
+
+                StateGroup::Clear => {
+                    let [r, g, b, a] = get_floats::<4>(gl, gl::COLOR_CLEAR_VALUE);
+                    state.clear_color = (r, g, b, a);
+
+                    let [depth] = get_floats::<1>(gl, gl::DEPTH_CLEAR_VALUE);
+                    state.clear_depth = depth;
+                    state.clear_stencil = get_int(gl, gl::STENCIL_CLEAR_VALUE);
+                },
+
+                StateGroup::PixelStore => {
+                    state.pixel_store_unpack_alignment = get_int(gl, gl::UNPACK_ALIGNMENT);
+                    state.pixel_store_pack_alignment = get_int(gl, gl::PACK_ALIGNMENT);
+
+                    if version >= &Version(Api::Gl, 3, 0) {
+                        state.clamp_color = get_uint(gl, gl::CLAMP_READ_COLOR);
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::context::ExtensionsList;
+use crate::context::Profile;
+use crate::version::Api;
+use crate::version::Version;
+use crate::IncompatibleOpenGl;
+
+/// Restricts the OpenGL version and extensions that glium sees when creating a context.
+///
+/// glium chooses its code paths according to the version and the extensions of the backend.
+/// Masking them allows running glium on a recent implementation as if it was an older one, for
+/// example in order to test the OpenGL ES 2.0 code paths on a desktop driver. Pass the mask to
+/// `Context::with_capability_mask`.
+///
+/// The mask only affects the choices made by glium. The implementation itself still exposes all
+/// of its features, and for example still accepts shaders that use a more recent version of GLSL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityMask {
+    /// The version that glium will report and use. Must not be more recent than the version of
+    /// the implementation.
+    ///
+    /// OpenGL ES versions can be used on top of desktop OpenGL if the implementation supports
+    /// the corresponding `GL_ARB_ES*_compatibility` extension.
+    pub version: Version,
+
+    /// The profile that glium will report and use, or `None` to use the profile of the
+    /// implementation. Ignored if `version` doesn't have profiles.
+    ///
+    /// A compatibility profile can't be spoofed on top of a core profile.
+    pub profile: Option<Profile>,
+
+    /// The only extensions that glium is allowed to use, like `GL_ARB_vertex_array_object`.
+    /// Extensions that the implementation doesn't support are ignored.
+    pub extensions: Vec<String>,
+}
+
+impl CapabilityMask {
+    /// Builds a mask that reports the given version and no extension.
+    #[inline]
+    pub fn new(version: Version) -> CapabilityMask {
+        CapabilityMask {
+            version,
+            profile: None,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// OpenGL 3.3 with the core profile and no extension.
+    #[inline]
+    pub fn gl33_core() -> CapabilityMask {
+        CapabilityMask {
+            profile: Some(Profile::Core),
+            .. CapabilityMask::new(Version(Api::Gl, 3, 3))
+        }
+    }
+
+    /// OpenGL ES 2.0 with no extension.
+    ///
+    /// On desktop OpenGL, this requires `GL_ARB_ES2_compatibility`.
+    #[inline]
+    pub fn gles20() -> CapabilityMask {
+        CapabilityMask::new(Version(Api::GlEs, 2, 0))
+    }
+
+    /// OpenGL 2.1 with `GL_ARB_vertex_array_object`.
+    ///
+    /// `GL_ARB_framebuffer_object` and `GL_EXT_framebuffer_blit` are also allowed, as glium
+    /// requires framebuffer objects.
+    #[inline]
+    pub fn gl21_vertex_array_object() -> CapabilityMask {
+        CapabilityMask::new(Version(Api::Gl, 2, 1))
+            .with_extension("GL_ARB_vertex_array_object")
+            .with_extension("GL_ARB_framebuffer_object")
+            .with_extension("GL_EXT_framebuffer_blit")
+    }
+
+    /// Allows glium to use an additional extension.
+    #[inline]
+    pub fn with_extension(mut self, extension: &str) -> CapabilityMask {
+        self.extensions.push(extension.to_owned());
+        self
+    }
+
+    /// Returns true if the extension is allowed by the mask.
+    #[inline]
+    pub(crate) fn allows(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+    }
+
+    /// Checks that the mask can be applied on top of an implementation with the given version
+    /// and extensions.
+    pub(crate) fn check(&self, version: &Version, extensions: &ExtensionsList)
+                        -> Result<(), IncompatibleOpenGl>
+    {
+        let supported = match (version.0, self.version) {
+            (Api::Gl, Version(Api::Gl, _, _)) | (Api::GlEs, Version(Api::GlEs, _, _)) => {
+                version >= &self.version
+            },
+            (Api::Gl, Version(Api::GlEs, 2, 0)) => extensions.gl_arb_es2_compatibility,
+            (Api::Gl, Version(Api::GlEs, 3, 0)) => extensions.gl_arb_es3_compatibility,
+            (Api::Gl, Version(Api::GlEs, 3, 1)) => extensions.gl_arb_es3_1_compatibility,
+            (Api::Gl, Version(Api::GlEs, 3, 2)) => extensions.gl_arb_es3_2_compatibility,
+            _ => false,
+        };
+
+        if supported {
+            Ok(())
+        } else {
+            Err(IncompatibleOpenGl(format!("The implementation can't be used as {:?}",
+                                           self.version)))
+        }
+    }
+}
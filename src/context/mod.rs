@@ -5,14 +5,24 @@ use crate::gl;
 use std::collections::HashMap;
 use std::mem;
 use std::ptr;
+use std::slice;
 use std::str;
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell, RefMut};
+#[cfg(feature = "trace")]
+use std::cell::OnceCell;
 use std::marker::PhantomData;
+use std::panic::Location;
+use std::thread;
 use std::ffi::CStr;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::os::raw;
 use std::hash::BuildHasherDefault;
+#[cfg(feature = "trace")]
+use std::io;
+#[cfg(feature = "trace")]
+use std::io::Write;
 
 use fnv::FnvHasher;
 
@@ -28,20 +38,36 @@ use crate::version::Version;
 use crate::debug;
 use crate::fbo;
 use crate::ops;
+use crate::recovery;
 use crate::sampler_object;
 use crate::texture;
+use crate::image_format::TextureFormat;
+#[cfg(feature = "trace")]
+use crate::trace;
 use crate::uniforms;
 use crate::vertex_array_object;
 
-pub use self::capabilities::{ReleaseBehavior, Capabilities, Profile};
+pub use self::capabilities::{ReleaseBehavior, Capabilities, FormatInfos, FormatSupport, Profile};
 pub use self::extensions::ExtensionsList;
+pub use self::invalidate::StateGroup;
+pub use self::mask::CapabilityMask;
+pub use self::report::CapabilitiesReport;
+pub(crate) use self::report::LIMITS as REPORT_LIMITS;
+pub use self::resources::{LeakReport, LiveResource, ResourceKind, ResourceStats, ResourceUsage};
 pub use self::state::GlState;
 pub use self::uuid::UuidError;
 
+use self::resources::ResourceTracker;
+
 mod capabilities;
 mod extensions;
+mod invalidate;
+mod mask;
+mod report;
+mod resources;
 mod state;
 mod uuid;
+mod verify;
 
 /// Stores the state and information required for glium to execute commands. Most public glium
 /// functions require passing a `Rc<Context>`.
@@ -63,6 +89,9 @@ pub struct Context {
     /// of texture units, maximum size of the viewport, etc.
     capabilities: Capabilities,
 
+    /// The group of contexts that share their objects with this one.
+    share_group: ShareGroup,
+
     /// Glue between glium and the code that handles windowing. Contains functions that allows
     /// you to swap buffers, retrieve the size of the framebuffer, etc.
     backend: RefCell<Box<dyn Backend>>,
@@ -74,6 +103,10 @@ pub struct Context {
     /// The callback that is used by the debug output feature.
     debug_callback: Option<debug::DebugCallback>,
 
+    /// Whether or not the debug callback must be called synchronously. Needed to initialize
+    /// the debug output again when recovering from a context loss.
+    debug_output_synchronous: bool,
+
     /// Whether or not errors triggered by ARB_debug_output (and similar extensions) should be
     /// reported to the user when `DebugCallbackBehavior::DebugMessageOnError` is used. This must
     /// be set to `false` in some situations, like compiling/linking shaders.
@@ -97,6 +130,40 @@ pub struct Context {
     /// List of images handles that are resident. We need to call `MakeImageHandleResidentARB`
     /// when rebuilding the context.
     resident_image_handles: RefCell<Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
+
+    /// The tracer that records the commands, if a trace is being captured.
+    #[cfg(feature = "trace")]
+    tracer: RefCell<Option<Rc<trace::Tracer>>>,
+
+    /// Pointers to functions that record the calls before forwarding them to the backend.
+    /// Loaded the first time a trace is started.
+    #[cfg(feature = "trace")]
+    traced_gl: OnceCell<gl::Gl>,
+
+    /// If true, the state cache is compared with the real state of the backend after each
+    /// operation. See `set_state_verification`.
+    verify_state: Cell<bool>,
+
+    /// The objects that are alive in this context.
+    resources: ResourceTracker,
+
+    /// The resources that are recreated when recovering from a context loss. See the `recovery`
+    /// module.
+    recoverables: RefCell<Vec<Weak<dyn recovery::Reload>>>,
+}
+
+/// Identifies a group of contexts that share their objects. Returned by `Context::share_group`.
+///
+/// Contrary to the context itself, this identifier can be sent to other threads.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ShareGroup(u64);
+
+impl ShareGroup {
+    /// Builds an identifier that is different from all the existing ones.
+    fn new() -> ShareGroup {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        ShareGroup(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
 }
 
 /// This struct is a guard that is returned when you want to access the OpenGL backend.
@@ -136,6 +203,13 @@ pub struct CommandContext<'a> {
     /// List of image handles and their access that need to be made resident.
     pub resident_image_handles: RefMut<'a, Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
 
+    /// The objects that are alive in the context.
+    pub resources: &'a ResourceTracker,
+
+    /// If the state verification is enabled, the location where `make_current` was called.
+    /// The state is verified when the `CommandContext` is destroyed.
+    verify_state: Option<&'static Location<'static>>,
+
     /// This marker is here to prevent `CommandContext` from implementing `Send`
     // TODO: use this when possible
     //impl<'a, 'b> !Send for CommandContext<'a, 'b> {}
@@ -161,17 +235,86 @@ impl Context {
         callback_behavior: DebugCallbackBehavior,
     ) -> Result<Rc<Context>, IncompatibleOpenGl>
         where B: Backend + 'static
+    {
+        Context::build(backend, check_current_context, callback_behavior, None, None)
+    }
+
+    /// Builds a new context whose backend shares its objects with the contexts of the given
+    /// group, for example a context used to upload resources from another thread.
+    ///
+    /// Buffers and textures can then be moved between the contexts of the group with the
+    /// `transfer` module. See `Context::new` for the other parameters.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the requirements of `Context::new`, the OpenGL context of the backend must
+    /// really share its objects (its "display lists") with the contexts of the group.
+    pub unsafe fn new_shared<B>(
+        backend: B,
+        check_current_context: bool,
+        callback_behavior: DebugCallbackBehavior,
+        share_group: ShareGroup,
+    ) -> Result<Rc<Context>, IncompatibleOpenGl>
+        where B: Backend + 'static
+    {
+        Context::build(backend, check_current_context, callback_behavior, None, Some(share_group))
+    }
+
+    /// Builds a new context that restricts the version and the extensions that glium uses.
+    ///
+    /// glium behaves as if the backend only supported what the mask allows, which is useful to
+    /// test the code paths meant for older implementations. Returns an error if the backend
+    /// can't be used as the version of the mask.
+    ///
+    /// See `Context::new` for the other parameters.
+    pub unsafe fn with_capability_mask<B>(
+        backend: B,
+        check_current_context: bool,
+        callback_behavior: DebugCallbackBehavior,
+        mask: CapabilityMask,
+    ) -> Result<Rc<Context>, IncompatibleOpenGl>
+        where B: Backend + 'static
+    {
+        Context::build(backend, check_current_context, callback_behavior, Some(mask), None)
+    }
+
+    unsafe fn build<B>(
+        backend: B,
+        check_current_context: bool,
+        callback_behavior: DebugCallbackBehavior,
+        mask: Option<CapabilityMask>,
+        share_group: Option<ShareGroup>,
+    ) -> Result<Rc<Context>, IncompatibleOpenGl>
+        where B: Backend + 'static
     {
         backend.make_current();
 
         let gl = gl::Gl::load_with(|symbol| backend.get_proc_address(symbol) as *const _);
         let gl_state: RefCell<GlState> = RefCell::new(Default::default());
 
-        let version = version::get_gl_version(&gl);
-        let extensions = extensions::get_extensions(&gl, &version);
+        let mut version = version::get_gl_version(&gl);
+        let mut extensions = extensions::get_extensions(&gl, &version, None);
+
+        if let Some(ref mask) = mask {
+            mask.check(&version, &extensions)?;
+            extensions = extensions::get_extensions(&gl, &version, Some(mask));
+            version = mask.version;
+        }
+
         check_gl_compatibility(&version, &extensions)?;
 
-        let capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+        let mut capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+
+        if let Some(profile) = mask.as_ref().and_then(|mask| mask.profile) {
+            match (capabilities.profile, profile) {
+                (None, _) => (),
+                (Some(Profile::Core), Profile::Compatibility) => {
+                    return Err(IncompatibleOpenGl("A compatibility profile can't be used on top \
+                                                   of a core profile".to_owned()));
+                },
+                (Some(_), profile) => capabilities.profile = Some(profile),
+            }
+        }
         let report_debug_output_errors = Cell::new(true);
 
         let vertex_array_objects = vertex_array_object::VertexAttributesSystem::new();
@@ -204,7 +347,9 @@ impl Context {
             extensions,
             capabilities,
             debug_callback,
+            debug_output_synchronous: synchronous,
             report_debug_output_errors,
+            share_group: share_group.unwrap_or_else(ShareGroup::new),
             backend: RefCell::new(Box::new(backend)),
             check_current_context,
             framebuffer_objects: Some(framebuffer_objects),
@@ -212,6 +357,13 @@ impl Context {
             samplers,
             resident_texture_handles,
             resident_image_handles,
+            #[cfg(feature = "trace")]
+            tracer: RefCell::new(None),
+            #[cfg(feature = "trace")]
+            traced_gl: OnceCell::new(),
+            verify_state: Cell::new(false),
+            resources: ResourceTracker::new(),
+            recoverables: RefCell::new(Vec::new()),
         });
 
         if context.debug_callback.is_some() {
@@ -236,6 +388,13 @@ impl Context {
         Ok(context)
     }
 
+    /// Returns the group of contexts that share their objects with this one. Pass it to
+    /// `Context::new_shared` in order to build another context of the group.
+    #[inline]
+    pub fn share_group(&self) -> ShareGroup {
+        self.share_group
+    }
+
     /// Calls `get_framebuffer_dimensions` on the backend object stored by this context.
     #[inline]
     pub fn get_framebuffer_dimensions(&self) -> (u32, u32) {
@@ -262,6 +421,13 @@ impl Context {
         // FIXME: verify version, capabilities and extensions
         *self.backend.borrow_mut() = Box::new(new_backend);
 
+        self.make_handles_resident();
+        Ok(())
+    }
+
+    /// Makes the texture and image handles of `resident_texture_handles` and
+    /// `resident_image_handles` resident in the current backend.
+    unsafe fn make_handles_resident(&self) {
         // making textures resident
         let textures = self.resident_texture_handles.borrow();
         for &texture in textures.iter() {
@@ -273,10 +439,72 @@ impl Context {
         for &(image, access) in images.iter() {
             self.gl.MakeImageHandleResidentARB(image, access);
         }
+    }
+
+    /// Destroys the framebuffer objects, vertex array objects and samplers that glium has
+    /// created and cached. Used when recovering from a context loss.
+    pub(crate) fn purge_cached_objects(&self) {
+        let mut ctxt = self.make_current();
+        fbo::FramebuffersContainer::purge_all(&mut ctxt);
+        vertex_array_object::VertexAttributesSystem::purge_all(&mut ctxt);
+
+        for (_, s) in mem::replace(&mut *ctxt.samplers, HashMap::with_hasher(Default::default())) {
+            s.destroy(&mut ctxt);
+        }
+    }
+
+    /// Replaces a backend whose context has been lost with a new one that doesn't share any
+    /// object with it. Used when recovering from a context loss.
+    ///
+    /// The new backend must support at least the same version of OpenGL as the old one. All
+    /// the objects of the old context must have been destroyed beforehand.
+    pub(crate) unsafe fn replace_lost_backend<B>(context: &Rc<Context>, new_backend: B)
+                                                 -> Result<(), IncompatibleOpenGl>
+        where B: Backend + 'static
+    {
+        new_backend.make_current();
+
+        // the function pointers are those of the old backend, which is fine as long as the new
+        // context is created by the same implementation
+        let version = version::get_gl_version(&context.gl);
+        if version.0 != context.version.0 || version < context.version {
+            // leaving the old backend current, so that glium keeps using it
+            context.backend.borrow().make_current();
+            return Err(IncompatibleOpenGl(format!("The new context supports {:?} while the \
+                                                   lost one supported {:?}", version,
+                                                  context.version)));
+        }
+
+        *context.state.borrow_mut() = Default::default();
+        *context.backend.borrow_mut() = Box::new(new_backend);
+
+        if context.debug_callback.is_some() {
+            init_debug_callback(context, context.debug_output_synchronous);
+        }
 
+        let ctxt = context.make_current();
+        if ctxt.version >= &Version(Api::Gl, 3, 2) && ctxt.extensions.gl_arb_seamless_cube_map {
+            ctxt.gl.Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+        drop(ctxt);
+
+        context.make_handles_resident();
         Ok(())
     }
 
+    /// Registers a resource that must be recreated when recovering from a context loss.
+    pub(crate) fn register_recoverable(&self, resource: Weak<dyn recovery::Reload>) {
+        let mut recoverables = self.recoverables.borrow_mut();
+        recoverables.retain(|r| r.strong_count() != 0);
+        recoverables.push(resource);
+    }
+
+    /// Returns the resources that must be recreated when recovering from a context loss, in
+    /// the order in which they have been registered.
+    pub(crate) fn recoverables(&self) -> Vec<Rc<dyn recovery::Reload>> {
+        self.recoverables.borrow().iter().filter_map(|r| r.upgrade()).collect()
+    }
+
     /// Swaps the buffers in the backend.
     pub fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
         if self.state.borrow().lost_context {
@@ -316,12 +544,60 @@ impl Context {
 
         // swapping
         let err = backend.swap_buffers();
+        #[cfg(feature = "trace")]
+        if let Some(ref tracer) = *self.tracer.borrow() {
+            tracer.record_swap_buffers();
+        }
         if let Err(SwapBuffersError::ContextLost) = err {
             self.state.borrow_mut().lost_context = true;
         }
         err
     }
 
+    /// Starts writing all the OpenGL commands executed by glium to `output`.
+    ///
+    /// The trace can later be loaded with `glium::trace::Trace::read` and replayed. If a trace
+    /// was already being captured, it is stopped first. See the `trace` module for more
+    /// information.
+    ///
+    /// Only available if the `trace` feature is enabled.
+    #[cfg(feature = "trace")]
+    pub fn start_trace<W>(&self, output: W) -> io::Result<()> where W: Write + 'static {
+        self.stop_trace()?;
+
+        let backend = self.backend.borrow();
+        if self.check_current_context && !backend.is_current() {
+            unsafe { backend.make_current() };
+        }
+
+        self.traced_gl.get_or_init(|| {
+            trace::load_traced_functions(|symbol| unsafe { backend.get_proc_address(symbol) })
+        });
+
+        let tracer = trace::Tracer::new(self.gl.clone(), Box::new(output))?;
+        *self.tracer.borrow_mut() = Some(Rc::new(tracer));
+        Ok(())
+    }
+
+    /// Stops the trace started with `start_trace` and flushes the output.
+    ///
+    /// Returns the first error that happened while writing the trace. Does nothing if no trace
+    /// is being captured.
+    #[cfg(feature = "trace")]
+    pub fn stop_trace(&self) -> io::Result<()> {
+        match self.tracer.borrow_mut().take() {
+            Some(tracer) => tracer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if a trace is being captured.
+    #[cfg(feature = "trace")]
+    #[inline]
+    pub fn is_tracing(&self) -> bool {
+        self.tracer.borrow().is_some()
+    }
+
     /// Returns the OpenGL version
     #[inline]
     #[deprecated(note = "use `get_opengl_version` instead.")]
@@ -347,6 +623,26 @@ impl Context {
         self.capabilities().supported_glsl_versions.iter().any(|v| v == version)
     }
 
+    /// Returns a snapshot of the version, the extensions and the limits of this context.
+    ///
+    /// With the `serde` feature, the report can be serialized and sent back to you to find out
+    /// which features the machines of your users support.
+    pub fn capabilities_report(&self) -> CapabilitiesReport {
+        CapabilitiesReport::new(&self.version, &self.extensions, self.capabilities())
+    }
+
+    /// Returns what the given texture format can be used for.
+    ///
+    /// This lets you choose the formats of render targets at runtime. The values are queried
+    /// from the driver with `glGetInternalformativ` if `GL_ARB_internalformat_query2` is
+    /// supported, and are conservative otherwise.
+    pub fn get_format_support(&self, format: TextureFormat) -> FormatSupport {
+        let ctxt = self.make_current();
+        unsafe {
+            capabilities::get_format_support(ctxt.gl, ctxt.version, ctxt.extensions, format)
+        }
+    }
+
     /// Returns a string containing this GL version or release number used by this context.
     ///
     /// Vendor-specific information may follow the version number.
@@ -501,6 +797,69 @@ impl Context {
         }
     }
 
+    /// Returns the number and the size of the buffers, textures, render buffers, programs,
+    /// queries, framebuffer objects and vertex array objects that are alive in this context.
+    ///
+    /// Objects created with `from_id` are only counted if glium owns them. The sizes of textures
+    /// and render buffers are estimated from their format and dimensions.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # fn example(display: glium::Display) {
+    /// let stats = display.resource_stats();
+    /// println!("{} textures, {} bytes", stats.textures.count, stats.textures.bytes);
+    /// println!("{} bytes in total, {} at most", stats.total.bytes, stats.peak.bytes);
+    ///
+    /// if let Some(free) = stats.free_video_memory {
+    ///     println!("{} bytes of video memory available", free);
+    /// }
+    /// # }
+    /// ```
+    pub fn resource_stats(&self) -> ResourceStats {
+        ResourceStats {
+            free_video_memory: self.get_free_video_memory(),
+            .. self.resources.stats()
+        }
+    }
+
+    /// Enables or disables leak tracking.
+    ///
+    /// When enabled, a backtrace is captured whenever an OpenGL object is created, so that
+    /// `leak_report` can tell where each object that is still alive comes from. Nothing is
+    /// reported automatically: call `leak_report` before destroying the context to find the
+    /// objects that haven't been destroyed.
+    ///
+    /// Capturing a backtrace is slow, so this should only be enabled while looking for leaks.
+    /// Objects created while leak tracking is disabled are still reported, but without their
+    /// creation site.
+    #[inline]
+    pub fn set_leak_tracking(&self, enabled: bool) {
+        self.resources.set_record_backtraces(enabled);
+    }
+
+    /// Returns the list of the buffers, textures, render buffers, programs, queries, framebuffer
+    /// objects and vertex array objects that are alive in this context.
+    ///
+    /// See `set_leak_tracking`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # fn example(display: glium::Display) {
+    /// display.set_leak_tracking(true);
+    /// // ... load and unload a level ...
+    /// let report = display.leak_report();
+    /// if !report.is_empty() {
+    ///     eprintln!("{}", report);
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn leak_report(&self) -> LeakReport {
+        self.resources.report()
+    }
+
     /// Reads the content of the front buffer.
     ///
     /// You will only see the data that has finished being drawn.
@@ -541,6 +900,65 @@ impl Context {
         action()
     }
 
+    /// Calls raw OpenGL functions with the context active, then updates glium's state cache.
+    ///
+    /// This is the way to mix glium with code that uses OpenGL directly, for example a library
+    /// that shares the context. Contrary to `exec_in_context`, `action` doesn't have to restore
+    /// the state that it modifies. `groups` are the groups of states that `action` may modify:
+    /// once it returns, they are queried from the OpenGL context again like with
+    /// `invalidate_state_groups`, and the other groups are assumed to be unchanged. Pass
+    /// `StateGroup::ALL` if you don't know what `action` modifies, at the cost of glium setting
+    /// all its states again afterwards.
+    ///
+    /// If a tracer is active (see `start_trace`), the functions called by `action` are traced
+    /// as well.
+    ///
+    /// ```no_run
+    /// # use glium::backend::StateGroup;
+    /// # fn example(display: glium::Display) {
+    /// unsafe {
+    ///     display.exec_raw(&[StateGroup::Capabilities, StateGroup::Blending], |gl| {
+    ///         gl.Enable(glium::gl::BLEND);
+    ///         gl.BlendFunc(glium::gl::SRC_ALPHA, glium::gl::ONE_MINUS_SRC_ALPHA);
+    ///     });
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `action` must not modify states outside of `groups`, must not delete the objects created
+    /// by glium, and must not leave a query or transform feedback active.
+    ///
+    /// `action` must not use glium, for example through a clone of the display: the context is
+    /// already in use and glium panics.
+    pub unsafe fn exec_raw<T, F>(&self, groups: &[StateGroup], action: F) -> T
+        where F: FnOnce(&gl::Gl) -> T
+    {
+        let mut ctxt = self.make_current();
+        let result = action(ctxt.gl);
+        invalidate::invalidate(&mut ctxt, groups);
+        result
+    }
+
+    /// Updates glium's state cache after OpenGL functions have been called without going
+    /// through glium, for example by another library that shares the context.
+    ///
+    /// The states are queried from the OpenGL context, which is slow. The few states that can't
+    /// be queried, like the textures bound to each texture unit, are set again by glium the next
+    /// time it uses them. If you know which states have been modified, use
+    /// `invalidate_state_groups` instead.
+    #[inline]
+    pub fn invalidate_state(&self) {
+        self.invalidate_state_groups(StateGroup::ALL);
+    }
+
+    /// Same as `invalidate_state`, but only updates the given groups of states.
+    pub fn invalidate_state_groups(&self, groups: &[StateGroup]) {
+        let mut ctxt = self.make_current();
+        invalidate::invalidate(&mut ctxt, groups);
+    }
+
     /// Asserts that there are no OpenGL errors pending.
     ///
     /// This function should be used in tests.
@@ -626,6 +1044,39 @@ impl Context {
             Ok(())
         }
     }
+
+    /// Enables or disables the state verification mode.
+    ///
+    /// glium keeps a cache of the state of the OpenGL context in order to avoid redundant calls.
+    /// When the verification is enabled, each operation is followed by `glGet*` queries that
+    /// compare the cache with the real state (bound buffers, program, framebuffers, enable flags,
+    /// blending, depth and stencil parameters, viewport and scissor box). A mismatch means that
+    /// the cache has been corrupted, for example by modifying the state in `exec_in_context`,
+    /// and triggers a panic that lists the mismatching values and the location of the glium
+    /// operation. The backtrace of the panic includes the code that called this operation.
+    ///
+    /// This is very slow and is meant for debugging. It has no effect in release builds.
+    #[inline]
+    pub fn set_state_verification(&self, enabled: bool) {
+        self.verify_state.set(enabled);
+    }
+
+    /// Pushes a debug group with the given name. The group is popped when the returned guard
+    /// is destroyed.
+    ///
+    /// Debug groups are shown by debugging tools like RenderDoc or apitrace. Does nothing if
+    /// the backend supports neither `GL_KHR_debug` nor `GL_EXT_debug_marker`.
+    ///
+    /// ```no_run
+    /// # fn example(display: glium::Display) {
+    /// let _group = display.debug_group("shadow pass");
+    /// // draw the shadows here
+    /// # }
+    /// ```
+    #[inline]
+    pub fn debug_group(&self, name: &str) -> debug::DebugGroup<'_> {
+        debug::DebugGroup::push(self, name)
+    }
 }
 
 impl ContextExt for Context {
@@ -634,6 +1085,7 @@ impl ContextExt for Context {
         self.report_debug_output_errors.set(value);
     }
 
+    #[track_caller]
     fn make_current(&self) -> CommandContext<'_> {
         if self.check_current_context {
             let backend = self.backend.borrow();
@@ -643,8 +1095,19 @@ impl ContextExt for Context {
             }
         }
 
+        #[cfg(feature = "trace")]
+        let gl = match *self.tracer.borrow() {
+            Some(ref tracer) => {
+                tracer.make_current();
+                self.traced_gl.get().unwrap()
+            },
+            None => &self.gl,
+        };
+        #[cfg(not(feature = "trace"))]
+        let gl = &self.gl;
+
         CommandContext {
-            gl: &self.gl,
+            gl,
             state: self.state.borrow_mut(),
             version: &self.version,
             extensions: &self.extensions,
@@ -655,6 +1118,12 @@ impl ContextExt for Context {
             samplers: self.samplers.borrow_mut(),
             resident_texture_handles: self.resident_texture_handles.borrow_mut(),
             resident_image_handles: self.resident_image_handles.borrow_mut(),
+            resources: &self.resources,
+            verify_state: if cfg!(debug_assertions) && self.verify_state.get() {
+                Some(Location::caller())
+            } else {
+                None
+            },
             marker: PhantomData,
         }
     }
@@ -684,6 +1153,11 @@ impl CapabilitiesSource for Context {
 
 impl Drop for Context {
     fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        if let Some(tracer) = self.tracer.get_mut().take() {
+            let _ = tracer.finish();
+        }
+
         unsafe {
             // this is the code of make_current duplicated here because we can't borrow
             // `self` twice
@@ -706,6 +1180,8 @@ impl Drop for Context {
                 samplers: self.samplers.borrow_mut(),
                 resident_texture_handles: self.resident_texture_handles.borrow_mut(),
                 resident_image_handles: self.resident_image_handles.borrow_mut(),
+                resources: &self.resources,
+                verify_state: None,
                 marker: PhantomData,
             };
 
@@ -732,6 +1208,27 @@ impl Drop for Context {
     }
 }
 
+impl<'a> Drop for CommandContext<'a> {
+    fn drop(&mut self) {
+        // a second panic while unwinding would abort the process
+        if thread::panicking() {
+            return;
+        }
+
+        let location = match self.verify_state {
+            Some(location) => location,
+            None => return,
+        };
+
+        let mismatches = verify::compare(self);
+        if !mismatches.is_empty() {
+            let mismatches = mismatches.iter().map(|m| format!("\n - {}", m)).collect::<String>();
+            panic!("The state cache of glium doesn't match the state of the OpenGL context after \
+                    the operation at {}:{}", location, mismatches);
+        }
+    }
+}
+
 impl<'a> CapabilitiesSource for CommandContext<'a> {
     #[inline]
     fn get_version(&self) -> &Version {
@@ -885,7 +1382,7 @@ fn init_debug_callback(context: &Rc<Context>, synchronous: bool) {
     // this is the C callback
     extern "system" fn callback_wrapper(source: gl::types::GLenum, ty: gl::types::GLenum,
                                         id: gl::types::GLuint, severity: gl::types::GLenum,
-                                        _length: gl::types::GLsizei,
+                                        length: gl::types::GLsizei,
                                         message: *const gl::types::GLchar,
                                         user_param: *mut raw::c_void)
     {
@@ -896,8 +1393,15 @@ fn init_debug_callback(context: &Rc<Context>, synchronous: bool) {
         let user_param = user_param as *const Context;
         let user_param: &mut Context = unsafe { mem::transmute(user_param) };
 
+        // some drivers forward the messages of `glPushDebugGroup` without adding a null
+        // terminator, so the length is used when it is known
         let message = unsafe {
-            String::from_utf8(CStr::from_ptr(message).to_bytes().to_vec()).unwrap()
+            let bytes = if length >= 0 {
+                slice::from_raw_parts(message as *const u8, length as usize)
+            } else {
+                CStr::from_ptr(message).to_bytes()
+            };
+            String::from_utf8_lossy(bytes).into_owned()
         };
 
         let severity = match severity {
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasherDefault;
+
+use fnv::FnvHasher;
+
+use crate::context::{Capabilities, ExtensionsList, FormatInfos, Profile, ReleaseBehavior};
+use crate::gl;
+use crate::image_format::TextureFormat;
+use crate::version::Version;
+
+/// The limits stored in a `CapabilitiesReport`: the name of the `GLenum` that is queried, the
+/// `GLenum` itself, and a function that returns the value of the limit from the capabilities, or
+/// `None` if the context doesn't support it.
+pub(crate) const LIMITS: &[(&str, gl::types::GLenum, fn(&Capabilities) -> Option<Vec<i64>>)] = &[
+    ("GL_MAX_COMBINED_TEXTURE_IMAGE_UNITS", gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS,
+     |c| Some(vec![c.max_combined_texture_image_units as i64])),
+    ("GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT", gl::MAX_TEXTURE_MAX_ANISOTROPY_EXT,
+     |c| c.max_texture_max_anisotropy.map(|value| vec![value.round() as i64])),
+    ("GL_MAX_TEXTURE_SIZE", gl::MAX_TEXTURE_SIZE,
+     |c| Some(vec![c.max_texture_size as i64])),
+    ("GL_MAX_TEXTURE_BUFFER_SIZE", gl::MAX_TEXTURE_BUFFER_SIZE,
+     |c| c.max_texture_buffer_size.map(|value| vec![value as i64])),
+    ("GL_MAX_VIEWPORT_DIMS", gl::MAX_VIEWPORT_DIMS,
+     |c| Some(vec![c.max_viewport_dims.0 as i64, c.max_viewport_dims.1 as i64])),
+    ("GL_MAX_DRAW_BUFFERS", gl::MAX_DRAW_BUFFERS,
+     |c| Some(vec![c.max_draw_buffers as i64])),
+    ("GL_MAX_PATCH_VERTICES", gl::MAX_PATCH_VERTICES,
+     |c| c.max_patch_vertices.map(|value| vec![value as i64])),
+    ("GL_MAX_ATOMIC_COUNTER_BUFFER_BINDINGS", gl::MAX_ATOMIC_COUNTER_BUFFER_BINDINGS,
+     |c| Some(vec![c.max_indexed_atomic_counter_buffer as i64])),
+    ("GL_MAX_SHADER_STORAGE_BUFFER_BINDINGS", gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS,
+     |c| Some(vec![c.max_indexed_shader_storage_buffer as i64])),
+    ("GL_MAX_TRANSFORM_FEEDBACK_BUFFERS", gl::MAX_TRANSFORM_FEEDBACK_BUFFERS,
+     |c| Some(vec![c.max_indexed_transform_feedback_buffer as i64])),
+    ("GL_MAX_UNIFORM_BUFFER_BINDINGS", gl::MAX_UNIFORM_BUFFER_BINDINGS,
+     |c| Some(vec![c.max_indexed_uniform_buffer as i64])),
+    ("GL_MAX_COMPUTE_WORK_GROUP_COUNT", gl::MAX_COMPUTE_WORK_GROUP_COUNT, |c| {
+        let (x, y, z) = c.max_compute_work_group_count;
+        Some(vec![x as i64, y as i64, z as i64])
+    }),
+    ("GL_MAX_COLOR_ATTACHMENTS", gl::MAX_COLOR_ATTACHMENTS,
+     |c| Some(vec![c.max_color_attachments as i64])),
+    ("GL_MAX_FRAMEBUFFER_WIDTH", gl::MAX_FRAMEBUFFER_WIDTH,
+     |c| c.max_framebuffer_width.map(|value| vec![value as i64])),
+    ("GL_MAX_FRAMEBUFFER_HEIGHT", gl::MAX_FRAMEBUFFER_HEIGHT,
+     |c| c.max_framebuffer_height.map(|value| vec![value as i64])),
+    ("GL_MAX_FRAMEBUFFER_LAYERS", gl::MAX_FRAMEBUFFER_LAYERS,
+     |c| c.max_framebuffer_layers.map(|value| vec![value as i64])),
+    ("GL_MAX_FRAMEBUFFER_SAMPLES", gl::MAX_FRAMEBUFFER_SAMPLES,
+     |c| c.max_framebuffer_samples.map(|value| vec![value as i64])),
+];
+
+/// A snapshot of the version, the extensions and the limits of a context. Returned by
+/// `Context::capabilities_report`.
+///
+/// With the `serde` feature, reports can be serialized, for example to collect the
+/// capabilities of the machines of your users. With the `recording` feature,
+/// `DriverProfile::from_report` builds a profile for the recording backend that reproduces the
+/// environment described by a report.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilitiesReport {
+    /// Version of the context.
+    pub version: Version,
+
+    /// Value of `glGetString(GL_VERSION)`.
+    pub version_string: String,
+
+    /// Value of `glGetString(GL_VENDOR)`.
+    pub vendor: String,
+
+    /// Value of `glGetString(GL_RENDERER)`.
+    pub renderer: String,
+
+    /// The OpenGL context profile, if available.
+    pub profile: Option<Profile>,
+
+    /// True if the context is in debug mode.
+    pub debug: bool,
+
+    /// True if the context is in "forward-compatible" mode.
+    pub forward_compatible: bool,
+
+    /// True if out-of-bound access on the GPU side can't result in crashes.
+    pub robustness: bool,
+
+    /// True if it is possible for the OpenGL context to be lost.
+    pub can_lose_context: bool,
+
+    /// What happens when you change the current OpenGL context.
+    pub release_behavior: ReleaseBehavior,
+
+    /// Whether the context supports left and right buffers.
+    pub stereo: bool,
+
+    /// True if the default framebuffer is in sRGB.
+    pub srgb: bool,
+
+    /// Number of bits in the default framebuffer's depth buffer.
+    pub depth_bits: Option<u16>,
+
+    /// Number of bits in the default framebuffer's stencil buffer.
+    pub stencil_bits: Option<u16>,
+
+    /// List of versions of GLSL that are supported by the compiler.
+    pub supported_glsl_versions: Vec<Version>,
+
+    /// Every extension known to glium, and whether it is supported. The extensions that glium
+    /// doesn't use aren't included.
+    pub extensions: BTreeMap<String, bool>,
+
+    /// The limits of the context, indexed by the name of the `GLenum` that is queried, for
+    /// example `GL_MAX_TEXTURE_SIZE`. Floating-point limits are rounded. The limits that aren't
+    /// supported by the context are absent.
+    pub limits: BTreeMap<String, Vec<i64>>,
+
+    /// The possible numbers of samples of the multisample textures of each format supported
+    /// for textures, indexed by the debug representation of the format. An empty list means
+    /// that the number of samples couldn't be queried.
+    pub texture_samples: BTreeMap<String, Vec<i32>>,
+
+    /// Same as `texture_samples`, but for render buffers.
+    pub render_buffer_samples: BTreeMap<String, Vec<i32>>,
+}
+
+impl CapabilitiesReport {
+    /// Builds a report from the capabilities of a context.
+    pub(crate) fn new(version: &Version, extensions: &ExtensionsList,
+                      capabilities: &Capabilities) -> CapabilitiesReport
+    {
+        let limits = LIMITS.iter()
+            .filter_map(|&(name, _, get)| get(capabilities).map(|values| (name.to_owned(), values)))
+            .collect();
+
+        CapabilitiesReport {
+            version: *version,
+            version_string: capabilities.version.clone(),
+            vendor: capabilities.vendor.clone(),
+            renderer: capabilities.renderer.clone(),
+            profile: capabilities.profile,
+            debug: capabilities.debug,
+            forward_compatible: capabilities.forward_compatible,
+            robustness: capabilities.robustness,
+            can_lose_context: capabilities.can_lose_context,
+            release_behavior: capabilities.release_behavior,
+            stereo: capabilities.stereo,
+            srgb: capabilities.srgb,
+            depth_bits: capabilities.depth_bits,
+            stencil_bits: capabilities.stencil_bits,
+            supported_glsl_versions: capabilities.supported_glsl_versions.clone(),
+            extensions: extensions.flags().into_iter()
+                                  .map(|(name, supported)| (name.to_owned(), supported))
+                                  .collect(),
+            limits,
+            texture_samples: samples(&capabilities.internal_formats_textures),
+            render_buffer_samples: samples(&capabilities.internal_formats_renderbuffers),
+        }
+    }
+
+    /// Returns the names of the supported extensions.
+    pub fn supported_extensions(&self) -> impl Iterator<Item = &str> {
+        self.extensions.iter().filter(|(_, &supported)| supported).map(|(name, _)| &name[..])
+    }
+}
+
+/// Returns the numbers of samples of each format, indexed by the debug representation of the
+/// format.
+fn samples(formats: &HashMap<TextureFormat, FormatInfos, BuildHasherDefault<FnvHasher>>)
+           -> BTreeMap<String, Vec<i32>>
+{
+    formats.iter().map(|(format, infos)| {
+        (format!("{:?}", format), infos.multisamples.clone().unwrap_or_default())
+    }).collect()
+}
@@ -0,0 +1,379 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasherDefault;
+use std::ops::{Add, Sub};
+
+use backtrace::Backtrace;
+use fnv::FnvHasher;
+
+use crate::Handle;
+
+/// Kind of an OpenGL object created by glium.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// A buffer, like a vertex buffer or a uniform buffer.
+    Buffer,
+    /// A texture.
+    Texture,
+    /// A render buffer.
+    RenderBuffer,
+    /// A program.
+    Program,
+    /// A query, including timestamp queries.
+    Query,
+    /// A framebuffer object. glium creates and caches them automatically.
+    Framebuffer,
+    /// A vertex array object. glium creates and caches them automatically.
+    VertexArray,
+}
+
+impl ResourceKind {
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Number of objects and total size in bytes of a group of resources.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Number of live objects.
+    pub count: usize,
+
+    /// Total size in bytes of the live objects.
+    ///
+    /// The size of textures and render buffers is an estimate based on their format and
+    /// dimensions. Programs, queries, framebuffer objects and vertex array objects count as
+    /// zero bytes.
+    pub bytes: usize,
+}
+
+impl Add for ResourceUsage {
+    type Output = ResourceUsage;
+
+    #[inline]
+    fn add(self, other: ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            count: self.count + other.count,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+impl Sub for ResourceUsage {
+    type Output = ResourceUsage;
+
+    #[inline]
+    fn sub(self, other: ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            count: self.count - other.count,
+            bytes: self.bytes - other.bytes,
+        }
+    }
+}
+
+/// Snapshot of the resources that are alive in a context. Returned by
+/// `Context::resource_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceStats {
+    /// Buffers, including vertex, index and uniform buffers.
+    pub buffers: ResourceUsage,
+    /// Textures.
+    pub textures: ResourceUsage,
+    /// Render buffers.
+    pub render_buffers: ResourceUsage,
+    /// Programs.
+    pub programs: ResourceUsage,
+    /// Queries.
+    pub queries: ResourceUsage,
+    /// Framebuffer objects.
+    pub framebuffers: ResourceUsage,
+    /// Vertex array objects.
+    pub vertex_arrays: ResourceUsage,
+
+    /// Sum of all the resources.
+    pub total: ResourceUsage,
+
+    /// The highest values that `total.count` and `total.bytes` have reached since the creation
+    /// of the context. The two values are not necessarily reached at the same time.
+    pub peak: ResourceUsage,
+
+    /// Estimate of the amount of video memory available in bytes, as returned by
+    /// `Context::get_free_video_memory`.
+    pub free_video_memory: Option<usize>,
+}
+
+impl ResourceStats {
+    /// Returns the usage of the given kind of resources.
+    #[inline]
+    pub fn get(&self, kind: ResourceKind) -> ResourceUsage {
+        match kind {
+            ResourceKind::Buffer => self.buffers,
+            ResourceKind::Texture => self.textures,
+            ResourceKind::RenderBuffer => self.render_buffers,
+            ResourceKind::Program => self.programs,
+            ResourceKind::Query => self.queries,
+            ResourceKind::Framebuffer => self.framebuffers,
+            ResourceKind::VertexArray => self.vertex_arrays,
+        }
+    }
+}
+
+/// An object that is alive in a context. Part of a `LeakReport`.
+#[derive(Debug, Clone)]
+pub struct LiveResource {
+    /// The kind of object.
+    pub kind: ResourceKind,
+
+    /// The OpenGL name of the object.
+    pub id: Handle,
+
+    /// Size of the object in bytes. See `ResourceUsage::bytes`.
+    pub bytes: usize,
+
+    /// The function outside of glium that created the object, with its file and line if they
+    /// are known.
+    ///
+    /// `None` if leak tracking was disabled when the object was created, or if the symbols
+    /// aren't available.
+    pub creation_site: Option<String>,
+
+    /// The backtrace of the creation of the object, or `None` if leak tracking was disabled
+    /// when the object was created.
+    pub backtrace: Option<String>,
+}
+
+/// List of the objects that are alive in a context, in the order in which they have been
+/// created. Returned by `Context::leak_report`.
+///
+/// The `Display` implementation prints one line per object.
+#[derive(Debug, Clone, Default)]
+pub struct LeakReport {
+    /// The objects.
+    pub resources: Vec<LiveResource>,
+}
+
+impl LeakReport {
+    /// Returns true if no object is alive.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Returns the number of objects that are alive.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns the objects that have been created at the given site. The site is any part of
+    /// `LiveResource::creation_site`, like the name of a function.
+    pub fn created_at<'a>(&'a self, site: &'a str) -> impl Iterator<Item = &'a LiveResource> {
+        self.resources.iter().filter(move |r| {
+            r.creation_site.as_ref().is_some_and(|s| s.contains(site))
+        })
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.resources.iter().map(|r| r.bytes).sum::<usize>();
+        write!(fmt, "{} object(s) alive, {} bytes", self.resources.len(), bytes)?;
+
+        for resource in self.resources.iter() {
+            write!(fmt, "\n - {:?} ", resource.kind)?;
+            match resource.id {
+                Handle::Id(id) => write!(fmt, "{}", id)?,
+                Handle::Handle(id) => write!(fmt, "{:?}", id)?,
+            }
+            write!(fmt, " ({} bytes), created ", resource.bytes)?;
+            match (&resource.creation_site, &resource.backtrace) {
+                (Some(site), _) => write!(fmt, "at {}", site)?,
+                (None, Some(_)) => write!(fmt, "at an unknown location")?,
+                (None, None) => write!(fmt, "while leak tracking was disabled")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps track of the objects created in a context.
+pub struct ResourceTracker {
+    inner: RefCell<TrackerInner>,
+
+    /// If true, a backtrace is captured when an object is created.
+    record_backtraces: Cell<bool>,
+}
+
+struct TrackerInner {
+    /// Each live object.
+    objects: HashMap<(ResourceKind, Handle), Object, BuildHasherDefault<FnvHasher>>,
+
+    /// Number of objects that have been created, used to sort `objects`.
+    created: u64,
+
+    /// Usage of each kind of resource, indexed by `ResourceKind::index`.
+    usage: [ResourceUsage; 7],
+
+    /// Sum of `usage`.
+    total: ResourceUsage,
+
+    /// High-water marks of `total`.
+    peak: ResourceUsage,
+}
+
+struct Object {
+    /// Size in bytes.
+    bytes: usize,
+
+    /// Value of `TrackerInner::created` when the object was created.
+    serial: u64,
+
+    /// Backtrace of the creation, if `record_backtraces` was true. Symbols are resolved when a
+    /// report is built, which is much slower than capturing the backtrace.
+    backtrace: Option<Backtrace>,
+}
+
+impl ResourceTracker {
+    pub fn new() -> ResourceTracker {
+        ResourceTracker {
+            inner: RefCell::new(TrackerInner {
+                objects: HashMap::with_hasher(Default::default()),
+                created: 0,
+                usage: [ResourceUsage::default(); 7],
+                total: ResourceUsage::default(),
+                peak: ResourceUsage::default(),
+            }),
+            record_backtraces: Cell::new(false),
+        }
+    }
+
+    /// Sets whether a backtrace is captured when an object is created.
+    #[inline]
+    pub fn set_record_backtraces(&self, enabled: bool) {
+        self.record_backtraces.set(enabled);
+    }
+
+    /// Registers an object that has just been created.
+    pub fn created(&self, kind: ResourceKind, id: Handle, bytes: usize) {
+        let backtrace = if self.record_backtraces.get() {
+            Some(Backtrace::new_unresolved())
+        } else {
+            None
+        };
+
+        let mut inner = self.inner.borrow_mut();
+        let serial = inner.created;
+        inner.created += 1;
+
+        // an object whose destruction wasn't registered, which shouldn't happen
+        if let Some(object) = inner.objects.insert((kind, id), Object { bytes, serial, backtrace }) {
+            inner.remove(kind, object.bytes);
+        }
+
+        let added = ResourceUsage { count: 1, bytes };
+        inner.usage[kind.index()] = inner.usage[kind.index()] + added;
+        inner.total = inner.total + added;
+        inner.peak.count = inner.peak.count.max(inner.total.count);
+        inner.peak.bytes = inner.peak.bytes.max(inner.total.bytes);
+    }
+
+    /// Registers an object that has just been destroyed.
+    pub fn destroyed(&self, kind: ResourceKind, id: Handle) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(object) = inner.objects.remove(&(kind, id)) {
+            inner.remove(kind, object.bytes);
+        }
+    }
+
+    /// Returns the current usage. `free_video_memory` is always `None`.
+    pub fn stats(&self) -> ResourceStats {
+        let inner = self.inner.borrow();
+        let usage = |kind: ResourceKind| inner.usage[kind.index()];
+
+        ResourceStats {
+            buffers: usage(ResourceKind::Buffer),
+            textures: usage(ResourceKind::Texture),
+            render_buffers: usage(ResourceKind::RenderBuffer),
+            programs: usage(ResourceKind::Program),
+            queries: usage(ResourceKind::Query),
+            framebuffers: usage(ResourceKind::Framebuffer),
+            vertex_arrays: usage(ResourceKind::VertexArray),
+            total: inner.total,
+            peak: inner.peak,
+            free_video_memory: None,
+        }
+    }
+
+    /// Builds the list of the live objects.
+    pub fn report(&self) -> LeakReport {
+        let mut inner = self.inner.borrow_mut();
+
+        let mut objects = inner.objects.iter_mut().collect::<Vec<_>>();
+        objects.sort_by_key(|(_, object)| object.serial);
+
+        let resources = objects.into_iter().map(|(&(kind, id), object)| {
+            let (creation_site, backtrace) = match object.backtrace {
+                Some(ref mut backtrace) => {
+                    backtrace.resolve();
+                    (creation_site(backtrace), Some(format!("{:?}", backtrace)))
+                },
+                None => (None, None),
+            };
+
+            LiveResource {
+                kind,
+                id,
+                bytes: object.bytes,
+                creation_site,
+                backtrace,
+            }
+        }).collect();
+
+        LeakReport { resources }
+    }
+}
+
+impl TrackerInner {
+    fn remove(&mut self, kind: ResourceKind, bytes: usize) {
+        let removed = ResourceUsage { count: 1, bytes };
+        self.usage[kind.index()] = self.usage[kind.index()] - removed;
+        self.total = self.total - removed;
+    }
+}
+
+/// Returns the first function of a resolved backtrace that is called by glium.
+fn creation_site(backtrace: &Backtrace) -> Option<String> {
+    let mut in_glium = false;
+
+    for frame in backtrace.frames() {
+        for symbol in frame.symbols() {
+            let name = match symbol.name() {
+                Some(name) => format!("{:#}", name),
+                None => continue,
+            };
+
+            let name_trimmed = name.trim_start_matches('<');
+            let internal = name_trimmed.starts_with("glium::") || name.contains(" as glium::");
+            if internal {
+                in_glium = true;
+                continue;
+            }
+
+            let plumbing = ["backtrace::", "core::", "alloc::", "std::"]
+                .iter().any(|prefix| name_trimmed.starts_with(prefix));
+            if !in_glium || plumbing {
+                continue;
+            }
+
+            return Some(match (symbol.filename(), symbol.lineno()) {
+                (Some(file), Some(line)) => format!("{} at {}:{}", name, file.display(), line),
+                _ => name,
+            });
+        }
+    }
+
+    None
+}
@@ -0,0 +1,335 @@
+//! Comparison between the state cache and the real state of the OpenGL context.
+//!
+//! Used by the state verification mode (see `Context::set_state_verification`).
+
+use std::fmt;
+use std::fmt::Debug;
+
+use crate::context::CommandContext;
+use crate::gl;
+use crate::version::Api;
+use crate::version::Version;
+use crate::Handle;
+
+/// A value of the state cache that doesn't match the state of the OpenGL context.
+pub struct Mismatch {
+    /// Name of the OpenGL state.
+    pub name: &'static str,
+    /// Value in the state cache.
+    pub cached: String,
+    /// Value returned by the OpenGL context.
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}: cached {}, actual {}", self.name, self.cached, self.actual)
+    }
+}
+
+/// Queries the state of the OpenGL context with `glGet*` and returns the values that don't
+/// match `ctxt.state`.
+///
+/// Only the states whose queries are supported by the backend are compared.
+pub fn compare(ctxt: &CommandContext<'_>) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+
+    if ctxt.state.lost_context {
+        return mismatches;
+    }
+
+    let mut check = |name: &'static str, cached: &dyn Debug, actual: &dyn Debug, equal: bool| {
+        if !equal {
+            mismatches.push(Mismatch {
+                name,
+                cached: format!("{:?}", cached),
+                actual: format!("{:?}", actual),
+            });
+        }
+    };
+
+    macro_rules! compare {
+        ($name:expr, $cached:expr, $actual:expr) => ({
+            let cached = $cached;
+            let actual = $actual;
+            check($name, &cached, &actual, cached == actual);
+        });
+    }
+
+    let gl = ctxt.gl;
+    let version = ctxt.version;
+    let extensions = ctxt.extensions;
+    let state = &*ctxt.state;
+
+    unsafe {
+        // enable flags
+        compare!("GL_BLEND", state.enabled_blend, is_enabled(gl, gl::BLEND));
+        compare!("GL_CULL_FACE", state.enabled_cull_face, is_enabled(gl, gl::CULL_FACE));
+        compare!("GL_DEPTH_TEST", state.enabled_depth_test, is_enabled(gl, gl::DEPTH_TEST));
+        compare!("GL_DITHER", state.enabled_dither, is_enabled(gl, gl::DITHER));
+        compare!("GL_POLYGON_OFFSET_FILL", state.enabled_polygon_offset_fill,
+                 is_enabled(gl, gl::POLYGON_OFFSET_FILL));
+        compare!("GL_SAMPLE_ALPHA_TO_COVERAGE", state.enabled_sample_alpha_to_coverage,
+                 is_enabled(gl, gl::SAMPLE_ALPHA_TO_COVERAGE));
+        compare!("GL_SAMPLE_COVERAGE", state.enabled_sample_coverage,
+                 is_enabled(gl, gl::SAMPLE_COVERAGE));
+        compare!("GL_SCISSOR_TEST", state.enabled_scissor_test,
+                 is_enabled(gl, gl::SCISSOR_TEST));
+        compare!("GL_STENCIL_TEST", state.enabled_stencil_test,
+                 is_enabled(gl, gl::STENCIL_TEST));
+
+        if version.0 == Api::Gl {
+            compare!("GL_MULTISAMPLE", state.enabled_multisample,
+                     is_enabled(gl, gl::MULTISAMPLE));
+            compare!("GL_LINE_SMOOTH", state.enabled_line_smooth,
+                     is_enabled(gl, gl::LINE_SMOOTH));
+            compare!("GL_POLYGON_SMOOTH", state.enabled_polygon_smooth,
+                     is_enabled(gl, gl::POLYGON_SMOOTH));
+        }
+
+        if version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_framebuffer_srgb ||
+           extensions.gl_ext_framebuffer_srgb || extensions.gl_ext_srgb_write_control
+        {
+            compare!("GL_FRAMEBUFFER_SRGB", state.enabled_framebuffer_srgb,
+                     is_enabled(gl, gl::FRAMEBUFFER_SRGB));
+        }
+
+        if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_ext_transform_feedback
+        {
+            compare!("GL_RASTERIZER_DISCARD", state.enabled_rasterizer_discard,
+                     is_enabled(gl, gl::RASTERIZER_DISCARD));
+        }
+
+        if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_es3_compatibility
+        {
+            compare!("GL_PRIMITIVE_RESTART_FIXED_INDEX", state.enabled_primitive_fixed_restart,
+                     is_enabled(gl, gl::PRIMITIVE_RESTART_FIXED_INDEX));
+        }
+
+        // program and vertex array
+        if let Handle::Id(program) = state.program {
+            compare!("GL_CURRENT_PROGRAM", program, get_uint(gl, gl::CURRENT_PROGRAM));
+        }
+
+        if version >= &Version(Api::Gl, 4, 1) || version >= &Version(Api::GlEs, 3, 1) ||
+           extensions.gl_arb_separate_shader_objects
+        {
+            compare!("GL_PROGRAM_PIPELINE_BINDING", state.program_pipeline,
+                     get_uint(gl, gl::PROGRAM_PIPELINE_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_vertex_array_object || extensions.gl_oes_vertex_array_object ||
+           extensions.gl_apple_vertex_array_object
+        {
+            compare!("GL_VERTEX_ARRAY_BINDING", state.vertex_array,
+                     get_uint(gl, gl::VERTEX_ARRAY_BINDING));
+        }
+
+        // buffers
+        compare!("GL_ARRAY_BUFFER_BINDING", state.array_buffer_binding,
+                 get_uint(gl, gl::ARRAY_BUFFER_BINDING));
+
+        if version >= &Version(Api::Gl, 2, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_pixel_buffer_object
+        {
+            compare!("GL_PIXEL_PACK_BUFFER_BINDING", state.pixel_pack_buffer_binding,
+                     get_uint(gl, gl::PIXEL_PACK_BUFFER_BINDING));
+            compare!("GL_PIXEL_UNPACK_BUFFER_BINDING", state.pixel_unpack_buffer_binding,
+                     get_uint(gl, gl::PIXEL_UNPACK_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_uniform_buffer_object
+        {
+            compare!("GL_UNIFORM_BUFFER_BINDING", state.uniform_buffer_binding,
+                     get_uint(gl, gl::UNIFORM_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_copy_buffer
+        {
+            compare!("GL_COPY_READ_BUFFER_BINDING", state.copy_read_buffer_binding,
+                     get_uint(gl, gl::COPY_READ_BUFFER_BINDING));
+            compare!("GL_COPY_WRITE_BUFFER_BINDING", state.copy_write_buffer_binding,
+                     get_uint(gl, gl::COPY_WRITE_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 3, 1) || version >= &Version(Api::GlEs, 3, 2) {
+            compare!("GL_TEXTURE_BUFFER_BINDING", state.texture_buffer_binding,
+                     get_uint(gl, gl::TEXTURE_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 4, 0) || version >= &Version(Api::GlEs, 3, 1) {
+            compare!("GL_DRAW_INDIRECT_BUFFER_BINDING", state.draw_indirect_buffer_binding,
+                     get_uint(gl, gl::DRAW_INDIRECT_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 4, 2) || version >= &Version(Api::GlEs, 3, 1) ||
+           extensions.gl_arb_shader_atomic_counters
+        {
+            compare!("GL_ATOMIC_COUNTER_BUFFER_BINDING", state.atomic_counter_buffer_binding,
+                     get_uint(gl, gl::ATOMIC_COUNTER_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) ||
+           extensions.gl_arb_compute_shader
+        {
+            compare!("GL_DISPATCH_INDIRECT_BUFFER_BINDING",
+                     state.dispatch_indirect_buffer_binding,
+                     get_uint(gl, gl::DISPATCH_INDIRECT_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 4, 3) || version >= &Version(Api::GlEs, 3, 1) ||
+           extensions.gl_arb_shader_storage_buffer_object
+        {
+            compare!("GL_SHADER_STORAGE_BUFFER_BINDING", state.shader_storage_buffer_binding,
+                     get_uint(gl, gl::SHADER_STORAGE_BUFFER_BINDING));
+        }
+
+        if version >= &Version(Api::Gl, 4, 4) || extensions.gl_arb_query_buffer_object {
+            compare!("GL_QUERY_BUFFER_BINDING", state.query_buffer_binding,
+                     get_uint(gl, gl::QUERY_BUFFER_BINDING));
+        }
+
+        // framebuffers
+        if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+           extensions.gl_arb_framebuffer_object
+        {
+            compare!("GL_DRAW_FRAMEBUFFER_BINDING", state.draw_framebuffer,
+                     get_uint(gl, gl::DRAW_FRAMEBUFFER_BINDING));
+            compare!("GL_READ_FRAMEBUFFER_BINDING", state.read_framebuffer,
+                     get_uint(gl, gl::READ_FRAMEBUFFER_BINDING));
+            compare!("GL_RENDERBUFFER_BINDING", state.renderbuffer,
+                     get_uint(gl, gl::RENDERBUFFER_BINDING));
+
+        } else if version >= &Version(Api::GlEs, 2, 0) || extensions.gl_ext_framebuffer_object {
+            compare!("GL_FRAMEBUFFER_BINDING", state.draw_framebuffer,
+                     get_uint(gl, gl::FRAMEBUFFER_BINDING));
+            compare!("GL_RENDERBUFFER_BINDING", state.renderbuffer,
+                     get_uint(gl, gl::RENDERBUFFER_BINDING));
+        }
+
+        // blending
+        compare!("GL_BLEND_EQUATION", state.blend_equation,
+                 (get_uint(gl, gl::BLEND_EQUATION_RGB), get_uint(gl, gl::BLEND_EQUATION_ALPHA)));
+        compare!("GL_BLEND_FUNC", state.blend_func,
+                 (get_uint(gl, gl::BLEND_SRC_RGB), get_uint(gl, gl::BLEND_DST_RGB),
+                  get_uint(gl, gl::BLEND_SRC_ALPHA), get_uint(gl, gl::BLEND_DST_ALPHA)));
+
+        let [r, g, b, a] = get_floats::<4>(gl, gl::BLEND_COLOR);
+        compare!("GL_BLEND_COLOR", state.blend_color, (r, g, b, a));
+
+        // depth
+        compare!("GL_DEPTH_FUNC", state.depth_func, get_uint(gl, gl::DEPTH_FUNC));
+        compare!("GL_DEPTH_WRITEMASK", state.depth_mask, get_bool(gl, gl::DEPTH_WRITEMASK));
+
+        let [near, far] = get_floats::<2>(gl, gl::DEPTH_RANGE);
+        compare!("GL_DEPTH_RANGE", state.depth_range, (near, far));
+
+        // stencil
+        let stencil_func_front = (get_uint(gl, gl::STENCIL_FUNC), get_int(gl, gl::STENCIL_REF),
+                                  get_uint(gl, gl::STENCIL_VALUE_MASK));
+        let stencil_func_back = (get_uint(gl, gl::STENCIL_BACK_FUNC),
+                                 get_int(gl, gl::STENCIL_BACK_REF),
+                                 get_uint(gl, gl::STENCIL_BACK_VALUE_MASK));
+        check("GL_STENCIL_FUNC", &state.stencil_func_front, &stencil_func_front,
+              stencil_func_matches(state.stencil_func_front, stencil_func_front));
+        check("GL_STENCIL_BACK_FUNC", &state.stencil_func_back, &stencil_func_back,
+              stencil_func_matches(state.stencil_func_back, stencil_func_back));
+
+        let stencil_mask_front = get_uint(gl, gl::STENCIL_WRITEMASK);
+        let stencil_mask_back = get_uint(gl, gl::STENCIL_BACK_WRITEMASK);
+        check("GL_STENCIL_WRITEMASK", &state.stencil_mask_front, &stencil_mask_front,
+              stencil_mask_matches(state.stencil_mask_front, stencil_mask_front));
+        check("GL_STENCIL_BACK_WRITEMASK", &state.stencil_mask_back, &stencil_mask_back,
+              stencil_mask_matches(state.stencil_mask_back, stencil_mask_back));
+        compare!("GL_STENCIL_OP", state.stencil_op_front,
+                 (get_uint(gl, gl::STENCIL_FAIL), get_uint(gl, gl::STENCIL_PASS_DEPTH_FAIL),
+                  get_uint(gl, gl::STENCIL_PASS_DEPTH_PASS)));
+        compare!("GL_STENCIL_BACK_OP", state.stencil_op_back,
+                 (get_uint(gl, gl::STENCIL_BACK_FAIL),
+                  get_uint(gl, gl::STENCIL_BACK_PASS_DEPTH_FAIL),
+                  get_uint(gl, gl::STENCIL_BACK_PASS_DEPTH_PASS)));
+
+        // viewport and scissor
+        if let Some(viewport) = state.viewport {
+            let [x, y, width, height] = get_ints::<4>(gl, gl::VIEWPORT);
+            compare!("GL_VIEWPORT", viewport, (x, y, width, height));
+        }
+
+        if let Some(scissor) = state.scissor {
+            let [x, y, width, height] = get_ints::<4>(gl, gl::SCISSOR_BOX);
+            compare!("GL_SCISSOR_BOX", scissor, (x, y, width, height));
+        }
+
+        compare!("GL_ACTIVE_TEXTURE", state.active_texture,
+                 get_uint(gl, gl::ACTIVE_TEXTURE).wrapping_sub(gl::TEXTURE0));
+    }
+
+    mismatches
+}
+
+/// Compares the values passed to `glStencilFunc` with the values returned by `glGet`.
+fn stencil_func_matches(cached: (gl::types::GLenum, gl::types::GLint, gl::types::GLuint),
+                        actual: (gl::types::GLenum, gl::types::GLint, gl::types::GLuint))
+                        -> bool
+{
+    // the reference value returned by `glGet` is clamped to the range of the stencil buffer
+    let reference_matches = cached.1 == actual.1 || (cached.1 < 0 && actual.1 == 0) ||
+                            (cached.1 > actual.1 && is_stencil_range(actual.1 as u32));
+
+    cached.0 == actual.0 && reference_matches && stencil_mask_matches(cached.2, actual.2)
+}
+
+/// Compares a stencil mask with the value returned by `glGet`.
+///
+/// Some implementations only return the bits that correspond to the stencil buffer of the
+/// current framebuffer, whose size is not known here.
+fn stencil_mask_matches(cached: gl::types::GLuint, actual: gl::types::GLuint) -> bool {
+    cached == actual || (is_stencil_range(actual) && cached & actual == actual)
+}
+
+/// Returns true if `value` is `2^n - 1`, which is the maximal value of a stencil buffer of
+/// `n` bits.
+fn is_stencil_range(value: gl::types::GLuint) -> bool {
+    value.wrapping_add(1).is_power_of_two() || value == u32::MAX
+}
+
+pub(super) unsafe fn is_enabled(gl: &gl::Gl, cap: gl::types::GLenum) -> bool {
+    gl.IsEnabled(cap) != 0
+}
+
+pub(super) unsafe fn get_bool(gl: &gl::Gl, pname: gl::types::GLenum) -> bool {
+    let mut value = 0;
+    gl.GetBooleanv(pname, &mut value);
+    value != 0
+}
+
+pub(super) unsafe fn get_int(gl: &gl::Gl, pname: gl::types::GLenum) -> gl::types::GLint {
+    let mut value = 0;
+    gl.GetIntegerv(pname, &mut value);
+    value
+}
+
+pub(super) unsafe fn get_uint(gl: &gl::Gl, pname: gl::types::GLenum) -> gl::types::GLuint {
+    get_int(gl, pname) as gl::types::GLuint
+}
+
+pub(super) unsafe fn get_ints<const N: usize>(gl: &gl::Gl, pname: gl::types::GLenum)
+                                              -> [gl::types::GLint; N]
+{
+    let mut values = [0; N];
+    gl.GetIntegerv(pname, values.as_mut_ptr());
+    values
+}
+
+pub(super) unsafe fn get_floats<const N: usize>(gl: &gl::Gl, pname: gl::types::GLenum)
+                                                -> [gl::types::GLfloat; N]
+{
+    let mut values = [0.0; N];
+    gl.GetFloatv(pname, values.as_mut_ptr());
+    values
+}
@@ -4,11 +4,15 @@
 */
 
 use crate::backend::Facade;
+use crate::context::CommandContext;
 use crate::context::Context;
+use crate::context::ResourceKind;
 use crate::ContextExt;
+use crate::Handle;
 use crate::version::Api;
 use crate::version::Version;
 use crate::gl;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 pub use crate::context::DebugCallbackBehavior;
@@ -41,7 +45,7 @@ pub enum Severity {
 }
 
 /// Source of a debug message.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Source {
     /// Calls to the OpenGL API.
@@ -66,7 +70,7 @@ pub enum Source {
 }
 
 /// Type of a debug message.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum MessageType {
     /// An error, typically from the API
@@ -89,6 +93,156 @@ pub enum MessageType {
     Other = gl::DEBUG_TYPE_OTHER,
 }
 
+/// A message of the debug output, as stored by a `DebugMessageLog`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugMessage {
+    /// Source of the message.
+    pub source: Source,
+    /// Type of the message.
+    pub ty: MessageType,
+    /// Severity of the message.
+    pub severity: Severity,
+    /// Implementation-defined identifier of the message.
+    pub id: u32,
+    /// The message generated by the OpenGL implementation.
+    pub message: String,
+}
+
+impl DebugMessage {
+    /// Returns true if the message is an error or has a high severity.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.ty == MessageType::Error || self.severity == Severity::High
+    }
+}
+
+/// Collects the messages of the debug output so that they can be inspected later.
+///
+/// Pass the result of `behavior` when creating the context. The log can be cloned, and all the
+/// clones share the same messages.
+///
+/// ## Example
+///
+/// ```
+/// use std::rc::Rc;
+/// use glium::backend::Context;
+/// use glium::backend::recording::{DriverProfile, RecordingBackend};
+/// use glium::debug::{DebugMessageLog, MessageType};
+///
+/// let log = DebugMessageLog::new();
+/// let backend = Rc::new(RecordingBackend::new(DriverProfile::default(), (800, 600)));
+/// let context = unsafe { Context::new(backend, true, log.behavior()) }.unwrap();
+///
+/// log.assert_no_errors(|| {
+///     // draw some stuff here
+/// });
+///
+/// assert!(log.filter(|m| m.ty == MessageType::Performance).is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct DebugMessageLog {
+    messages: Rc<RefCell<Vec<DebugMessage>>>,
+}
+
+impl DebugMessageLog {
+    /// Builds a new empty log.
+    #[inline]
+    pub fn new() -> DebugMessageLog {
+        DebugMessageLog::default()
+    }
+
+    /// Returns a synchronous `DebugCallbackBehavior` that stores the messages in this log.
+    ///
+    /// The messages are reported synchronously so that they are stored before the function that
+    /// triggered them returns.
+    #[inline]
+    pub fn behavior(&self) -> DebugCallbackBehavior {
+        DebugCallbackBehavior::Custom {
+            callback: self.callback(),
+            synchronous: true,
+        }
+    }
+
+    /// Returns a callback that stores the messages in this log.
+    pub fn callback(&self) -> DebugCallback {
+        let messages = self.messages.clone();
+
+        Box::new(move |source, ty, severity, id, _, message| {
+            messages.borrow_mut().push(DebugMessage {
+                source,
+                ty,
+                severity,
+                id,
+                message: message.to_owned(),
+            });
+        })
+    }
+
+    /// Returns the number of messages in the log.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.messages.borrow().len()
+    }
+
+    /// Returns true if the log is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.messages.borrow().is_empty()
+    }
+
+    /// Returns a copy of all the messages in the log.
+    #[inline]
+    pub fn messages(&self) -> Vec<DebugMessage> {
+        self.messages.borrow().clone()
+    }
+
+    /// Returns a copy of the messages that match the predicate.
+    pub fn filter<F>(&self, mut predicate: F) -> Vec<DebugMessage>
+        where F: FnMut(&DebugMessage) -> bool
+    {
+        self.messages.borrow().iter().filter(|m| predicate(m)).cloned().collect()
+    }
+
+    /// Removes all the messages from the log and returns them.
+    #[inline]
+    pub fn drain(&self) -> Vec<DebugMessage> {
+        std::mem::take(&mut *self.messages.borrow_mut())
+    }
+
+    /// Removes the messages that match the predicate from the log and returns them.
+    pub fn drain_filter<F>(&self, mut predicate: F) -> Vec<DebugMessage>
+        where F: FnMut(&DebugMessage) -> bool
+    {
+        let mut messages = self.messages.borrow_mut();
+        let (matching, others) = messages.drain(..).partition(|m| predicate(m));
+        *messages = others;
+        matching
+    }
+
+    /// Removes all the messages from the log.
+    #[inline]
+    pub fn clear(&self) {
+        self.messages.borrow_mut().clear();
+    }
+
+    /// Calls the function and panics if it produced an error or a message with a high
+    /// severity. Meant to be used in tests.
+    ///
+    /// The messages stay in the log.
+    pub fn assert_no_errors<R, F>(&self, f: F) -> R where F: FnOnce() -> R {
+        let start = self.len();
+        let result = f();
+
+        let errors = self.messages.borrow().iter().skip(start).filter(|m| m.is_error())
+                                           .cloned().collect::<Vec<_>>();
+        if !errors.is_empty() {
+            panic!("The debug output reported {} error(s): {:#?}", errors.len(), errors);
+        }
+
+        result
+    }
+}
+
 /// Allows you to obtain the timestamp inside the OpenGL commands queue.
 ///
 /// When you call functions in glium, they are not instantly executed. Instead they are
@@ -150,6 +304,10 @@ impl TimestampQuery {
             None
         };
 
+        if let Some(id) = id {
+            ctxt.resources.created(ResourceKind::Query, Handle::Id(id), 0);
+        }
+
         id.map(|q| TimestampQuery {
             context: facade.get_context().clone(),
             id: q
@@ -182,17 +340,43 @@ impl TimestampQuery {
         }
     }
 
+    /// Attaches a label to the query object. See `Buffer::set_label`.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        let mut ctxt = self.context.make_current();
+        set_object_label(&mut ctxt, gl::QUERY, self.id, label);
+    }
+
     /// Returns the value of the timestamp. Blocks until it is available.
     ///
     /// This function doesn't block if `is_ready` returns true.
+    #[inline]
     pub fn get(self) -> u64 {
+        self.value()
+    }
+
+    /// Records a new timestamp with the same query object.
+    pub(crate) fn restart(&self) {
+        let ctxt = self.context.make_current();
+
+        if ctxt.version >= &Version(Api::Gl, 3, 2) {    // TODO: extension
+            unsafe { ctxt.gl.QueryCounter(self.id, gl::TIMESTAMP); }
+        } else if ctxt.extensions.gl_ext_disjoint_timer_query {
+            unsafe { ctxt.gl.QueryCounterEXT(self.id, gl::TIMESTAMP); }
+        } else {
+            unreachable!();
+        }
+    }
+
+    /// Returns the value of the timestamp without destroying the query. Blocks until it is
+    /// available.
+    pub(crate) fn value(&self) -> u64 {
         let ctxt = self.context.make_current();
 
         if ctxt.version >= &Version(Api::Gl, 3, 2) {    // TODO: extension
             unsafe {
                 let mut value = 0;
                 ctxt.gl.GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut value);
-                ctxt.gl.DeleteQueries(1, [self.id].as_ptr());
                 value
             }
 
@@ -200,7 +384,6 @@ impl TimestampQuery {
             unsafe {
                 let mut value = 0;
                 ctxt.gl.GetQueryObjectui64vEXT(self.id, gl::QUERY_RESULT_EXT, &mut value);
-                ctxt.gl.DeleteQueriesEXT(1, [self.id].as_ptr());
                 value
             }
 
@@ -209,3 +392,122 @@ impl TimestampQuery {
         }
     }
 }
+
+impl Drop for TimestampQuery {
+    fn drop(&mut self) {
+        let ctxt = self.context.make_current();
+        ctxt.resources.destroyed(ResourceKind::Query, Handle::Id(self.id));
+
+        if ctxt.version >= &Version(Api::Gl, 3, 2) {    // TODO: extension
+            unsafe { ctxt.gl.DeleteQueries(1, [self.id].as_ptr()); }
+        } else if ctxt.extensions.gl_ext_disjoint_timer_query {
+            unsafe { ctxt.gl.DeleteQueriesEXT(1, [self.id].as_ptr()); }
+        } else {
+            unreachable!();
+        }
+    }
+}
+
+/// Guard returned by `Context::debug_group`. The debug group is popped when the guard is
+/// destroyed.
+///
+/// Debug groups are shown by debugging tools like RenderDoc or apitrace, and the messages of
+/// the debug output include the name of the group.
+pub struct DebugGroup<'a> {
+    context: &'a Context,
+    pop: Option<PopGroup>,
+}
+
+/// The function to call in order to pop a debug group.
+#[derive(Copy, Clone)]
+enum PopGroup {
+    Khr,
+    KhrSuffix,
+    Marker,
+}
+
+impl<'a> DebugGroup<'a> {
+    /// Pushes a debug group with the given name. Does nothing if the backend supports neither
+    /// `GL_KHR_debug` nor `GL_EXT_debug_marker`.
+    pub(crate) fn push(context: &'a Context, name: &str) -> DebugGroup<'a> {
+        let ctxt = context.make_current();
+        let name = name.as_bytes();
+        let length = name.len() as gl::types::GLsizei;
+
+        let pop = unsafe {
+            if supports_khr_debug_core(&ctxt) {
+                ctxt.gl.PushDebugGroup(gl::DEBUG_SOURCE_APPLICATION, 0, length,
+                                       name.as_ptr() as *const _);
+                Some(PopGroup::Khr)
+
+            } else if ctxt.extensions.gl_khr_debug {
+                ctxt.gl.PushDebugGroupKHR(gl::DEBUG_SOURCE_APPLICATION_KHR, 0, length,
+                                          name.as_ptr() as *const _);
+                Some(PopGroup::KhrSuffix)
+
+            } else if ctxt.extensions.gl_ext_debug_marker {
+                ctxt.gl.PushGroupMarkerEXT(length, name.as_ptr() as *const _);
+                Some(PopGroup::Marker)
+
+            } else {
+                None
+            }
+        };
+
+        DebugGroup { context, pop }
+    }
+}
+
+impl<'a> Drop for DebugGroup<'a> {
+    fn drop(&mut self) {
+        let pop = match self.pop {
+            Some(pop) => pop,
+            None => return,
+        };
+
+        let ctxt = self.context.make_current();
+        unsafe {
+            match pop {
+                PopGroup::Khr => ctxt.gl.PopDebugGroup(),
+                PopGroup::KhrSuffix => ctxt.gl.PopDebugGroupKHR(),
+                PopGroup::Marker => ctxt.gl.PopGroupMarkerEXT(),
+            }
+        }
+    }
+}
+
+/// Maximum length in bytes of the labels. This is the minimum value of `GL_MAX_LABEL_LENGTH`
+/// required by the specifications.
+const MAX_LABEL_LENGTH: usize = 256;
+
+/// Attaches a label to an object with `glObjectLabel`. Does nothing if the backend doesn't
+/// support `GL_KHR_debug`.
+///
+/// Labels longer than 255 bytes are truncated.
+pub(crate) fn set_object_label(ctxt: &mut CommandContext<'_>, identifier: gl::types::GLenum,
+                               id: gl::types::GLuint, label: &str)
+{
+    let mut length = label.len().min(MAX_LABEL_LENGTH - 1);
+    while !label.is_char_boundary(length) {
+        length -= 1;
+    }
+
+    let label = &label.as_bytes()[.. length];
+
+    unsafe {
+        if supports_khr_debug_core(ctxt) {
+            ctxt.gl.ObjectLabel(identifier, id, length as gl::types::GLsizei,
+                                label.as_ptr() as *const _);
+
+        } else if ctxt.extensions.gl_khr_debug {
+            ctxt.gl.ObjectLabelKHR(identifier, id, length as gl::types::GLsizei,
+                                   label.as_ptr() as *const _);
+        }
+    }
+}
+
+/// Returns true if the functions of `GL_KHR_debug` are available without a suffix.
+fn supports_khr_debug_core(ctxt: &CommandContext<'_>) -> bool {
+    ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+    (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+}
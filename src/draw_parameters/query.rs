@@ -1,10 +1,12 @@
 use crate::backend::Facade;
 use crate::context::Context;
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::ContextExt;
 use crate::DrawError;
 use crate::ToGlEnum;
 use crate::GlObject;
+use crate::Handle;
 use crate::QueryExt;
 
 use std::cell::Cell;
@@ -18,6 +20,7 @@ use crate::BufferExt;
 use crate::BufferSliceExt;
 
 use crate::gl;
+use crate::debug;
 use crate::version::Api;
 use crate::version::Version;
 
@@ -184,6 +187,8 @@ impl RawQuery {
             id
         };
 
+        ctxt.resources.created(ResourceKind::Query, Handle::Id(id), 0);
+
         Ok(RawQuery {
             context,
             id,
@@ -192,6 +197,12 @@ impl RawQuery {
         })
     }
 
+    /// Attaches a label to the query object.
+    pub fn set_label(&self, label: &str) {
+        let mut ctxt = self.context.make_current();
+        debug::set_object_label(&mut ctxt, gl::QUERY, self.id, label);
+    }
+
     /// Queries the counter to see if the result is already available.
     pub fn is_ready(&self) -> bool {
         let mut ctxt = self.context.make_current();
@@ -382,6 +393,8 @@ impl Drop for RawQuery {
             }
         }
 
+        ctxt.resources.destroyed(ResourceKind::Query, Handle::Id(self.id));
+
         unsafe {
             if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                ctxt.version >= &Version(Api::GlEs, 3, 0)
@@ -737,6 +750,12 @@ macro_rules! impl_helper {
                 self.query.$get_fn()
             }
 
+            /// Attaches a label to the query object. See `Buffer::set_label`.
+            #[inline]
+            pub fn set_label(&self, label: &str) {
+                self.query.set_label(label)
+            }
+
             /// Writes the result of the query to a buffer when it is available.
             ///
             /// This function doesn't block. Instead it submits a commands to the GPU's commands
@@ -61,6 +61,7 @@ use smallvec::SmallVec;
 
 use crate::CapabilitiesSource;
 use crate::GlObject;
+use crate::Handle;
 use crate::TextureExt;
 
 use crate::texture::CubeLayer;
@@ -71,6 +72,7 @@ use crate::framebuffer::RenderBufferAny;
 
 use crate::gl;
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::version::Version;
 use crate::version::Api;
 
@@ -984,6 +986,8 @@ impl FrameBufferObject {
             id
         };
 
+        ctxt.resources.created(ResourceKind::Framebuffer, Handle::Id(id), 0);
+
         // framebuffer parameters
         // TODO: DSA
         if let Some(width) = attachments.default_width {
@@ -1123,6 +1127,8 @@ impl FrameBufferObject {
 
     /// Destroys the FBO. Must be called, or things will leak.
     fn destroy(self, ctxt: &mut CommandContext<'_>) {
+        ctxt.resources.destroyed(ResourceKind::Framebuffer, Handle::Id(self.id));
+
         // unbinding framebuffer
         if ctxt.state.draw_framebuffer == self.id {
             ctxt.state.draw_framebuffer = 0;
@@ -21,10 +21,13 @@ use crate::texture::{UncompressedFloatFormat, DepthFormat, StencilFormat, DepthS
 use crate::image_format;
 
 use crate::gl;
+use crate::debug;
 use crate::GlObject;
+use crate::Handle;
 use crate::fbo::FramebuffersContainer;
 use crate::backend::Facade;
 use crate::context::Context;
+use crate::context::ResourceKind;
 use crate::ContextExt;
 use crate::version::Version;
 use crate::version::Api;
@@ -84,6 +87,24 @@ impl RenderBuffer {
             buffer: RenderBufferAny::new(facade, format, TextureKind::Float, width, height, Some(samples))
         })
     }
+
+    /// Builds a render buffer from an existing, externally created OpenGL render buffer.
+    ///
+    /// If `owned` is true, this render buffer will take ownership of the OpenGL render buffer and
+    /// be responsible for cleaning it up. Otherwise, the render buffer must be cleaned up
+    /// externally, but only after this render buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a render buffer of the facade's context (or of a context sharing
+    /// its objects) whose storage has been allocated with a color format.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> RenderBuffer where F: Facade + ?Sized
+    {
+        RenderBuffer {
+            buffer: RenderBufferAny::from_id(facade, id, TextureKind::Float, owned)
+        }
+    }
 }
 
 impl<'a> ToColorAttachment<'a> for &'a RenderBuffer {
@@ -148,6 +169,24 @@ impl DepthRenderBuffer {
             buffer: RenderBufferAny::new(facade, format, TextureKind::Depth, width, height, Some(samples))
         })
     }
+
+    /// Builds a render buffer from an existing, externally created OpenGL render buffer.
+    ///
+    /// If `owned` is true, this render buffer will take ownership of the OpenGL render buffer and
+    /// be responsible for cleaning it up. Otherwise, the render buffer must be cleaned up
+    /// externally, but only after this render buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a render buffer of the facade's context (or of a context sharing
+    /// its objects) whose storage has been allocated with a depth format.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> DepthRenderBuffer where F: Facade + ?Sized
+    {
+        DepthRenderBuffer {
+            buffer: RenderBufferAny::from_id(facade, id, TextureKind::Depth, owned)
+        }
+    }
 }
 
 impl<'a> ToDepthAttachment<'a> for &'a DepthRenderBuffer {
@@ -211,6 +250,24 @@ impl StencilRenderBuffer {
             buffer: RenderBufferAny::new(facade, format, TextureKind::Stencil, width, height, Some(samples))
         })
     }
+
+    /// Builds a render buffer from an existing, externally created OpenGL render buffer.
+    ///
+    /// If `owned` is true, this render buffer will take ownership of the OpenGL render buffer and
+    /// be responsible for cleaning it up. Otherwise, the render buffer must be cleaned up
+    /// externally, but only after this render buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a render buffer of the facade's context (or of a context sharing
+    /// its objects) whose storage has been allocated with a stencil format.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> StencilRenderBuffer where F: Facade + ?Sized
+    {
+        StencilRenderBuffer {
+            buffer: RenderBufferAny::from_id(facade, id, TextureKind::Stencil, owned)
+        }
+    }
 }
 
 impl<'a> ToStencilAttachment<'a> for &'a StencilRenderBuffer {
@@ -275,6 +332,24 @@ impl DepthStencilRenderBuffer {
             buffer: RenderBufferAny::new(facade, format, TextureKind::DepthStencil, width, height, Some(samples))
         })
     }
+
+    /// Builds a render buffer from an existing, externally created OpenGL render buffer.
+    ///
+    /// If `owned` is true, this render buffer will take ownership of the OpenGL render buffer and
+    /// be responsible for cleaning it up. Otherwise, the render buffer must be cleaned up
+    /// externally, but only after this render buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a render buffer of the facade's context (or of a context sharing
+    /// its objects) whose storage has been allocated with a depth-stencil format.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> DepthStencilRenderBuffer where F: Facade + ?Sized
+    {
+        DepthStencilRenderBuffer {
+            buffer: RenderBufferAny::from_id(facade, id, TextureKind::DepthStencil, owned)
+        }
+    }
 }
 
 impl<'a> ToDepthStencilAttachment<'a> for &'a DepthStencilRenderBuffer {
@@ -317,6 +392,7 @@ pub struct RenderBufferAny {
     height: u32,
     samples: Option<u32>,
     kind: TextureKind,
+    owned: bool,
 }
 
 impl RenderBufferAny {
@@ -434,6 +510,10 @@ impl RenderBufferAny {
                 unreachable!();
             }
 
+            let size = image_format::image_size(format, width, height, 1, 1,
+                                                samples.unwrap_or(1), 1);
+            ctxt.resources.created(ResourceKind::RenderBuffer, Handle::Id(id), size);
+
             RenderBufferAny {
                 context: facade.get_context().clone(),
                 id,
@@ -441,10 +521,94 @@ impl RenderBufferAny {
                 height,
                 samples,
                 kind,
+                owned: true,
             }
         }
     }
 
+    /// Builds a render buffer from an existing, externally created OpenGL render buffer. The
+    /// dimensions and the number of samples are queried from the backend.
+    unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, kind: TextureKind, owned: bool)
+                         -> RenderBufferAny where F: Facade + ?Sized
+    {
+        let mut ctxt = facade.get_context().make_current();
+
+        let mut width = 0;
+        let mut height = 0;
+        let mut samples = 0;
+        let mut format = 0;
+
+        if ctxt.version >= &Version(Api::Gl, 4, 5) ||
+           ctxt.extensions.gl_arb_direct_state_access
+        {
+            ctxt.gl.GetNamedRenderbufferParameteriv(id, gl::RENDERBUFFER_WIDTH, &mut width);
+            ctxt.gl.GetNamedRenderbufferParameteriv(id, gl::RENDERBUFFER_HEIGHT, &mut height);
+            ctxt.gl.GetNamedRenderbufferParameteriv(id, gl::RENDERBUFFER_SAMPLES, &mut samples);
+            ctxt.gl.GetNamedRenderbufferParameteriv(id, gl::RENDERBUFFER_INTERNAL_FORMAT,
+                                                    &mut format);
+
+        } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                  ctxt.version >= &Version(Api::GlEs, 2, 0)
+        {
+            ctxt.gl.BindRenderbuffer(gl::RENDERBUFFER, id);
+            ctxt.state.renderbuffer = id;
+
+            ctxt.gl.GetRenderbufferParameteriv(gl::RENDERBUFFER, gl::RENDERBUFFER_WIDTH,
+                                               &mut width);
+            ctxt.gl.GetRenderbufferParameteriv(gl::RENDERBUFFER, gl::RENDERBUFFER_HEIGHT,
+                                               &mut height);
+            ctxt.gl.GetRenderbufferParameteriv(gl::RENDERBUFFER, gl::RENDERBUFFER_INTERNAL_FORMAT,
+                                               &mut format);
+
+            if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 0)
+            {
+                ctxt.gl.GetRenderbufferParameteriv(gl::RENDERBUFFER, gl::RENDERBUFFER_SAMPLES,
+                                                   &mut samples);
+            }
+
+        } else if ctxt.extensions.gl_ext_framebuffer_object {
+            ctxt.gl.BindRenderbufferEXT(gl::RENDERBUFFER_EXT, id);
+            ctxt.state.renderbuffer = id;
+
+            ctxt.gl.GetRenderbufferParameterivEXT(gl::RENDERBUFFER_EXT, gl::RENDERBUFFER_WIDTH_EXT,
+                                                  &mut width);
+            ctxt.gl.GetRenderbufferParameterivEXT(gl::RENDERBUFFER_EXT, gl::RENDERBUFFER_HEIGHT_EXT,
+                                                  &mut height);
+            ctxt.gl.GetRenderbufferParameterivEXT(gl::RENDERBUFFER_EXT,
+                                                  gl::RENDERBUFFER_INTERNAL_FORMAT_EXT,
+                                                  &mut format);
+
+            if ctxt.extensions.gl_ext_framebuffer_multisample {
+                ctxt.gl.GetRenderbufferParameterivEXT(gl::RENDERBUFFER_EXT,
+                                                      gl::RENDERBUFFER_SAMPLES_EXT, &mut samples);
+            }
+
+        } else {
+            unreachable!();
+        }
+
+        let width = width as u32;
+        let height = height as u32;
+        let samples = if samples == 0 { None } else { Some(samples as u32) };
+
+        if owned {
+            let size = image_format::image_size(format as gl::types::GLenum, width, height, 1, 1,
+                                                samples.unwrap_or(1), 1);
+            ctxt.resources.created(ResourceKind::RenderBuffer, Handle::Id(id), size);
+        }
+
+        RenderBufferAny {
+            context: facade.get_context().clone(),
+            id,
+            width,
+            height,
+            samples,
+            kind,
+            owned,
+        }
+    }
+
     /// Returns the dimensions of the render buffer.
     #[inline]
     pub fn get_dimensions(&self) -> (u32, u32) {
@@ -464,6 +628,13 @@ impl RenderBufferAny {
         &self.context
     }
 
+    /// Attaches a label to the render buffer object. See `Buffer::set_label`.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        let mut ctxt = self.context.make_current();
+        debug::set_object_label(&mut ctxt, gl::RENDERBUFFER, self.id, label);
+    }
+
     /// Returns the kind of renderbuffer.
     #[inline]
     pub fn kind(&self) -> TextureKind {
@@ -494,6 +665,12 @@ impl Drop for RenderBufferAny {
             // removing FBOs which contain this buffer
             FramebuffersContainer::purge_renderbuffer(&mut ctxt, self.id);
 
+            if !self.owned {
+                return;
+            }
+
+            ctxt.resources.destroyed(ResourceKind::RenderBuffer, Handle::Id(self.id));
+
             if ctxt.version >= &Version(Api::Gl, 3, 0) ||
                ctxt.version >= &Version(Api::GlEs, 2, 0)
             {
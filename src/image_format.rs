@@ -98,6 +98,7 @@ pub enum ClientFormat {
     U5U5U5U1,
     U1U5U5U5Reversed,
     U10U10U10U2,
+    U2U10U10U10Reversed,
     F16,
     F16F16,
     F16F16F16,
@@ -144,6 +145,7 @@ impl ClientFormat {
             ClientFormat::U5U5U5U1 => (5 + 5 + 5 + 1) / 8,
             ClientFormat::U1U5U5U5Reversed => (1 + 5 + 5 + 5) / 8,
             ClientFormat::U10U10U10U2 => (10 + 10 + 10 + 2) / 8,
+            ClientFormat::U2U10U10U10Reversed => (2 + 10 + 10 + 10) / 8,
             ClientFormat::F16 => 16 / 8,
             ClientFormat::F16F16 => (16 + 16) / 8,
             ClientFormat::F16F16F16 => (16 + 16 + 16) / 8,
@@ -188,6 +190,7 @@ impl ClientFormat {
             ClientFormat::U5U5U5U1 => 4,
             ClientFormat::U1U5U5U5Reversed => 4,
             ClientFormat::U10U10U10U2 => 4,
+            ClientFormat::U2U10U10U10Reversed => 4,
             ClientFormat::F16 => 1,
             ClientFormat::F16F16 => 2,
             ClientFormat::F16F16F16 => 3,
@@ -2157,6 +2160,7 @@ pub fn client_format_to_glenum(context: &Context, client: ClientFormatAny,
                 ClientFormatAny::ClientFormat(ClientFormat::U5U5U5U1) => Ok((gl::RGBA, gl::UNSIGNED_SHORT_5_5_5_1)),
                 ClientFormatAny::ClientFormat(ClientFormat::U1U5U5U5Reversed) => Ok((gl::RGBA, gl::UNSIGNED_SHORT_1_5_5_5_REV)),
                 ClientFormatAny::ClientFormat(ClientFormat::U10U10U10U2) => Ok((gl::RGBA, gl::UNSIGNED_INT_10_10_10_2)),
+                ClientFormatAny::ClientFormat(ClientFormat::U2U10U10U10Reversed) => Ok((gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16) => Ok((gl::RED, gl::HALF_FLOAT)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16F16) => Ok((gl::RG, gl::HALF_FLOAT)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16F16F16) => Ok((gl::RGB, gl::HALF_FLOAT)),
@@ -2208,6 +2212,7 @@ pub fn client_format_to_glenum(context: &Context, client: ClientFormatAny,
                 ClientFormatAny::ClientFormat(ClientFormat::U5U5U5U1) => Ok((gl::RGBA_INTEGER, gl::UNSIGNED_SHORT_5_5_5_1)),
                 ClientFormatAny::ClientFormat(ClientFormat::U1U5U5U5Reversed) => Ok((gl::RGBA_INTEGER, gl::UNSIGNED_SHORT_1_5_5_5_REV)),
                 ClientFormatAny::ClientFormat(ClientFormat::U10U10U10U2) => Ok((gl::RGBA_INTEGER, gl::UNSIGNED_INT_10_10_10_2)),
+                ClientFormatAny::ClientFormat(ClientFormat::U2U10U10U10Reversed) => Ok((gl::RGBA_INTEGER, gl::UNSIGNED_INT_2_10_10_10_REV)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16) => Ok((gl::RED_INTEGER, gl::HALF_FLOAT)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16F16) => Ok((gl::RG_INTEGER, gl::HALF_FLOAT)),
                 ClientFormatAny::ClientFormat(ClientFormat::F16F16F16) => Ok((gl::RGB_INTEGER, gl::HALF_FLOAT)),
@@ -2271,3 +2276,172 @@ pub fn client_format_to_glenum(context: &Context, client: ClientFormatAny,
         value
     }
 }
+
+/// Returns the client format corresponding to a format and a type suitable for
+/// `glTexImage#D`, or `None` if glium has no equivalent.
+///
+/// The boolean is true if the red and blue components are swapped, like with `GL_BGRA`.
+pub fn client_format_from_glenums(format: gl::types::GLenum, ty: gl::types::GLenum)
+                                  -> Option<(ClientFormat, bool)>
+{
+    let (components, inverted) = match format {
+        gl::RED | gl::RED_INTEGER | gl::DEPTH_COMPONENT | gl::STENCIL_INDEX => (1, false),
+        gl::RG | gl::RG_INTEGER => (2, false),
+        gl::RGB | gl::RGB_INTEGER => (3, false),
+        gl::BGR | gl::BGR_INTEGER => (3, true),
+        gl::RGBA | gl::RGBA_INTEGER => (4, false),
+        gl::BGRA | gl::BGRA_INTEGER => (4, true),
+        _ => return None,
+    };
+
+    let format = match (ty, components) {
+        (gl::UNSIGNED_BYTE, 1) => ClientFormat::U8,
+        (gl::UNSIGNED_BYTE, 2) => ClientFormat::U8U8,
+        (gl::UNSIGNED_BYTE, 3) => ClientFormat::U8U8U8,
+        (gl::UNSIGNED_BYTE, 4) => ClientFormat::U8U8U8U8,
+        (gl::BYTE, 1) => ClientFormat::I8,
+        (gl::BYTE, 2) => ClientFormat::I8I8,
+        (gl::BYTE, 3) => ClientFormat::I8I8I8,
+        (gl::BYTE, 4) => ClientFormat::I8I8I8I8,
+        (gl::UNSIGNED_SHORT, 1) => ClientFormat::U16,
+        (gl::UNSIGNED_SHORT, 2) => ClientFormat::U16U16,
+        (gl::UNSIGNED_SHORT, 3) => ClientFormat::U16U16U16,
+        (gl::UNSIGNED_SHORT, 4) => ClientFormat::U16U16U16U16,
+        (gl::SHORT, 1) => ClientFormat::I16,
+        (gl::SHORT, 2) => ClientFormat::I16I16,
+        (gl::SHORT, 3) => ClientFormat::I16I16I16,
+        (gl::SHORT, 4) => ClientFormat::I16I16I16I16,
+        (gl::UNSIGNED_INT, 1) => ClientFormat::U32,
+        (gl::UNSIGNED_INT, 2) => ClientFormat::U32U32,
+        (gl::UNSIGNED_INT, 3) => ClientFormat::U32U32U32,
+        (gl::UNSIGNED_INT, 4) => ClientFormat::U32U32U32U32,
+        (gl::INT, 1) => ClientFormat::I32,
+        (gl::INT, 2) => ClientFormat::I32I32,
+        (gl::INT, 3) => ClientFormat::I32I32I32,
+        (gl::INT, 4) => ClientFormat::I32I32I32I32,
+        (gl::HALF_FLOAT, 1) => ClientFormat::F16,
+        (gl::HALF_FLOAT, 2) => ClientFormat::F16F16,
+        (gl::HALF_FLOAT, 3) => ClientFormat::F16F16F16,
+        (gl::HALF_FLOAT, 4) => ClientFormat::F16F16F16F16,
+        (gl::FLOAT, 1) => ClientFormat::F32,
+        (gl::FLOAT, 2) => ClientFormat::F32F32,
+        (gl::FLOAT, 3) => ClientFormat::F32F32F32,
+        (gl::FLOAT, 4) => ClientFormat::F32F32F32F32,
+        (gl::UNSIGNED_BYTE_3_3_2, 3) => ClientFormat::U3U3U2,
+        (gl::UNSIGNED_SHORT_5_6_5, 3) => ClientFormat::U5U6U5,
+        (gl::UNSIGNED_SHORT_4_4_4_4, 4) => ClientFormat::U4U4U4U4,
+        (gl::UNSIGNED_SHORT_5_5_5_1, 4) => ClientFormat::U5U5U5U1,
+        (gl::UNSIGNED_SHORT_1_5_5_5_REV, 4) => ClientFormat::U1U5U5U5Reversed,
+        (gl::UNSIGNED_INT_10_10_10_2, 4) => ClientFormat::U10U10U10U2,
+        (gl::UNSIGNED_INT_2_10_10_10_REV, 4) => ClientFormat::U2U10U10U10Reversed,
+        // the first component is in the lowest byte, which is the first one in memory
+        (gl::UNSIGNED_INT_8_8_8_8_REV, 4) if cfg!(target_endian = "little") => {
+            ClientFormat::U8U8U8U8
+        },
+        _ => return None,
+    };
+
+    Some((format, inverted))
+}
+
+/// Returns the number of bits per texel of an internal format, or `None` if it is unknown.
+///
+/// The size of unsized formats is an estimate, as the implementation is free to choose the
+/// representation. Compressed formats return the average number of bits per texel.
+pub fn internal_format_bits(format: gl::types::GLenum) -> Option<usize> {
+    Some(match format {
+        gl::STENCIL_INDEX1 => 1,
+        gl::STENCIL_INDEX4 => 4,
+        gl::R3_G3_B2 | gl::RGBA2 | gl::R8 | gl::R8_SNORM | gl::R8I | gl::R8UI |
+        gl::STENCIL_INDEX8 | gl::STENCIL_INDEX | gl::RED | gl::COMPRESSED_RED => 8,
+        gl::RGB4 => 12,
+        gl::RGB5 => 15,
+        gl::R16 | gl::R16_SNORM | gl::R16F | gl::R16I | gl::R16UI | gl::RG8 | gl::RG8_SNORM |
+        gl::RG8I | gl::RG8UI | gl::RGBA4 | gl::RGB5_A1 | gl::RGB565 | gl::DEPTH_COMPONENT16 |
+        gl::STENCIL_INDEX16 | gl::RG | gl::COMPRESSED_RG => 16,
+        gl::RGB8 | gl::RGB8_SNORM | gl::RGB8I | gl::RGB8UI | gl::SRGB8 | gl::DEPTH_COMPONENT24 |
+        gl::RGB | gl::COMPRESSED_RGB | gl::COMPRESSED_SRGB => 24,
+        gl::RGB10 => 30,
+        gl::R32F | gl::R32I | gl::R32UI | gl::RG16 | gl::RG16_SNORM | gl::RG16F | gl::RG16I |
+        gl::RG16UI | gl::RGBA8 | gl::RGBA8_SNORM | gl::RGBA8I | gl::RGBA8UI | gl::SRGB8_ALPHA8 |
+        gl::RGB10_A2 | gl::RGB10_A2UI | gl::R11F_G11F_B10F | gl::RGB9_E5 |
+        gl::DEPTH_COMPONENT32 | gl::DEPTH_COMPONENT32F | gl::DEPTH24_STENCIL8 |
+        gl::DEPTH_COMPONENT | gl::DEPTH_STENCIL | gl::RGBA | gl::COMPRESSED_RGBA |
+        gl::COMPRESSED_SRGB_ALPHA => 32,
+        gl::RGB12 => 36,
+        gl::DEPTH32F_STENCIL8 => 40,
+        gl::RGB16 | gl::RGB16_SNORM | gl::RGB16F | gl::RGB16I | gl::RGB16UI | gl::RGBA12 => 48,
+        gl::RG32F | gl::RG32I | gl::RG32UI | gl::RGBA16 | gl::RGBA16_SNORM | gl::RGBA16F |
+        gl::RGBA16I | gl::RGBA16UI => 64,
+        gl::RGB32F | gl::RGB32I | gl::RGB32UI => 96,
+        gl::RGBA32F | gl::RGBA32I | gl::RGBA32UI => 128,
+
+        gl::COMPRESSED_RED_RGTC1 | gl::COMPRESSED_SIGNED_RED_RGTC1 |
+        gl::COMPRESSED_RGB_S3TC_DXT1_EXT | gl::COMPRESSED_RGBA_S3TC_DXT1_EXT |
+        gl::COMPRESSED_SRGB_S3TC_DXT1_EXT | gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT => 4,
+        gl::COMPRESSED_RG_RGTC2 | gl::COMPRESSED_SIGNED_RG_RGTC2 |
+        gl::COMPRESSED_RGBA_BPTC_UNORM | gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM |
+        gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT | gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT |
+        gl::COMPRESSED_RGBA_S3TC_DXT3_EXT | gl::COMPRESSED_RGBA_S3TC_DXT5_EXT |
+        gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT | gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT => 8,
+
+        _ => return None,
+    })
+}
+
+/// Returns an estimate of the size in bytes of a texture or render buffer with the given
+/// internal format, dimensions and number of mipmap levels.
+///
+/// Returns `0` if the format is unknown.
+pub fn image_size(internal_format: gl::types::GLenum, width: u32, height: u32, depth: u32,
+                  layers: u32, samples: u32, levels: u32) -> usize
+{
+    let bits = match internal_format_bits(internal_format) {
+        Some(bits) => bits,
+        None => return 0,
+    };
+
+    let texels = (0 .. levels.max(1)).map(|level| {
+        let width = (width >> level).max(1) as usize;
+        let height = (height >> level).max(1) as usize;
+        let depth = (depth >> level).max(1) as usize;
+        width * height * depth
+    }).sum::<usize>();
+
+    texels * layers.max(1) as usize * samples.max(1) as usize * bits / 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{client_format_from_glenums, ClientFormat};
+    use crate::gl;
+
+    #[test]
+    fn client_formats_from_glenums() {
+        assert_eq!(client_format_from_glenums(gl::RGBA, gl::UNSIGNED_BYTE),
+                   Some((ClientFormat::U8U8U8U8, false)));
+        assert_eq!(client_format_from_glenums(gl::RG_INTEGER, gl::INT),
+                   Some((ClientFormat::I32I32, false)));
+        assert_eq!(client_format_from_glenums(gl::RED, gl::HALF_FLOAT),
+                   Some((ClientFormat::F16, false)));
+
+        // the formats that desktop drivers usually prefer
+        assert_eq!(client_format_from_glenums(gl::BGRA, gl::UNSIGNED_BYTE),
+                   Some((ClientFormat::U8U8U8U8, true)));
+        assert_eq!(client_format_from_glenums(gl::BGR, gl::UNSIGNED_BYTE),
+                   Some((ClientFormat::U8U8U8, true)));
+        assert_eq!(client_format_from_glenums(gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV),
+                   Some((ClientFormat::U2U10U10U10Reversed, false)));
+        assert_eq!(client_format_from_glenums(gl::BGRA_INTEGER, gl::UNSIGNED_INT_2_10_10_10_REV),
+                   Some((ClientFormat::U2U10U10U10Reversed, true)));
+
+        if cfg!(target_endian = "little") {
+            assert_eq!(client_format_from_glenums(gl::BGRA, gl::UNSIGNED_INT_8_8_8_8_REV),
+                       Some((ClientFormat::U8U8U8U8, true)));
+        }
+
+        assert_eq!(client_format_from_glenums(gl::RGB, gl::UNSIGNED_INT_2_10_10_10_REV), None);
+        assert_eq!(client_format_from_glenums(gl::LUMINANCE, gl::UNSIGNED_BYTE), None);
+        assert_eq!(client_format_from_glenums(gl::NONE, gl::NONE), None);
+    }
+}
@@ -179,6 +179,34 @@ impl<T> IndexBuffer<T> where T: Index {
         })
     }
 
+    /// Builds an index buffer from an existing, externally created OpenGL buffer. The number of
+    /// indices is deduced from the size of the buffer.
+    ///
+    /// If `owned` is true, this index buffer will take ownership of the OpenGL buffer and be
+    /// responsible for cleaning it up. Otherwise, the buffer must be cleaned up externally, but
+    /// only after this index buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// See `Buffer::from_id`.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, prim: PrimitiveType,
+                             owned: bool) -> Result<IndexBuffer<T>, CreationError>
+                             where F: Facade + ?Sized
+    {
+        if !prim.is_supported(facade) {
+            return Err(CreationError::PrimitiveTypeNotSupported);
+        }
+
+        if !T::is_supported(facade) {
+            return Err(CreationError::IndexTypeNotSupported);
+        }
+
+        Ok(IndexBuffer {
+            buffer: Buffer::from_id(facade, id, BufferType::ElementArrayBuffer, owned)?,
+            primitives: prim,
+        })
+    }
+
     /// Returns the type of primitives associated with this index buffer.
     #[inline]
     pub fn get_primitives_type(&self) -> PrimitiveType {
@@ -159,6 +159,11 @@ pub mod vertex;
 pub mod semaphore;
 pub mod texture;
 pub mod field;
+pub mod profiler;
+pub mod recovery;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod transfer;
 
 mod context;
 mod fbo;
@@ -170,8 +175,12 @@ mod utils;
 mod version;
 mod vertex_array_object;
 
-mod gl {
-    #![allow(clippy::all)]
+/// The raw OpenGL bindings that glium uses. Passed to the closure of `Context::exec_raw`.
+///
+/// The bindings are generated from the OpenGL registry, and contain the functions of the
+/// extensions that glium loads.
+pub mod gl {
+    #![allow(clippy::all, missing_docs)]
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 }
 
@@ -302,6 +311,7 @@ trait ContextExt {
     fn set_report_debug_output_errors(&self, value: bool);
 
     /// Start executing OpenGL commands by checking the current context.
+    #[track_caller]
     fn make_current(&self) -> context::CommandContext<'_>;
 
     /// Returns the capabilities of the backend.
@@ -370,7 +370,6 @@ macro_rules! implement_uniform_block {
                 fn matches(layout: &$crate::program::BlockLayout, base_offset: usize)
                            -> ::std::result::Result<(), $crate::uniforms::LayoutMismatchError>
                 {
-                    use std::mem;
                     use $crate::program::BlockLayout;
                     use $crate::uniforms::LayoutMismatchError;
 
@@ -384,7 +383,7 @@ macro_rules! implement_uniform_block {
                             }
                         }
 
-                        fn matches_from_ty<T: $crate::uniforms::UniformBlock + ?Sized>(_: &T,
+                        fn matches_from_ty<T: $crate::uniforms::UniformBlock + ?Sized>(_: Option<&T>,
                             layout: &$crate::program::BlockLayout, base_offset: usize)
                             -> ::std::result::Result<(), $crate::uniforms::LayoutMismatchError>
                         {
@@ -402,15 +401,10 @@ macro_rules! implement_uniform_block {
                                     name: stringify!($field_name).to_owned(),
                                 })
                             };
-                            let dummy: *const $struct_name = unsafe { mem::zeroed() };
-                            let input_offset = {
-                                let possibly_fat_pointer_to_field=unsafe{&(*dummy).$field_name};
-                                let pointer_to_possibly_fat_pointer_to_field:&u64=unsafe{mem::transmute( &possibly_fat_pointer_to_field )};
-                                let pointer_to_field=*pointer_to_possibly_fat_pointer_to_field;
-                                pointer_to_field as usize
-                            };
+                            let input_offset = $crate::__glium_offset_of!($struct_name, $field_name);
+                            let field_option = None::<&$struct_name>.map(|v| &v.$field_name);
 
-                            match matches_from_ty(unsafe{&(*dummy).$field_name}, reflected_ty, input_offset) {
+                            match matches_from_ty(field_option, reflected_ty, input_offset) {
                                 Ok(_) => (),
                                 Err(e) => return Err(LayoutMismatchError::MemberMismatch {
                                     member: stringify!($field_name).to_owned(),
@@ -643,6 +637,125 @@ macro_rules! program {
     });
 }
 
+/// Builds a `ProgramVariants` depending on the GLSL version supported by the backend.
+///
+/// The syntax is the same as the one of `program!`, but none of the shaders are compiled. They
+/// are compiled when a variant is requested with `ProgramVariants::get`.
+///
+/// Returns a `Result<ProgramVariants, glium::program::ProgramChooserCreationError>`, whose error
+/// is always `NoVersion`.
+///
+/// ## Example
+///
+/// ```no_run
+/// use glium::program_variants;
+/// use glium::program::ShaderDefines;
+/// # fn example(display: glium::Display) {
+/// let mut variants = program_variants!(&display,
+///     140 => {
+///         vertex: r#"
+///             #version 140
+///
+///             void main() {
+///                 gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+///             }
+///         "#,
+///         fragment: r#"
+///             #version 140
+///
+///             out vec4 color;
+///             void main() {
+///                 color = vec4(INTENSITY, INTENSITY, 0.0, 1.0);
+///             }
+///         "#,
+///     },
+///     110 => {
+///         vertex: r#"
+///             #version 110
+///
+///             void main() {
+///                 gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+///             }
+///         "#,
+///         fragment: r#"
+///             #version 110
+///
+///             void main() {
+///                 gl_FragColor = vec4(INTENSITY, INTENSITY, 0.0, 1.0);
+///             }
+///         "#,
+///     },
+/// ).unwrap();
+///
+/// let program = variants.get(&ShaderDefines::new().define_value("INTENSITY", "0.5")).unwrap();
+/// # }
+/// ```
+///
+#[macro_export]
+macro_rules! program_variants {
+    ($facade:expr,) => (
+        Err($crate::program::ProgramChooserCreationError::NoVersion)
+    );
+
+    ($facade:expr,,$($rest:tt)*) => (
+        $crate::program_variants!($facade,$($rest)*)
+    );
+
+    ($facade:expr, $num:tt => $($rest:tt)*) => (
+        {
+            let context = $crate::backend::Facade::get_context($facade);
+            let version = $crate::program!(_parse_num_gl $num);
+            $crate::program_variants!(_inner, context, version, $($rest)*)
+        }
+    );
+
+    ($facade:expr, $num:tt es => $($rest:tt)*) => (
+        {
+            let context = $crate::backend::Facade::get_context($facade);
+            let version = $crate::program!(_parse_num_gles $num);
+            $crate::program_variants!(_inner, context, version, $($rest)*)
+        }
+    );
+
+    (_inner, $context:ident, $vers:ident, {$($ty:ident:$src:expr),+}$($rest:tt)*) => (
+        if $context.is_glsl_version_supported(&$vers) {
+            let __vertex_shader: &str = "";
+            let __tessellation_control_shader: Option<&str> = None;
+            let __tessellation_evaluation_shader: Option<&str> = None;
+            let __geometry_shader: Option<&str> = None;
+            let __fragment_shader: &str = "";
+            let __outputs_srgb: bool = false;
+            let __uses_point_size: bool = false;
+
+            $(
+                $crate::program!(_program_ty $ty, $src, __vertex_shader, __tessellation_control_shader,
+                         __tessellation_evaluation_shader, __geometry_shader, __fragment_shader,
+                         __outputs_srgb, __uses_point_size);
+            )+
+
+            let source = $crate::program::SourceCode {
+                vertex_shader: __vertex_shader,
+                tessellation_control_shader: __tessellation_control_shader,
+                tessellation_evaluation_shader: __tessellation_evaluation_shader,
+                geometry_shader: __geometry_shader,
+                fragment_shader: __fragment_shader,
+            };
+
+            let variants = $crate::program::ProgramVariants::new($context, source)
+                                .outputs_srgb(__outputs_srgb)
+                                .uses_point_size(__uses_point_size);
+            Ok::<_, $crate::program::ProgramChooserCreationError>(variants)
+
+        } else {
+            $crate::program_variants!($context, $($rest)*)
+        }
+    );
+
+    (_inner, $context:ident, $vers:ident, {$($ty:ident:$src:expr),+,}$($rest:tt)*) => (
+        $crate::program_variants!(_inner, $context, $vers, {$($ty:$src),+} $($rest)*);
+    );
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
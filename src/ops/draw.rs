@@ -97,9 +97,6 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
         let mut instances_count: Option<usize> = None;
 
         for src in vertex_buffers.iter() {
-            // Allow single match for consistency with the match below.
-            // Integrating the two matches wouldn't improve the code either.
-            #[allow(clippy::single_match)]
             match src {
                 VerticesSource::VertexBuffer(buffer, format, per_instance) => {
                     // TODO: assert!(buffer.get_elements_size() == total_size(format));
@@ -110,6 +107,13 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
 
                     binder = binder.add(&buffer, format, if per_instance { Some(1) } else { None });
                 },
+                VerticesSource::VertexBufferWithDivisor(buffer, format, divisor) => {
+                    if let Some(fence) = buffer.add_fence() {
+                        fences.push(fence);
+                    }
+
+                    binder = binder.add(&buffer, format, Some(divisor));
+                },
                 _ => {}
             }
 
@@ -133,6 +137,17 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                         instances_count = Some(buffer.get_elements_count());
                     }
                 },
+                VerticesSource::VertexBufferWithDivisor(ref buffer, _, divisor) => {
+                    let len = buffer.get_elements_count() * divisor as usize;
+
+                    if let Some(curr) = instances_count {
+                        if curr != len {
+                            return Err(DrawError::InstancesCountMismatch);
+                        }
+                    } else {
+                        instances_count = Some(len);
+                    }
+                },
                 VerticesSource::Marker { len, per_instance } if !per_instance => {
                     if let Some(curr) = vertices_count {
                         if curr != len {
@@ -290,6 +290,7 @@ fn client_format_to_gl_enum(format: &ClientFormat, integer: bool)
         ClientFormat::U5U5U5U1 => (gl::RGBA, gl::UNSIGNED_SHORT_5_5_5_1),
         ClientFormat::U1U5U5U5Reversed => (gl::RGBA, gl::UNSIGNED_SHORT_1_5_5_5_REV),
         ClientFormat::U10U10U10U2 => (gl::RGBA, gl::UNSIGNED_INT_10_10_10_2),
+        ClientFormat::U2U10U10U10Reversed => (gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV),
         ClientFormat::F16 => (gl::RED, gl::HALF_FLOAT),
         ClientFormat::F16F16 => (gl::RG, gl::HALF_FLOAT),
         ClientFormat::F16F16F16 => (gl::RGB, gl::HALF_FLOAT),
@@ -0,0 +1,490 @@
+/*!
+Measurement of the time that the GPU spends executing parts of a frame.
+
+A `Profiler` records a timestamp query at the start and at the end of each named scope. Scopes
+can be nested, and the results of a frame are read back a few frames later so that the CPU never
+waits for the GPU in the common case. Each scope is then identified by its path, which is the
+list of the names of the scopes that contain it, separated with `/`.
+
+```no_run
+# fn example(display: glium::Display) {
+use glium::profiler::Profiler;
+
+let mut profiler = Profiler::new(&display, 3).expect("timer queries not supported");
+
+loop {
+    profiler.begin_frame();
+
+    {
+        let _shadows = profiler.scope("shadows");
+        // ... draw the shadow maps ...
+    }
+
+    {
+        let _scene = profiler.scope("scene");
+        let _opaque = profiler.scope("opaque");
+        // ... draw the opaque objects, measured as `scene/opaque` ...
+    }
+
+    profiler.end_frame();
+
+    for scope in profiler.stats() {
+        println!("{}: {:?} (max {:?})", scope.path, scope.average(), scope.max);
+    }
+}
+# }
+```
+
+The frames whose results have been read can be saved in the Chrome trace event format with
+`Profiler::write_chrome_trace`, and opened with `chrome://tracing` or Perfetto.
+
+## OpenGL
+
+This requires OpenGL 3.2 or `GL_EXT_disjoint_timer_query`. glium uses `GL_TIMESTAMP` queries
+rather than `TimeElapsedQuery`, because time elapsed queries can't be nested.
+
+*/
+use crate::backend::Facade;
+use crate::context::Context;
+use crate::debug::TimestampQuery;
+use crate::CapabilitiesSource;
+use crate::version::{Api, Version};
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Default number of frames kept by `Profiler::frames`.
+const DEFAULT_HISTORY_LEN: usize = 300;
+
+/// Measures the time spent by the GPU in named scopes. See the module-level documentation.
+pub struct Profiler {
+    context: Rc<Context>,
+
+    /// Maximum number of frames that can be waiting for their results.
+    latency: usize,
+
+    /// The frame being recorded. In a `RefCell` so that scopes only need a shared reference.
+    recording: RefCell<Recording>,
+
+    /// Frames whose queries have been sent but not read yet, oldest first.
+    pending: VecDeque<PendingFrame>,
+
+    /// Number of the next frame.
+    next_frame: u64,
+
+    /// Frames whose results have been read, oldest first.
+    history: VecDeque<FrameTimings>,
+
+    /// Maximum length of `history`.
+    history_len: usize,
+
+    /// Statistics of each scope, in the order in which they first appeared.
+    stats: Vec<ScopeStats>,
+
+    /// Index in `stats` of each path.
+    stats_index: HashMap<String, usize>,
+}
+
+struct Recording {
+    /// The frame being recorded, between `begin_frame` and `end_frame`.
+    frame: Option<PendingFrame>,
+
+    /// Indices in `frame.scopes` of the scopes that are open.
+    stack: Vec<usize>,
+
+    /// Queries that are not in use.
+    pool: Vec<TimestampQuery>,
+}
+
+struct PendingFrame {
+    number: u64,
+    scopes: Vec<PendingScope>,
+}
+
+struct PendingScope {
+    name: String,
+    path: String,
+    depth: u32,
+    start: TimestampQuery,
+    end: Option<TimestampQuery>,
+}
+
+/// The timings of a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameTimings {
+    /// Number of the frame, starting at 0 for the first call to `begin_frame`.
+    pub number: u64,
+
+    /// The scopes of the frame, in the order in which they have been opened.
+    pub scopes: Vec<ScopeTiming>,
+}
+
+/// The timing of a scope during a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeTiming {
+    /// The name passed to `Profiler::scope`.
+    pub name: String,
+
+    /// The names of the enclosing scopes and of this scope, separated with `/`.
+    pub path: String,
+
+    /// Number of enclosing scopes.
+    pub depth: u32,
+
+    /// GPU time in nanoseconds when the commands of the scope started.
+    pub start: u64,
+
+    /// GPU time in nanoseconds when the commands of the scope were finished.
+    pub end: u64,
+}
+
+impl ScopeTiming {
+    /// Returns the time spent by the GPU in this scope.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.end.saturating_sub(self.start))
+    }
+}
+
+/// Statistics about a scope over all the frames that have been read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeStats {
+    /// The path of the scope. See `ScopeTiming::path`.
+    pub path: String,
+
+    /// Number of times the scope was measured. A scope that is opened multiple times in the
+    /// same frame is counted multiple times.
+    pub count: u64,
+
+    /// Shortest duration.
+    pub min: Duration,
+
+    /// Longest duration.
+    pub max: Duration,
+
+    /// Sum of all the durations.
+    pub total: Duration,
+}
+
+impl ScopeStats {
+    /// Returns the average duration.
+    #[inline]
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            Duration::new(0, 0)
+        } else {
+            Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64)
+        }
+    }
+}
+
+/// Guard returned by `Profiler::scope`. The scope ends when the guard is destroyed.
+pub struct ProfilerScope<'a> {
+    profiler: &'a Profiler,
+    index: usize,
+}
+
+impl<'a> Drop for ProfilerScope<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        self.profiler.end_scope(self.index);
+    }
+}
+
+impl Profiler {
+    /// Builds a new profiler.
+    ///
+    /// `latency` is the number of frames that can be recorded before the results of a frame
+    /// are read. The results are read as soon as they are available, but if they are not
+    /// available after `latency` other frames, `end_frame` waits for them. A value of `2` or `3`
+    /// is enough to never wait for the GPU.
+    ///
+    /// Returns `None` if the backend doesn't support timestamp queries.
+    pub fn new<F>(facade: &F, latency: usize) -> Option<Profiler> where F: Facade + ?Sized {
+        let context = facade.get_context();
+
+        if context.get_version() < &Version(Api::Gl, 3, 2) &&
+           !context.get_extensions().gl_ext_disjoint_timer_query
+        {
+            return None;
+        }
+
+        Some(Profiler {
+            context: context.clone(),
+            latency,
+            recording: RefCell::new(Recording {
+                frame: None,
+                stack: Vec::new(),
+                pool: Vec::new(),
+            }),
+            pending: VecDeque::new(),
+            next_frame: 0,
+            history: VecDeque::new(),
+            history_len: DEFAULT_HISTORY_LEN,
+            stats: Vec::new(),
+            stats_index: HashMap::new(),
+        })
+    }
+
+    /// Starts recording a frame. If a frame is already being recorded, it is ended first.
+    pub fn begin_frame(&mut self) {
+        if self.recording.get_mut().frame.is_some() {
+            self.end_frame();
+        }
+
+        let recording = self.recording.get_mut();
+        recording.frame = Some(PendingFrame {
+            number: self.next_frame,
+            scopes: Vec::new(),
+        });
+        self.next_frame += 1;
+    }
+
+    /// Starts a scope. The scope ends when the returned guard is destroyed.
+    ///
+    /// # Panic
+    ///
+    /// Panics if no frame is being recorded.
+    pub fn scope(&self, name: &str) -> ProfilerScope<'_> {
+        let mut recording = self.recording.borrow_mut();
+        let recording = &mut *recording;
+
+        let start = self.timestamp(&mut recording.pool);
+        let frame = recording.frame.as_mut()
+                                   .expect("Profiler::scope called outside of a frame");
+
+        let path = match recording.stack.last() {
+            Some(&parent) => format!("{}/{}", frame.scopes[parent].path, name),
+            None => name.to_owned(),
+        };
+
+        let index = frame.scopes.len();
+        frame.scopes.push(PendingScope {
+            name: name.to_owned(),
+            path,
+            depth: recording.stack.len() as u32,
+            start,
+            end: None,
+        });
+        recording.stack.push(index);
+
+        ProfilerScope { profiler: self, index }
+    }
+
+    /// Ends the frame being recorded, and reads the results of the previous frames that are
+    /// available. Does nothing if no frame is being recorded.
+    pub fn end_frame(&mut self) {
+        // scopes whose guard has been leaked
+        if let Some(&outermost) = self.recording.get_mut().stack.first() {
+            self.end_scope(outermost);
+        }
+
+        let frame = match self.recording.get_mut().frame.take() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        self.pending.push_back(frame);
+
+        while let Some(frame) = self.pending.front() {
+            if self.pending.len() <= self.latency && !frame.is_ready() {
+                break;
+            }
+
+            let frame = self.pending.pop_front().unwrap();
+            self.read_frame(frame);
+        }
+    }
+
+    /// Waits for the results of all the frames that have been ended and reads them.
+    pub fn flush(&mut self) {
+        while let Some(frame) = self.pending.pop_front() {
+            self.read_frame(frame);
+        }
+    }
+
+    /// Returns the frames whose results have been read, oldest first.
+    ///
+    /// Only the latest frames are kept. See `set_history_len`.
+    #[inline]
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTimings> {
+        self.history.iter()
+    }
+
+    /// Returns the latest frame whose results have been read.
+    #[inline]
+    pub fn latest_frame(&self) -> Option<&FrameTimings> {
+        self.history.back()
+    }
+
+    /// Sets the number of frames kept by `frames`. The default is 300.
+    pub fn set_history_len(&mut self, frames: usize) {
+        self.history_len = frames;
+        while self.history.len() > frames {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the statistics of each scope, in the order in which they first appeared.
+    #[inline]
+    pub fn stats(&self) -> &[ScopeStats] {
+        &self.stats
+    }
+
+    /// Returns the statistics of the scope with the given path.
+    #[inline]
+    pub fn scope_stats(&self, path: &str) -> Option<&ScopeStats> {
+        self.stats_index.get(path).map(|&index| &self.stats[index])
+    }
+
+    /// Clears the statistics and the frames kept by `frames`.
+    pub fn reset(&mut self) {
+        self.stats.clear();
+        self.stats_index.clear();
+        self.history.clear();
+    }
+
+    /// Writes the frames kept by `frames` in the Chrome trace event format.
+    ///
+    /// Each scope is a complete event (`"ph": "X"`) whose arguments contain the number of the
+    /// frame. Times are relative to the start of the oldest frame.
+    pub fn write_chrome_trace<W>(&self, mut writer: W) -> io::Result<()> where W: Write {
+        let origin = self.history.iter().flat_map(|f| f.scopes.iter()).map(|s| s.start).min()
+                                 .unwrap_or(0);
+
+        writer.write_all(b"{\"traceEvents\":[")?;
+
+        let mut first = true;
+        for frame in self.history.iter() {
+            for scope in frame.scopes.iter() {
+                if !first {
+                    writer.write_all(b",")?;
+                }
+                first = false;
+
+                writer.write_all(b"\n{\"name\":")?;
+                write_json_string(&mut writer, &scope.name)?;
+                write!(writer, ",\"cat\":\"gpu\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\
+                                \"pid\":0,\"tid\":0,\"args\":{{\"frame\":{},\"path\":",
+                       format_micros(scope.start.saturating_sub(origin)),
+                       format_micros(scope.end.saturating_sub(scope.start)), frame.number)?;
+                write_json_string(&mut writer, &scope.path)?;
+                writer.write_all(b"}}")?;
+            }
+        }
+
+        writer.write_all(b"\n],\"displayTimeUnit\":\"ms\"}\n")?;
+        writer.flush()
+    }
+
+    /// Returns a query that has recorded the current timestamp.
+    fn timestamp(&self, pool: &mut Vec<TimestampQuery>) -> TimestampQuery {
+        match pool.pop() {
+            Some(query) => {
+                query.restart();
+                query
+            },
+            None => TimestampQuery::new(&self.context).unwrap(),
+        }
+    }
+
+    /// Ends the scope at the given index, and the scopes that it contains.
+    fn end_scope(&self, index: usize) {
+        let mut recording = self.recording.borrow_mut();
+        let recording = &mut *recording;
+
+        while let Some(open) = recording.stack.pop() {
+            let end = self.timestamp(&mut recording.pool);
+            recording.frame.as_mut().unwrap().scopes[open].end = Some(end);
+
+            if open == index {
+                break;
+            }
+        }
+    }
+
+    /// Reads the results of a frame, blocking if necessary.
+    fn read_frame(&mut self, frame: PendingFrame) {
+        let pool = &mut self.recording.get_mut().pool;
+        let mut scopes = Vec::with_capacity(frame.scopes.len());
+
+        for scope in frame.scopes {
+            let end = scope.end.expect("scope was not ended");
+            let timing = ScopeTiming {
+                name: scope.name,
+                path: scope.path,
+                depth: scope.depth,
+                start: scope.start.value(),
+                end: end.value(),
+            };
+            pool.push(scope.start);
+            pool.push(end);
+
+            let index = match self.stats_index.get(&timing.path) {
+                Some(&index) => index,
+                None => {
+                    self.stats.push(ScopeStats {
+                        path: timing.path.clone(),
+                        count: 0,
+                        min: Duration::MAX,
+                        max: Duration::new(0, 0),
+                        total: Duration::new(0, 0),
+                    });
+                    self.stats_index.insert(timing.path.clone(), self.stats.len() - 1);
+                    self.stats.len() - 1
+                },
+            };
+
+            let stats = &mut self.stats[index];
+            let duration = timing.duration();
+            stats.count += 1;
+            stats.min = stats.min.min(duration);
+            stats.max = stats.max.max(duration);
+            stats.total += duration;
+
+            scopes.push(timing);
+        }
+
+        if self.history_len == 0 {
+            return;
+        }
+        if self.history.len() >= self.history_len {
+            self.history.pop_front();
+        }
+        self.history.push_back(FrameTimings { number: frame.number, scopes });
+    }
+}
+
+impl PendingFrame {
+    /// Returns true if all the queries of the frame have their result available.
+    fn is_ready(&self) -> bool {
+        self.scopes.iter().all(|scope| {
+            scope.start.is_ready() && scope.end.as_ref().is_some_and(|end| end.is_ready())
+        })
+    }
+}
+
+/// Formats a number of nanoseconds as microseconds.
+fn format_micros(nanos: u64) -> String {
+    format!("{}.{:03}", nanos / 1000, nanos % 1000)
+}
+
+/// Writes a string as a JSON string literal.
+fn write_json_string<W>(writer: &mut W, value: &str) -> io::Result<()> where W: Write {
+    writer.write_all(b"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\r' => writer.write_all(b"\\r")?,
+            '\t' => writer.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    writer.write_all(b"\"")
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use fnv::FnvHasher;
+
+use crate::backend::Facade;
+use crate::program;
+use crate::program::{Binary, Program, ProgramCreationError, ProgramCreationInput};
+use crate::program::{ShaderIncludes, TransformFeedbackMode};
+
+/// Identifies the files written by a `ProgramCache`, and the version of their format.
+const MAGIC: &[u8] = b"GLIUMPC1";
+
+/// A directory that stores the binaries of programs, in order to avoid compiling them again the
+/// next time the application starts.
+///
+/// The binaries are indexed by a hash of the source code of the shaders, of the transform
+/// feedback varyings, and of the vendor, renderer and version strings of the driver. If a driver
+/// rejects a cached binary, for example after an update, the program is compiled from its source
+/// code and the binary is replaced.
+///
+/// Only programs built from GLSL source code are cached. Caching is skipped when the backend
+/// doesn't support retrieving binaries. The errors that happen while reading or writing the
+/// cache are ignored, since the program can always be compiled instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(display: glium::Display) {
+/// # let vertex_source = ""; let fragment_source = "";
+/// let cache = glium::program::ProgramCache::new("shader-cache").unwrap();
+/// let program = cache.get_or_create(&display, glium::program::SourceCode {
+///     vertex_shader: vertex_source,
+///     fragment_shader: fragment_source,
+///     geometry_shader: None,
+///     tessellation_control_shader: None,
+///     tessellation_evaluation_shader: None,
+/// }).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgramCache {
+    directory: PathBuf,
+}
+
+impl ProgramCache {
+    /// Builds a cache that stores the binaries in `directory`. The directory is created if it
+    /// doesn't exist.
+    pub fn new<P>(directory: P) -> io::Result<ProgramCache> where P: Into<PathBuf> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(ProgramCache { directory })
+    }
+
+    /// Returns the directory where the binaries are stored.
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Builds a program from its cached binary if possible, or builds it like `Program::new`
+    /// and stores its binary.
+    #[inline]
+    pub fn get_or_create<'a, F, I>(&self, facade: &F, input: I)
+                                   -> Result<Program, ProgramCreationError>
+                                   where I: Into<ProgramCreationInput<'a>>, F: Facade + ?Sized
+    {
+        self.build(facade, input.into(), None)
+    }
+
+    /// Same as `get_or_create`, but builds the program like `Program::with_includes`. The
+    /// content of the included files is part of the hash.
+    #[inline]
+    pub fn get_or_create_with_includes<'a, F, I>(&self, facade: &F, input: I,
+                                                 includes: &ShaderIncludes)
+                                                 -> Result<Program, ProgramCreationError>
+                                                 where I: Into<ProgramCreationInput<'a>>,
+                                                       F: Facade + ?Sized
+    {
+        self.build(facade, input.into(), Some(includes))
+    }
+
+    /// Removes all the binaries of the cache.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "bin") {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build<F>(&self, facade: &F, input: ProgramCreationInput<'_>,
+                includes: Option<&ShaderIncludes>) -> Result<Program, ProgramCreationError>
+                where F: Facade + ?Sized
+    {
+        let create = |input| match includes {
+            Some(includes) => Program::with_includes(facade, input, includes),
+            None => Program::new(facade, input),
+        };
+
+        if !program::is_binary_supported(facade.get_context()) {
+            return create(input);
+        }
+
+        let (key, outputs_srgb, uses_point_size) = match input {
+            ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
+                                               tessellation_evaluation_shader, geometry_shader,
+                                               fragment_shader, ref transform_feedback_varyings,
+                                               outputs_srgb, uses_point_size } =>
+            {
+                let stages = [
+                    ("vertex", Some(vertex_shader)),
+                    ("tessellation control", tessellation_control_shader),
+                    ("tessellation evaluation", tessellation_evaluation_shader),
+                    ("geometry", geometry_shader),
+                    ("fragment", Some(fragment_shader)),
+                ];
+
+                (cache_key(facade, &stages, transform_feedback_varyings, includes)?,
+                 outputs_srgb, uses_point_size)
+            },
+            _ => return create(input),
+        };
+
+        let path = self.directory.join(format!("{:016x}.bin", hash(&key)));
+
+        if let Some(binary) = read_binary(&path, &key) {
+            let cached = Program::new(facade, ProgramCreationInput::Binary {
+                data: binary,
+                outputs_srgb,
+                uses_point_size,
+            });
+
+            match cached {
+                Ok(program) => return Ok(program),
+                Err(_) => { let _ = fs::remove_file(&path); },
+            }
+        }
+
+        let program = create(input)?;
+
+        if let Ok(binary) = program.get_binary() {
+            // the binary only contains the glium header if the driver doesn't return anything
+            if binary.content.len() > 1 {
+                let _ = write_binary(&path, &key, &binary);
+            }
+        }
+
+        Ok(program)
+    }
+}
+
+/// Builds the data that identifies a program. The files of the cache contain the whole key,
+/// which guarantees that a hash collision can't load the wrong binary.
+fn cache_key<F>(facade: &F, stages: &[(&str, Option<&str>)],
+                transform_feedback_varyings: &Option<(Vec<String>, TransformFeedbackMode)>,
+                includes: Option<&ShaderIncludes>) -> Result<Vec<u8>, ProgramCreationError>
+                where F: Facade + ?Sized
+{
+    let context = facade.get_context();
+    let mut key = Vec::new();
+
+    let mut push = |data: &[u8]| {
+        key.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        key.extend_from_slice(data);
+    };
+
+    push(env!("CARGO_PKG_VERSION").as_bytes());
+    push(context.get_opengl_vendor_string().as_bytes());
+    push(context.get_opengl_renderer_string().as_bytes());
+    push(context.get_opengl_version_string().as_bytes());
+
+    for &(name, source) in stages {
+        if let Some(source) = source {
+            push(name.as_bytes());
+            match includes {
+                Some(includes) => push(includes.expand(source)?.source.as_bytes()),
+                None => push(source.as_bytes()),
+            }
+        }
+    }
+
+    match *transform_feedback_varyings {
+        Some((ref varyings, mode)) => {
+            push(format!("{:?}", mode).as_bytes());
+            for varying in varyings {
+                push(varying.as_bytes());
+            }
+        },
+        None => push(b""),
+    }
+
+    Ok(key)
+}
+
+fn hash(key: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(key);
+    hasher.finish()
+}
+
+/// Reads a binary from the cache. Returns `None` if the file doesn't exist, is corrupt or
+/// has a different key.
+fn read_binary(path: &Path, key: &[u8]) -> Option<Binary> {
+    let data = fs::read(path).ok()?;
+    let data = data.strip_prefix(MAGIC)?;
+
+    let read_u64 = |data: &[u8]| -> Option<u64> {
+        Some(u64::from_le_bytes(data.get(..8)?.try_into().ok()?))
+    };
+
+    let key_len = read_u64(data)? as usize;
+    let data = data.get(8..)?;
+    if data.get(..key_len)? != key {
+        return None;
+    }
+
+    let data = data.get(key_len..)?;
+    let format = read_u64(data)? as u32;
+    let content = data.get(8..)?.to_vec();
+
+    if content.is_empty() {
+        return None;
+    }
+
+    Some(Binary { format, content })
+}
+
+/// Writes a binary to the cache. The file is written next to its destination and renamed,
+/// so that other processes never read a partially written file.
+fn write_binary(path: &Path, key: &[u8], binary: &Binary) -> io::Result<()> {
+    let mut data = Vec::with_capacity(MAGIC.len() + key.len() + binary.content.len() + 16);
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&(key.len() as u64).to_le_bytes());
+    data.extend_from_slice(key);
+    data.extend_from_slice(&(binary.format as u64).to_le_bytes());
+    data.extend_from_slice(&binary.content);
+
+    let temporary = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&temporary, data)?;
+
+    if let Err(err) = fs::rename(&temporary, path) {
+        let _ = fs::remove_file(&temporary);
+        return Err(err);
+    }
+
+    Ok(())
+}
@@ -11,18 +11,23 @@ use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
 
 use crate::CapabilitiesSource;
+use crate::ContextExt;
 use crate::GlObject;
 use crate::ProgramExt;
 use crate::Handle;
 use crate::RawUniformValue;
 
 use crate::program::{COMPILER_GLOBAL_LOCK, ProgramCreationError, Binary, GetBinaryError, SpirvEntryPoint};
+use crate::program::ShaderType;
 
 use crate::program::reflection::{Uniform, UniformBlock};
 use crate::program::reflection::{ShaderStage, SubroutineData};
-use crate::program::shader::{build_shader, build_spirv_shader, check_shader_type_compatibility};
+use crate::program::include::ShaderIncludes;
+use crate::program::shader::{build_shader, build_shader_with_includes, build_spirv_shader};
+use crate::program::shader::check_shader_type_compatibility;
 
 use crate::program::raw::RawProgram;
+use crate::program::spirv;
 
 use crate::buffer::BufferSlice;
 use crate::uniforms::Uniforms;
@@ -53,6 +58,21 @@ impl ComputeShader {
         })
     }
 
+    /// Builds a new compute shader from some source code that can include the files of
+    /// `includes` with `#include "path"`. See `ShaderIncludes` for more information.
+    #[inline]
+    pub fn with_includes<F>(facade: &F, src: &str, includes: &ShaderIncludes)
+                            -> Result<ComputeShader, ProgramCreationError> where F: Facade + ?Sized
+    {
+        let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+        let shader = build_shader_with_includes(facade, gl::COMPUTE_SHADER, src, includes)?;
+
+        Ok(ComputeShader {
+            raw: RawProgram::from_shaders(facade, &[shader], false, false, false, None)?
+        })
+    }
+
     /// Builds a new compute shader from SPIR-V module.
     #[inline]
     pub fn from_spirv<F: ?Sized>(facade: &F, spirv: &SpirvEntryPoint) -> Result<ComputeShader, ProgramCreationError>
@@ -62,8 +82,12 @@ impl ComputeShader {
 
         let shader = build_spirv_shader(facade, gl::COMPUTE_SHADER, spirv)?;
 
+        let mut raw = RawProgram::from_shaders(facade, &[shader], false, false, false, None)?;
+        raw.apply_spirv_reflection(&mut facade.get_context().make_current(),
+                                   spirv::reflect(spirv, ShaderType::Compute));
+
         Ok(ComputeShader {
-            raw: RawProgram::from_shaders(facade, &[shader], false, false, false, None)?
+            raw
         })
     }
 
@@ -0,0 +1,286 @@
+use std::fmt;
+
+use crate::program::include::{IncludeError, ShaderIncludes};
+
+/// Severity of a `Diagnostic`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informative message.
+    Info,
+    /// The shader is valid, but probably not what you want.
+    Warning,
+    /// The shader is invalid.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match *self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A message of the shader compiler or linker, parsed from the log of the driver.
+///
+/// See `Diagnostic::parse_log` and `ProgramCreationError::diagnostics`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Path of the file that contains the problem, or `None` for the main source of the shader
+    /// or if the message doesn't have a location.
+    ///
+    /// This is the path of a file of the `ShaderIncludes` if the error comes from an included
+    /// file. Source string numbers that don't correspond to an included file are kept as-is.
+    pub file: Option<String>,
+
+    /// Line of the problem, starting from 1.
+    pub line: Option<u32>,
+
+    /// Column of the problem, starting from 1. Only some drivers report it.
+    pub column: Option<u32>,
+
+    /// Severity of the message.
+    pub severity: Severity,
+
+    /// The message, without the location and the severity.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parses the log of a shader compiler or linker.
+    ///
+    /// The formats of Mesa (`0:12(5): error: ...`), NVIDIA (`0(12) : error C0000: ...`) and
+    /// AMD and Intel on Windows (`ERROR: 0:12: ...`) are recognized, with either a source string
+    /// number or a path in place of the `0`. The lines that aren't recognized are added to the
+    /// message of the previous diagnostic, or become a diagnostic without a location.
+    pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+        for line in log.lines() {
+            let line = line.trim_end();
+            if line.trim().is_empty() || is_summary(line) {
+                continue;
+            }
+
+            match parse_line(line) {
+                Some(diagnostic) => diagnostics.push(diagnostic),
+                None => match diagnostics.last_mut() {
+                    Some(last) => {
+                        last.message.push('\n');
+                        last.message.push_str(line.trim());
+                    },
+                    None => diagnostics.push(Diagnostic {
+                        file: None,
+                        line: None,
+                        column: None,
+                        severity: Severity::Info,
+                        message: line.trim().to_owned(),
+                    }),
+                },
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Formats the diagnostic followed by the line of source code that it refers to, with carets
+    /// pointing to the problem.
+    ///
+    /// `source` must be the source code of the shader that the diagnostic comes from, and
+    /// `includes` the files that it can include. If the line can't be found, only the diagnostic
+    /// is formatted.
+    pub fn format_with_source(&self, source: &str, includes: Option<&ShaderIncludes>) -> String {
+        let mut output = format!("{}: {}\n", self.severity, self.message);
+
+        let line_num = match self.line {
+            Some(line) if line >= 1 => line,
+            _ => return output,
+        };
+
+        output.push_str(&format!("  --> {}:{}", self.file.as_deref().unwrap_or("<source>"),
+                                 line_num));
+        if let Some(column) = self.column {
+            output.push_str(&format!(":{}", column));
+        }
+        output.push('\n');
+
+        let content = match self.file {
+            Some(ref file) => match includes.and_then(|includes| includes.get(file)) {
+                Some(content) => content,
+                None => return output,
+            },
+            None => source,
+        };
+
+        let line = match content.lines().nth(line_num as usize - 1) {
+            Some(line) => line,
+            None => return output,
+        };
+
+        // the carets point to the column, or to the whole line
+        let (padding, carets) = match self.column {
+            Some(column) if column >= 1 && (column as usize) <= line.chars().count() => {
+                let padding: String = line.chars().take(column as usize - 1)
+                                          .map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+                (padding, 1)
+            },
+            _ => {
+                let trimmed = line.trim_start();
+                let padding = line[..line.len() - trimmed.len()].to_owned();
+                (padding, trimmed.trim_end().chars().count().max(1))
+            },
+        };
+
+        let number = line_num.to_string();
+        let margin = " ".repeat(number.len());
+        output.push_str(&format!("{} |\n", margin));
+        output.push_str(&format!("{} | {}\n", number, line));
+        output.push_str(&format!("{} | {}{}\n", margin, padding, "^".repeat(carets)));
+
+        output
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(fmt, "{}:{}:", self.file.as_deref().unwrap_or("<source>"), line)?;
+            if let Some(column) = self.column {
+                write!(fmt, "{}:", column)?;
+            }
+            write!(fmt, " ")?;
+        }
+
+        write!(fmt, "{}: {}", self.severity, self.message)
+    }
+}
+
+impl<'a> From<&'a IncludeError> for Diagnostic {
+    fn from(err: &'a IncludeError) -> Diagnostic {
+        let (file, line) = err.location();
+
+        Diagnostic {
+            file: file.map(|file| file.to_owned()),
+            line: Some(line),
+            column: None,
+            severity: Severity::Error,
+            message: err.message(),
+        }
+    }
+}
+
+/// Returns true for lines such as `ERROR: 2 compilation errors.  No code generated.`.
+fn is_summary(line: &str) -> bool {
+    line.starts_with("ERROR: ") && line.ends_with("No code generated.")
+}
+
+/// Parses a line of a log that starts with a location or a severity.
+fn parse_line(line: &str) -> Option<Diagnostic> {
+    // AMD and Intel put the severity before the location
+    let (mut severity, rest) = match split_severity(line) {
+        Some((severity, rest)) => (Some(severity), rest.trim_start()),
+        None => (None, line),
+    };
+
+    let (file, line_num, column, rest) = match parse_location(rest) {
+        Some((file, line_num, column, rest)) => (file, Some(line_num), column, rest),
+        None if severity.is_some() => (None, None, None, rest),
+        None => return None,
+    };
+
+    let mut rest = rest.trim_start().trim_start_matches(':').trim_start();
+
+    if severity.is_none() {
+        if let Some((parsed, after)) = split_severity(rest) {
+            severity = Some(parsed);
+            rest = after.trim_start();
+        } else if let Some((parsed, after)) = split_severity_with_code(rest) {
+            severity = Some(parsed);
+            rest = after.trim_start();
+        }
+    }
+
+    Some(Diagnostic {
+        file,
+        line: line_num,
+        column,
+        severity: severity.unwrap_or(Severity::Error),
+        message: rest.to_owned(),
+    })
+}
+
+/// Parses `error:`, `WARNING:`, etc. at the start of a string.
+fn split_severity(text: &str) -> Option<(Severity, &str)> {
+    let colon = text.find(':')?;
+    let severity = parse_severity(&text[..colon])?;
+    Some((severity, &text[colon + 1..]))
+}
+
+/// Parses the NVIDIA format, `error C0000:`, at the start of a string.
+fn split_severity_with_code(text: &str) -> Option<(Severity, &str)> {
+    let space = text.find(' ')?;
+    let severity = parse_severity(&text[..space])?;
+    let rest = &text[space + 1..];
+    let colon = rest.find(':')?;
+
+    if !rest[..colon].bytes().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some((severity, &rest[colon + 1..]))
+}
+
+fn parse_severity(word: &str) -> Option<Severity> {
+    match &word.to_ascii_lowercase()[..] {
+        "error" | "fatal error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "info" | "note" | "remark" => Some(Severity::Info),
+        _ => None,
+    }
+}
+
+/// Parses `file:line(column)`, `file:line` or `file(line)` at the start of a string, where
+/// `file` is a source string number or a path. Returns the file, the line, the column and the
+/// rest of the string.
+fn parse_location(text: &str) -> Option<(Option<String>, u32, Option<u32>, &str)> {
+    let bytes = text.as_bytes();
+
+    // the file ends with the first `:` or `(` that is followed by a digit
+    let file_end = (1 .. bytes.len().saturating_sub(1)).find(|&pos| {
+        (bytes[pos] == b':' || bytes[pos] == b'(') && bytes[pos + 1].is_ascii_digit()
+    })?;
+
+    let file = &text[..file_end];
+    if file.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let (line, rest) = split_number(&text[file_end + 1..])?;
+
+    let (column, rest) = if bytes[file_end] == b'(' {
+        (None, rest.strip_prefix(')')?)
+    } else {
+        match rest.strip_prefix('(').and_then(split_number) {
+            Some((column, after)) => match after.strip_prefix(')') {
+                Some(after) => (Some(column), after),
+                None => (None, rest),
+            },
+            None => (None, rest),
+        }
+    };
+
+    let file = match file.parse::<u32>() {
+        Ok(0) => None,
+        _ => Some(file.to_owned()),
+    };
+
+    Some((file, line, column, rest))
+}
+
+fn split_number(text: &str) -> Option<(u32, &str)> {
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    let number = text[..digits].parse().ok()?;
+    Some((number, &text[digits..]))
+}
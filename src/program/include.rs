@@ -0,0 +1,649 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasherDefault;
+use std::error::Error;
+use std::fmt;
+
+use fnv::FnvHasher;
+
+/// Name of the extension that allows using `#include` with named strings.
+const INCLUDE_EXTENSION: &str = "GL_ARB_shading_language_include";
+
+/// A set of virtual files that shaders can include with `#include "path"` or `#include <path>`.
+///
+/// Paths are relative to the root of the set, which means that `lighting.glsl` and
+/// `/lighting.glsl` designate the same file. `#include "path"` looks for the file in the
+/// directory of the including file first, then in the root, while `#include <path>` only looks
+/// in the root.
+///
+/// A file is only included once per shader if it contains `#pragma once`, or if its content is
+/// enclosed in a `#ifndef NAME` / `#define NAME` / `#endif` guard.
+///
+/// If the backend supports `GL_ARB_shading_language_include`, the files are passed to the driver
+/// as named strings, which replace any named string with the same name. Otherwise, the includes
+/// are expanded before the source code is passed to the driver. In both cases `#line` directives
+/// are inserted, and the source string numbers in the compilation errors are replaced with the
+/// paths of the files.
+///
+/// Note that the includes are resolved before the driver's preprocessor runs. An `#include`
+/// directive in a disabled `#if` block must still refer to an existing file.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(display: glium::Display) {
+/// let mut includes = glium::program::ShaderIncludes::new();
+/// includes.insert("lighting.glsl", "
+///     #pragma once
+///     float lambert(vec3 normal, vec3 light) { return max(dot(normal, light), 0.0); }
+/// ");
+///
+/// let program = glium::Program::with_includes(&display, glium::program::SourceCode {
+///     vertex_shader: "...",
+///     fragment_shader: "
+///         #version 140
+///         #include \"lighting.glsl\"
+///         ...
+///     ",
+///     geometry_shader: None,
+///     tessellation_control_shader: None,
+///     tessellation_evaluation_shader: None,
+/// }, &includes);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShaderIncludes {
+    files: HashMap<String, String, BuildHasherDefault<FnvHasher>>,
+}
+
+impl ShaderIncludes {
+    /// Builds an empty set of files.
+    #[inline]
+    pub fn new() -> ShaderIncludes {
+        ShaderIncludes::default()
+    }
+
+    /// Adds a file to the set. Returns the previous content of the file, if any.
+    pub fn insert<S>(&mut self, path: &str, source: S) -> Option<String> where S: Into<String> {
+        self.files.insert(normalize_path(path), source.into())
+    }
+
+    /// Removes a file from the set. Returns its content, if any.
+    #[inline]
+    pub fn remove(&mut self, path: &str) -> Option<String> {
+        self.files.remove(&normalize_path(path))
+    }
+
+    /// Returns the content of a file of the set.
+    #[inline]
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.files.get(&normalize_path(path)).map(|source| &source[..])
+    }
+
+    /// Returns true if the set doesn't contain any file.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Expands the includes of some source code, in the same way as when the backend doesn't
+    /// support `GL_ARB_shading_language_include`.
+    ///
+    /// A `#extension GL_ARB_shading_language_include` directive in the source code is removed.
+    pub fn expand(&self, source: &str) -> Result<ExpandedSource, IncludeError> {
+        let mut expander = Expander {
+            includes: self,
+            line_offset: line_offset(source),
+            files: Vec::new(),
+            once: HashSet::new(),
+            stack: Vec::new(),
+            output: String::with_capacity(source.len()),
+        };
+
+        expander.expand_file(None, source, 0)?;
+
+        Ok(ExpandedSource {
+            source: expander.output,
+            files: expander.files,
+        })
+    }
+
+    /// Builds the source code and the named strings to pass to a driver that supports
+    /// `GL_ARB_shading_language_include`. `expanded` must have been built from the same source
+    /// code, and is used to number the files and to check that all the includes are valid.
+    pub(crate) fn driver_sources(&self, source: &str, expanded: &ExpandedSource)
+                                 -> (String, Vec<(String, String)>)
+    {
+        let line_offset = line_offset(source);
+        let main = self.driver_source(expanded, None, source, line_offset);
+
+        let named_strings = expanded.files.iter().map(|path| {
+            let content = &self.files[path];
+            (path.clone(), self.driver_source(expanded, Some(path), content, line_offset))
+        }).collect();
+
+        (main, named_strings)
+    }
+
+    /// Builds the source code of a file to pass to a driver that supports
+    /// `GL_ARB_shading_language_include`.
+    ///
+    /// The includes are replaced with absolute paths and followed by `#line` directives, and
+    /// `#pragma once` is replaced with an include guard. If `path` is `None`, the extension is
+    /// enabled after the `#version` directive.
+    fn driver_source(&self, expanded: &ExpandedSource, path: Option<&str>, content: &str,
+                     line_offset: u32) -> String
+    {
+        let source_string = match path {
+            Some(path) => expanded.files.iter().position(|f| f == path).unwrap() as u32 + 1,
+            None => 0,
+        };
+
+        let has_pragma_once = path.is_some() && significant_lines(content).iter()
+            .any(|line| matches!(parse_directive(line), Some(Directive::PragmaOnce)));
+
+        // the extension is enabled after the `#version` directive, or at the start of the source
+        let enable_extension_after = match path {
+            Some(_) => None,
+            None => Some(version_line(content).map_or(0, |(num, _)| num)),
+        };
+
+        let mut output = String::with_capacity(content.len());
+
+        if has_pragma_once {
+            output.push_str(&format!("#ifndef GLIUM_INCLUDE_ONCE_{0}\n\
+                                      #define GLIUM_INCLUDE_ONCE_{0}\n", source_string));
+        }
+
+        if enable_extension_after == Some(0) {
+            output.push_str(&format!("#extension {} : require\n", INCLUDE_EXTENSION));
+        }
+
+        if path.is_some() || enable_extension_after == Some(0) {
+            output.push_str(&line_directive(1, source_string, line_offset));
+        }
+
+        let directory = path.map_or("/", parent_directory);
+        let mut in_comment = false;
+
+        for (num, line) in content.lines().enumerate() {
+            let line_num = num as u32 + 1;
+            let directive = if in_comment { None } else { parse_directive(line) };
+            update_comment_state(line, &mut in_comment);
+
+            match directive {
+                Some(Directive::Include(Some((target, quoted)))) => {
+                    // the includes have already been checked by `expand`
+                    let resolved = self.resolve(directory, target, quoted).unwrap();
+                    output.push_str(&format!("#include \"{}\"\n", resolved));
+                    output.push_str(&line_directive(line_num + 1, source_string, line_offset));
+                },
+                Some(Directive::PragmaOnce) | Some(Directive::IncludeExtension) => {
+                    output.push('\n');
+                },
+                _ => {
+                    output.push_str(line);
+                    output.push('\n');
+                },
+            }
+
+            if enable_extension_after == Some(line_num) {
+                output.push_str(&format!("#extension {} : require\n", INCLUDE_EXTENSION));
+                output.push_str(&line_directive(line_num + 1, source_string, line_offset));
+            }
+        }
+
+        if has_pragma_once {
+            output.push_str("#endif\n");
+        }
+
+        output
+    }
+
+    /// Returns the normalized path of the file designated by an `#include` directive.
+    fn resolve(&self, directory: &str, path: &str, quoted: bool) -> Option<String> {
+        if quoted && !path.starts_with('/') {
+            let relative = normalize_path(&format!("{}/{}", directory, path));
+            if self.files.contains_key(&relative) {
+                return Some(relative);
+            }
+        }
+
+        let absolute = normalize_path(path);
+        if self.files.contains_key(&absolute) {
+            Some(absolute)
+        } else {
+            None
+        }
+    }
+}
+
+/// Source code whose includes have been expanded. Returned by `ShaderIncludes::expand`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedSource {
+    /// The source code, including the content of the included files and `#line` directives.
+    pub source: String,
+
+    /// Paths of the included files. The file at index `n` is the source string `n + 1` in the
+    /// `#line` directives and in the logs of the driver. The source string 0 is the main source.
+    pub files: Vec<String>,
+}
+
+impl ExpandedSource {
+    /// Returns the path of the file that corresponds to a source string number, or `None` for
+    /// the main source.
+    #[inline]
+    pub fn file_path(&self, source_string: u32) -> Option<&str> {
+        if source_string == 0 {
+            return None;
+        }
+
+        self.files.get(source_string as usize - 1).map(|path| &path[..])
+    }
+
+    /// Replaces the source string numbers at the start of the lines of a compilation log with
+    /// the paths of the corresponding files.
+    ///
+    /// The formats `0:12(5): error`, `0(12) : error` and `ERROR: 0:12: ` are recognized.
+    pub fn remap_log(&self, log: &str) -> String {
+        let mut result = String::with_capacity(log.len());
+
+        for line in log.split_inclusive('\n') {
+            // skipping an optional `ERROR: ` or `WARNING: ` prefix
+            let prefix_len = match line.find(": ") {
+                Some(pos) if pos > 0 && line[..pos].bytes().all(|c| c.is_ascii_uppercase()) => {
+                    pos + 2
+                },
+                _ => 0,
+            };
+
+            let rest = &line[prefix_len..];
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            let after = &rest[digits..];
+
+            let is_location = digits != 0 && (after.starts_with(':') || after.starts_with('(')) &&
+                              after[1..].starts_with(|c: char| c.is_ascii_digit());
+
+            let path = if is_location {
+                rest[..digits].parse().ok().and_then(|num| self.file_path(num))
+            } else {
+                None
+            };
+
+            match path {
+                Some(path) => {
+                    result.push_str(&line[..prefix_len]);
+                    result.push_str(path);
+                    result.push_str(after);
+                },
+                None => result.push_str(line),
+            }
+        }
+
+        result
+    }
+}
+
+/// Error that can happen while resolving the includes of a shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IncludeError {
+    /// An `#include` directive refers to a file that isn't in the `ShaderIncludes`.
+    NotFound {
+        /// The path, as written in the directive.
+        path: String,
+        /// Path of the file that contains the directive, or `None` for the main source.
+        file: Option<String>,
+        /// Line of the directive, starting from 1.
+        line: u32,
+    },
+
+    /// A file includes itself, directly or indirectly, and doesn't have an include guard.
+    Recursive {
+        /// Path of the file that is included recursively.
+        path: String,
+        /// Path of the file that contains the directive, or `None` for the main source.
+        file: Option<String>,
+        /// Line of the directive, starting from 1.
+        line: u32,
+    },
+
+    /// An `#include` directive isn't followed by a path between `""` or `<>`.
+    InvalidDirective {
+        /// Path of the file that contains the directive, or `None` for the main source.
+        file: Option<String>,
+        /// Line of the directive, starting from 1.
+        line: u32,
+    },
+}
+
+impl IncludeError {
+    /// Returns the path of the file that contains the faulty directive, or `None` for the main
+    /// source, and the line of the directive.
+    #[inline]
+    pub fn location(&self) -> (Option<&str>, u32) {
+        match *self {
+            IncludeError::NotFound { ref file, line, .. } |
+            IncludeError::Recursive { ref file, line, .. } |
+            IncludeError::InvalidDirective { ref file, line } => (file.as_deref(), line),
+        }
+    }
+
+    /// Returns the description of the error, without its location.
+    pub(crate) fn message(&self) -> String {
+        match *self {
+            IncludeError::NotFound { ref path, .. } => {
+                format!("Included file `{}` not found", path)
+            },
+            IncludeError::Recursive { ref path, .. } => format!("File `{}` includes itself", path),
+            IncludeError::InvalidDirective { .. } => "Invalid #include directive".to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            (Some(file), line) => write!(fmt, "{} at {}:{}", self.message(), file, line),
+            (None, line) => write!(fmt, "{} at line {}", self.message(), line),
+        }
+    }
+}
+
+impl Error for IncludeError {}
+
+/// State of the expansion of the includes of a shader.
+struct Expander<'a> {
+    includes: &'a ShaderIncludes,
+    line_offset: u32,
+    files: Vec<String>,
+    /// Files that have an include guard and have already been included.
+    once: HashSet<String>,
+    /// Files that are being expanded.
+    stack: Vec<String>,
+    output: String,
+}
+
+impl<'a> Expander<'a> {
+    fn expand_file(&mut self, path: Option<&str>, content: &str, source_string: u32)
+                   -> Result<(), IncludeError>
+    {
+        let directory = path.map_or("/", parent_directory);
+        let mut in_comment = false;
+
+        for (num, line) in content.lines().enumerate() {
+            let line_num = num as u32 + 1;
+            let directive = if in_comment { None } else { parse_directive(line) };
+            update_comment_state(line, &mut in_comment);
+
+            let (target, quoted) = match directive {
+                Some(Directive::Include(Some(include))) => include,
+                Some(Directive::Include(None)) => {
+                    return Err(IncludeError::InvalidDirective {
+                        file: path.map(|p| p.to_owned()),
+                        line: line_num,
+                    });
+                },
+                Some(Directive::PragmaOnce) | Some(Directive::IncludeExtension) => {
+                    self.output.push('\n');
+                    continue;
+                },
+                _ => {
+                    self.output.push_str(line);
+                    self.output.push('\n');
+                    continue;
+                },
+            };
+
+            let resolved = match self.includes.resolve(directory, target, quoted) {
+                Some(resolved) => resolved,
+                None => return Err(IncludeError::NotFound {
+                    path: target.to_owned(),
+                    file: path.map(|p| p.to_owned()),
+                    line: line_num,
+                }),
+            };
+
+            if self.once.contains(&resolved) {
+                self.output.push('\n');
+                continue;
+            }
+
+            if self.stack.contains(&resolved) {
+                return Err(IncludeError::Recursive {
+                    path: resolved,
+                    file: path.map(|p| p.to_owned()),
+                    line: line_num,
+                });
+            }
+
+            let included_source_string = match self.files.iter().position(|f| *f == resolved) {
+                Some(pos) => pos as u32 + 1,
+                None => {
+                    self.files.push(resolved.clone());
+                    self.files.len() as u32
+                },
+            };
+
+            let includes = self.includes;
+            let included = &includes.files[&resolved][..];
+            if has_include_guard(included) {
+                self.once.insert(resolved.clone());
+            }
+
+            let directive = line_directive(1, included_source_string, self.line_offset);
+            self.output.push_str(&directive);
+
+            self.stack.push(resolved.clone());
+            self.expand_file(Some(&resolved), included, included_source_string)?;
+            self.stack.pop();
+
+            let directive = line_directive(line_num + 1, source_string, self.line_offset);
+            self.output.push_str(&directive);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a `#line` directive after which the next line has the number `line`.
+///
+/// `line_offset` is 1 for the GLSL versions where `#line` sets the number of the directive's own
+/// line, and 0 otherwise.
+fn line_directive(line: u32, source_string: u32, line_offset: u32) -> String {
+    format!("#line {} {}\n", line - line_offset, source_string)
+}
+
+/// Returns the offset to pass to `line_directive` for some source code. Before GLSL 3.30, the line
+/// that follows `#line N` has the number `N + 1`.
+fn line_offset(source: &str) -> u32 {
+    match version_line(source) {
+        Some((_, Directive::Version(version, es))) if es || version == 100 || version >= 330 => 0,
+        _ => 1,
+    }
+}
+
+/// Inserts some lines after the `#version` directive of some source code, or at its start if
+/// there's none. The lines are followed by a `#line` directive, so that the line numbers in the
+/// compilation errors still refer to the original source code.
+pub(super) fn insert_after_version(source: &str, lines: &str) -> String {
+    let version_line = version_line(source).map_or(0, |(num, _)| num);
+
+    let mut output = String::with_capacity(source.len() + lines.len() + 16);
+    for line in source.lines().take(version_line as usize) {
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    output.push_str(lines);
+    output.push_str(&line_directive(version_line + 1, 0, line_offset(source)));
+
+    for line in source.lines().skip(version_line as usize) {
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Returns the number of the line of the `#version` directive, if the source code starts with
+/// one.
+fn version_line(source: &str) -> Option<(u32, Directive<'_>)> {
+    let mut in_comment = false;
+
+    for (num, line) in source.lines().enumerate() {
+        let was_in_comment = in_comment;
+        update_comment_state(line, &mut in_comment);
+
+        if was_in_comment || is_blank(line) {
+            continue;
+        }
+
+        return match parse_directive(line) {
+            Some(directive @ Directive::Version(..)) => Some((num as u32 + 1, directive)),
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// Returns true if a file has an include guard.
+fn has_include_guard(content: &str) -> bool {
+    let lines = significant_lines(content);
+
+    if lines.iter().any(|line| matches!(parse_directive(line), Some(Directive::PragmaOnce))) {
+        return true;
+    }
+
+    if lines.len() < 3 {
+        return false;
+    }
+
+    match (parse_directive(lines[0]), parse_directive(lines[1]),
+           parse_directive(lines[lines.len() - 1]))
+    {
+        (Some(Directive::Ifndef(a)), Some(Directive::Define(b)), Some(Directive::Endif)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns the lines that aren't blank and don't start in a comment.
+fn significant_lines(content: &str) -> Vec<&str> {
+    let mut in_comment = false;
+
+    content.lines().filter(|line| {
+        let was_in_comment = in_comment;
+        update_comment_state(line, &mut in_comment);
+        !was_in_comment && !is_blank(line)
+    }).collect()
+}
+
+/// Returns true if a line only contains whitespace or a comment.
+fn is_blank(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with("//") || line.starts_with("/*")
+}
+
+/// Updates `in_comment` with the block comments that start or end in a line.
+fn update_comment_state(line: &str, in_comment: &mut bool) {
+    let bytes = line.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        if *in_comment {
+            if bytes[pos..].starts_with(b"*/") {
+                *in_comment = false;
+                pos += 2;
+                continue;
+            }
+        } else if bytes[pos..].starts_with(b"//") {
+            break;
+        } else if bytes[pos..].starts_with(b"/*") {
+            *in_comment = true;
+            pos += 2;
+            continue;
+        }
+
+        pos += 1;
+    }
+}
+
+/// Preprocessor directives that are relevant for the includes.
+enum Directive<'a> {
+    /// `#include`, with the path and whether it is between quotes. `None` if the directive is
+    /// malformed.
+    Include(Option<(&'a str, bool)>),
+    PragmaOnce,
+    /// `#extension GL_ARB_shading_language_include`.
+    IncludeExtension,
+    /// `#version`, with the version and whether it is followed by `es`.
+    Version(u32, bool),
+    Ifndef(&'a str),
+    Define(&'a str),
+    Endif,
+}
+
+fn parse_directive(line: &str) -> Option<Directive<'_>> {
+    let line = line.trim_start().strip_prefix('#')?.trim_start();
+    let keyword_len = line.find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                          .unwrap_or(line.len());
+    let (keyword, args) = line.split_at(keyword_len);
+    let args = args.trim();
+    let mut tokens = args.split_whitespace();
+
+    match keyword {
+        "include" => {
+            let closing = match args.chars().next() {
+                Some('"') => '"',
+                Some('<') => '>',
+                _ => return Some(Directive::Include(None)),
+            };
+
+            let end = match args[1..].find(closing) {
+                Some(end) => end + 1,
+                None => return Some(Directive::Include(None)),
+            };
+
+            let rest = args[end + 1..].trim_start();
+            if end == 1 || !(rest.is_empty() || rest.starts_with("//") || rest.starts_with("/*")) {
+                return Some(Directive::Include(None));
+            }
+
+            Some(Directive::Include(Some((&args[1..end], closing == '"'))))
+        },
+        "pragma" if tokens.next() == Some("once") => Some(Directive::PragmaOnce),
+        "extension" if args.split(':').next().map(str::trim) == Some(INCLUDE_EXTENSION) => {
+            Some(Directive::IncludeExtension)
+        },
+        "version" => {
+            let version = tokens.next()?.parse().ok()?;
+            Some(Directive::Version(version, tokens.next() == Some("es")))
+        },
+        "ifndef" => tokens.next().map(Directive::Ifndef),
+        "define" => tokens.next().map(Directive::Define),
+        "endif" => Some(Directive::Endif),
+        _ => None,
+    }
+}
+
+/// Turns a path into an absolute path without `.` and `..` components.
+fn normalize_path(path: &str) -> String {
+    let mut components = Vec::new();
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => (),
+            ".." => { components.pop(); },
+            component => components.push(component),
+        }
+    }
+
+    format!("/{}", components.join("/"))
+}
+
+/// Returns the directory of a normalized path.
+fn parent_directory(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(pos) => &path[..pos],
+    }
+}
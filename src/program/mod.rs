@@ -9,18 +9,33 @@ use crate::gl;
 use crate::version::Api;
 use crate::version::Version;
 
+pub use self::cache::ProgramCache;
 pub use self::compute::{ComputeShader, ComputeCommand};
+pub use self::diagnostics::{Diagnostic, Severity};
+pub use self::include::{ShaderIncludes, ExpandedSource, IncludeError};
+pub use self::pending::PendingProgram;
+pub use self::pipeline::{PipelineStages, ProgramPipeline, ShaderStageProgram};
 pub use self::program::Program;
 pub use self::reflection::{Uniform, UniformBlock, BlockLayout, OutputPrimitives};
 pub use self::reflection::{Attribute, TransformFeedbackVarying, TransformFeedbackBuffer, TransformFeedbackMode};
 pub use self::reflection::{ShaderStage, SubroutineData, SubroutineUniform};
+pub use self::reloadable::{ReloadableProgram, ReloadError, SourcePaths};
+pub use self::variants::{ProgramVariants, ShaderDefines};
 
+mod cache;
 mod compute;
+mod diagnostics;
+mod include;
+mod pending;
+mod pipeline;
 mod program;
 mod raw;
 mod reflection;
+mod reloadable;
 mod shader;
+mod spirv;
 mod uniforms_storage;
+mod variants;
 mod binary_header;
 
 /// Returns true if the backend supports geometry shaders.
@@ -42,6 +57,16 @@ pub fn is_binary_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSou
         || ctxt.get_extensions().gl_arb_get_programy_binary
 }
 
+/// Returns true if the backend supports separate shader objects, in other words
+/// `ShaderStageProgram` and `ProgramPipeline`.
+#[inline]
+pub fn is_separate_shader_objects_supported<C>(ctxt: &C) -> bool
+                                                where C: CapabilitiesSource + ?Sized
+{
+    ctxt.get_version() >= &Version(Api::Gl, 4, 1) || ctxt.get_version() >= &Version(Api::GlEs, 3, 1)
+        || ctxt.get_extensions().gl_arb_separate_shader_objects
+}
+
 /// Returns true if the backend supports shader subroutines.
 #[inline]
 pub fn is_subroutine_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
@@ -135,6 +160,29 @@ pub enum ProgramCreationError {
 
     /// The glium-specific binary header was not found or is corrupt.
     BinaryHeaderError,
+
+    /// Error while resolving the `#include` directives of one of the shaders.
+    IncludeError(IncludeError),
+
+    /// You have requested a `ShaderStageProgram` or a `ProgramPipeline`, but separate shader
+    /// objects are not supported by the backend.
+    SeparateShaderObjectsNotSupported,
+}
+
+impl ProgramCreationError {
+    /// Returns the structured diagnostics of the error.
+    ///
+    /// The logs of `CompilationError` and `LinkingError` are parsed with
+    /// `Diagnostic::parse_log`, and an `IncludeError` is turned into a diagnostic that points to
+    /// the faulty directive. The other errors don't have any diagnostic.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match *self {
+            ProgramCreationError::CompilationError(ref log, _) |
+            ProgramCreationError::LinkingError(ref log) => Diagnostic::parse_log(log),
+            ProgramCreationError::IncludeError(ref err) => vec![Diagnostic::from(err)],
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl fmt::Display for ProgramCreationError {
@@ -163,19 +211,40 @@ impl fmt::Display for ProgramCreationError {
                 "Point size is not supported by the backend.",
             BinaryHeaderError =>
                 "The glium-specific binary header was not found or is corrupt.",
+            IncludeError(_) =>
+                "Error while resolving the includes of a shader",
+            SeparateShaderObjectsNotSupported =>
+                "Separate shader objects are not supported by the backend.",
         };
         match *self {
             CompilationError(ref s, _) =>
                 write!(fmt, "{}: {}", desc, s),
             LinkingError(ref s) =>
                 write!(fmt, "{}: {}", desc, s),
+            IncludeError(ref err) =>
+                write!(fmt, "{}: {}", desc, err),
             _ =>
                 write!(fmt, "{}", desc),
         }
     }
 }
 
-impl Error for ProgramCreationError {}
+impl Error for ProgramCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ProgramCreationError::IncludeError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IncludeError> for ProgramCreationError {
+    #[inline]
+    fn from(err: IncludeError) -> ProgramCreationError {
+        ProgramCreationError::IncludeError(err)
+    }
+}
 
 /// Error type that is returned by the `program!` macro.
 #[derive(Clone, Debug)]
@@ -376,13 +445,42 @@ impl<'a> SpirvProgram<'a> {
 }
 
 /// Represents an entry point of a binary SPIR-V module.
+///
+/// Build it with `SpirvEntryPoint::new`, as fields may be added in the future.
 #[derive(Copy, Clone)]
+#[non_exhaustive]
 pub struct SpirvEntryPoint<'a> {
     /// The binary module data.
     pub binary: &'a [u8],
 
     /// The entry point to use, e.g. "main".
     pub entry_point: &'a str,
+
+    /// The values of the specialization constants of the entry point, as pairs of a
+    /// `constant_id` and of the bits of the value, e.g. `1.5f32.to_bits()`.
+    ///
+    /// The specialization constants that are not in this list keep their default value.
+    pub specialization_constants: &'a [(u32, u32)],
+}
+
+impl<'a> SpirvEntryPoint<'a> {
+    /// Create new `SpirvEntryPoint` from a module and the name of an entry point, without
+    /// specialization constants.
+    #[inline]
+    pub fn new(binary: &'a [u8], entry_point: &'a str) -> Self {
+        SpirvEntryPoint {
+            binary,
+            entry_point,
+            specialization_constants: &[],
+        }
+    }
+
+    /// Builder method to set `specialization_constants`.
+    #[inline]
+    pub fn specialization_constants(mut self, specialization_constants: &'a [(u32, u32)]) -> Self {
+        self.specialization_constants = specialization_constants;
+        self
+    }
 }
 
 /// Represents the source code of a program.
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::backend::Context;
+use crate::ContextExt;
+use crate::Handle;
+
+use crate::program::{COMPILER_GLOBAL_LOCK, Program, ProgramCreationError, ShaderType};
+use crate::program::raw::{self, RawProgram};
+use crate::program::reflection::ShaderStage;
+use crate::program::shader::{Shader, check_shader_compilation};
+
+/// A program whose shaders are being compiled and linked by the driver.
+///
+/// Returned by `Program::new_async`. Dropping a pending program cancels its creation.
+pub struct PendingProgram {
+    state: Option<State>,
+}
+
+enum State {
+    Linking(Linking),
+    Built(Box<Program>),
+}
+
+/// A program that has been sent to the driver, but whose status hasn't been checked.
+struct Linking {
+    context: Rc<Context>,
+    id: Handle,
+    shaders: Vec<(Shader, ShaderType)>,
+    outputs_srgb: bool,
+    uses_point_size: bool,
+}
+
+impl PendingProgram {
+    /// Builds a pending program from a program that the driver is linking.
+    pub(crate) fn linking(context: &Rc<Context>, id: Handle, shaders: Vec<(Shader, ShaderType)>,
+                          outputs_srgb: bool, uses_point_size: bool) -> PendingProgram
+    {
+        PendingProgram {
+            state: Some(State::Linking(Linking {
+                context: context.clone(),
+                id,
+                shaders,
+                outputs_srgb,
+                uses_point_size,
+            })),
+        }
+    }
+
+    /// Builds a pending program from a program that has already been built.
+    #[inline]
+    pub(crate) fn built(program: Program) -> PendingProgram {
+        PendingProgram {
+            state: Some(State::Built(Box::new(program))),
+        }
+    }
+
+    /// Returns true if the driver has finished compiling and linking the program, in which case
+    /// `wait` doesn't block on the driver.
+    ///
+    /// Only the status of the driver is checked. The errors and the reflection of the program
+    /// are still handled by `wait`.
+    ///
+    /// Always returns true if the backend doesn't support `GL_KHR_parallel_shader_compile` or
+    /// `GL_ARB_parallel_shader_compile`, since there is no way to know.
+    pub fn is_ready(&self) -> bool {
+        match self.state {
+            Some(State::Linking(ref linking)) => {
+                let mut ctxt = linking.context.make_current();
+                unsafe { RawProgram::is_linking_complete(&mut ctxt, linking.id) }
+            },
+            _ => true,
+        }
+    }
+
+    /// Waits until the driver has finished compiling and linking the program, then checks for
+    /// errors and returns the program.
+    ///
+    /// This is where the reflection of the uniforms, attributes and blocks of the program
+    /// happens, even if `is_ready` has returned true. Call `is_ready` first to avoid blocking
+    /// on the driver.
+    pub fn wait(mut self) -> Result<Program, ProgramCreationError> {
+        match self.state.take().unwrap() {
+            State::Linking(linking) => linking.finish(),
+            State::Built(program) => Ok(*program),
+        }
+    }
+}
+
+impl Linking {
+    fn finish(self) -> Result<Program, ProgramCreationError> {
+        let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+        // a shader that doesn't compile makes the linking fail, but its log is more useful
+        let compilation = self.shaders.iter().try_for_each(|(shader, ty)| {
+            check_shader_compilation(shader, ty.to_opengl_type())
+        });
+
+        let stages = self.shaders.iter().filter_map(|&(_, ty)| ShaderStage::from_shader_type(ty))
+                                 .collect::<Vec<_>>();
+
+        let mut ctxt = self.context.make_current();
+
+        let raw = match compilation {
+            Ok(()) => unsafe {
+                RawProgram::finish_linking(&self.context, &mut ctxt, self.id, &stages)
+            },
+            Err(err) => {
+                unsafe { raw::delete_program(&mut ctxt, self.id) };
+                Err(err)
+            },
+        };
+
+        drop(ctxt);
+        Ok(Program::from_raw(raw?, self.outputs_srgb, self.uses_point_size))
+    }
+}
+
+impl fmt::Debug for PendingProgram {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.state {
+            Some(State::Linking(ref linking)) => {
+                write!(formatter, "PendingProgram #{:?}", linking.id)
+            },
+            Some(State::Built(ref program)) => write!(formatter, "PendingProgram({:?})", program),
+            None => write!(formatter, "PendingProgram"),
+        }
+    }
+}
+
+impl Drop for PendingProgram {
+    fn drop(&mut self) {
+        if let Some(State::Linking(linking)) = self.state.take() {
+            let mut ctxt = linking.context.make_current();
+            unsafe { raw::delete_program(&mut ctxt, linking.id) };
+        }
+    }
+}
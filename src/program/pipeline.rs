@@ -0,0 +1,569 @@
+use crate::gl;
+
+use crate::backend::Facade;
+use crate::context::CommandContext;
+use crate::context::Context;
+use crate::ContextExt;
+
+use std::fmt;
+use std::collections::hash_map::{self, HashMap};
+use std::hash::BuildHasherDefault;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use fnv::FnvHasher;
+use smallvec::SmallVec;
+
+use crate::GlObject;
+use crate::ProgramExt;
+use crate::Handle;
+use crate::RawUniformValue;
+
+use crate::program;
+use crate::program::{COMPILER_GLOBAL_LOCK, Program, ProgramCreationError, ShaderType};
+use crate::program::include::ShaderIncludes;
+use crate::program::raw::RawProgram;
+use crate::program::reflection::{Attribute, OutputPrimitives, ShaderStage, SubroutineData};
+use crate::program::reflection::{TransformFeedbackBuffer, Uniform, UniformBlock};
+use crate::program::shader::{build_shader, build_shader_with_includes};
+
+/// A program that contains a single shader stage, and that can be combined with the programs
+/// of other stages in a `ProgramPipeline`.
+///
+/// Linking a program for every combination of vertex and fragment shaders can be expensive.
+/// Instead, you can build a `ShaderStageProgram` for each shader and combine them at will.
+///
+/// Since the stages are linked separately, the outputs of a stage must exactly match the inputs
+/// of the next stage. With GLSL 1.50 and above, a vertex shader must also redeclare the
+/// `gl_PerVertex` block that it writes to, for example with
+/// `out gl_PerVertex { vec4 gl_Position; };`.
+pub struct ShaderStageProgram {
+    raw: Rc<RawProgram>,
+    stage: ShaderType,
+}
+
+impl ShaderStageProgram {
+    /// Builds a program that contains a single stage from GLSL source code.
+    #[inline]
+    pub fn new<F>(facade: &F, stage: ShaderType, source_code: &str)
+                  -> Result<ShaderStageProgram, ProgramCreationError> where F: Facade + ?Sized
+    {
+        ShaderStageProgram::build(facade, stage, source_code, None)
+    }
+
+    /// Builds a program that contains a single stage, whose source code can include the files
+    /// of `includes`. See `ShaderIncludes` for more information.
+    #[inline]
+    pub fn with_includes<F>(facade: &F, stage: ShaderType, source_code: &str,
+                            includes: &ShaderIncludes)
+                            -> Result<ShaderStageProgram, ProgramCreationError>
+                            where F: Facade + ?Sized
+    {
+        ShaderStageProgram::build(facade, stage, source_code, Some(includes))
+    }
+
+    fn build<F>(facade: &F, stage: ShaderType, source_code: &str,
+                includes: Option<&ShaderIncludes>)
+                -> Result<ShaderStageProgram, ProgramCreationError> where F: Facade + ?Sized
+    {
+        if !program::is_separate_shader_objects_supported(facade.get_context()) {
+            return Err(ProgramCreationError::SeparateShaderObjectsNotSupported);
+        }
+
+        if stage == ShaderType::Compute {
+            return Err(ProgramCreationError::ShaderTypeNotSupported);
+        }
+
+        let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+        let shader = match includes {
+            Some(includes) => build_shader_with_includes(facade, stage.to_opengl_type(),
+                                                         source_code, includes)?,
+            None => build_shader(facade, stage.to_opengl_type(), source_code)?,
+        };
+
+        let raw = {
+            let mut ctxt = facade.get_context().make_current();
+
+            unsafe {
+                let id = RawProgram::start_linking(&mut ctxt, &[shader.get_id()], None, true);
+                let stages = ShaderStage::from_shader_type(stage).into_iter().collect::<Vec<_>>();
+                RawProgram::finish_linking(facade.get_context(), &mut ctxt, id, &stages)?
+            }
+        };
+
+        Ok(ShaderStageProgram {
+            raw: Rc::new(raw),
+            stage,
+        })
+    }
+
+    /// Returns the stage of the program.
+    #[inline]
+    pub fn get_stage(&self) -> ShaderType {
+        self.stage
+    }
+
+    /// Returns informations about a uniform variable of this stage, if it exists.
+    #[inline]
+    pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
+        self.raw.get_uniform(name)
+    }
+
+    /// Returns an iterator to the list of uniforms of this stage.
+    #[inline]
+    pub fn uniforms(&self) -> hash_map::Iter<'_, String, Uniform> {
+        self.raw.uniforms()
+    }
+
+    /// Returns informations about an attribute, if it exists. Only vertex stages have
+    /// attributes.
+    #[inline]
+    pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.raw.get_attribute(name)
+    }
+}
+
+impl fmt::Debug for ShaderStageProgram {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(formatter, "{:?} ({:?} stage)", self.raw, self.stage)
+    }
+}
+
+impl GlObject for ShaderStageProgram {
+    type Id = Handle;
+
+    #[inline]
+    fn get_id(&self) -> Handle {
+        self.raw.get_id()
+    }
+}
+
+/// The stages of a `ProgramPipeline`.
+///
+/// Each `ShaderStageProgram` must have been built for the stage that it is used for.
+#[derive(Copy, Clone, Debug)]
+pub struct PipelineStages<'a> {
+    /// Program of the vertex stage.
+    pub vertex_shader: &'a ShaderStageProgram,
+    /// Program of the tessellation control stage.
+    pub tessellation_control_shader: Option<&'a ShaderStageProgram>,
+    /// Program of the tessellation evaluation stage.
+    pub tessellation_evaluation_shader: Option<&'a ShaderStageProgram>,
+    /// Program of the geometry stage.
+    pub geometry_shader: Option<&'a ShaderStageProgram>,
+    /// Program of the fragment stage.
+    pub fragment_shader: &'a ShaderStageProgram,
+    /// Whether the fragment shader outputs colors in `sRGB` instead of `RGB`. See
+    /// `Program::has_srgb_output`.
+    pub outputs_srgb: bool,
+    /// Whether the shaders set `gl_PointSize`. See `Program::uses_point_size`.
+    pub uses_point_size: bool,
+}
+
+/// A combination of `ShaderStageProgram`s, created with `glGenProgramPipelines`.
+///
+/// A pipeline dereferences to a `Program`, which means that it can be used anywhere a `Program`
+/// can, for example with `Surface::draw`. The uniforms, blocks and subroutines of the program
+/// are the ones of all the stages. If several stages have a uniform or a block with the same
+/// name, they must have the same type, and they receive the same value.
+///
+/// The pipeline keeps its stages alive, and the same `ShaderStageProgram` can be used in
+/// several pipelines.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(display: glium::Display) {
+/// # let vertex_source = ""; let fragment_source = "";
+/// use glium::program::{PipelineStages, ProgramPipeline, ShaderStageProgram, ShaderType};
+///
+/// let vertex = ShaderStageProgram::new(&display, ShaderType::Vertex, vertex_source).unwrap();
+/// let fragment = ShaderStageProgram::new(&display, ShaderType::Fragment,
+///                                        fragment_source).unwrap();
+///
+/// let pipeline = ProgramPipeline::new(&display, PipelineStages {
+///     vertex_shader: &vertex,
+///     tessellation_control_shader: None,
+///     tessellation_evaluation_shader: None,
+///     geometry_shader: None,
+///     fragment_shader: &fragment,
+///     outputs_srgb: false,
+///     uses_point_size: false,
+/// }).unwrap();
+/// # }
+/// ```
+pub struct ProgramPipeline {
+    id: gl::types::GLuint,
+    program: Program,
+}
+
+impl ProgramPipeline {
+    /// Builds a new program pipeline.
+    ///
+    /// Returns a `LinkingError` if a `ShaderStageProgram` is used for another stage than the
+    /// one it was built for, or if two stages have a uniform or a block with the same name but
+    /// a different type.
+    pub fn new<F>(facade: &F, stages: PipelineStages<'_>)
+                  -> Result<ProgramPipeline, ProgramCreationError> where F: Facade + ?Sized
+    {
+        if !program::is_separate_shader_objects_supported(facade.get_context()) {
+            return Err(ProgramCreationError::SeparateShaderObjectsNotSupported);
+        }
+
+        let slots = [
+            (Some(stages.vertex_shader), ShaderType::Vertex),
+            (stages.tessellation_control_shader, ShaderType::TesselationControl),
+            (stages.tessellation_evaluation_shader, ShaderType::TesselationEvaluation),
+            (stages.geometry_shader, ShaderType::Geometry),
+            (Some(stages.fragment_shader), ShaderType::Fragment),
+        ];
+
+        let mut programs = Vec::with_capacity(slots.len());
+        for (program, stage) in slots {
+            if let Some(program) = program {
+                if program.stage != stage {
+                    return Err(ProgramCreationError::LinkingError(
+                        format!("A program of the {:?} stage was used as the {:?} stage of the \
+                                 pipeline", program.stage, stage)));
+                }
+
+                programs.push((program.raw.clone(), stage));
+            }
+        }
+
+        let reflection = merge_reflection(&programs)?;
+
+        let id = unsafe {
+            let ctxt = facade.get_context().make_current();
+
+            let mut id = 0;
+            ctxt.gl.GenProgramPipelines(1, &mut id);
+
+            for (program, stage) in programs.iter() {
+                let program_id = match program.get_id() {
+                    Handle::Id(id) => id,
+                    Handle::Handle(_) => unreachable!(),
+                };
+
+                ctxt.gl.UseProgramStages(id, stage_bit(*stage), program_id);
+            }
+
+            id
+        };
+
+        let pipeline = Pipeline {
+            context: facade.get_context().clone(),
+            id,
+            programs,
+            uniforms: reflection.uniform_locations,
+            uniform_blocks: reflection.uniform_block_ids,
+            shader_storage_blocks: reflection.shader_storage_block_ids,
+        };
+
+        let raw = RawProgram::from_pipeline(facade.get_context(), pipeline, reflection.merged);
+
+        Ok(ProgramPipeline {
+            id,
+            program: Program::from_raw(raw, stages.outputs_srgb, stages.uses_point_size),
+        })
+    }
+}
+
+impl Deref for ProgramPipeline {
+    type Target = Program;
+
+    #[inline]
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+impl fmt::Debug for ProgramPipeline {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(formatter, "ProgramPipeline #{}", self.id)
+    }
+}
+
+impl GlObject for ProgramPipeline {
+    type Id = gl::types::GLuint;
+
+    #[inline]
+    fn get_id(&self) -> gl::types::GLuint {
+        self.id
+    }
+}
+
+/// The program pipeline object behind a `RawProgram`, and where to find the uniforms and the
+/// blocks of the merged reflection in the programs of the stages.
+pub(crate) struct Pipeline {
+    context: Rc<Context>,
+    id: gl::types::GLuint,
+    programs: Vec<(Rc<RawProgram>, ShaderType)>,
+
+    // for each location of the merged reflection, the programs that contain the uniform or the
+    // block, and its location in these programs
+    uniforms: Vec<SmallVec<[(usize, gl::types::GLint); 2]>>,
+    uniform_blocks: Vec<SmallVec<[(usize, gl::types::GLuint); 2]>>,
+    shader_storage_blocks: Vec<SmallVec<[(usize, gl::types::GLuint); 2]>>,
+}
+
+/// The reflection of a pipeline, merged from the reflection of its stages.
+pub(crate) struct PipelineReflection {
+    pub uniforms: HashMap<String, Uniform, BuildHasherDefault<FnvHasher>>,
+    pub uniform_blocks: HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>>,
+    pub shader_storage_blocks: HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>>,
+    pub atomic_counters: HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>>,
+    pub subroutine_data: SubroutineData,
+    pub attributes: HashMap<String, Attribute, BuildHasherDefault<FnvHasher>>,
+    pub transform_feedback_buffers: Vec<TransformFeedbackBuffer>,
+    pub output_primitives: Option<OutputPrimitives>,
+    pub has_geometry_shader: bool,
+    pub has_tessellation_control_shader: bool,
+    pub has_tessellation_evaluation_shader: bool,
+}
+
+impl Pipeline {
+    /// Returns the program of the vertex stage, which determines the attributes of the
+    /// pipeline.
+    #[inline]
+    pub fn vertex_program(&self) -> &RawProgram {
+        &self.programs[0].0
+    }
+
+    /// Returns the name of the program pipeline object.
+    #[inline]
+    pub fn get_id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    /// Binds the pipeline. The current program is unbound, since it would take precedence
+    /// over the pipeline.
+    pub fn bind(&self, ctxt: &mut CommandContext<'_>) {
+        unsafe {
+            if ctxt.state.program != Handle::Id(0) {
+                ctxt.gl.UseProgram(0);
+                ctxt.state.program = Handle::Id(0);
+            }
+
+            if ctxt.state.program_pipeline != self.id {
+                ctxt.gl.BindProgramPipeline(self.id);
+                ctxt.state.program_pipeline = self.id;
+            }
+        }
+    }
+
+    /// Sets the value of a uniform in all the stages that contain it.
+    pub fn set_uniform(&self, ctxt: &mut CommandContext<'_>, location: gl::types::GLint,
+                       value: &RawUniformValue)
+    {
+        for &(program, location) in self.uniforms[location as usize].iter() {
+            self.programs[program].0.set_uniform(ctxt, location, value);
+        }
+    }
+
+    /// Sets the binding of a uniform block in all the stages that contain it.
+    pub fn set_uniform_block_binding(&self, ctxt: &mut CommandContext<'_>,
+                                     block_location: gl::types::GLuint,
+                                     value: gl::types::GLuint)
+    {
+        for &(program, block) in self.uniform_blocks[block_location as usize].iter() {
+            self.programs[program].0.set_uniform_block_binding(ctxt, block, value);
+        }
+    }
+
+    /// Sets the binding of a shader storage block in all the stages that contain it.
+    pub fn set_shader_storage_block_binding(&self, ctxt: &mut CommandContext<'_>,
+                                            block_location: gl::types::GLuint,
+                                            value: gl::types::GLuint)
+    {
+        for &(program, block) in self.shader_storage_blocks[block_location as usize].iter() {
+            self.programs[program].0.set_shader_storage_block_binding(ctxt, block, value);
+        }
+    }
+
+    /// Sets the subroutine uniforms of a stage of the pipeline, which must be bound.
+    pub fn set_subroutine_uniforms_for_stage(&self, ctxt: &mut CommandContext<'_>,
+                                             stage: ShaderStage, indices: &[gl::types::GLuint])
+    {
+        // binding a pipeline resets the subroutine uniforms, so they can't be cached
+        debug_assert!(ctxt.state.program_pipeline == self.id);
+        unsafe {
+            ctxt.gl.UniformSubroutinesuiv(stage.to_gl_enum(), indices.len() as gl::types::GLsizei,
+                                          indices.as_ptr());
+        }
+    }
+
+    /// Returns the location of an output of the fragment stage.
+    pub fn get_frag_data_location(&self, name: &str) -> Option<u32> {
+        self.programs.iter()
+            .find(|&&(_, stage)| stage == ShaderType::Fragment)
+            .and_then(|(program, _)| program.get_frag_data_location(name))
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        let mut ctxt = self.context.make_current();
+
+        unsafe {
+            if ctxt.state.program_pipeline == self.id {
+                ctxt.gl.BindProgramPipeline(0);
+                ctxt.state.program_pipeline = 0;
+            }
+
+            ctxt.gl.DeleteProgramPipelines(1, &self.id);
+        }
+    }
+}
+
+struct MergedReflection {
+    merged: PipelineReflection,
+    uniform_locations: Vec<SmallVec<[(usize, gl::types::GLint); 2]>>,
+    uniform_block_ids: Vec<SmallVec<[(usize, gl::types::GLuint); 2]>>,
+    shader_storage_block_ids: Vec<SmallVec<[(usize, gl::types::GLuint); 2]>>,
+}
+
+/// Merges the reflection of the programs of a pipeline. The locations of the uniforms and the
+/// identifiers of the blocks are replaced by indices in the returned lists.
+fn merge_reflection(programs: &[(Rc<RawProgram>, ShaderType)])
+                    -> Result<MergedReflection, ProgramCreationError>
+{
+    let has_stage = |stage| programs.iter().any(|&(_, s)| s == stage);
+
+    let mut uniforms: HashMap<String, Uniform, BuildHasherDefault<FnvHasher>> =
+        HashMap::with_hasher(Default::default());
+    let mut uniform_locations: Vec<SmallVec<[(usize, gl::types::GLint); 2]>> = Vec::new();
+
+    for (index, (program, _)) in programs.iter().enumerate() {
+        for (name, uniform) in program.uniforms() {
+            match uniforms.get(name) {
+                Some(existing) => {
+                    if existing.ty != uniform.ty || existing.size != uniform.size {
+                        return Err(ProgramCreationError::LinkingError(
+                            format!("The uniform `{}` has different types in the stages of \
+                                     the pipeline", name)));
+                    }
+
+                    uniform_locations[existing.location as usize].push((index, uniform.location));
+                },
+                None => {
+                    uniforms.insert(name.clone(), Uniform {
+                        location: uniform_locations.len() as i32,
+                        .. *uniform
+                    });
+
+                    let mut locations = SmallVec::new();
+                    locations.push((index, uniform.location));
+                    uniform_locations.push(locations);
+                },
+            }
+        }
+    }
+
+    let (uniform_blocks, uniform_block_ids) =
+        merge_blocks(programs, |program| program.get_uniform_blocks())?;
+    let (shader_storage_blocks, shader_storage_block_ids) =
+        merge_blocks(programs, |program| program.get_shader_storage_blocks())?;
+
+    // atomic counters are bound to the binding point written in the shader
+    let mut atomic_counters = HashMap::with_hasher(Default::default());
+    let mut subroutine_data = SubroutineData {
+        location_counts: HashMap::with_hasher(Default::default()),
+        subroutine_uniforms: HashMap::with_hasher(Default::default()),
+    };
+
+    for (program, _) in programs.iter() {
+        for (name, counter) in program.get_atomic_counters().iter() {
+            atomic_counters.entry(name.clone()).or_insert_with(|| counter.clone());
+        }
+
+        let data = program.get_subroutine_data();
+        subroutine_data.location_counts.extend(data.location_counts.iter()
+                                                   .map(|(&stage, &count)| (stage, count)));
+        subroutine_data.subroutine_uniforms.extend(data.subroutine_uniforms.iter()
+                                                       .map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    // the last stage before the rasterization determines the transform feedback outputs and the
+    // primitives
+    let last_vertex_stage = programs.iter()
+        .rfind(|&&(_, stage)| stage != ShaderType::Fragment)
+        .map(|(program, _)| program).unwrap();
+
+    let vertex_program = &programs[0].0;
+
+    Ok(MergedReflection {
+        merged: PipelineReflection {
+            uniforms,
+            uniform_blocks,
+            shader_storage_blocks,
+            atomic_counters,
+            subroutine_data,
+            attributes: vertex_program.attributes().map(|(k, v)| (k.clone(), *v)).collect(),
+            transform_feedback_buffers: last_vertex_stage.get_transform_feedback_buffers().to_vec(),
+            output_primitives: last_vertex_stage.get_output_primitives(),
+            has_geometry_shader: has_stage(ShaderType::Geometry),
+            has_tessellation_control_shader: has_stage(ShaderType::TesselationControl),
+            has_tessellation_evaluation_shader: has_stage(ShaderType::TesselationEvaluation),
+        },
+        uniform_locations,
+        uniform_block_ids,
+        shader_storage_block_ids,
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn merge_blocks<'a, G>(programs: &'a [(Rc<RawProgram>, ShaderType)], get: G)
+                       -> Result<(HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>>,
+                                  Vec<SmallVec<[(usize, gl::types::GLuint); 2]>>),
+                                 ProgramCreationError>
+                       where G: Fn(&'a RawProgram)
+                                   -> &'a HashMap<String, UniformBlock,
+                                                  BuildHasherDefault<FnvHasher>>
+{
+    let mut blocks: HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>> =
+        HashMap::with_hasher(Default::default());
+    let mut ids: Vec<SmallVec<[(usize, gl::types::GLuint); 2]>> = Vec::new();
+
+    for (index, (program, _)) in programs.iter().enumerate() {
+        for (name, block) in get(program).iter() {
+            match blocks.get(name) {
+                Some(existing) => {
+                    if existing.layout != block.layout || existing.size != block.size {
+                        return Err(ProgramCreationError::LinkingError(
+                            format!("The block `{}` has different layouts in the stages of the \
+                                     pipeline", name)));
+                    }
+
+                    ids[existing.id as usize].push((index, block.id as gl::types::GLuint));
+                },
+                None => {
+                    blocks.insert(name.clone(), UniformBlock {
+                        id: ids.len() as i32,
+                        .. block.clone()
+                    });
+
+                    let mut block_ids = SmallVec::new();
+                    block_ids.push((index, block.id as gl::types::GLuint));
+                    ids.push(block_ids);
+                },
+            }
+        }
+    }
+
+    Ok((blocks, ids))
+}
+
+fn stage_bit(stage: ShaderType) -> gl::types::GLbitfield {
+    match stage {
+        ShaderType::Vertex => gl::VERTEX_SHADER_BIT,
+        ShaderType::Geometry => gl::GEOMETRY_SHADER_BIT,
+        ShaderType::Fragment => gl::FRAGMENT_SHADER_BIT,
+        ShaderType::TesselationControl => gl::TESS_CONTROL_SHADER_BIT,
+        ShaderType::TesselationEvaluation => gl::TESS_EVALUATION_SHADER_BIT,
+        ShaderType::Compute => gl::COMPUTE_SHADER_BIT,
+    }
+}
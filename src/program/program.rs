@@ -6,6 +6,7 @@ use crate::version::Api;
 
 use crate::backend::Facade;
 use crate::CapabilitiesSource;
+use crate::ContextExt;
 
 use std::fmt;
 use std::collections::hash_map::{self, HashMap};
@@ -24,9 +25,13 @@ use crate::program::GetBinaryError;
 use crate::program::reflection::{Uniform, UniformBlock, OutputPrimitives};
 use crate::program::reflection::{Attribute, TransformFeedbackBuffer};
 use crate::program::reflection::{SubroutineData, ShaderStage, SubroutineUniform};
-use crate::program::shader::{build_shader, build_spirv_shader};
+use crate::program::include::ShaderIncludes;
+use crate::program::pending::PendingProgram;
+use crate::program::shader::{build_shader, build_shader_with_includes, build_spirv_shader};
+use crate::program::shader::submit_shader;
 
 use crate::program::raw::RawProgram;
+use crate::program::spirv;
 
 use crate::vertex::VertexFormat;
 
@@ -39,59 +44,58 @@ pub struct Program {
 
 impl Program {
     /// Builds a new program.
+    #[inline]
     pub fn new<'a, F: ?Sized, I>(facade: &F, input: I) -> Result<Program, ProgramCreationError>
                          where I: Into<ProgramCreationInput<'a>>, F: Facade
     {
-        let input = input.into();
+        Program::build(facade, input.into(), None)
+    }
+
+    /// Builds a new program whose shaders can include the files of `includes` with
+    /// `#include "path"`.
+    ///
+    /// The includes are only used if the input is GLSL source code. See `ShaderIncludes` for more
+    /// information.
+    #[inline]
+    pub fn with_includes<'a, F, I>(facade: &F, input: I, includes: &ShaderIncludes)
+                                   -> Result<Program, ProgramCreationError>
+                                   where I: Into<ProgramCreationInput<'a>>, F: Facade + ?Sized
+    {
+        Program::build(facade, input.into(), Some(includes))
+    }
 
+    fn build<F>(facade: &F, input: ProgramCreationInput<'_>, includes: Option<&ShaderIncludes>)
+                -> Result<Program, ProgramCreationError> where F: Facade + ?Sized
+    {
         let (raw, outputs_srgb, uses_point_size) = match input {
             ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
                                                tessellation_evaluation_shader, geometry_shader,
                                                fragment_shader, transform_feedback_varyings,
                                                outputs_srgb, uses_point_size } =>
             {
-                let mut has_geometry_shader = false;
-                let mut has_tessellation_control_shader = false;
-                let mut has_tessellation_evaluation_shader = false;
-
-                let mut shaders = vec![
-                    (vertex_shader, ShaderType::Vertex),
-                    (fragment_shader, ShaderType::Fragment)
-                ];
-
-                if let Some(gs) = geometry_shader {
-                    shaders.push((gs, ShaderType::Geometry));
-                    has_geometry_shader = true;
-                }
-
-                if let Some(ts) = tessellation_control_shader {
-                    shaders.push((ts, ShaderType::TesselationControl));
-                    has_tessellation_control_shader = true;
-                }
-
-                if let Some(ts) = tessellation_evaluation_shader {
-                    shaders.push((ts, ShaderType::TesselationEvaluation));
-                    has_tessellation_evaluation_shader = true;
-                }
+                let has_geometry_shader = geometry_shader.is_some();
+                let has_tessellation_control_shader = tessellation_control_shader.is_some();
+                let has_tessellation_evaluation_shader = tessellation_evaluation_shader.is_some();
 
-                // TODO: move somewhere else
-                if transform_feedback_varyings.is_some() &&
-                    !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) &&
-                    !facade.get_context().get_extensions().gl_ext_transform_feedback
-                {
-                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
-                }
+                let shaders = source_code_shaders(vertex_shader, tessellation_control_shader,
+                                                  tessellation_evaluation_shader,
+                                                  geometry_shader, fragment_shader);
 
-                if uses_point_size && (facade.get_context().get_version().0 == Api::Gl) && !(facade.get_context().get_version() >= &Version(Api::Gl, 2, 0)) {
-                    return Err(ProgramCreationError::PointSizeNotSupported);
-                }
+                check_stages_support(facade, transform_feedback_varyings.is_some(),
+                                     uses_point_size)?;
 
                 let _lock = COMPILER_GLOBAL_LOCK.lock();
 
                 let shaders_store = {
                     let mut shaders_store = Vec::new();
                     for (src, ty) in shaders.into_iter() {
-                        shaders_store.push(build_shader(facade, ty.to_opengl_type(), src)?);
+                        let shader = match includes {
+                            Some(includes) => build_shader_with_includes(facade,
+                                                                         ty.to_opengl_type(),
+                                                                         src, includes)?,
+                            None => build_shader(facade, ty.to_opengl_type(), src)?,
+                        };
+                        shaders_store.push(shader);
                     }
                     shaders_store
                 };
@@ -139,32 +143,31 @@ impl Program {
                     has_tessellation_evaluation_shader = true;
                 }
 
-                // TODO: move somewhere else
-                if transform_feedback_varyings.is_some() &&
-                    !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 0)) &&
-                    !facade.get_context().get_extensions().gl_ext_transform_feedback
-                {
-                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
-                }
-
-                if uses_point_size && (facade.get_context().get_version().0 == Api::Gl) && !(facade.get_context().get_version() >= &Version(Api::Gl, 2, 0)) {
-                    return Err(ProgramCreationError::PointSizeNotSupported);
-                }
+                check_stages_support(facade, transform_feedback_varyings.is_some(),
+                                     uses_point_size)?;
 
                 let _lock = COMPILER_GLOBAL_LOCK.lock();
 
                 let shaders_store = {
                     let mut shaders_store = Vec::new();
-                    for (src, ty) in shaders.into_iter() {
-                        shaders_store.push(build_spirv_shader(facade, ty.to_opengl_type(), &src)?);
+                    for &(ref src, ty) in shaders.iter() {
+                        shaders_store.push(build_spirv_shader(facade, ty.to_opengl_type(), src)?);
                     }
                     shaders_store
                 };
 
-                (RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
-                                               has_tessellation_control_shader, has_tessellation_evaluation_shader,
-                                               transform_feedback_varyings)?,
-                 outputs_srgb, uses_point_size)
+                let mut raw = RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
+                                                       has_tessellation_control_shader,
+                                                       has_tessellation_evaluation_shader,
+                                                       transform_feedback_varyings)?;
+
+                let mut ctxt = facade.get_context().make_current();
+                for (src, ty) in shaders {
+                    raw.apply_spirv_reflection(&mut ctxt, spirv::reflect(&src, ty));
+                }
+                drop(ctxt);
+
+                (raw, outputs_srgb, uses_point_size)
             }
         };
         Ok(Program {
@@ -174,6 +177,90 @@ impl Program {
         })
     }
 
+    /// Starts building a new program, without waiting for the driver to compile and link it.
+    ///
+    /// With `GL_KHR_parallel_shader_compile` or `GL_ARB_parallel_shader_compile`, the driver
+    /// compiles and links the shaders in the background. `PendingProgram::is_ready` tells whether
+    /// the program is ready, and `PendingProgram::wait` returns it. The compilation and linking
+    /// errors, and the reflection of the uniforms and attributes of the program, are only
+    /// handled by `wait`: once `is_ready` returns true, `wait` doesn't block on the driver
+    /// anymore, but it still queries the program, which takes some time for big programs.
+    ///
+    /// Only GLSL source code is compiled in the background. Programs built from a binary or from
+    /// SPIR-V are built before this function returns, exactly like with `Program::new`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn example(display: glium::Display) {
+    /// # let vertex_source = ""; let fragment_source = "";
+    /// let pending = glium::Program::new_async(&display, glium::program::SourceCode {
+    ///     vertex_shader: vertex_source,
+    ///     fragment_shader: fragment_source,
+    ///     geometry_shader: None,
+    ///     tessellation_control_shader: None,
+    ///     tessellation_evaluation_shader: None,
+    /// }).unwrap();
+    ///
+    /// // ... draw the next frames with another program ...
+    ///
+    /// if pending.is_ready() {
+    ///     // the driver has finished, but the reflection of the program happens here
+    ///     let program = pending.wait().unwrap();
+    /// }
+    /// # }
+    /// ```
+    pub fn new_async<'a, F, I>(facade: &F, input: I) -> Result<PendingProgram, ProgramCreationError>
+                               where I: Into<ProgramCreationInput<'a>>, F: Facade + ?Sized
+    {
+        match input.into() {
+            ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
+                                               tessellation_evaluation_shader, geometry_shader,
+                                               fragment_shader, transform_feedback_varyings,
+                                               outputs_srgb, uses_point_size } =>
+            {
+                let shaders = source_code_shaders(vertex_shader, tessellation_control_shader,
+                                                  tessellation_evaluation_shader,
+                                                  geometry_shader, fragment_shader);
+
+                check_stages_support(facade, transform_feedback_varyings.is_some(),
+                                     uses_point_size)?;
+
+                let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+                let mut shaders_store = Vec::with_capacity(shaders.len());
+                for (src, ty) in shaders.into_iter() {
+                    shaders_store.push((submit_shader(facade, ty.to_opengl_type(), src)?, ty));
+                }
+
+                let id = {
+                    let mut ctxt = facade.get_context().make_current();
+                    let shaders_ids = shaders_store.iter().map(|(s, _)| s.get_id())
+                                                   .collect::<Vec<_>>();
+                    unsafe {
+                        RawProgram::start_linking(&mut ctxt, &shaders_ids,
+                                                  transform_feedback_varyings, false)
+                    }
+                };
+
+                Ok(PendingProgram::linking(facade.get_context(), id, shaders_store, outputs_srgb,
+                                           uses_point_size))
+            },
+
+            input => Program::build(facade, input, None).map(PendingProgram::built),
+        }
+    }
+
+    /// Builds the program object of a `RawProgram`.
+    #[inline]
+    pub(crate) fn from_raw(raw: RawProgram, outputs_srgb: bool, uses_point_size: bool) -> Program {
+        Program {
+            raw,
+            outputs_srgb,
+            uses_point_size,
+        }
+    }
+
     /// Builds a new program from GLSL source code.
     ///
     /// A program is a group of shaders linked together.
@@ -211,6 +298,32 @@ impl Program {
         })
     }
 
+    /// Builds a program from an existing, externally created and linked OpenGL program. The
+    /// uniforms, attributes and blocks of the program are queried from the backend.
+    ///
+    /// The stages of the program are detected from the shaders that are still attached to it.
+    /// Like with `from_source`, the program is assumed not to output sRGB or to set the size of
+    /// points.
+    ///
+    /// If `owned` is true, this program will take ownership of the OpenGL program and be
+    /// responsible for cleaning it up. Otherwise, the program must be cleaned up externally, but
+    /// only after this program's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a program of the facade's context (or of a context sharing its
+    /// objects).
+    #[inline]
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> Result<Program, ProgramCreationError> where F: Facade + ?Sized
+    {
+        Ok(Program {
+            raw: RawProgram::from_id(facade, id, owned)?,
+            outputs_srgb: false,
+            uses_point_size: false,
+        })
+    }
+
     /// Returns the program's compiled binary.
     ///
     /// You can store the result in a file, then reload it later. This avoids having to compile
@@ -220,6 +333,14 @@ impl Program {
         self.raw.get_binary()
     }
 
+    /// Attaches a label to the program object. See `Buffer::set_label`.
+    ///
+    /// Does nothing if the program was created with `GL_ARB_shader_objects`.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        self.raw.set_label(label)
+    }
+
     /// Returns the *location* of an output fragment, if it exists.
     ///
     /// The *location* is low-level information that is used internally by glium.
@@ -415,6 +536,52 @@ impl Program {
     }
 }
 
+/// Returns the GLSL shaders of a program, in the order in which they are compiled.
+fn source_code_shaders<'a>(vertex_shader: &'a str, tessellation_control_shader: Option<&'a str>,
+                           tessellation_evaluation_shader: Option<&'a str>,
+                           geometry_shader: Option<&'a str>, fragment_shader: &'a str)
+                           -> Vec<(&'a str, ShaderType)>
+{
+    let mut shaders = vec![
+        (vertex_shader, ShaderType::Vertex),
+        (fragment_shader, ShaderType::Fragment)
+    ];
+
+    if let Some(gs) = geometry_shader {
+        shaders.push((gs, ShaderType::Geometry));
+    }
+
+    if let Some(ts) = tessellation_control_shader {
+        shaders.push((ts, ShaderType::TesselationControl));
+    }
+
+    if let Some(ts) = tessellation_evaluation_shader {
+        shaders.push((ts, ShaderType::TesselationEvaluation));
+    }
+
+    shaders
+}
+
+/// Checks that the backend supports the features used by a program built from shaders.
+fn check_stages_support<F>(facade: &F, transform_feedback: bool, uses_point_size: bool)
+                           -> Result<(), ProgramCreationError> where F: Facade + ?Sized
+{
+    let context = facade.get_context();
+    let version = context.get_version();
+
+    if transform_feedback && !(version >= &Version(Api::Gl, 3, 0)) &&
+       !context.get_extensions().gl_ext_transform_feedback
+    {
+        return Err(ProgramCreationError::TransformFeedbackNotSupported);
+    }
+
+    if uses_point_size && version.0 == Api::Gl && !(version >= &Version(Api::Gl, 2, 0)) {
+        return Err(ProgramCreationError::PointSizeNotSupported);
+    }
+
+    Ok(())
+}
+
 impl fmt::Debug for Program {
     #[inline]
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -1,6 +1,8 @@
 use crate::gl;
+use crate::debug;
 
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::version::Version;
 use crate::version::Api;
 
@@ -32,6 +34,7 @@ use crate::BufferExt;
 use crate::BufferSliceExt;
 
 use crate::program::{ProgramCreationError, Binary, GetBinaryError};
+use crate::program::pipeline::{Pipeline, PipelineReflection};
 use crate::program::uniforms_storage::UniformsStorage;
 
 use crate::program::compute::ComputeCommand;
@@ -41,8 +44,10 @@ use crate::program::reflection::{SubroutineData, ShaderStage};
 use crate::program::reflection::{reflect_uniforms, reflect_attributes, reflect_uniform_blocks};
 use crate::program::reflection::{reflect_transform_feedback, reflect_geometry_output_type};
 use crate::program::reflection::{reflect_tess_eval_output_type, reflect_shader_storage_blocks};
-use crate::program::reflection::{reflect_subroutine_data};
+use crate::program::reflection::{reflect_subroutine_data, get_shader_stages};
+use crate::program::reflection::reflect_uniform_locations;
 use crate::program::shader::Shader;
+use crate::program::spirv::{SpirvBlock, SpirvReflection};
 use crate::program::binary_header::{attach_glium_header, process_glium_header};
 
 use crate::uniforms::Uniforms;
@@ -67,6 +72,8 @@ pub struct RawProgram {
     has_geometry_shader: bool,
     has_tessellation_control_shader: bool,
     has_tessellation_evaluation_shader: bool,
+    owned: bool,
+    pipeline: Option<Pipeline>,
 }
 
 impl RawProgram {
@@ -83,123 +90,149 @@ impl RawProgram {
 
         let shaders_ids = shaders.into_iter().map(|s| s.get_id()).collect::<Vec<_>>();
 
-        let id = unsafe {
-            let id = create_program(&mut ctxt);
+        unsafe {
+            let id = RawProgram::start_linking(&mut ctxt, &shaders_ids, transform_feedback,
+                                               false);
+            let stages = get_shader_stages(has_geometry_shader, has_tessellation_control_shader,
+                                           has_tessellation_evaluation_shader);
+            RawProgram::finish_linking(facade.get_context(), &mut ctxt, id, &stages)
+        }
+    }
 
-            // attaching shaders
-            for sh in shaders_ids.iter() {
-                match (id, sh) {
-                    (Handle::Id(id), &Handle::Id(sh)) => {
-                        assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
-                                ctxt.version >= &Version(Api::GlEs, 2, 0));
-                        ctxt.gl.AttachShader(id, sh);
-                    },
-                    (Handle::Handle(id), &Handle::Handle(sh)) => {
-                        assert!(ctxt.extensions.gl_arb_shader_objects);
-                        ctxt.gl.AttachObjectARB(id, sh);
-                    },
-                    _ => unreachable!()
-                }
+    /// Creates a program, attaches the shaders to it and starts linking it, without waiting
+    /// for the result.
+    ///
+    /// If `separable` is true, the program can be used as a stage of a program pipeline.
+    pub(crate) unsafe fn start_linking(ctxt: &mut CommandContext<'_>, shaders_ids: &[Handle],
+                                       transform_feedback: Option<(Vec<String>,
+                                                                   TransformFeedbackMode)>,
+                                       separable: bool) -> Handle
+    {
+        let id = create_program(ctxt);
+
+        if separable {
+            match id {
+                Handle::Id(id) => {
+                    ctxt.gl.ProgramParameteri(id, gl::PROGRAM_SEPARABLE,
+                                              gl::TRUE as gl::types::GLint);
+                },
+                Handle::Handle(_) => unreachable!(),
             }
+        }
 
-            // transform feedback varyings
-            if let Some((names, mode)) = transform_feedback {
-                let id = match id {
-                    Handle::Id(id) => id,
-                    Handle::Handle(id) => unreachable!()    // transf. feedback shouldn't be
-                                                            // available with handles
+        // attaching shaders
+        for sh in shaders_ids.iter() {
+            match (id, sh) {
+                (Handle::Id(id), &Handle::Id(sh)) => {
+                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                            ctxt.version >= &Version(Api::GlEs, 2, 0));
+                    ctxt.gl.AttachShader(id, sh);
+                },
+                (Handle::Handle(id), &Handle::Handle(sh)) => {
+                    assert!(ctxt.extensions.gl_arb_shader_objects);
+                    ctxt.gl.AttachObjectARB(id, sh);
+                },
+                _ => unreachable!()
+            }
+        }
+
+        // transform feedback varyings
+        if let Some((names, mode)) = transform_feedback {
+            let id = match id {
+                Handle::Id(id) => id,
+                Handle::Handle(id) => unreachable!()    // transf. feedback shouldn't be
+                                                        // available with handles
+            };
+
+            let names = names.into_iter().map(|name| {
+                ffi::CString::new(name.into_bytes()).unwrap()
+            }).collect::<Vec<_>>();
+            let names_ptr = names.iter().map(|n| n.as_ptr()).collect::<Vec<_>>();
+
+            if ctxt.version >= &Version(Api::Gl, 3, 0) {
+                let mode = match mode {
+                    TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+                    TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
                 };
 
-                let names = names.into_iter().map(|name| {
-                    ffi::CString::new(name.into_bytes()).unwrap()
-                }).collect::<Vec<_>>();
-                let names_ptr = names.iter().map(|n| n.as_ptr()).collect::<Vec<_>>();
+                ctxt.gl.TransformFeedbackVaryings(id, names_ptr.len() as gl::types::GLsizei,
+                                                  names_ptr.as_ptr(), mode);
 
-                if ctxt.version >= &Version(Api::Gl, 3, 0) {
-                    let mode = match mode {
-                        TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
-                        TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
-                    };
+            } else if ctxt.extensions.gl_ext_transform_feedback {
+                let mode = match mode {
+                    TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS_EXT,
+                    TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS_EXT,
+                };
 
-                    ctxt.gl.TransformFeedbackVaryings(id, names_ptr.len() as gl::types::GLsizei,
-                                                      names_ptr.as_ptr(), mode);
+                ctxt.gl.TransformFeedbackVaryingsEXT(id, names_ptr.len()
+                                                     as gl::types::GLsizei,
+                                                     names_ptr.as_ptr(), mode);
 
-                } else if ctxt.extensions.gl_ext_transform_feedback {
-                    let mode = match mode {
-                        TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS_EXT,
-                        TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS_EXT,
-                    };
+            } else {
+                unreachable!();     // has been checked in the frontend
+            }
+        }
 
-                    ctxt.gl.TransformFeedbackVaryingsEXT(id, names_ptr.len()
-                                                         as gl::types::GLsizei,
-                                                         names_ptr.as_ptr(), mode);
+        // linking
+        {
+            ctxt.report_debug_output_errors.set(false);
 
-                } else {
-                    unreachable!();     // has been checked in the frontend
+            match id {
+                Handle::Id(id) => {
+                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                            ctxt.version >= &Version(Api::GlEs, 2, 0));
+                    ctxt.gl.LinkProgram(id);
+                },
+                Handle::Handle(id) => {
+                    assert!(ctxt.extensions.gl_arb_shader_objects);
+                    ctxt.gl.LinkProgramARB(id);
                 }
             }
 
-            // linking
-            {
-                ctxt.report_debug_output_errors.set(false);
-
-                match id {
-                    Handle::Id(id) => {
-                        assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
-                                ctxt.version >= &Version(Api::GlEs, 2, 0));
-                        ctxt.gl.LinkProgram(id);
-                    },
-                    Handle::Handle(id) => {
-                        assert!(ctxt.extensions.gl_arb_shader_objects);
-                        ctxt.gl.LinkProgramARB(id);
-                    }
-                }
+            ctxt.report_debug_output_errors.set(true);
+        }
 
-                ctxt.report_debug_output_errors.set(true);
-            }
+        id
+    }
 
-            // checking for errors
-            check_program_link_errors(&mut ctxt, id)?;
+    /// Returns true if the driver has finished linking a program started with `start_linking`,
+    /// meaning that `finish_linking` won't block.
+    ///
+    /// Always returns true if the backend doesn't support `GL_KHR_parallel_shader_compile`.
+    pub(crate) unsafe fn is_linking_complete(ctxt: &mut CommandContext<'_>, id: Handle) -> bool {
+        if !ctxt.extensions.gl_khr_parallel_shader_compile &&
+           !ctxt.extensions.gl_arb_parallel_shader_compile
+        {
+            return true;
+        }
 
-            id
-        };
+        match id {
+            Handle::Id(id) => {
+                let mut completion_status: gl::types::GLint = 0;
+                ctxt.gl.GetProgramiv(id, gl::COMPLETION_STATUS_KHR, &mut completion_status);
+                completion_status != 0
+            },
+            Handle::Handle(_) => true,
+        }
+    }
 
-        let (uniforms, atomic_counters) = unsafe { reflect_uniforms(&mut ctxt, id) };
-        let attributes = unsafe { reflect_attributes(&mut ctxt, id) };
-        let blocks = unsafe { reflect_uniform_blocks(&mut ctxt, id) };
-        let tf_buffers = unsafe { reflect_transform_feedback(&mut ctxt, id) };
-        let ssbos = unsafe { reflect_shader_storage_blocks(&mut ctxt, id) };
-        let subroutine_data = unsafe {
-            reflect_subroutine_data(&mut ctxt, id, has_geometry_shader,
-                                    has_tessellation_control_shader,
-                                    has_tessellation_evaluation_shader)
-            };
+    /// Checks the result of linking a program started with `start_linking`, then builds the
+    /// program object by querying its reflection data. The program is destroyed if the linking
+    /// failed.
+    ///
+    /// `stages` are the stages of the shaders that are attached to the program.
+    pub(crate) unsafe fn finish_linking(context: &Rc<Context>, ctxt: &mut CommandContext<'_>,
+                                        id: Handle, stages: &[ShaderStage])
+                                        -> Result<RawProgram, ProgramCreationError>
+    {
+        if let Err(err) = check_program_link_errors(ctxt, id) {
+            delete_program(ctxt, id);
+            return Err(err);
+        }
 
-        let output_primitives = if has_geometry_shader {
-            Some(unsafe { reflect_geometry_output_type(&mut ctxt, id) })
-        } else if has_tessellation_evaluation_shader {
-            Some(unsafe { reflect_tess_eval_output_type(&mut ctxt, id) })
-        } else {
-            None
-        };
+        ctxt.resources.created(ResourceKind::Program, id, 0);
 
-        Ok(RawProgram {
-            context: facade.get_context().clone(),
-            id,
-            uniforms,
-            uniform_values: UniformsStorage::new(),
-            uniform_blocks: blocks,
-            subroutine_data,
-            attributes,
-            frag_data_locations: RefCell::new(HashMap::with_hasher(Default::default())),
-            tf_buffers,
-            ssbos,
-            atomic_counters,
-            output_primitives,
-            has_geometry_shader,
-            has_tessellation_control_shader,
-            has_tessellation_evaluation_shader,
-        })
+        Ok(RawProgram::from_linked(context, ctxt, id, stages, true))
     }
 
     /// Creates a program from binary.
@@ -218,6 +251,9 @@ impl RawProgram {
         let id = unsafe {
             let id = create_program(&mut ctxt);
 
+            // the driver can reject binaries that have been built by another version
+            ctxt.report_debug_output_errors.set(false);
+
             match id {
                 Handle::Id(id) => {
                     assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
@@ -228,35 +264,111 @@ impl RawProgram {
                 Handle::Handle(id) => unreachable!()
             };
 
+            ctxt.report_debug_output_errors.set(true);
+
             // checking for errors
-            check_program_link_errors(&mut ctxt, id)?;
+            if let Err(err) = check_program_link_errors(&mut ctxt, id) {
+                delete_program(&mut ctxt, id);
+                return Err(err);
+            }
+            ctxt.resources.created(ResourceKind::Program, id, 0);
 
             id
         };
 
-        let ((uniforms, atomic_counters), attributes, blocks, tf_buffers, ssbos, subroutine_data) = unsafe {
-            (
-                reflect_uniforms(&mut ctxt, id),
-                reflect_attributes(&mut ctxt, id),
-                reflect_uniform_blocks(&mut ctxt, id),
-                reflect_transform_feedback(&mut ctxt, id),
-                reflect_shader_storage_blocks(&mut ctxt, id),
-                reflect_subroutine_data(&mut ctxt, id, has_geometry_shader,
-                                        has_tessellation_control_shader,
-                                        has_tessellation_evaluation_shader),
-            )
-        };
+        Ok(unsafe {
+            let stages = get_shader_stages(has_geometry_shader, has_tessellation_control_shader,
+                                           has_tessellation_evaluation_shader);
+            RawProgram::from_linked(facade.get_context(), &mut ctxt, id, &stages, true)
+        })
+    }
+
+    /// Builds a program from an existing, externally created and linked OpenGL program. The
+    /// uniforms, attributes and blocks of the program are queried from the backend.
+    ///
+    /// The stages of the program are detected from the shaders that are attached to it. If the
+    /// shaders have been detached after linking, the program is considered to only contain
+    /// a vertex and a fragment shader.
+    ///
+    /// If `owned` is true, this program will take ownership of the OpenGL program and be
+    /// responsible for cleaning it up. Otherwise, the program must be cleaned up externally, but
+    /// only after this program's lifetime has ended. If an error is returned, the OpenGL program
+    /// is never destroyed.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a program of the facade's context (or of a context sharing its
+    /// objects).
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> Result<RawProgram, ProgramCreationError> where F: Facade + ?Sized
+    {
+        let mut ctxt = facade.get_context().make_current();
+
+        assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                ctxt.version >= &Version(Api::GlEs, 2, 0));
+
+        check_program_link_errors(&mut ctxt, Handle::Id(id))?;
+
+        let mut num_shaders = 0;
+        ctxt.gl.GetProgramiv(id, gl::ATTACHED_SHADERS, &mut num_shaders);
+
+        let mut shaders = vec![0; num_shaders as usize];
+        ctxt.gl.GetAttachedShaders(id, num_shaders, &mut num_shaders, shaders.as_mut_ptr());
+        shaders.truncate(num_shaders as usize);
+
+        let mut has_geometry_shader = false;
+        let mut has_tessellation_control_shader = false;
+        let mut has_tessellation_evaluation_shader = false;
+
+        for shader in shaders {
+            let mut ty = 0;
+            ctxt.gl.GetShaderiv(shader, gl::SHADER_TYPE, &mut ty);
+
+            match ty as gl::types::GLenum {
+                gl::GEOMETRY_SHADER => has_geometry_shader = true,
+                gl::TESS_CONTROL_SHADER => has_tessellation_control_shader = true,
+                gl::TESS_EVALUATION_SHADER => has_tessellation_evaluation_shader = true,
+                _ => ()
+            }
+        }
+
+        if owned {
+            ctxt.resources.created(ResourceKind::Program, Handle::Id(id), 0);
+        }
+
+        let stages = get_shader_stages(has_geometry_shader, has_tessellation_control_shader,
+                                       has_tessellation_evaluation_shader);
+        Ok(RawProgram::from_linked(facade.get_context(), &mut ctxt, Handle::Id(id), &stages,
+                                   owned))
+    }
+
+    /// Builds the program object from a successfully linked program by querying its
+    /// reflection data.
+    unsafe fn from_linked(context: &Rc<Context>, ctxt: &mut CommandContext<'_>, id: Handle,
+                          stages: &[ShaderStage], owned: bool) -> RawProgram
+    {
+        let has_geometry_shader = stages.contains(&ShaderStage::Geometry);
+        let has_tessellation_control_shader = stages.contains(&ShaderStage::TessellationControl);
+        let has_tessellation_evaluation_shader =
+            stages.contains(&ShaderStage::TessellationEvaluation);
+
+        let (uniforms, atomic_counters) = reflect_uniforms(ctxt, id);
+        let attributes = reflect_attributes(ctxt, id);
+        let blocks = reflect_uniform_blocks(ctxt, id);
+        let tf_buffers = reflect_transform_feedback(ctxt, id);
+        let ssbos = reflect_shader_storage_blocks(ctxt, id);
+        let subroutine_data = reflect_subroutine_data(ctxt, id, stages);
 
         let output_primitives = if has_geometry_shader {
-            Some(unsafe { reflect_geometry_output_type(&mut ctxt, id) })
+            Some(reflect_geometry_output_type(ctxt, id))
         } else if has_tessellation_evaluation_shader {
-            Some(unsafe { reflect_tess_eval_output_type(&mut ctxt, id) })
+            Some(reflect_tess_eval_output_type(ctxt, id))
         } else {
             None
         };
 
-        Ok(RawProgram {
-            context: facade.get_context().clone(),
+        RawProgram {
+            context: context.clone(),
             id,
             uniforms,
             uniform_values: UniformsStorage::new(),
@@ -271,7 +383,83 @@ impl RawProgram {
             has_geometry_shader,
             has_tessellation_control_shader,
             has_tessellation_evaluation_shader,
-        })
+            owned,
+            pipeline: None,
+        }
+    }
+
+    /// Builds a program from a program pipeline. The program shares the identifier of the
+    /// program of the vertex stage, so that the vertex array objects are shared between the
+    /// pipelines that use the same vertex stage.
+    pub(crate) fn from_pipeline(context: &Rc<Context>, pipeline: Pipeline,
+                                reflection: PipelineReflection) -> RawProgram
+    {
+        RawProgram {
+            context: context.clone(),
+            id: pipeline.vertex_program().get_id(),
+            uniforms: reflection.uniforms,
+            uniform_values: UniformsStorage::new(),
+            uniform_blocks: reflection.uniform_blocks,
+            subroutine_data: reflection.subroutine_data,
+            attributes: reflection.attributes,
+            frag_data_locations: RefCell::new(HashMap::with_hasher(Default::default())),
+            tf_buffers: reflection.transform_feedback_buffers,
+            ssbos: reflection.shader_storage_blocks,
+            atomic_counters: reflection.atomic_counters,
+            output_primitives: reflection.output_primitives,
+            has_geometry_shader: reflection.has_geometry_shader,
+            has_tessellation_control_shader: reflection.has_tessellation_control_shader,
+            has_tessellation_evaluation_shader: reflection.has_tessellation_evaluation_shader,
+            owned: false,
+            pipeline: Some(pipeline),
+        }
+    }
+
+    /// Adds the variables found by the reflection of a SPIR-V module to the ones returned by
+    /// the driver, which often doesn't know their names.
+    ///
+    /// The uniforms are matched with their location, and the blocks with their binding point.
+    /// The uniforms that the driver has optimized out are ignored.
+    pub(crate) fn apply_spirv_reflection(&mut self, ctxt: &mut CommandContext<'_>,
+                                         reflection: SpirvReflection)
+    {
+        let active_locations = unsafe { reflect_uniform_locations(ctxt, self.id) };
+
+        for (name, uniform) in reflection.uniforms {
+            if let Some(ref active_locations) = active_locations {
+                if !active_locations.contains(&uniform.location) {
+                    continue;
+                }
+            }
+
+            self.uniforms.retain(|_, u| u.location != uniform.location);
+            self.uniforms.insert(name, uniform);
+        }
+
+        fn rename_blocks(blocks: &mut HashMap<String, UniformBlock, BuildHasherDefault<FnvHasher>>,
+                         spirv_blocks: Vec<SpirvBlock>)
+        {
+            for block in spirv_blocks {
+                let previous = blocks.iter().find(|&(_, b)| b.initial_binding == block.binding)
+                                     .map(|(name, _)| name.clone());
+
+                if let Some(previous) = previous {
+                    let previous = blocks.remove(&previous).unwrap();
+                    blocks.insert(block.name, UniformBlock {
+                        layout: block.layout,
+                        .. previous
+                    });
+                }
+            }
+        }
+
+        rename_blocks(&mut self.uniform_blocks, reflection.uniform_blocks);
+        rename_blocks(&mut self.ssbos, reflection.shader_storage_blocks);
+
+        for (name, attribute) in reflection.attributes {
+            self.attributes.retain(|_, a| a.location != attribute.location);
+            self.attributes.insert(name, attribute);
+        }
     }
 
     /// Returns the program's compiled binary.
@@ -279,6 +467,11 @@ impl RawProgram {
     /// You can store the result in a file, then reload it later. This avoids having to compile
     /// the source code every time.
     pub fn get_binary(&self) -> Result<Binary, GetBinaryError> {
+        // a pipeline doesn't have a binary of its own
+        if self.pipeline.is_some() {
+            return Err(GetBinaryError::NotSupported);
+        }
+
         unsafe {
             let ctxt = self.context.make_current();
 
@@ -316,6 +509,18 @@ impl RawProgram {
         }
     }
 
+    /// Attaches a label to the program object. Does nothing if the program was created with
+    /// `GL_ARB_shader_objects`.
+    pub fn set_label(&self, label: &str) {
+        if let Some(ref pipeline) = self.pipeline {
+            let mut ctxt = self.context.make_current();
+            debug::set_object_label(&mut ctxt, gl::PROGRAM_PIPELINE, pipeline.get_id(), label);
+        } else if let Handle::Id(id) = self.id {
+            let mut ctxt = self.context.make_current();
+            debug::set_object_label(&mut ctxt, gl::PROGRAM, id, label);
+        }
+    }
+
     /// Returns the *location* of an output fragment, if it exists.
     ///
     /// The *location* is low-level information that is used internally by glium.
@@ -328,6 +533,10 @@ impl RawProgram {
     /// ```
     ///
     pub fn get_frag_data_location(&self, name: &str) -> Option<u32> {
+        if let Some(ref pipeline) = self.pipeline {
+            return pipeline.get_frag_data_location(name);
+        }
+
         // looking for a cached value
         if let Some(result) = self.frag_data_locations.borrow_mut().get(name) {
             return *result;
@@ -642,6 +851,11 @@ impl GlObject for RawProgram {
 impl ProgramExt for RawProgram {
     #[inline]
     fn use_program(&self, ctxt: &mut CommandContext<'_>) {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.bind(ctxt);
+            return;
+        }
+
         unsafe {
             let program_id = self.get_id();
             if ctxt.state.program != program_id {
@@ -658,6 +872,11 @@ impl ProgramExt for RawProgram {
     fn set_uniform(&self, ctxt: &mut CommandContext<'_>, uniform_location: gl::types::GLint,
                    value: &RawUniformValue)
     {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.set_uniform(ctxt, uniform_location, value);
+            return;
+        }
+
         self.uniform_values.set_uniform_value(ctxt, self.id, uniform_location, value);
     }
 
@@ -665,6 +884,11 @@ impl ProgramExt for RawProgram {
     fn set_uniform_block_binding(&self, ctxt: &mut CommandContext<'_>, block_location: gl::types::GLuint,
                                  value: gl::types::GLuint)
     {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.set_uniform_block_binding(ctxt, block_location, value);
+            return;
+        }
+
         self.uniform_values.set_uniform_block_binding(ctxt, self.id, block_location, value);
     }
 
@@ -673,6 +897,11 @@ impl ProgramExt for RawProgram {
                                         block_location: gl::types::GLuint,
                                         value: gl::types::GLuint)
     {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.set_shader_storage_block_binding(ctxt, block_location, value);
+            return;
+        }
+
         self.uniform_values.set_shader_storage_block_binding(ctxt, self.id, block_location, value);
     }
 
@@ -681,6 +910,11 @@ impl ProgramExt for RawProgram {
                                          stage: ShaderStage,
                                          indices: &[gl::types::GLuint])
     {
+        if let Some(ref pipeline) = self.pipeline {
+            pipeline.set_subroutine_uniforms_for_stage(ctxt, stage, indices);
+            return;
+        }
+
         self.uniform_values.set_subroutine_uniforms_for_stage(ctxt, self.id, stage, indices);
     }
 
@@ -714,6 +948,12 @@ impl ProgramExt for RawProgram {
 
 impl Drop for RawProgram {
     fn drop(&mut self) {
+        // the program objects belong to the stages, and the pipeline object is destroyed by
+        // `Pipeline`
+        if self.pipeline.is_some() {
+            return;
+        }
+
         let mut ctxt = self.context.make_current();
 
         // removing VAOs which contain this program
@@ -731,7 +971,10 @@ impl Drop for RawProgram {
                         ctxt.state.program = Handle::Id(0);
                     }
 
-                    ctxt.gl.DeleteProgram(id);
+                    if self.owned {
+                        ctxt.resources.destroyed(ResourceKind::Program, self.id);
+                        ctxt.gl.DeleteProgram(id);
+                    }
                 },
                 Handle::Handle(id) => {
                     assert!(ctxt.extensions.gl_arb_shader_objects);
@@ -741,6 +984,7 @@ impl Drop for RawProgram {
                         ctxt.state.program = Handle::Handle(0 as gl::types::GLhandleARB);
                     }
 
+                    ctxt.resources.destroyed(ResourceKind::Program, self.id);
                     ctxt.gl.DeleteObjectARB(id);
                 }
             }
@@ -748,6 +992,14 @@ impl Drop for RawProgram {
     }
 }
 
+/// Destroys a program that has never been turned into a `RawProgram`.
+pub(crate) unsafe fn delete_program(ctxt: &mut CommandContext<'_>, id: Handle) {
+    match id {
+        Handle::Id(id) => ctxt.gl.DeleteProgram(id),
+        Handle::Handle(id) => ctxt.gl.DeleteObjectARB(id),
+    }
+}
+
 /// Builds an empty program from within the GL context.
 unsafe fn create_program(ctxt: &mut CommandContext<'_>) -> Handle {
     let id = if ctxt.version >= &Version(Api::Gl, 2, 0) ||
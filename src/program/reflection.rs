@@ -1,7 +1,7 @@
 use crate::gl;
 
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasherDefault;
 use std::ffi;
 use std::ptr;
@@ -16,6 +16,7 @@ use crate::version::Api;
 use crate::uniforms::UniformType;
 use crate::vertex::AttributeType;
 use crate::program;
+use crate::program::ShaderType;
 
 use crate::Handle;
 
@@ -242,6 +243,13 @@ pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext<'_>, program: Handle)
         uniform_name_tmp.set_len(uniform_name_tmp_len as usize);
 
         let uniform_name = String::from_utf8(uniform_name_tmp).unwrap();
+
+        // the names of the uniforms of SPIR-V shaders are often stripped by the driver, in which
+        // case there's no way to refer to the uniform
+        if uniform_name.is_empty() {
+            continue;
+        }
+
         let location = match program {
             Handle::Id(program) => {
                 assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
@@ -321,6 +329,43 @@ pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext<'_>, program: Handle)
     (uniforms_flattened, atomic_counters)
 }
 
+/// Returns the locations of all the active uniforms of a program, including the uniforms whose
+/// name is unknown. Returns `None` if `GL_ARB_program_interface_query` is not supported.
+pub unsafe fn reflect_uniform_locations(ctxt: &mut CommandContext<'_>, program: Handle)
+                                        -> Option<HashSet<i32>>
+{
+    if !(ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+         ctxt.extensions.gl_arb_program_interface_query)
+    {
+        return None;
+    }
+
+    let program = match program {
+        Handle::Id(program) => program,
+        Handle::Handle(_) => return None
+    };
+
+    let mut active_uniforms: gl::types::GLint = 0;
+    ctxt.gl.GetProgramInterfaceiv(program, gl::UNIFORM, gl::ACTIVE_RESOURCES,
+                                  &mut active_uniforms);
+
+    let mut locations = HashSet::new();
+
+    for uniform_id in 0 .. active_uniforms as gl::types::GLuint {
+        let mut output: [gl::types::GLint; 2] = [0; 2];
+        ctxt.gl.GetProgramResourceiv(program, gl::UNIFORM, uniform_id, 2,
+                                     [gl::LOCATION, gl::ARRAY_SIZE].as_ptr(), 2,
+                                     ptr::null_mut(), output.as_mut_ptr());
+
+        // the members of the uniform blocks don't have a location
+        if output[0] >= 0 {
+            locations.extend(output[0] .. output[0] + cmp::max(output[1], 1));
+        }
+    }
+
+    Some(locations)
+}
+
 pub unsafe fn reflect_attributes(ctxt: &mut CommandContext<'_>, program: Handle)
                                  -> HashMap<String, Attribute, BuildHasherDefault<FnvHasher>>
 {
@@ -1188,9 +1233,23 @@ impl ShaderStage {
             // Compute => gl::COMPUTE_SHADER,
         }
     }
+
+    /// Converts a `ShaderType` to its `ShaderStage` equivalent. Returns `None` for compute
+    /// shaders.
+    pub(crate) fn from_shader_type(ty: ShaderType) -> Option<ShaderStage> {
+        match ty {
+            ShaderType::Vertex => Some(ShaderStage::Vertex),
+            ShaderType::Fragment => Some(ShaderStage::Fragment),
+            ShaderType::TesselationControl => Some(ShaderStage::TessellationControl),
+            ShaderType::TesselationEvaluation => Some(ShaderStage::TessellationEvaluation),
+            ShaderType::Geometry => Some(ShaderStage::Geometry),
+            ShaderType::Compute => None,
+        }
+    }
 }
 
-fn get_shader_stages(has_geometry_shader: bool,
+/// Returns the stages of a program that contains a vertex and a fragment shader.
+pub fn get_shader_stages(has_geometry_shader: bool,
                      has_tessellation_control_shader: bool,
                      has_tessellation_evaluation_shader: bool)
                      -> Vec<ShaderStage> {
@@ -1209,10 +1268,7 @@ fn get_shader_stages(has_geometry_shader: bool,
 
 /// Returns the data associated with a programs subroutines.
 pub unsafe fn reflect_subroutine_data(ctxt: &mut CommandContext<'_>, program: Handle,
-                                      has_geometry_shader: bool,
-                                      has_tessellation_control_shader: bool,
-                                      has_tessellation_evaluation_shader: bool)
-                                      -> SubroutineData
+                                      shader_stages: &[ShaderStage]) -> SubroutineData
 {
     if !program::is_subroutine_supported(ctxt) {
         return SubroutineData {
@@ -1230,9 +1286,6 @@ pub unsafe fn reflect_subroutine_data(ctxt: &mut CommandContext<'_>, program: Ha
         Handle::Id(id) => id
     };
 
-    let shader_stages = get_shader_stages(has_geometry_shader,
-                                          has_tessellation_control_shader,
-                                          has_tessellation_evaluation_shader);
     let mut subroutine_uniforms = HashMap::with_hasher(Default::default());
     let mut location_counts = HashMap::with_hasher(Default::default());
     for stage in shader_stages.iter() {
@@ -0,0 +1,285 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::backend::{Context, Facade};
+
+use crate::program::{Diagnostic, Program, ProgramCreationError, Severity, ShaderType};
+use crate::program::SourceCode;
+
+/// The paths of the files that contain the source code of the shaders of a `ReloadableProgram`.
+#[derive(Copy, Clone, Debug)]
+pub struct SourcePaths<'a> {
+    /// Path of the vertex shader.
+    pub vertex_shader: &'a Path,
+
+    /// Path of the optional tessellation control shader.
+    pub tessellation_control_shader: Option<&'a Path>,
+
+    /// Path of the optional tessellation evaluation shader.
+    pub tessellation_evaluation_shader: Option<&'a Path>,
+
+    /// Path of the optional geometry shader.
+    pub geometry_shader: Option<&'a Path>,
+
+    /// Path of the fragment shader.
+    pub fragment_shader: &'a Path,
+}
+
+/// A program built from shader files, which is built again when the files are modified.
+///
+/// `reload_if_changed` compares the modification times of the files with the ones of the last
+/// build, and builds the program again if one of them has changed. The new program replaces the
+/// current one only if it compiles and links. Otherwise the current program is kept, and the
+/// error is returned by `last_error` until the next successful build.
+///
+/// A `ReloadableProgram` dereferences to the current `Program`, which means that it can be
+/// passed to `Surface::draw`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::path::Path;
+/// # fn example(display: glium::Display) {
+/// use glium::program::{ReloadableProgram, SourcePaths};
+///
+/// let mut program = ReloadableProgram::new(&display, SourcePaths {
+///     vertex_shader: Path::new("shaders/sprite.vert"),
+///     fragment_shader: Path::new("shaders/sprite.frag"),
+///     geometry_shader: None,
+///     tessellation_control_shader: None,
+///     tessellation_evaluation_shader: None,
+/// }).unwrap();
+///
+/// loop {
+///     if program.reload_if_changed() {
+///         if let Some(err) = program.last_error() {
+///             for diagnostic in err.diagnostics() {
+///                 println!("{}", diagnostic);
+///             }
+///         }
+///     }
+///
+///     // draw with `&program`
+/// #   break;
+/// }
+/// # }
+/// ```
+pub struct ReloadableProgram {
+    context: Rc<Context>,
+    stages: Vec<Stage>,
+    program: Program,
+    last_error: Option<ReloadError>,
+}
+
+/// A shader file, with its modification time when it was last read.
+struct Stage {
+    ty: ShaderType,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ReloadableProgram {
+    /// Reads the shader files and builds the program.
+    ///
+    /// Returns an error if a file can't be read or if the program can't be built, since there is
+    /// no previous program to fall back to.
+    pub fn new<F>(facade: &F, paths: SourcePaths<'_>) -> Result<ReloadableProgram, ReloadError>
+                  where F: Facade + ?Sized
+    {
+        let stages = [
+            (ShaderType::Vertex, Some(paths.vertex_shader)),
+            (ShaderType::TesselationControl, paths.tessellation_control_shader),
+            (ShaderType::TesselationEvaluation, paths.tessellation_evaluation_shader),
+            (ShaderType::Geometry, paths.geometry_shader),
+            (ShaderType::Fragment, Some(paths.fragment_shader)),
+        ];
+
+        let mut stages = stages.iter().filter_map(|&(ty, path)| path.map(|path| Stage {
+            ty,
+            path: path.to_owned(),
+            modified: None,
+        })).collect::<Vec<_>>();
+
+        let program = build(facade.get_context(), &mut stages)?;
+
+        Ok(ReloadableProgram {
+            context: facade.get_context().clone(),
+            stages,
+            program,
+            last_error: None,
+        })
+    }
+
+    /// Builds the program again if the modification time of one of the files has changed since
+    /// the last build. Returns true if the program has been built again, successfully or not.
+    ///
+    /// A file that can't be read is considered modified, so that the program is built again as
+    /// soon as the file is back.
+    pub fn reload_if_changed(&mut self) -> bool {
+        let changed = self.stages.iter().any(|stage| {
+            stage.modified.is_none() || modification_time(&stage.path).ok() != stage.modified
+        });
+
+        if changed {
+            // the error is kept in `last_error`
+            let _ = self.reload();
+        }
+
+        changed
+    }
+
+    /// Builds the program again, whether the files have changed or not. Returns the error if the
+    /// build fails, in which case the previous program is kept.
+    pub fn reload(&mut self) -> Result<(), &ReloadError> {
+        match build(&self.context, &mut self.stages) {
+            Ok(program) => {
+                self.program = program;
+                self.last_error = None;
+                Ok(())
+            },
+            Err(err) => Err(self.last_error.insert(err)),
+        }
+    }
+
+    /// Returns the error of the last build, or `None` if it was successful.
+    #[inline]
+    pub fn last_error(&self) -> Option<&ReloadError> {
+        self.last_error.as_ref()
+    }
+
+    /// Returns the current program, which is the result of the last successful build.
+    #[inline]
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Returns the path of the file of a shader stage, if the program has this stage.
+    ///
+    /// The diagnostics of a `ProgramCreationError::CompilationError` refer to this file.
+    #[inline]
+    pub fn path(&self, ty: ShaderType) -> Option<&Path> {
+        self.stages.iter().find(|stage| stage.ty == ty).map(|stage| stage.path.as_path())
+    }
+}
+
+impl Deref for ReloadableProgram {
+    type Target = Program;
+
+    #[inline]
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+impl fmt::Debug for ReloadableProgram {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        formatter.debug_struct("ReloadableProgram")
+                 .field("paths", &self.stages.iter().map(|stage| &stage.path)
+                                                    .collect::<Vec<_>>())
+                 .field("program", &self.program)
+                 .field("last_error", &self.last_error)
+                 .finish()
+    }
+}
+
+/// Reads the files of the stages and builds a program with them. The modification times of the
+/// stages are updated, even if the build fails.
+fn build(context: &Rc<Context>, stages: &mut [Stage]) -> Result<Program, ReloadError> {
+    let mut sources = Vec::with_capacity(stages.len());
+
+    for stage in stages.iter_mut() {
+        stage.modified = modification_time(&stage.path).ok();
+
+        match fs::read_to_string(&stage.path) {
+            Ok(source) => sources.push(source),
+            Err(error) => {
+                stage.modified = None;
+                return Err(ReloadError::Io { path: stage.path.clone(), error });
+            },
+        }
+    }
+
+    let source = |ty| stages.iter().position(|stage| stage.ty == ty)
+                                   .map(|index| &sources[index][..]);
+
+    let program = Program::new(context, SourceCode {
+        vertex_shader: source(ShaderType::Vertex).unwrap(),
+        tessellation_control_shader: source(ShaderType::TesselationControl),
+        tessellation_evaluation_shader: source(ShaderType::TesselationEvaluation),
+        geometry_shader: source(ShaderType::Geometry),
+        fragment_shader: source(ShaderType::Fragment).unwrap(),
+    })?;
+
+    Ok(program)
+}
+
+fn modification_time(path: &Path) -> io::Result<SystemTime> {
+    fs::metadata(path)?.modified()
+}
+
+/// Error that can happen while building a `ReloadableProgram`.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// A shader file couldn't be read.
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// The error returned when reading the file.
+        error: io::Error,
+    },
+
+    /// The program couldn't be built from the content of the files.
+    Program(ProgramCreationError),
+}
+
+impl ReloadError {
+    /// Returns the messages of the compiler or of the linker, parsed with
+    /// `Diagnostic::parse_log`. A file that couldn't be read is turned into a diagnostic that
+    /// refers to it.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match *self {
+            ReloadError::Io { ref path, ref error } => vec![Diagnostic {
+                file: Some(path.display().to_string()),
+                line: None,
+                column: None,
+                severity: Severity::Error,
+                message: error.to_string(),
+            }],
+            ReloadError::Program(ref err) => err.diagnostics(),
+        }
+    }
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            ReloadError::Io { ref path, ref error } => {
+                write!(fmt, "Error while reading `{}`: {}", path.display(), error)
+            },
+            ReloadError::Program(ref err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl Error for ReloadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ReloadError::Io { ref error, .. } => Some(error),
+            ReloadError::Program(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<ProgramCreationError> for ReloadError {
+    #[inline]
+    fn from(err: ProgramCreationError) -> ReloadError {
+        ReloadError::Program(err)
+    }
+}
@@ -14,6 +14,7 @@ use crate::GlObject;
 use crate::Handle;
 
 use crate::program::{ProgramCreationError, ShaderType, SpirvEntryPoint};
+use crate::program::include::ShaderIncludes;
 
 /// A single, compiled but unlinked, shader.
 pub struct Shader {
@@ -51,8 +52,68 @@ impl Drop for Shader {
 }
 
 /// Builds an individual shader.
+#[inline]
 pub fn build_shader<F: ?Sized>(facade: &F, shader_type: gl::types::GLenum, source_code: &str)
                        -> Result<Shader, ProgramCreationError> where F: Facade
+{
+    compile_shader(facade, shader_type, source_code, &[])
+}
+
+/// Builds an individual shader whose `#include` directives refer to the files of `includes`.
+pub fn build_shader_with_includes<F>(facade: &F, shader_type: gl::types::GLenum, source_code: &str,
+                                     includes: &ShaderIncludes)
+                                     -> Result<Shader, ProgramCreationError>
+                                     where F: Facade + ?Sized
+{
+    let expanded = includes.expand(source_code)?;
+
+    let driver_includes = !expanded.files.is_empty() && {
+        let context = facade.get_context();
+        context.get_extensions().gl_arb_shading_language_include &&
+            context.get_version() >= &Version(Api::Gl, 2, 0)
+    };
+
+    let result = if driver_includes {
+        let (source_code, named_strings) = includes.driver_sources(source_code, &expanded);
+        compile_shader(facade, shader_type, &source_code, &named_strings)
+    } else {
+        compile_shader(facade, shader_type, &expanded.source, &[])
+    };
+
+    result.map_err(|err| match err {
+        ProgramCreationError::CompilationError(log, ty) => {
+            ProgramCreationError::CompilationError(expanded.remap_log(&log), ty)
+        },
+        err => err,
+    })
+}
+
+/// Builds an individual shader. If `named_strings` isn't empty, they are registered with
+/// `GL_ARB_shading_language_include` during the compilation.
+fn compile_shader<F>(facade: &F, shader_type: gl::types::GLenum, source_code: &str,
+                     named_strings: &[(String, String)]) -> Result<Shader, ProgramCreationError>
+                     where F: Facade + ?Sized
+{
+    let shader = start_compilation(facade, shader_type, source_code, named_strings)?;
+    check_shader_compilation(&shader, shader_type)?;
+    Ok(shader)
+}
+
+/// Starts compiling an individual shader, without checking whether the compilation succeeded.
+///
+/// With `GL_KHR_parallel_shader_compile`, the driver can compile the shader in the background
+/// until its status is queried with `check_shader_compilation`, or until a program that
+/// contains it is queried.
+#[inline]
+pub fn submit_shader<F>(facade: &F, shader_type: gl::types::GLenum, source_code: &str)
+                        -> Result<Shader, ProgramCreationError> where F: Facade + ?Sized
+{
+    start_compilation(facade, shader_type, source_code, &[])
+}
+
+fn start_compilation<F>(facade: &F, shader_type: gl::types::GLenum, source_code: &str,
+                        named_strings: &[(String, String)]) -> Result<Shader, ProgramCreationError>
+                        where F: Facade + ?Sized
 {
     unsafe {
         let ctxt = facade.get_context().make_current();
@@ -97,7 +158,18 @@ pub fn build_shader<F: ?Sized>(facade: &F, shader_type: gl::types::GLenum, sourc
         {
             ctxt.report_debug_output_errors.set(false);
 
+            for (name, string) in named_strings {
+                ctxt.gl.NamedStringARB(gl::SHADER_INCLUDE_ARB, name.len() as gl::types::GLint,
+                                       name.as_ptr() as *const _,
+                                       string.len() as gl::types::GLint,
+                                       string.as_ptr() as *const _);
+            }
+
             match id {
+                Handle::Id(id) if !named_strings.is_empty() => {
+                    assert!(ctxt.extensions.gl_arb_shading_language_include);
+                    ctxt.gl.CompileShaderIncludeARB(id, 0, ptr::null(), ptr::null());
+                },
                 Handle::Id(id) => {
                     assert!(ctxt.version >= &Version(Api::Gl, 2, 0)||
                             ctxt.version >= &Version(Api::GlEs, 2, 0));
@@ -109,9 +181,30 @@ pub fn build_shader<F: ?Sized>(facade: &F, shader_type: gl::types::GLenum, sourc
                 }
             }
 
+            for (name, _) in named_strings {
+                ctxt.gl.DeleteNamedStringARB(name.len() as gl::types::GLint,
+                                             name.as_ptr() as *const _);
+            }
+
             ctxt.report_debug_output_errors.set(true);
         }
 
+        Ok(Shader {
+            context: facade.get_context().clone(),
+            id
+        })
+    }
+}
+
+/// Checks whether the compilation of a shader succeeded, and returns its log otherwise.
+pub fn check_shader_compilation(shader: &Shader, shader_type: gl::types::GLenum)
+                                -> Result<(), ProgramCreationError>
+{
+    let id = shader.id;
+
+    unsafe {
+        let ctxt = shader.context.make_current();
+
         // checking compilation success by reading a flag on the shader
         let compilation_success = {
             let mut compilation_success: gl::types::GLint = 0;
@@ -131,10 +224,7 @@ pub fn build_shader<F: ?Sized>(facade: &F, shader_type: gl::types::GLenum, sourc
         };
 
         if compilation_success == 1 {
-            Ok(Shader {
-                context: facade.get_context().clone(),
-                id
-            })
+            Ok(())
 
         } else {
             // compilation error
@@ -220,7 +310,11 @@ pub fn build_spirv_shader<F: ?Sized>(facade: &F, shader_type: gl::types::GLenum,
         {
             ctxt.report_debug_output_errors.set(false);
 
-            ctxt.gl.SpecializeShader(id, entry_point.as_ptr() as _, 0, ptr::null(), ptr::null());
+            let (indices, values): (Vec<u32>, Vec<u32>) =
+                spirv.specialization_constants.iter().cloned().unzip();
+
+            ctxt.gl.SpecializeShader(id, entry_point.as_ptr() as _, indices.len() as _,
+                                     indices.as_ptr(), values.as_ptr());
 
             ctxt.report_debug_output_errors.set(true);
         }
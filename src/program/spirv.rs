@@ -0,0 +1,728 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::program::{ShaderType, SpirvEntryPoint};
+use crate::program::reflection::{Attribute, BlockLayout, Uniform};
+use crate::uniforms::UniformType;
+use crate::vertex::AttributeType;
+
+/// The first word of a SPIR-V module.
+const MAGIC: u32 = 0x0723_0203;
+
+// opcodes
+const OP_NAME: u32 = 5;
+const OP_MEMBER_NAME: u32 = 6;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_BOOL: u32 = 20;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_SPEC_CONSTANT: u32 = 50;
+const OP_FUNCTION: u32 = 54;
+const OP_FUNCTION_END: u32 = 56;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+// decorations
+const DECORATION_SPEC_ID: u32 = 1;
+const DECORATION_BLOCK: u32 = 2;
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_BUILT_IN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_OFFSET: u32 = 35;
+
+// storage classes
+const STORAGE_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_INPUT: u32 = 1;
+const STORAGE_UNIFORM: u32 = 2;
+const STORAGE_STORAGE_BUFFER: u32 = 12;
+
+// execution models
+const EXECUTION_MODEL_VERTEX: u32 = 0;
+const EXECUTION_MODEL_TESSELLATION_CONTROL: u32 = 1;
+const EXECUTION_MODEL_TESSELLATION_EVALUATION: u32 = 2;
+const EXECUTION_MODEL_GEOMETRY: u32 = 3;
+const EXECUTION_MODEL_FRAGMENT: u32 = 4;
+const EXECUTION_MODEL_GL_COMPUTE: u32 = 5;
+
+/// The variables of an entry point of a SPIR-V module, with the names found in its debug
+/// instructions.
+///
+/// Drivers are not required to keep the names of the variables of SPIR-V shaders, which means
+/// that the names returned by the reflection of the driver are often empty.
+#[derive(Debug, Default)]
+pub struct SpirvReflection {
+    /// The uniforms that have an explicit location, with arrays and structs flattened like
+    /// OpenGL does.
+    pub uniforms: Vec<(String, Uniform)>,
+
+    /// The uniform blocks.
+    pub uniform_blocks: Vec<SpirvBlock>,
+
+    /// The shader storage blocks.
+    pub shader_storage_blocks: Vec<SpirvBlock>,
+
+    /// The inputs of a vertex shader.
+    pub attributes: Vec<(String, Attribute)>,
+}
+
+/// A uniform or shader storage block of a SPIR-V module.
+#[derive(Debug)]
+pub struct SpirvBlock {
+    /// Name of the block, which is the name of its type.
+    pub name: String,
+
+    /// Binding point of the block.
+    pub binding: i32,
+
+    /// Layout of the members of the block.
+    pub layout: BlockLayout,
+}
+
+/// Returns the variables used by an entry point. Returns an empty reflection if the module
+/// can't be parsed or doesn't contain the entry point.
+///
+/// The values of the specialization constants of the entry point are used for the length of
+/// the arrays.
+pub fn reflect(spirv: &SpirvEntryPoint<'_>, ty: ShaderType) -> SpirvReflection {
+    Module::parse(spirv.binary, spirv.specialization_constants)
+        .and_then(|module| module.reflect(spirv.entry_point, ty))
+        .unwrap_or_default()
+}
+
+enum Type {
+    Bool,
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, count: u32 },
+    Image { sampled_type: u32, dim: u32, depth: u32, arrayed: bool, multisampled: bool,
+            sampled: u32 },
+    SampledImage { image: u32 },
+    Array { element: u32, length: u32 },
+    RuntimeArray { element: u32 },
+    Struct { members: Vec<u32> },
+    Pointer { pointee: u32 },
+}
+
+struct Variable {
+    id: u32,
+    ty: u32,
+    storage: u32,
+}
+
+struct EntryPoint {
+    model: u32,
+    function: u32,
+    name: String,
+    interface: Vec<u32>,
+}
+
+/// The instructions of a module that matter for the reflection.
+#[derive(Default)]
+struct Module {
+    names: HashMap<u32, String>,
+    member_names: HashMap<(u32, u32), String>,
+    decorations: HashMap<u32, Vec<(u32, u32)>>,
+    member_decorations: HashMap<(u32, u32), Vec<(u32, u32)>>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    variables: Vec<Variable>,
+    entry_points: Vec<EntryPoint>,
+    // for each function, all the words of its instructions, which include the identifiers of
+    // the variables and the functions that it uses
+    functions: HashMap<u32, HashSet<u32>>,
+}
+
+impl Module {
+    fn parse(binary: &[u8], specialization_constants: &[(u32, u32)]) -> Option<Module> {
+        let chunks = binary.chunks_exact(4);
+        if !chunks.remainder().is_empty() || chunks.len() < 5 {
+            return None;
+        }
+
+        let mut words = chunks.map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                              .collect::<Vec<_>>();
+
+        if words[0] != MAGIC {
+            if words[0].swap_bytes() != MAGIC {
+                return None;
+            }
+
+            for word in words.iter_mut() {
+                *word = word.swap_bytes();
+            }
+        }
+
+        let mut module = Module::default();
+        let mut spec_constants = Vec::new();
+        let mut current_function = None;
+        let mut words = &words[5..];
+
+        while !words.is_empty() {
+            let count = (words[0] >> 16) as usize;
+            let opcode = words[0] & 0xffff;
+
+            if count == 0 || count > words.len() {
+                return None;
+            }
+
+            let operands = &words[1 .. count];
+            words = &words[count..];
+
+            if let Some(function) = current_function {
+                if opcode == OP_FUNCTION_END {
+                    current_function = None;
+                } else {
+                    module.functions.entry(function).or_insert_with(HashSet::new)
+                          .extend(operands.iter().cloned());
+                }
+
+                continue;
+            }
+
+            match (opcode, operands) {
+                (OP_NAME, &[target, ref name @ ..]) => {
+                    module.names.insert(target, parse_string(name));
+                },
+                (OP_MEMBER_NAME, &[ty, member, ref name @ ..]) => {
+                    module.member_names.insert((ty, member), parse_string(name));
+                },
+                (OP_ENTRY_POINT, &[model, function, ref rest @ ..]) => {
+                    let name_len = rest.iter().position(|word| word.to_le_bytes().contains(&0))?;
+                    module.entry_points.push(EntryPoint {
+                        model,
+                        function,
+                        name: parse_string(rest),
+                        interface: rest[name_len + 1 ..].to_vec(),
+                    });
+                },
+                (OP_TYPE_BOOL, &[id]) => {
+                    module.types.insert(id, Type::Bool);
+                },
+                (OP_TYPE_INT, &[id, width, signed]) => {
+                    module.types.insert(id, Type::Int { width, signed: signed != 0 });
+                },
+                (OP_TYPE_FLOAT, &[id, width, ..]) => {
+                    module.types.insert(id, Type::Float { width });
+                },
+                (OP_TYPE_VECTOR, &[id, component, count]) => {
+                    module.types.insert(id, Type::Vector { component, count });
+                },
+                (OP_TYPE_MATRIX, &[id, column, count]) => {
+                    module.types.insert(id, Type::Matrix { column, count });
+                },
+                (OP_TYPE_IMAGE, &[id, sampled_type, dim, depth, arrayed, multisampled, sampled,
+                                  ..]) => {
+                    module.types.insert(id, Type::Image {
+                        sampled_type, dim, depth, sampled,
+                        arrayed: arrayed != 0,
+                        multisampled: multisampled != 0,
+                    });
+                },
+                (OP_TYPE_SAMPLED_IMAGE, &[id, image]) => {
+                    module.types.insert(id, Type::SampledImage { image });
+                },
+                (OP_TYPE_ARRAY, &[id, element, length]) => {
+                    module.types.insert(id, Type::Array { element, length });
+                },
+                (OP_TYPE_RUNTIME_ARRAY, &[id, element]) => {
+                    module.types.insert(id, Type::RuntimeArray { element });
+                },
+                (OP_TYPE_STRUCT, &[id, ref members @ ..]) => {
+                    module.types.insert(id, Type::Struct { members: members.to_vec() });
+                },
+                (OP_TYPE_POINTER, &[id, _, pointee]) => {
+                    module.types.insert(id, Type::Pointer { pointee });
+                },
+                (OP_CONSTANT, &[_, id, value, ..]) => {
+                    module.constants.insert(id, value);
+                },
+                (OP_SPEC_CONSTANT, &[_, id, value, ..]) => {
+                    module.constants.insert(id, value);
+                    spec_constants.push(id);
+                },
+                (OP_VARIABLE, &[ty, id, storage, ..]) => {
+                    module.variables.push(Variable { id, ty, storage });
+                },
+                (OP_DECORATE, &[target, decoration, ref rest @ ..]) => {
+                    module.decorations.entry(target).or_insert_with(Vec::new)
+                          .push((decoration, rest.first().cloned().unwrap_or(0)));
+                },
+                (OP_MEMBER_DECORATE, &[ty, member, decoration, ref rest @ ..]) => {
+                    module.member_decorations.entry((ty, member)).or_insert_with(Vec::new)
+                          .push((decoration, rest.first().cloned().unwrap_or(0)));
+                },
+                (OP_FUNCTION, &[_, id, ..]) => {
+                    current_function = Some(id);
+                },
+                _ => ()
+            }
+        }
+
+        // the specialization constants are identified by their `SpecId` decoration
+        for id in spec_constants {
+            let spec_id = match module.decoration(id, DECORATION_SPEC_ID) {
+                Some(spec_id) => spec_id,
+                None => continue,
+            };
+
+            if let Some(&(_, value)) = specialization_constants.iter()
+                                                               .find(|&&(c, _)| c == spec_id)
+            {
+                module.constants.insert(id, value);
+            }
+        }
+
+        Some(module)
+    }
+
+    fn decoration(&self, id: u32, decoration: u32) -> Option<u32> {
+        self.decorations.get(&id)?.iter().find(|d| d.0 == decoration).map(|d| d.1)
+    }
+
+    fn member_decoration(&self, ty: u32, member: u32, decoration: u32) -> Option<u32> {
+        self.member_decorations.get(&(ty, member))?.iter().find(|d| d.0 == decoration)
+            .map(|d| d.1)
+    }
+
+    fn reflect(&self, entry_point: &str, ty: ShaderType) -> Option<SpirvReflection> {
+        let model = match ty {
+            ShaderType::Vertex => EXECUTION_MODEL_VERTEX,
+            ShaderType::TesselationControl => EXECUTION_MODEL_TESSELLATION_CONTROL,
+            ShaderType::TesselationEvaluation => EXECUTION_MODEL_TESSELLATION_EVALUATION,
+            ShaderType::Geometry => EXECUTION_MODEL_GEOMETRY,
+            ShaderType::Fragment => EXECUTION_MODEL_FRAGMENT,
+            ShaderType::Compute => EXECUTION_MODEL_GL_COMPUTE,
+        };
+
+        let entry_point = self.entry_points.iter()
+                              .find(|e| e.model == model && e.name == entry_point)?;
+
+        // the global variables that are used by the functions reachable from the entry point,
+        // in order to ignore the variables of the other entry points
+        let used = {
+            let mut used = HashSet::new();
+            let mut visited = HashSet::new();
+            let mut to_visit = vec![entry_point.function];
+
+            while let Some(function) = to_visit.pop() {
+                if !visited.insert(function) {
+                    continue;
+                }
+
+                if let Some(words) = self.functions.get(&function) {
+                    used.extend(words.iter().cloned());
+                    to_visit.extend(words.iter().filter(|w| self.functions.contains_key(w)));
+                }
+            }
+
+            used.extend(entry_point.interface.iter().cloned());
+            used
+        };
+
+        let mut reflection = SpirvReflection::default();
+
+        for variable in self.variables.iter().filter(|v| used.contains(&v.id)) {
+            let ty = match self.types.get(&variable.ty) {
+                Some(&Type::Pointer { pointee }) => pointee,
+                _ => continue,
+            };
+
+            match variable.storage {
+                STORAGE_UNIFORM_CONSTANT => {
+                    let name = self.names.get(&variable.id);
+                    let location = self.decoration(variable.id, DECORATION_LOCATION);
+
+                    if let (Some(name), Some(location)) = (name, location) {
+                        let mut location = location as i32;
+                        self.flatten_uniform(name.clone(), ty, &mut location,
+                                             &mut reflection.uniforms);
+                    }
+                },
+
+                STORAGE_UNIFORM | STORAGE_STORAGE_BUFFER => {
+                    // arrays of blocks use consecutive binding points
+                    let (block_ty, count) = match self.types.get(&ty) {
+                        Some(&Type::Array { element, length }) => {
+                            (element, Some(*self.constants.get(&length)?))
+                        },
+                        _ => (ty, None),
+                    };
+
+                    let storage = variable.storage == STORAGE_STORAGE_BUFFER ||
+                                  self.decoration(block_ty, DECORATION_BUFFER_BLOCK).is_some();
+                    if !storage && self.decoration(block_ty, DECORATION_BLOCK).is_none() {
+                        continue;
+                    }
+
+                    let (name, binding) = match (self.names.get(&block_ty),
+                                                 self.decoration(variable.id, DECORATION_BINDING))
+                    {
+                        (Some(name), Some(binding)) => (name, binding as i32),
+                        _ => continue,
+                    };
+
+                    let layout = match self.block_layout(block_ty, 0) {
+                        Some(layout) => layout,
+                        None => continue,
+                    };
+
+                    let blocks = if storage {
+                        &mut reflection.shader_storage_blocks
+                    } else {
+                        &mut reflection.uniform_blocks
+                    };
+
+                    match count {
+                        Some(count) => {
+                            for index in 0 .. count {
+                                blocks.push(SpirvBlock {
+                                    name: format!("{}[{}]", name, index),
+                                    binding: binding + index as i32,
+                                    layout: layout.clone(),
+                                });
+                            }
+                        },
+                        None => {
+                            blocks.push(SpirvBlock { name: name.clone(), binding, layout });
+                        },
+                    }
+                },
+
+                STORAGE_INPUT if model == EXECUTION_MODEL_VERTEX => {
+                    if self.decoration(variable.id, DECORATION_BUILT_IN).is_some() {
+                        continue;
+                    }
+
+                    let name = self.names.get(&variable.id);
+                    let location = self.decoration(variable.id, DECORATION_LOCATION);
+
+                    let (element, size) = match self.types.get(&ty) {
+                        Some(&Type::Array { element, length }) => {
+                            (element, *self.constants.get(&length)? as usize)
+                        },
+                        _ => (ty, 1),
+                    };
+
+                    if let (Some(name), Some(location), Some(ty)) =
+                        (name, location, self.attribute_type(element))
+                    {
+                        reflection.attributes.push((name.clone(), Attribute {
+                            location: location as i32,
+                            ty,
+                            size,
+                        }));
+                    }
+                },
+
+                _ => ()
+            }
+        }
+
+        Some(reflection)
+    }
+
+    /// Adds the uniforms that a variable of the default uniform block is made of. Each element
+    /// of an array and each member of a struct has its own location.
+    fn flatten_uniform(&self, name: String, ty: u32, location: &mut i32,
+                       output: &mut Vec<(String, Uniform)>)
+    {
+        match self.types.get(&ty) {
+            Some(&Type::Array { element, length }) => {
+                let length = match self.constants.get(&length) {
+                    Some(&length) => length,
+                    None => return,
+                };
+
+                for index in 0 .. length {
+                    self.flatten_uniform(format!("{}[{}]", name, index), element, location,
+                                         output);
+                }
+            },
+
+            Some(Type::Struct { members }) => {
+                for (index, &member) in members.iter().enumerate() {
+                    let member_name = match self.member_names.get(&(ty, index as u32)) {
+                        Some(member_name) => member_name,
+                        None => return,
+                    };
+
+                    self.flatten_uniform(format!("{}.{}", name, member_name), member, location,
+                                         output);
+                }
+            },
+
+            _ => {
+                if let Some(ty) = self.uniform_type(ty) {
+                    output.push((name, Uniform { location: *location, ty, size: None }));
+                }
+
+                *location += 1;
+            },
+        }
+    }
+
+    /// Returns the layout of a type in a block, where `offset` is the offset of the type from
+    /// the start of the block.
+    fn block_layout(&self, ty: u32, offset: usize) -> Option<BlockLayout> {
+        Some(match *self.types.get(&ty)? {
+            Type::Struct { ref members } => {
+                let mut layout = Vec::with_capacity(members.len());
+
+                for (index, &member) in members.iter().enumerate() {
+                    let name = self.member_names.get(&(ty, index as u32))?;
+                    let member_offset = self.member_decoration(ty, index as u32,
+                                                               DECORATION_OFFSET)?;
+                    layout.push((name.clone(),
+                                 self.block_layout(member, offset + member_offset as usize)?));
+                }
+
+                BlockLayout::Struct { members: layout }
+            },
+
+            Type::Array { element, length } => BlockLayout::Array {
+                content: Box::new(self.block_layout(element, offset)?),
+                length: *self.constants.get(&length)? as usize,
+            },
+
+            Type::RuntimeArray { element } => BlockLayout::DynamicSizedArray {
+                content: Box::new(self.block_layout(element, offset)?),
+            },
+
+            _ => BlockLayout::BasicType {
+                ty: self.uniform_type(ty)?,
+                offset_in_buffer: offset,
+            },
+        })
+    }
+
+    /// Returns the type of a uniform of a non-aggregate type.
+    fn uniform_type(&self, ty: u32) -> Option<UniformType> {
+        use crate::uniforms::UniformType::*;
+
+        Some(match *self.types.get(&ty)? {
+            Type::Bool => Bool,
+            Type::Int { width: 32, signed: true } => Int,
+            Type::Int { width: 32, signed: false } => UnsignedInt,
+            Type::Int { width: 64, signed: true } => Int64,
+            Type::Int { width: 64, signed: false } => UnsignedInt64,
+            Type::Float { width: 32 } => Float,
+            Type::Float { width: 64 } => Double,
+
+            Type::Vector { component, count } => {
+                let types = match *self.types.get(&component)? {
+                    Type::Bool => [BoolVec2, BoolVec3, BoolVec4],
+                    Type::Int { width: 32, signed: true } => [IntVec2, IntVec3, IntVec4],
+                    Type::Int { width: 32, signed: false } => {
+                        [UnsignedIntVec2, UnsignedIntVec3, UnsignedIntVec4]
+                    },
+                    Type::Int { width: 64, signed: true } => [Int64Vec2, Int64Vec3, Int64Vec4],
+                    Type::Int { width: 64, signed: false } => {
+                        [UnsignedInt64Vec2, UnsignedInt64Vec3, UnsignedInt64Vec4]
+                    },
+                    Type::Float { width: 32 } => [FloatVec2, FloatVec3, FloatVec4],
+                    Type::Float { width: 64 } => [DoubleVec2, DoubleVec3, DoubleVec4],
+                    _ => return None,
+                };
+
+                *types.get((count as usize).checked_sub(2)?)?
+            },
+
+            Type::Matrix { column, count: columns } => {
+                let (component, rows) = match *self.types.get(&column)? {
+                    Type::Vector { component, count } => (component, count),
+                    _ => return None,
+                };
+
+                let double = match *self.types.get(&component)? {
+                    Type::Float { width: 32 } => false,
+                    Type::Float { width: 64 } => true,
+                    _ => return None,
+                };
+
+                match (double, columns, rows) {
+                    (false, 2, 2) => FloatMat2,
+                    (false, 3, 3) => FloatMat3,
+                    (false, 4, 4) => FloatMat4,
+                    (false, 2, 3) => FloatMat2x3,
+                    (false, 2, 4) => FloatMat2x4,
+                    (false, 3, 2) => FloatMat3x2,
+                    (false, 3, 4) => FloatMat3x4,
+                    (false, 4, 2) => FloatMat4x2,
+                    (false, 4, 3) => FloatMat4x3,
+                    (true, 2, 2) => DoubleMat2,
+                    (true, 3, 3) => DoubleMat3,
+                    (true, 4, 4) => DoubleMat4,
+                    (true, 2, 3) => DoubleMat2x3,
+                    (true, 2, 4) => DoubleMat2x4,
+                    (true, 3, 2) => DoubleMat3x2,
+                    (true, 3, 4) => DoubleMat3x4,
+                    (true, 4, 2) => DoubleMat4x2,
+                    (true, 4, 3) => DoubleMat4x3,
+                    _ => return None,
+                }
+            },
+
+            Type::SampledImage { image } => self.image_type(image, true)?,
+            Type::Image { .. } => self.image_type(ty, false)?,
+
+            _ => return None,
+        })
+    }
+
+    /// Returns the type of a sampler or of an image.
+    fn image_type(&self, image: u32, sampler: bool) -> Option<UniformType> {
+        use crate::uniforms::UniformType::*;
+
+        let (sampled_type, dim, depth, arrayed, multisampled, sampled) = match *self.types.get(&image)? {
+            Type::Image { sampled_type, dim, depth, arrayed, multisampled, sampled } => {
+                (sampled_type, dim, depth, arrayed, multisampled, sampled)
+            },
+            _ => return None,
+        };
+
+        // storage images have `Sampled` set to 2
+        if sampler == (sampled == 2) {
+            return None;
+        }
+
+        // 0 for floats, 1 for signed integers and 2 for unsigned integers
+        let kind = match *self.types.get(&sampled_type)? {
+            Type::Float { .. } => 0,
+            Type::Int { signed: true, .. } => 1,
+            Type::Int { signed: false, .. } => 2,
+            _ => return None,
+        };
+
+        let shadow = sampler && depth == 1;
+
+        // dimensions: 0 is 1D, 1 is 2D, 2 is 3D, 3 is cube, 4 is rect and 5 is buffer
+        let types = match (sampler, dim, arrayed, multisampled, shadow) {
+            (true, 0, false, false, false) => [Sampler1d, ISampler1d, USampler1d],
+            (true, 1, false, false, false) => [Sampler2d, ISampler2d, USampler2d],
+            (true, 2, false, false, false) => [Sampler3d, ISampler3d, USampler3d],
+            (true, 3, false, false, false) => [SamplerCube, ISamplerCube, USamplerCube],
+            (true, 4, false, false, false) => [Sampler2dRect, ISampler2dRect, USampler2dRect],
+            (true, 5, false, false, false) => [SamplerBuffer, ISamplerBuffer, USamplerBuffer],
+            (true, 0, true, false, false) => [Sampler1dArray, ISampler1dArray, USampler1dArray],
+            (true, 1, true, false, false) => [Sampler2dArray, ISampler2dArray, USampler2dArray],
+            (true, 3, true, false, false) => {
+                [SamplerCubeArray, ISamplerCubeArray, USamplerCubeArray]
+            },
+            (true, 1, false, true, false) => {
+                [Sampler2dMultisample, ISampler2dMultisample, USampler2dMultisample]
+            },
+            (true, 1, true, true, false) => {
+                [Sampler2dMultisampleArray, ISampler2dMultisampleArray, USampler2dMultisampleArray]
+            },
+            (true, 0, false, false, true) => return Some(Sampler1dShadow),
+            (true, 1, false, false, true) => return Some(Sampler2dShadow),
+            (true, 3, false, false, true) => return Some(SamplerCubeShadow),
+            (true, 4, false, false, true) => return Some(Sampler2dRectShadow),
+            (true, 0, true, false, true) => return Some(Sampler1dArrayShadow),
+            (true, 1, true, false, true) => return Some(Sampler2dArrayShadow),
+            (true, 3, true, false, true) => return Some(SamplerCubeArrayShadow),
+            (false, 0, false, false, _) => [Image1d, IImage1d, UImage1d],
+            (false, 1, false, false, _) => [Image2d, IImage2d, UImage2d],
+            (false, 2, false, false, _) => [Image3d, IImage3d, UImage3d],
+            (false, 3, false, false, _) => [ImageCube, IImageCube, UImageCube],
+            (false, 4, false, false, _) => [Image2dRect, IImage2dRect, UImage2dRect],
+            (false, 5, false, false, _) => [ImageBuffer, IImageBuffer, UImageBuffer],
+            (false, 0, true, false, _) => [Image1dArray, IImage1dArray, UImage1dArray],
+            (false, 1, true, false, _) => [Image2dArray, IImage2dArray, UImage2dArray],
+            (false, 3, true, false, _) => [ImageCubeArray, IImageCubeArray, UImageCubeArray],
+            (false, 1, false, true, _) => {
+                [Image2dMultisample, IImage2dMultisample, UImage2dMultisample]
+            },
+            (false, 1, true, true, _) => {
+                [Image2dMultisampleArray, IImage2dMultisampleArray, UImage2dMultisampleArray]
+            },
+            _ => return None,
+        };
+
+        Some(types[kind])
+    }
+
+    /// Returns the type of a vertex attribute.
+    fn attribute_type(&self, ty: u32) -> Option<AttributeType> {
+        use crate::vertex::AttributeType::*;
+
+        Some(match *self.types.get(&ty)? {
+            Type::Int { width: 32, signed: true } => I32,
+            Type::Int { width: 32, signed: false } => U32,
+            Type::Int { width: 64, signed: true } => I64,
+            Type::Int { width: 64, signed: false } => U64,
+            Type::Float { width: 32 } => F32,
+            Type::Float { width: 64 } => F64,
+
+            Type::Vector { component, count } => {
+                let types = match *self.types.get(&component)? {
+                    Type::Int { width: 32, signed: true } => [I32I32, I32I32I32, I32I32I32I32],
+                    Type::Int { width: 32, signed: false } => [U32U32, U32U32U32, U32U32U32U32],
+                    Type::Int { width: 64, signed: true } => [I64I64, I64I64I64, I64I64I64I64],
+                    Type::Int { width: 64, signed: false } => [U64U64, U64U64U64, U64U64U64U64],
+                    Type::Float { width: 32 } => [F32F32, F32F32F32, F32F32F32F32],
+                    Type::Float { width: 64 } => [F64F64, F64F64F64, F64F64F64F64],
+                    _ => return None,
+                };
+
+                *types.get((count as usize).checked_sub(2)?)?
+            },
+
+            Type::Matrix { column, count: columns } => {
+                let (component, rows) = match *self.types.get(&column)? {
+                    Type::Vector { component, count } => (component, count),
+                    _ => return None,
+                };
+
+                let double = match *self.types.get(&component)? {
+                    Type::Float { width: 32 } => false,
+                    Type::Float { width: 64 } => true,
+                    _ => return None,
+                };
+
+                match (double, columns, rows) {
+                    (false, 2, 2) => F32x2x2,
+                    (false, 2, 3) => F32x2x3,
+                    (false, 2, 4) => F32x2x4,
+                    (false, 3, 2) => F32x3x2,
+                    (false, 3, 3) => F32x3x3,
+                    (false, 3, 4) => F32x3x4,
+                    (false, 4, 2) => F32x4x2,
+                    (false, 4, 3) => F32x4x3,
+                    (false, 4, 4) => F32x4x4,
+                    (true, 2, 2) => F64x2x2,
+                    (true, 2, 3) => F64x2x3,
+                    (true, 2, 4) => F64x2x4,
+                    (true, 3, 2) => F64x3x2,
+                    (true, 3, 3) => F64x3x3,
+                    (true, 3, 4) => F64x3x4,
+                    (true, 4, 2) => F64x4x2,
+                    (true, 4, 3) => F64x4x3,
+                    (true, 4, 4) => F64x4x4,
+                    _ => return None,
+                }
+            },
+
+            _ => return None,
+        })
+    }
+}
+
+/// Decodes a nul-terminated literal string.
+fn parse_string(words: &[u32]) -> String {
+    let bytes = words.iter().flat_map(|word| word.to_le_bytes())
+                     .take_while(|&byte| byte != 0)
+                     .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -41,13 +41,29 @@ impl UniformsStorage {
     {
         let mut values = self.values.borrow_mut();
 
-        // TODO: don't assume that, instead use DSA if the program is not current
-        assert!(ctxt.state.program == program);
+        // programs that aren't current, like the stages of a program pipeline, are modified
+        // with `glProgramUniform`
+        let program_id = if ctxt.state.program == program {
+            None
+        } else {
+            match program {
+                Handle::Id(id) => {
+                    assert!(ctxt.version >= &Version(Api::Gl, 4, 1) ||
+                            ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+                            ctxt.extensions.gl_arb_separate_shader_objects);
+                    Some(id)
+                },
+                Handle::Handle(_) => unreachable!(),
+            }
+        };
 
         macro_rules! uniform(
-            ($ctxt:expr, $uniform:ident, $uniform_arb:ident, $($params:expr),+) => (
+            ($ctxt:expr, $uniform:ident, $uniform_arb:ident, $program_uniform:ident,
+             $($params:expr),+) => (
                 unsafe {
-                    if $ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                    if let Some(id) = program_id {
+                        $ctxt.gl.$program_uniform(id, $($params),+)
+                    } else if $ctxt.version >= &Version(Api::Gl, 1, 5) ||
                        $ctxt.version >= &Version(Api::GlEs, 2, 0)
                     {
                         $ctxt.gl.$uniform($($params),+)
@@ -60,24 +76,28 @@ impl UniformsStorage {
         );
 
         macro_rules! uniform_f64(
-            ($ctxt:expr, $uniform:ident, $($params:expr),+) => (
+            ($ctxt:expr, $uniform:ident, $program_uniform:ident, $($params:expr),+) => (
                 unsafe {
-                    if $ctxt.extensions.gl_arb_gpu_shader_fp64 {
-                        $ctxt.gl.$uniform($($params),+)
-                    } else {
+                    if !$ctxt.extensions.gl_arb_gpu_shader_fp64 {
                         panic!("Double precision floats are not supported on this system.")
+                    } else if let Some(id) = program_id {
+                        $ctxt.gl.$program_uniform(id, $($params),+)
+                    } else {
+                        $ctxt.gl.$uniform($($params),+)
                     }
                 }
             )
         );
 
         macro_rules! uniform_i64(
-            ($ctxt:expr, $uniform:ident, $($params:expr),+) => (
+            ($ctxt:expr, $uniform:ident, $program_uniform:ident, $($params:expr),+) => (
                 unsafe {
-                    if $ctxt.extensions.gl_arb_gpu_shader_int64 {
-                        $ctxt.gl.$uniform($($params),+)
-                    } else {
+                    if !$ctxt.extensions.gl_arb_gpu_shader_int64 {
                         panic!("64 bit integers are not supported on this system.")
+                    } else if let Some(id) = program_id {
+                        $ctxt.gl.$program_uniform(id, $($params),+)
+                    } else {
+                        $ctxt.gl.$uniform($($params),+)
                     }
                 }
             )
@@ -117,7 +137,7 @@ impl UniformsStorage {
 
             (&RawUniformValue::SignedInt(v), target) => {
                 *target = Some(RawUniformValue::SignedInt(v));
-                uniform!(ctxt, Uniform1i, Uniform1iARB, location, v);
+                uniform!(ctxt, Uniform1i, Uniform1iARB, ProgramUniform1i, location, v);
             },
 
             (&RawUniformValue::UnsignedInt(v), target) => {
@@ -125,7 +145,9 @@ impl UniformsStorage {
 
                 // Uniform1uiARB doesn't exist
                 unsafe {
-                    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                    if let Some(id) = program_id {
+                        ctxt.gl.ProgramUniform1ui(id, location, v)
+                    } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                        ctxt.version >= &Version(Api::GlEs, 2, 0)
                     {
                         ctxt.gl.Uniform1ui(location, v)
@@ -138,55 +160,55 @@ impl UniformsStorage {
 
             (&RawUniformValue::Float(v), target) => {
                 *target = Some(RawUniformValue::Float(v));
-                uniform!(ctxt, Uniform1f, Uniform1fARB, location, v);
+                uniform!(ctxt, Uniform1f, Uniform1fARB, ProgramUniform1f, location, v);
             },
 
             (&RawUniformValue::Mat2(v), target) => {
                 *target = Some(RawUniformValue::Mat2(v));
-                uniform!(ctxt, UniformMatrix2fv, UniformMatrix2fvARB,
+                uniform!(ctxt, UniformMatrix2fv, UniformMatrix2fvARB, ProgramUniformMatrix2fv,
                          location, 1, gl::FALSE, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::Mat3(v), target) => {
                 *target = Some(RawUniformValue::Mat3(v));
-                uniform!(ctxt, UniformMatrix3fv, UniformMatrix3fvARB,
+                uniform!(ctxt, UniformMatrix3fv, UniformMatrix3fvARB, ProgramUniformMatrix3fv,
                          location, 1, gl::FALSE, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::Mat4(v), target) => {
                 *target = Some(RawUniformValue::Mat4(v));
-                uniform!(ctxt, UniformMatrix4fv, UniformMatrix4fvARB,
+                uniform!(ctxt, UniformMatrix4fv, UniformMatrix4fvARB, ProgramUniformMatrix4fv,
                          location, 1, gl::FALSE, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::Vec2(v), target) => {
                 *target = Some(RawUniformValue::Vec2(v));
-                uniform!(ctxt, Uniform2fv, Uniform2fvARB, location, 1, v.as_ptr() as *const f32);
+                uniform!(ctxt, Uniform2fv, Uniform2fvARB, ProgramUniform2fv, location, 1, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::Vec3(v), target) => {
                 *target = Some(RawUniformValue::Vec3(v));
-                uniform!(ctxt, Uniform3fv, Uniform3fvARB, location, 1, v.as_ptr() as *const f32);
+                uniform!(ctxt, Uniform3fv, Uniform3fvARB, ProgramUniform3fv, location, 1, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::Vec4(v), target) => {
                 *target = Some(RawUniformValue::Vec4(v));
-                uniform!(ctxt, Uniform4fv, Uniform4fvARB, location, 1, v.as_ptr() as *const f32);
+                uniform!(ctxt, Uniform4fv, Uniform4fvARB, ProgramUniform4fv, location, 1, v.as_ptr() as *const f32);
             },
 
             (&RawUniformValue::IntVec2(v), target) => {
                 *target = Some(RawUniformValue::IntVec2(v));
-                uniform!(ctxt, Uniform2iv, Uniform2ivARB, location, 1, v.as_ptr() as *const gl::types::GLint);
+                uniform!(ctxt, Uniform2iv, Uniform2ivARB, ProgramUniform2iv, location, 1, v.as_ptr() as *const gl::types::GLint);
             },
 
             (&RawUniformValue::IntVec3(v), target) => {
                 *target = Some(RawUniformValue::IntVec3(v));
-                uniform!(ctxt, Uniform3iv, Uniform3ivARB, location, 1, v.as_ptr() as *const gl::types::GLint);
+                uniform!(ctxt, Uniform3iv, Uniform3ivARB, ProgramUniform3iv, location, 1, v.as_ptr() as *const gl::types::GLint);
             },
 
             (&RawUniformValue::IntVec4(v), target) => {
                 *target = Some(RawUniformValue::IntVec4(v));
-                uniform!(ctxt, Uniform4iv, Uniform4ivARB, location, 1, v.as_ptr() as *const gl::types::GLint);
+                uniform!(ctxt, Uniform4iv, Uniform4ivARB, ProgramUniform4iv, location, 1, v.as_ptr() as *const gl::types::GLint);
             },
 
             (&RawUniformValue::UnsignedIntVec2(v), target) => {
@@ -194,7 +216,10 @@ impl UniformsStorage {
 
                 // Uniform2uivARB doesn't exist
                 unsafe {
-                    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                    if let Some(id) = program_id {
+                        ctxt.gl.ProgramUniform2uiv(id, location, 1,
+                                                    v.as_ptr() as *const gl::types::GLuint)
+                    } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                        ctxt.version >= &Version(Api::GlEs, 2, 0)
                     {
                         ctxt.gl.Uniform2uiv(location, 1, v.as_ptr() as *const gl::types::GLuint)
@@ -210,7 +235,10 @@ impl UniformsStorage {
 
                 // Uniform3uivARB doesn't exist
                 unsafe {
-                    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                    if let Some(id) = program_id {
+                        ctxt.gl.ProgramUniform3uiv(id, location, 1,
+                                                    v.as_ptr() as *const gl::types::GLuint)
+                    } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                        ctxt.version >= &Version(Api::GlEs, 2, 0)
                     {
                         ctxt.gl.Uniform3uiv(location, 1, v.as_ptr() as *const gl::types::GLuint)
@@ -226,7 +254,10 @@ impl UniformsStorage {
 
                 // Uniform4uivARB doesn't exist
                 unsafe {
-                    if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                    if let Some(id) = program_id {
+                        ctxt.gl.ProgramUniform4uiv(id, location, 1,
+                                                    v.as_ptr() as *const gl::types::GLuint)
+                    } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                        ctxt.version >= &Version(Api::GlEs, 2, 0)
                     {
                         ctxt.gl.Uniform4uiv(location, 1, v.as_ptr() as *const gl::types::GLuint)
@@ -238,76 +269,76 @@ impl UniformsStorage {
             },
             (&RawUniformValue::Double(v), target) => {
                 *target = Some(RawUniformValue::Double(v));
-                uniform_f64!(ctxt, Uniform1d, location, v);
+                uniform_f64!(ctxt, Uniform1d, ProgramUniform1d, location, v);
             },
 
             (&RawUniformValue::DoubleMat2(v), target) => {
                 *target = Some(RawUniformValue::DoubleMat2(v));
-                uniform_f64!(ctxt, UniformMatrix2dv,
+                uniform_f64!(ctxt, UniformMatrix2dv, ProgramUniformMatrix2dv,
                          location, 1, gl::FALSE, v.as_ptr() as *const gl::types::GLdouble);
             },
 
             (&RawUniformValue::DoubleMat3(v), target) => {
                 *target = Some(RawUniformValue::DoubleMat3(v));
-                uniform_f64!(ctxt, UniformMatrix3dv,
+                uniform_f64!(ctxt, UniformMatrix3dv, ProgramUniformMatrix3dv,
                          location, 1, gl::FALSE, v.as_ptr() as *const gl::types::GLdouble);
             },
 
             (&RawUniformValue::DoubleMat4(v), target) => {
                 *target = Some(RawUniformValue::DoubleMat4(v));
-                uniform_f64!(ctxt, UniformMatrix4dv,
+                uniform_f64!(ctxt, UniformMatrix4dv, ProgramUniformMatrix4dv,
                          location, 1, gl::FALSE, v.as_ptr() as *const gl::types::GLdouble);
             },
 
             (&RawUniformValue::DoubleVec2(v), target) => {
                 *target = Some(RawUniformValue::DoubleVec2(v));
-                uniform_f64!(ctxt, Uniform2dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
+                uniform_f64!(ctxt, Uniform2dv, ProgramUniform2dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
             },
 
             (&RawUniformValue::DoubleVec3(v), target) => {
                 *target = Some(RawUniformValue::DoubleVec3(v));
-                uniform_f64!(ctxt, Uniform3dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
+                uniform_f64!(ctxt, Uniform3dv, ProgramUniform3dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
             },
 
             (&RawUniformValue::DoubleVec4(v), target) => {
                 *target = Some(RawUniformValue::DoubleVec4(v));
-                uniform_f64!(ctxt, Uniform4dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
+                uniform_f64!(ctxt, Uniform4dv, ProgramUniform4dv, location, 1, v.as_ptr() as *const gl::types::GLdouble);
             },
             (&RawUniformValue::Int64(v), target) => {
                 *target = Some(RawUniformValue::Int64(v));
-                uniform_i64!(ctxt, Uniform1i64ARB, location, v);
+                uniform_i64!(ctxt, Uniform1i64ARB, ProgramUniform1i64ARB, location, v);
             },
             (&RawUniformValue::Int64Vec2(v), target) => {
                 *target = Some(RawUniformValue::Int64Vec2(v));
-                uniform_i64!(ctxt, Uniform2i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
+                uniform_i64!(ctxt, Uniform2i64vARB, ProgramUniform2i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
             },
 
             (&RawUniformValue::Int64Vec3(v), target) => {
                 *target = Some(RawUniformValue::Int64Vec3(v));
-                uniform_i64!(ctxt, Uniform3i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
+                uniform_i64!(ctxt, Uniform3i64vARB, ProgramUniform3i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
             },
 
             (&RawUniformValue::Int64Vec4(v), target) => {
                 *target = Some(RawUniformValue::Int64Vec4(v));
-                uniform_i64!(ctxt, Uniform4i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
+                uniform_i64!(ctxt, Uniform4i64vARB, ProgramUniform4i64vARB, location, 1, v.as_ptr() as *const gl::types::GLint64);
             },
             (&RawUniformValue::UnsignedInt64(v), target) => {
                 *target = Some(RawUniformValue::UnsignedInt64(v));
-                uniform_i64!(ctxt, Uniform1ui64ARB, location, v);
+                uniform_i64!(ctxt, Uniform1ui64ARB, ProgramUniform1ui64ARB, location, v);
             },
             (&RawUniformValue::UnsignedInt64Vec2(v), target) => {
                 *target = Some(RawUniformValue::UnsignedInt64Vec2(v));
-                uniform_i64!(ctxt, Uniform2ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
+                uniform_i64!(ctxt, Uniform2ui64vARB, ProgramUniform2ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
             },
 
             (&RawUniformValue::UnsignedInt64Vec3(v), target) => {
                 *target = Some(RawUniformValue::UnsignedInt64Vec3(v));
-                uniform_i64!(ctxt, Uniform3ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
+                uniform_i64!(ctxt, Uniform3ui64vARB, ProgramUniform3ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
             },
 
             (&RawUniformValue::UnsignedInt64Vec4(v), target) => {
                 *target = Some(RawUniformValue::UnsignedInt64Vec4(v));
-                uniform_i64!(ctxt, Uniform4ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
+                uniform_i64!(ctxt, Uniform4ui64vARB, ProgramUniform4ui64vARB, location, 1, v.as_ptr() as *const gl::types::GLuint64);
             },
         }
     }
@@ -325,9 +356,6 @@ impl UniformsStorage {
             }
         }
 
-        // TODO: don't assume that, instead use DSA if the program is not current
-        assert!(ctxt.state.program == program);
-
         match (value, &mut blocks[location as usize]) {
             (a, &mut Some(b)) if a == b => (),
 
@@ -356,9 +384,6 @@ impl UniformsStorage {
             }
         }
 
-        // TODO: don't assume that, instead use DSA if the program is not current
-        assert!(ctxt.state.program == program);
-
         match (value, &mut blocks[location as usize]) {
             (a, &mut Some(b)) if a == b => (),
 
@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, HashMap};
+use std::collections::btree_map;
+use std::fmt;
+use std::hash::BuildHasherDefault;
+use std::rc::Rc;
+
+use fnv::FnvHasher;
+
+use crate::backend::{Context, Facade};
+
+use crate::program::{Program, ProgramCreationError, ProgramCreationInput, SourceCode};
+use crate::program::TransformFeedbackMode;
+use crate::program::include::insert_after_version;
+
+/// A set of preprocessor definitions, which are inserted after the `#version` directive of the
+/// shaders of a `ProgramVariants`.
+///
+/// Two sets that contain the same definitions are equal, no matter the order in which the
+/// definitions have been added.
+///
+/// # Example
+///
+/// ```
+/// let defines = glium::program::ShaderDefines::new()
+///     .define("SHADOWS")
+///     .define_value("LIGHT_COUNT", 4);
+///
+/// assert_eq!(defines.inject("#version 140\nvoid main() {}\n"),
+///            "#version 140\n#define LIGHT_COUNT 4\n#define SHADOWS\n#line 1 0\nvoid main() {}\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShaderDefines {
+    defines: BTreeMap<String, String>,
+}
+
+impl ShaderDefines {
+    /// Builds an empty set of definitions.
+    #[inline]
+    pub fn new() -> ShaderDefines {
+        ShaderDefines::default()
+    }
+
+    /// Builder method that defines a macro without a value, like `#define NAME`.
+    #[inline]
+    pub fn define<N>(mut self, name: N) -> ShaderDefines where N: Into<String> {
+        self.insert(name, "");
+        self
+    }
+
+    /// Builder method that defines a macro with a value, like `#define NAME value`.
+    #[inline]
+    pub fn define_value<N, V>(mut self, name: N, value: V) -> ShaderDefines
+                              where N: Into<String>, V: ToString
+    {
+        self.insert(name, value);
+        self
+    }
+
+    /// Adds a definition to the set. Returns the previous value of the macro, if any.
+    #[inline]
+    pub fn insert<N, V>(&mut self, name: N, value: V) -> Option<String>
+                        where N: Into<String>, V: ToString
+    {
+        self.defines.insert(name.into(), value.to_string())
+    }
+
+    /// Removes a definition from the set. Returns its value, if any.
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.defines.remove(name)
+    }
+
+    /// Returns the value of a macro of the set. The value of a macro without a value is empty.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.defines.get(name).map(|value| &value[..])
+    }
+
+    /// Returns the number of definitions in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.defines.len()
+    }
+
+    /// Returns true if the set doesn't contain any definition.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.defines.is_empty()
+    }
+
+    /// Returns an iterator over the names and the values of the macros, sorted by name.
+    #[inline]
+    pub fn iter(&self) -> btree_map::Iter<'_, String, String> {
+        self.defines.iter()
+    }
+
+    /// Inserts the definitions after the `#version` directive of some source code, or at its
+    /// start if there's none.
+    ///
+    /// The definitions are followed by a `#line` directive, so that the line numbers in the
+    /// compilation errors still refer to the original source code.
+    pub fn inject(&self, source: &str) -> String {
+        let mut lines = String::new();
+
+        for (name, value) in &self.defines {
+            if value.is_empty() {
+                lines.push_str(&format!("#define {}\n", name));
+            } else {
+                lines.push_str(&format!("#define {} {}\n", name, value));
+            }
+        }
+
+        insert_after_version(source, &lines)
+    }
+}
+
+impl<N, V> FromIterator<(N, V)> for ShaderDefines where N: Into<String>, V: ToString {
+    fn from_iter<I>(iter: I) -> ShaderDefines where I: IntoIterator<Item = (N, V)> {
+        let mut defines = ShaderDefines::new();
+        for (name, value) in iter {
+            defines.insert(name, value);
+        }
+        defines
+    }
+}
+
+/// A program whose shaders are compiled with various sets of preprocessor definitions.
+///
+/// A variant is only compiled the first time that it is requested with `get`, then it is kept in
+/// a cache. If a maximum number of variants is set, the least recently used variant is removed
+/// from the cache when a new one is compiled. Variants that fail to compile aren't cached.
+///
+/// Use the `program_variants!` macro to choose the source code depending on the GLSL versions
+/// that the backend supports.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example(display: glium::Display) {
+/// use glium::program::{ProgramVariants, ShaderDefines, SourceCode};
+///
+/// let mut variants = ProgramVariants::new(&display, SourceCode {
+///     vertex_shader: "...",
+///     fragment_shader: "
+///         #version 140
+///
+///         out vec4 color;
+///
+///         void main() {
+///         #ifdef RED
+///             color = vec4(1.0, 0.0, 0.0, 1.0);
+///         #else
+///             color = vec4(1.0);
+///         #endif
+///         }
+///     ",
+///     geometry_shader: None,
+///     tessellation_control_shader: None,
+///     tessellation_evaluation_shader: None,
+/// }).max_variants(Some(16));
+///
+/// let program = variants.get(&ShaderDefines::new().define("RED")).unwrap();
+/// # }
+/// ```
+pub struct ProgramVariants {
+    context: Rc<Context>,
+    vertex_shader: String,
+    tessellation_control_shader: Option<String>,
+    tessellation_evaluation_shader: Option<String>,
+    geometry_shader: Option<String>,
+    fragment_shader: String,
+    transform_feedback_varyings: Option<(Vec<String>, TransformFeedbackMode)>,
+    outputs_srgb: bool,
+    uses_point_size: bool,
+    max_variants: Option<usize>,
+    variants: HashMap<ShaderDefines, Variant, BuildHasherDefault<FnvHasher>>,
+    // incremented every time a variant is requested
+    clock: u64,
+}
+
+struct Variant {
+    program: Program,
+    last_use: u64,
+}
+
+impl ProgramVariants {
+    /// Builds a new set of variants from some source code. Nothing is compiled until a variant
+    /// is requested.
+    pub fn new<F>(facade: &F, source: SourceCode<'_>) -> ProgramVariants
+                  where F: Facade + ?Sized
+    {
+        ProgramVariants {
+            context: facade.get_context().clone(),
+            vertex_shader: source.vertex_shader.to_owned(),
+            tessellation_control_shader: source.tessellation_control_shader.map(str::to_owned),
+            tessellation_evaluation_shader: source.tessellation_evaluation_shader
+                                                  .map(str::to_owned),
+            geometry_shader: source.geometry_shader.map(str::to_owned),
+            fragment_shader: source.fragment_shader.to_owned(),
+            transform_feedback_varyings: None,
+            outputs_srgb: false,
+            uses_point_size: false,
+            max_variants: None,
+            variants: HashMap::with_hasher(Default::default()),
+            clock: 0,
+        }
+    }
+
+    /// Builder method to set `transform_feedback_varyings`. See
+    /// `ProgramCreationInput::SourceCode`.
+    #[inline]
+    pub fn transform_feedback_varyings(mut self, transform_feedback_varyings:
+                                       Option<(Vec<String>, TransformFeedbackMode)>) -> Self
+    {
+        self.transform_feedback_varyings = transform_feedback_varyings;
+        self
+    }
+
+    /// Builder method to set `outputs_srgb`. See `ProgramCreationInput::SourceCode`.
+    #[inline]
+    pub fn outputs_srgb(mut self, outputs_srgb: bool) -> Self {
+        self.outputs_srgb = outputs_srgb;
+        self
+    }
+
+    /// Builder method to set `uses_point_size`. See `ProgramCreationInput::SourceCode`.
+    #[inline]
+    pub fn uses_point_size(mut self, uses_point_size: bool) -> Self {
+        self.uses_point_size = uses_point_size;
+        self
+    }
+
+    /// Builder method to set the maximum number of variants in the cache. `None`, the default,
+    /// means that the variants are never removed. A maximum of 0 is treated as 1.
+    #[inline]
+    pub fn max_variants(mut self, max_variants: Option<usize>) -> Self {
+        self.max_variants = max_variants;
+        self
+    }
+
+    /// Returns the variant that corresponds to a set of definitions, compiling it if it isn't in
+    /// the cache.
+    pub fn get(&mut self, defines: &ShaderDefines) -> Result<&Program, ProgramCreationError> {
+        self.clock += 1;
+
+        if !self.variants.contains_key(defines) {
+            let program = self.build(defines)?;
+
+            if let Some(max_variants) = self.max_variants {
+                while self.variants.len() >= max_variants.max(1) {
+                    self.evict_least_recently_used();
+                }
+            }
+
+            self.variants.insert(defines.clone(), Variant { program, last_use: 0 });
+        }
+
+        let variant = self.variants.get_mut(defines).unwrap();
+        variant.last_use = self.clock;
+        Ok(&variant.program)
+    }
+
+    /// Returns true if the variant that corresponds to a set of definitions is in the cache.
+    #[inline]
+    pub fn contains(&self, defines: &ShaderDefines) -> bool {
+        self.variants.contains_key(defines)
+    }
+
+    /// Returns the number of variants in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Returns true if no variant has been compiled, or if they have all been removed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Removes a variant from the cache and returns it, if it was there.
+    #[inline]
+    pub fn evict(&mut self, defines: &ShaderDefines) -> Option<Program> {
+        self.variants.remove(defines).map(|variant| variant.program)
+    }
+
+    /// Removes all the variants from the cache.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.variants.clear();
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self.variants.iter().min_by_key(|&(_, variant)| variant.last_use)
+                                  .map(|(defines, _)| defines.clone());
+
+        if let Some(oldest) = oldest {
+            self.variants.remove(&oldest);
+        }
+    }
+
+    fn build(&self, defines: &ShaderDefines) -> Result<Program, ProgramCreationError> {
+        let vertex_shader = defines.inject(&self.vertex_shader);
+        let tessellation_control_shader = self.tessellation_control_shader.as_ref()
+                                              .map(|source| defines.inject(source));
+        let tessellation_evaluation_shader = self.tessellation_evaluation_shader.as_ref()
+                                                 .map(|source| defines.inject(source));
+        let geometry_shader = self.geometry_shader.as_ref().map(|source| defines.inject(source));
+        let fragment_shader = defines.inject(&self.fragment_shader);
+
+        Program::new(&self.context, ProgramCreationInput::SourceCode {
+            vertex_shader: &vertex_shader,
+            tessellation_control_shader: tessellation_control_shader.as_deref(),
+            tessellation_evaluation_shader: tessellation_evaluation_shader.as_deref(),
+            geometry_shader: geometry_shader.as_deref(),
+            fragment_shader: &fragment_shader,
+            transform_feedback_varyings: self.transform_feedback_varyings.clone(),
+            outputs_srgb: self.outputs_srgb,
+            uses_point_size: self.uses_point_size,
+        })
+    }
+}
+
+impl fmt::Debug for ProgramVariants {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        formatter.debug_struct("ProgramVariants")
+                 .field("variants", &self.variants.keys().collect::<Vec<_>>())
+                 .field("max_variants", &self.max_variants)
+                 .finish()
+    }
+}
@@ -0,0 +1,273 @@
+/*!
+Recovery from the loss of the OpenGL context.
+
+When the driver resets the GPU, the OpenGL context is lost along with all of its objects, and
+`Context::is_context_lost` starts returning true. The only way to continue is to create a new
+OpenGL context and to create all the buffers, textures and programs again.
+
+glium can't do that by itself, as it doesn't keep a copy of the content of the objects. Instead,
+the resources that must survive a context loss are wrapped in a `Recoverable`, which holds
+either their CPU-side source data or a callback that loads them. When `recover` is called, the
+recoverable resources are released, the backend is replaced with the new one, and the resources
+are recreated in the order in which they have been created.
+
+```no_run
+# fn example(display: glium::Display, event_loop: glium::glutin::event_loop::EventLoop<()>,
+#            image: glium::texture::RawImage2d<'static, u8>) {
+use glium::recovery::Recoverable;
+
+let texture = Recoverable::with_source(&display, image, |context, image| {
+    let image = glium::texture::RawImage2d {
+        data: image.data.clone(),
+        width: image.width,
+        height: image.height,
+        format: image.format,
+    };
+    glium::Texture2d::new(context, image)
+}).unwrap();
+
+loop {
+    // ... draw with `&*texture.get()` ...
+
+    if display.is_context_lost() {
+        let wb = glium::glutin::window::WindowBuilder::new();
+        let cb = glium::glutin::ContextBuilder::new();
+        display.recover(wb, cb, &event_loop).unwrap();
+    }
+}
+# }
+```
+
+## Objects that aren't recoverable
+
+Objects that aren't wrapped in a `Recoverable` must be destroyed before calling `recover`.
+Otherwise they would later delete the objects of the new context that have the same name.
+`recover` checks that no object is alive with `Context::leak_report` once the recoverable
+resources are released, and returns `RecoveryError::ObjectsStillAlive` if this isn't the case.
+The recoverable resources are then unavailable until `recover` succeeds.
+
+Resident textures (see the `bindless` module) can be recoverable, in which case they are made
+resident again in the new context.
+
+*/
+use crate::backend::{Backend, Facade};
+use crate::context::{Context, LeakReport};
+use crate::IncompatibleOpenGl;
+
+use std::cell::{Ref, RefCell};
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+/// A resource that is recreated when recovering from a context loss.
+///
+/// The resource is built once when the `Recoverable` is created, then again each time `recover`
+/// succeeds.
+pub struct Recoverable<T> {
+    entry: Rc<Entry<T>>,
+}
+
+/// Function that builds a recoverable resource.
+type ReloadFn<T> = Box<dyn Fn(&Rc<Context>) -> Result<T, Box<dyn Error>>>;
+
+struct Entry<T> {
+    /// The resource, or `None` if it has been released and couldn't be recreated yet.
+    value: RefCell<Option<T>>,
+
+    reload: ReloadFn<T>,
+}
+
+/// A resource that can be released and recreated. Implemented by the recoverable resources
+/// registered in a `Context`.
+pub(crate) trait Reload {
+    /// Destroys the resource.
+    fn release(&self);
+
+    /// Creates the resource again.
+    fn reload(&self, context: &Rc<Context>) -> Result<(), Box<dyn Error>>;
+}
+
+impl<T> Reload for Entry<T> {
+    #[inline]
+    fn release(&self) {
+        let value = self.value.borrow_mut().take();
+        drop(value);
+    }
+
+    fn reload(&self, context: &Rc<Context>) -> Result<(), Box<dyn Error>> {
+        let value = (self.reload)(context)?;
+        *self.value.borrow_mut() = Some(value);
+        Ok(())
+    }
+}
+
+impl<T: 'static> Recoverable<T> {
+    /// Builds a resource with the given function, and keeps the function in order to build it
+    /// again after a context loss.
+    pub fn new<F, L, E>(facade: &F, reload: L) -> Result<Recoverable<T>, E>
+        where F: Facade + ?Sized, L: Fn(&Rc<Context>) -> Result<T, E> + 'static,
+              E: Error + 'static
+    {
+        let context = facade.get_context();
+        let value = reload(context)?;
+
+        let entry = Rc::new(Entry {
+            value: RefCell::new(Some(value)),
+            reload: Box::new(move |context| reload(context).map_err(|e| Box::new(e) as Box<_>)),
+        });
+
+        let weak = Rc::downgrade(&entry);
+        context.register_recoverable(weak);
+        Ok(Recoverable { entry })
+    }
+
+    /// Builds a resource from some CPU-side data, and keeps the data in order to build it again
+    /// after a context loss.
+    #[inline]
+    pub fn with_source<F, S, B, E>(facade: &F, source: S, build: B) -> Result<Recoverable<T>, E>
+        where F: Facade + ?Sized, S: 'static, B: Fn(&Rc<Context>, &S) -> Result<T, E> + 'static,
+              E: Error + 'static
+    {
+        Recoverable::new(facade, move |context| build(context, &source))
+    }
+}
+
+impl<T> Recoverable<T> {
+    /// Returns the resource.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the resource isn't available, which happens if `recover` failed.
+    #[inline]
+    pub fn get(&self) -> Ref<'_, T> {
+        Ref::map(self.entry.value.borrow(), |value| {
+            value.as_ref().expect("The resource has been lost and couldn't be recreated")
+        })
+    }
+
+    /// Returns false if the resource has been released and couldn't be recreated yet.
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        self.entry.value.borrow().is_some()
+    }
+}
+
+impl<T> fmt::Debug for Recoverable<T> where T: fmt::Debug {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("Recoverable").field(&*self.entry.value.borrow()).finish()
+    }
+}
+
+/// Error that can happen when recovering from a context loss.
+#[derive(Debug)]
+pub enum RecoveryError {
+    /// The new OpenGL context couldn't be created.
+    ContextCreation(Box<dyn Error>),
+
+    /// Some objects that aren't recoverable are still alive. They must be destroyed before
+    /// trying again.
+    ObjectsStillAlive(LeakReport),
+
+    /// The new OpenGL context doesn't support the version of the lost one.
+    IncompatibleOpenGl(IncompatibleOpenGl),
+
+    /// Some resources couldn't be recreated. The other resources have been recreated, and the
+    /// new context is used.
+    ReloadFailed(Vec<Box<dyn Error>>),
+}
+
+impl fmt::Display for RecoveryError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::RecoveryError::*;
+        match self {
+            ContextCreation(err) => write!(fmt, "The new OpenGL context couldn't be created: {}",
+                                           err),
+            ObjectsStillAlive(report) => write!(fmt, "Objects that aren't recoverable are \
+                                                      still alive: {}", report),
+            IncompatibleOpenGl(err) => write!(fmt, "{}", err),
+            ReloadFailed(errors) => {
+                write!(fmt, "{} resource(s) couldn't be recreated", errors.len())?;
+                for err in errors {
+                    write!(fmt, "\n - {}", err)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Error for RecoveryError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::RecoveryError::*;
+        match *self {
+            ContextCreation(ref err) => Some(&**err),
+            IncompatibleOpenGl(ref err) => Some(err),
+            ReloadFailed(ref errors) if errors.len() == 1 => Some(&*errors[0]),
+            _ => None,
+        }
+    }
+}
+
+impl From<IncompatibleOpenGl> for RecoveryError {
+    #[inline]
+    fn from(err: IncompatibleOpenGl) -> RecoveryError {
+        RecoveryError::IncompatibleOpenGl(err)
+    }
+}
+
+/// Replaces the lost backend of a context with a new one and recreates the recoverable
+/// resources. See the module-level documentation.
+///
+/// The new backend must not share its objects with the old one, and must support at least the
+/// same version of OpenGL. The old backend is used one last time to destroy the objects of the
+/// lost context.
+///
+/// # Safety
+///
+/// The same as `Context::new`. The OpenGL context of the new backend must be newly-created.
+pub unsafe fn recover<F, B>(facade: &F, new_backend: B) -> Result<(), RecoveryError>
+    where F: Facade + ?Sized, B: Backend + 'static
+{
+    let context = facade.get_context();
+    let resources = release(context)?;
+    reload(context, &resources, new_backend)
+}
+
+/// First half of `recover`. Destroys the objects of the lost context and returns the resources
+/// to recreate.
+pub(crate) fn release(context: &Rc<Context>) -> Result<Vec<Rc<dyn Reload>>, RecoveryError> {
+    let resources = context.recoverables();
+
+    // in the reverse order, as resources can use the ones that have been created before them
+    for resource in resources.iter().rev() {
+        resource.release();
+    }
+
+    context.purge_cached_objects();
+
+    let report = context.leak_report();
+    if !report.is_empty() {
+        return Err(RecoveryError::ObjectsStillAlive(report));
+    }
+
+    Ok(resources)
+}
+
+/// Second half of `recover`. Switches to the new backend and recreates the resources.
+pub(crate) unsafe fn reload<B>(context: &Rc<Context>, resources: &[Rc<dyn Reload>],
+                               new_backend: B) -> Result<(), RecoveryError>
+    where B: Backend + 'static
+{
+    Context::replace_lost_backend(context, new_backend)?;
+
+    let errors = resources.iter()
+                          .filter_map(|resource| resource.reload(context).err())
+                          .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(RecoveryError::ReloadFailed(errors))
+    }
+}
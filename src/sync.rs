@@ -101,6 +101,57 @@ impl Drop for LinearSyncFence {
     }
 }
 
+/// A fence that isn't tied to a context. It can be sent to another thread and used by any
+/// context that shares its objects with the one that created it.
+///
+/// The fence is leaked if it is dropped without being waited upon.
+pub struct SharedSyncFence {
+    id: gl::types::GLsync,
+}
+
+unsafe impl Send for SharedSyncFence {}
+
+impl SharedSyncFence {
+    /// Builds a new fence and flushes the commands queue, so that the fence can be reached
+    /// even if the context doesn't execute any other command.
+    pub unsafe fn new(ctxt: &mut CommandContext<'_>) -> Result<SharedSyncFence, SyncNotSupportedError> {
+        let mut fence = new_linear_sync_fence(ctxt)?;
+        ctxt.gl.Flush();
+        Ok(SharedSyncFence { id: fence.id.take().unwrap() })
+    }
+
+    /// Returns true if the fence has been signaled. Doesn't block.
+    pub unsafe fn is_signaled(&self, ctxt: &mut CommandContext<'_>) -> bool {
+        let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                        ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+        {
+            ctxt.gl.ClientWaitSync(self.id, 0, 0)
+        } else if ctxt.extensions.gl_apple_sync {
+            ctxt.gl.ClientWaitSyncAPPLE(self.id, 0, 0)
+        } else {
+            unreachable!();
+        };
+
+        result == gl::ALREADY_SIGNALED || result == gl::CONDITION_SATISFIED
+    }
+
+    /// Makes the server wait for the fence before executing the next commands of the context,
+    /// then destroys the fence. Doesn't block the client.
+    pub unsafe fn wait_server_and_drop(self, ctxt: &mut CommandContext<'_>) {
+        if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+           ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+        {
+            ctxt.gl.WaitSync(self.id, 0, gl::TIMEOUT_IGNORED);
+        } else if ctxt.extensions.gl_apple_sync {
+            ctxt.gl.WaitSyncAPPLE(self.id, 0, gl::TIMEOUT_IGNORED_APPLE);
+        } else {
+            unreachable!();
+        }
+
+        delete_fence(ctxt, self.id);
+    }
+}
+
 pub unsafe fn new_linear_sync_fence(ctxt: &mut CommandContext<'_>)
                                     -> Result<LinearSyncFence, SyncNotSupportedError>
 {
@@ -1,18 +1,22 @@
 
 use crate::gl;
+use crate::debug;
 use crate::GlObject;
+use crate::Handle;
 
 use crate::backend::Facade;
 use crate::memory_object::MemoryObject;
 use crate::version::Version;
 use crate::context::Context;
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::CapabilitiesSource;
 use crate::ContextExt;
 use crate::TextureExt;
 use crate::TextureMipmapExt;
 use crate::version::Api;
 use crate::Rect;
+use crate::transfer::Transfer;
 
 use crate::image_format::{self, TextureFormatRequest, ClientFormatAny};
 use crate::texture::Texture2dDataSink;
@@ -101,6 +105,14 @@ fn extract_dimensions(ty: Dimensions)
     }
 }
 
+/// Returns an estimate of the size in bytes of a texture.
+fn estimate_size(ty: Dimensions, levels: u32, internal_format: gl::types::GLenum) -> usize {
+    let (width, height, depth, array_size, samples) = extract_dimensions(ty);
+    let faces = if let Dimensions::Cubemap { .. } = ty { 6 } else { 1 };
+    image_format::image_size(internal_format, width, height.unwrap_or(1), depth.unwrap_or(1),
+                             array_size.unwrap_or(1) * faces, samples.unwrap_or(1), levels)
+}
+
 #[inline]
 fn get_bind_point(ty: Dimensions) -> gl::types::GLenum {
     match ty {
@@ -448,6 +460,10 @@ pub fn new_texture<'a, F: ?Sized, P>(facade: &F, format: TextureFormatRequest,
             generate_mipmaps(&ctxt, bind_point);
         }
 
+        let size = estimate_size(ty, texture_levels as u32,
+                                 storage_internal_format.unwrap_or(teximg_internal_format));
+        ctxt.resources.created(ResourceKind::Texture, Handle::Id(id), size);
+
         id
     };
 
@@ -483,6 +499,13 @@ pub unsafe fn from_id<F: Facade + ?Sized>(facade: &F,
         let ctxt = facade.get_context().make_current();
         generate_mipmaps(&ctxt, get_bind_point(ty));
     }
+    if owned {
+        let internal_format = image_format::format_request_to_glenum(facade.get_context(), format,
+                                                                     image_format::RequestType::TexStorage);
+        let size = internal_format.map(|f| estimate_size(ty, mipmap_levels, f)).unwrap_or(0);
+        let ctxt = facade.get_context().make_current();
+        ctxt.resources.created(ResourceKind::Texture, Handle::Id(id), size);
+    }
     TextureAny {
         context: facade.get_context().clone(),
         id,
@@ -821,6 +844,80 @@ impl TextureAny {
         })
     }
 
+    /// Removes the texture from its context, so that it can be sent to another thread and
+    /// adopted by another context of the same share group. See the `transfer` module.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the texture is backed by external memory.
+    pub fn into_transfer(self) -> Transfer<TextureAny> {
+        assert!(self.memory.is_none(), "Textures backed by external memory can't be transferred");
+
+        let context = self.context.clone();
+        {
+            let mut ctxt = context.make_current();
+            fbo::FramebuffersContainer::purge_texture(&mut ctxt, self.id);
+
+            // the texture isn't deleted, so OpenGL doesn't unbind it automatically
+            let bind_point = self.get_bind_point();
+            for unit in 0 .. ctxt.state.texture_units.len() {
+                if ctxt.state.texture_units[unit].texture != self.id {
+                    continue;
+                }
+
+                if ctxt.state.active_texture != unit as gl::types::GLenum {
+                    unsafe { ctxt.gl.ActiveTexture(unit as gl::types::GLenum + gl::TEXTURE0) };
+                    ctxt.state.active_texture = unit as gl::types::GLenum;
+                }
+
+                unsafe { ctxt.gl.BindTexture(bind_point, 0) };
+                ctxt.state.texture_units[unit].texture = 0;
+            }
+            if self.owned {
+                ctxt.resources.destroyed(ResourceKind::Texture, Handle::Id(self.id));
+            }
+        }
+
+        let (id, requested_format, actual_format) = (self.id, self.requested_format,
+                                                     self.actual_format.get());
+        let (ty, levels, generate_mipmaps, owned) = (self.ty, self.levels, self.generate_mipmaps,
+                                                     self.owned);
+
+        // skipping the destructor, which would delete the texture
+        let this = mem::ManuallyDrop::new(self);
+        drop(unsafe { ptr::read(&this.context) });
+
+        Transfer::new(&context, move |context| {
+            if owned {
+                let internal_format = image_format::format_request_to_glenum(context,
+                                        requested_format, image_format::RequestType::TexStorage);
+                let size = internal_format.map(|f| estimate_size(ty, levels, f)).unwrap_or(0);
+                let ctxt = context.make_current();
+                ctxt.resources.created(ResourceKind::Texture, Handle::Id(id), size);
+            }
+
+            TextureAny {
+                context: context.clone(),
+                id,
+                requested_format,
+                actual_format: Cell::new(actual_format),
+                ty,
+                levels,
+                generate_mipmaps,
+                owned,
+                memory: None,
+                latest_shader_write: Cell::new(0),
+            }
+        })
+    }
+
+    /// Attaches a label to the texture object. See `Buffer::set_label`.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        let mut ctxt = self.context.make_current();
+        debug::set_object_label(&mut ctxt, gl::TEXTURE, self.id, label);
+    }
+
     /// Binds this texture and generates mipmaps.
     #[inline]
     pub unsafe fn generate_mipmaps(&self) {
@@ -923,6 +1020,7 @@ impl Drop for TextureAny {
         }
 
         if self.owned {
+            ctxt.resources.destroyed(ResourceKind::Texture, Handle::Id(self.id));
             unsafe { ctxt.gl.DeleteTextures(1, [ self.id ].as_ptr()); }
         }
     }
@@ -64,8 +64,10 @@ use crate::version::Api;
 use crate::backend::Facade;
 use crate::context::Context;
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::ContextExt;
 use crate::GlObject;
+use crate::Handle;
 
 use crate::TextureExt;
 
@@ -446,6 +448,9 @@ impl<T> BufferTexture<T> where [T]: BufferContent, T: TextureBufferContent + Cop
             id
         };
 
+        // the storage belongs to the buffer, which is already counted
+        ctxt.resources.created(ResourceKind::Texture, Handle::Id(id), 0);
+
         Ok(BufferTexture {
             buffer,
             ty,
@@ -481,6 +486,7 @@ impl<T> Drop for BufferTexture<T> where [T]: BufferContent {
             }
         }
 
+        ctxt.resources.destroyed(ResourceKind::Texture, Handle::Id(self.texture));
         unsafe { ctxt.gl.DeleteTextures(1, [ self.texture ].as_ptr()); }
     }
 }
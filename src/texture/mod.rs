@@ -122,6 +122,7 @@ pub use crate::image_format::{ClientFormat, TextureFormat};
 pub use crate::image_format::{UncompressedFloatFormat, UncompressedIntFormat, UncompressedUintFormat};
 pub use crate::image_format::{CompressedFormat, DepthFormat, DepthStencilFormat, StencilFormat};
 pub use crate::image_format::{CompressedSrgbFormat, SrgbFormat};
+pub use crate::context::FormatSupport;
 pub use self::any::{TextureAny, TextureAnyMipmap, TextureAnyLayer, TextureAnyLayerMipmap};
 pub use self::any::{TextureAnyImage, Dimensions};
 pub use self::bindless::{ResidentTexture, TextureHandle, BindlessTexturesNotSupportedError};
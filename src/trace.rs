@@ -0,0 +1,1420 @@
+/*!
+Capture and replay of the OpenGL commands executed by glium.
+
+Call `Context::start_trace` to write every OpenGL command that glium executes to a file, or to
+any other `Write`, and `Context::stop_trace` to stop. A trace contains the name, the arguments and
+the return value of each call, plus the content of the memory passed to the commands that upload
+data: buffer and texture uploads, shader sources, uniform values, etc. The swaps of the default
+framebuffer are recorded as well.
+
+A trace can then be loaded with `Trace::read` and executed again against any `Backend` with
+`replay`, for example in order to reproduce a rendering bug without the application that
+triggered it.
+
+```no_run
+# fn example(context: &glium::backend::Context) -> Result<(), Box<dyn std::error::Error>> {
+context.start_trace(std::fs::File::create("bug.trace")?)?;
+// ... draw the frame that shows the bug ...
+context.stop_trace()?;
+# Ok(())
+# }
+```
+
+# Replaying
+
+The commands are executed in the same order as they were recorded, with a few adjustments:
+
+ - Queries like `glGet*`, `glIs*` or `glReadPixels` are not replayed, as nobody reads their
+   output.
+ - Uniform locations, sync objects and bindless handles are remapped to the values returned
+   during the replay.
+ - The data written by glium through mapped buffers is copied again when the buffer is flushed
+   or unmapped.
+ - Object names returned by the implementation must be the same as the ones that were recorded,
+   otherwise `ReplayError::NameMismatch` is returned. This is normally the case for a newly-created
+   context, which means that you should start the trace right after creating the context.
+
+Pointers whose content isn't known are replayed as they are. This is correct for offsets in
+buffer objects, which is what glium uses for vertex attributes and indices.
+
+# Features
+
+Only available if the 'trace' feature is enabled.
+
+*/
+use crate::backend::Backend;
+use crate::backend::recording::{Arg, IntoArg};
+use crate::gl;
+use crate::SwapBuffersError;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io::{self, BufWriter, Read, Write};
+use std::mem;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+#[allow(clippy::all)]
+mod stubs {
+    include!(concat!(env!("OUT_DIR"), "/tracing_stubs.rs"));
+}
+
+thread_local! {
+    /// The tracer of the context whose commands are being executed in this thread.
+    static CURRENT: RefCell<Option<Rc<Tracer>>> = const { RefCell::new(None) };
+}
+
+/// First bytes of a trace file.
+const MAGIC: &[u8; 8] = b"GLIUMTRC";
+
+/// Version of the format of trace files.
+const FORMAT_VERSION: u32 = 1;
+
+/// A list of OpenGL commands that have been captured.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    /// The commands, in the order in which they were executed.
+    pub commands: Vec<Command>,
+}
+
+/// A command of a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// A call to an OpenGL function.
+    Call(TracedCall),
+
+    /// The buffers of the default framebuffer have been swapped.
+    SwapBuffers,
+}
+
+/// A call to an OpenGL function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedCall {
+    /// Name of the function, for example `glDrawArrays`.
+    pub name: String,
+
+    /// The arguments of the call.
+    ///
+    /// Calls to `glFlushMappedBufferRange` and `glUnmapBuffer` (and their variants) have an
+    /// additional `Value::Data` argument containing the bytes that were written through the
+    /// mapping.
+    pub args: Vec<Value>,
+
+    /// The value returned by the function, or `None` if it doesn't return anything.
+    pub ret: Option<Value>,
+}
+
+/// A value stored in a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A signed integer, for example a `GLint` or a `GLsizei`.
+    Int(i64),
+
+    /// An unsigned integer. This includes `GLenum`s, `GLbitfield`s, `GLboolean`s and object
+    /// names.
+    UInt(u64),
+
+    /// A `GLfloat` or a `GLdouble`.
+    Float(f64),
+
+    /// A pointer whose content wasn't captured, for example an offset in a buffer object.
+    Pointer(u64),
+
+    /// A pointer to memory whose content was captured. During the replay, the pointer points
+    /// to a copy of this data.
+    Data(Vec<u8>),
+}
+
+impl From<Arg> for Value {
+    #[inline]
+    fn from(arg: Arg) -> Value {
+        match arg {
+            Arg::Int(val) => Value::Int(val),
+            Arg::UInt(val) => Value::UInt(val),
+            Arg::Float(val) => Value::Float(val),
+            Arg::Pointer(val) => Value::Pointer(val as u64),
+        }
+    }
+}
+
+impl Value {
+    /// Returns the value as an unsigned integer, reinterpreting signed integers and pointers.
+    /// Returns `None` for floats and data.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::Int(val) => Some(val as u64),
+            Value::UInt(val) => Some(val),
+            Value::Pointer(val) => Some(val),
+            Value::Float(_) | Value::Data(_) => None,
+        }
+    }
+}
+
+/// Error that can happen when reading a trace.
+#[derive(Debug)]
+pub enum TraceReadError {
+    /// Error while reading from the source.
+    Io(io::Error),
+
+    /// The source doesn't start with the header of a trace.
+    NotATrace,
+
+    /// The trace was written with a format that isn't supported by this version of glium.
+    UnsupportedVersion(u32),
+
+    /// The trace contains invalid data.
+    Corrupted,
+}
+
+impl fmt::Display for TraceReadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::TraceReadError::*;
+        match *self {
+            Io(ref err) => write!(fmt, "error while reading the trace: {}", err),
+            NotATrace => fmt.write_str("the data is not a glium trace"),
+            UnsupportedVersion(version) => write!(fmt, "unsupported trace format version {}", version),
+            Corrupted => fmt.write_str("the trace contains invalid data"),
+        }
+    }
+}
+
+impl Error for TraceReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            TraceReadError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TraceReadError {
+    #[inline]
+    fn from(err: io::Error) -> TraceReadError {
+        TraceReadError::Io(err)
+    }
+}
+
+/// Error that can happen when replaying a trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The trace contains a function that glium doesn't know about, or that has the wrong number
+    /// of arguments.
+    UnknownFunction {
+        /// Index of the command in the trace.
+        index: usize,
+        /// Name of the function.
+        name: String,
+    },
+
+    /// The backend doesn't provide a function used by the trace.
+    MissingFunction {
+        /// Index of the command in the trace.
+        index: usize,
+        /// Name of the function.
+        name: String,
+    },
+
+    /// The implementation returned an object name or a location that is different from the
+    /// recorded one.
+    NameMismatch {
+        /// Index of the command in the trace.
+        index: usize,
+        /// Name of the function.
+        name: String,
+        /// The value in the trace.
+        recorded: u64,
+        /// The value returned during the replay.
+        obtained: u64,
+    },
+
+    /// Error while swapping buffers.
+    SwapBuffers(SwapBuffersError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::ReplayError::*;
+        match *self {
+            UnknownFunction { index, ref name } =>
+                write!(fmt, "command #{}: unknown function `{}`", index, name),
+            MissingFunction { index, ref name } =>
+                write!(fmt, "command #{}: the backend doesn't provide `{}`", index, name),
+            NameMismatch { index, ref name, recorded, obtained } =>
+                write!(fmt, "command #{}: `{}` returned {} instead of {}", index, name, obtained,
+                       recorded),
+            SwapBuffers(ref err) => write!(fmt, "error while swapping buffers: {}", err),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ReplayError::SwapBuffers(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SwapBuffersError> for ReplayError {
+    #[inline]
+    fn from(err: SwapBuffersError) -> ReplayError {
+        ReplayError::SwapBuffers(err)
+    }
+}
+
+impl Trace {
+    /// Reads a trace written by `Context::start_trace` or by `Trace::write`.
+    pub fn read<R>(reader: R) -> Result<Trace, TraceReadError> where R: Read {
+        let mut reader = Decoder { input: reader, names: Vec::new() };
+
+        let mut magic = [0; 8];
+        reader.input.read_exact(&mut magic).map_err(|_| TraceReadError::NotATrace)?;
+        if &magic != MAGIC {
+            return Err(TraceReadError::NotATrace);
+        }
+
+        let version = reader.read_u32()?;
+        if version != FORMAT_VERSION {
+            return Err(TraceReadError::UnsupportedVersion(version));
+        }
+
+        let mut commands = Vec::new();
+        while let Some(command) = reader.read_command()? {
+            commands.push(command);
+        }
+
+        Ok(Trace { commands })
+    }
+
+    /// Writes the trace in the same format as `Context::start_trace`.
+    pub fn write<W>(&self, writer: W) -> io::Result<()> where W: Write {
+        let mut encoder = Encoder::new(writer)?;
+        for command in &self.commands {
+            encoder.write_command(command)?;
+        }
+        encoder.output.flush()
+    }
+
+    /// Returns an iterator over the function calls of the trace.
+    #[inline]
+    pub fn calls(&self) -> impl Iterator<Item = &TracedCall> {
+        self.commands.iter().filter_map(|command| match *command {
+            Command::Call(ref call) => Some(call),
+            Command::SwapBuffers => None,
+        })
+    }
+}
+
+/// Executes the commands of a trace with the given backend.
+///
+/// The backend is made current before replaying. See the documentation of the module for what
+/// is replayed.
+///
+/// # Safety
+///
+/// The trace is trusted. Invalid arguments can lead to undefined behaviors in the
+/// implementation, and pointers that weren't captured are passed as they are.
+pub unsafe fn replay<B>(trace: &Trace, backend: &B) -> Result<(), ReplayError>
+    where B: Backend + ?Sized
+{
+    backend.make_current();
+    let gl = gl::Gl::load_with(|symbol| backend.get_proc_address(symbol) as *const _);
+
+    let mut replayer = Replayer::default();
+    let mut available = HashSet::new();
+
+    for (index, command) in trace.commands.iter().enumerate() {
+        let call = match *command {
+            Command::Call(ref call) => call,
+            Command::SwapBuffers => {
+                backend.swap_buffers()?;
+                continue;
+            },
+        };
+
+        if !is_replayed(&call.name) {
+            continue;
+        }
+
+        if !available.contains(&call.name) {
+            if backend.get_proc_address(&call.name).is_null() {
+                return Err(ReplayError::MissingFunction { index, name: call.name.clone() });
+            }
+            available.insert(call.name.clone());
+        }
+
+        replayer.replay_call(&gl, index, call)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the function pointers that record the calls with the current tracer and forward
+/// them to the real implementation.
+///
+/// The functions that `get_proc_address` doesn't provide aren't loaded either.
+pub(crate) fn load_traced_functions<F>(mut get_proc_address: F) -> gl::Gl
+    where F: FnMut(&str) -> *const c_void
+{
+    gl::Gl::load_with(|symbol| {
+        if get_proc_address(symbol).is_null() {
+            ptr::null()
+        } else {
+            stubs::get_proc_address(symbol)
+        }
+    })
+}
+
+/// Records the commands executed by a context while tracing is enabled.
+pub(crate) struct Tracer {
+    /// The function pointers of the implementation.
+    gl: gl::Gl,
+
+    /// False once the trace has been stopped.
+    active: Cell<bool>,
+
+    output: RefCell<Encoder<BufWriter<Box<dyn Write>>>>,
+
+    /// The first error that happened while writing, if any.
+    error: RefCell<Option<io::Error>>,
+
+    state: RefCell<TracerState>,
+}
+
+impl Tracer {
+    /// Builds a tracer that forwards the calls to `gl`, and writes the header of the trace.
+    pub fn new(gl: gl::Gl, output: Box<dyn Write>) -> io::Result<Tracer> {
+        Ok(Tracer {
+            gl,
+            active: Cell::new(true),
+            output: RefCell::new(Encoder::new(BufWriter::new(output))?),
+            error: RefCell::new(None),
+            state: RefCell::new(TracerState::default()),
+        })
+    }
+
+    /// Makes this tracer the one that receives the calls made in this thread.
+    pub fn make_current(self: &Rc<Tracer>) {
+        CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            if !current.as_ref().is_some_and(|tracer| Rc::ptr_eq(tracer, self)) {
+                *current = Some(self.clone());
+            }
+        });
+    }
+
+    /// Records a swap of the default framebuffer, and flushes the output.
+    pub fn record_swap_buffers(&self) {
+        self.write(&Command::SwapBuffers);
+        let result = self.output.borrow_mut().output.flush();
+        self.store_result(result);
+    }
+
+    /// Stops recording, flushes the output and returns the first error that happened.
+    ///
+    /// If this tracer is the current one, it stops being current, so that the output is closed
+    /// as soon as the tracer is dropped.
+    pub fn finish(&self) -> io::Result<()> {
+        self.active.set(false);
+
+        // `try_with` fails if the context is dropped while the thread-local storage is destroyed
+        let _ = CURRENT.try_with(|current| {
+            let mut current = current.borrow_mut();
+            if current.as_ref().is_some_and(|tracer| ptr::eq(&**tracer, self)) {
+                *current = None;
+            }
+        });
+
+        let result = self.output.borrow_mut().output.flush();
+        self.store_result(result);
+        match self.error.borrow_mut().take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn record(&self, name: &'static str, args: &[Arg], ret: Option<Arg>) {
+        let args = unsafe { self.state.borrow_mut().capture(&self.gl, name, args, ret) };
+        self.write(&Command::Call(TracedCall {
+            name: name.to_owned(),
+            args,
+            ret: ret.map(Value::from),
+        }));
+    }
+
+    fn write(&self, command: &Command) {
+        if self.error.borrow().is_some() {
+            return;
+        }
+
+        let result = self.output.borrow_mut().write_command(command);
+        self.store_result(result);
+    }
+
+    fn store_result(&self, result: io::Result<()>) {
+        if let Err(err) = result {
+            let mut error = self.error.borrow_mut();
+            if error.is_none() {
+                *error = Some(err);
+            }
+        }
+    }
+}
+
+/// Called by all the stubs. Calls the real function, then records the call if tracing is
+/// enabled.
+fn trace<R, F>(name: &'static str, args: &[Arg], call: F) -> R
+    where R: IntoReturn + Copy, F: FnOnce(&gl::Gl) -> R
+{
+    let tracer = match CURRENT.with(|current| current.borrow().clone()) {
+        Some(tracer) => tracer,
+        // there is no function to forward the call to, so we return zero like the recording
+        // backend does, which is valid for all the return types of OpenGL functions
+        None => return unsafe { mem::zeroed() },
+    };
+
+    // no borrow must be held while calling the function, as the implementation can invoke
+    // the debug callback
+    let ret = call(&tracer.gl);
+
+    if tracer.active.get() {
+        tracer.record(name, args, ret.into_return());
+    }
+
+    ret
+}
+
+/// Conversion from the return value of a function.
+trait IntoReturn {
+    fn into_return(self) -> Option<Arg>;
+}
+
+impl IntoReturn for () {
+    #[inline]
+    fn into_return(self) -> Option<Arg> {
+        None
+    }
+}
+
+impl<T> IntoReturn for T where T: IntoArg {
+    #[inline]
+    fn into_return(self) -> Option<Arg> {
+        Some(self.into_arg())
+    }
+}
+
+/// Conversion from a value of a trace to the argument of a function.
+trait FromValue {
+    fn from_value(value: &mut Value) -> Self;
+}
+
+macro_rules! impl_from_value {
+    ($($ty:ty),+) => (
+        $(
+            impl FromValue for $ty {
+                #[inline]
+                fn from_value(value: &mut Value) -> $ty {
+                    match *value {
+                        Value::Int(val) => val as $ty,
+                        Value::UInt(val) => val as $ty,
+                        Value::Float(val) => val as $ty,
+                        Value::Pointer(val) => val as $ty,
+                        Value::Data(_) => 0 as $ty,
+                    }
+                }
+            }
+        )+
+    );
+}
+
+impl_from_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T> FromValue for *const T {
+    #[inline]
+    fn from_value(value: &mut Value) -> *const T {
+        match *value {
+            Value::Data(ref data) => data.as_ptr() as *const T,
+            Value::Pointer(val) => val as usize as *const T,
+            _ => ptr::null(),
+        }
+    }
+}
+
+impl<T> FromValue for *mut T {
+    #[inline]
+    fn from_value(value: &mut Value) -> *mut T {
+        match *value {
+            Value::Data(ref mut data) => data.as_mut_ptr() as *mut T,
+            Value::Pointer(val) => val as usize as *mut T,
+            _ => ptr::null_mut(),
+        }
+    }
+}
+
+// Debug callbacks are never replayed.
+impl<T> FromValue for Option<T> {
+    #[inline]
+    fn from_value(_: &mut Value) -> Option<T> {
+        None
+    }
+}
+
+/// Buffer of a mapping that is being recorded or replayed.
+struct Mapping {
+    /// Address of the mapping.
+    ptr: usize,
+    /// Number of bytes mapped.
+    length: usize,
+    /// True if the data written to the mapping must be captured when unmapping.
+    capture_on_unmap: bool,
+}
+
+/// Keeps track of which buffer is bound to each target, in order to know which buffer is
+/// designated by the functions that take a target.
+#[derive(Default)]
+struct BufferBindings {
+    bindings: HashMap<u32, u32>,
+}
+
+impl BufferBindings {
+    /// Updates the bindings if `name` binds a buffer.
+    fn update(&mut self, name: &str, args: &[u64]) {
+        match name {
+            "glBindBuffer" | "glBindBufferARB" => {
+                self.bindings.insert(args[0] as u32, args[1] as u32);
+            },
+            "glBindBufferBase" | "glBindBufferRange" | "glBindBufferBaseEXT" |
+            "glBindBufferRangeEXT" => {
+                self.bindings.insert(args[0] as u32, args[2] as u32);
+            },
+            _ => (),
+        }
+    }
+
+    fn bound(&self, target: u32) -> u32 {
+        self.bindings.get(&target).cloned().unwrap_or(0)
+    }
+
+    /// Returns the buffer designated by the first argument of a function, which is either a
+    /// buffer name for DSA functions or a bind point.
+    fn designated(&self, function: &str, arg: u64) -> u32 {
+        if function.contains("Named") {
+            arg as u32
+        } else {
+            self.bound(arg as u32)
+        }
+    }
+}
+
+/// State required to capture the memory pointed to by the arguments of the calls.
+struct TracerState {
+    buffers: BufferBindings,
+    unpack_alignment: usize,
+    mappings: HashMap<u32, Mapping>,
+}
+
+impl Default for TracerState {
+    fn default() -> TracerState {
+        TracerState {
+            buffers: BufferBindings::default(),
+            unpack_alignment: 4,
+            mappings: HashMap::new(),
+        }
+    }
+}
+
+impl TracerState {
+    /// Converts the arguments of a call that has just been executed into values, capturing the
+    /// memory they point to when it is known.
+    ///
+    /// # Safety
+    ///
+    /// The arguments must be valid according to the OpenGL specifications.
+    unsafe fn capture(&mut self, gl: &gl::Gl, name: &str, args: &[Arg], ret: Option<Arg>)
+                      -> Vec<Value>
+    {
+        let raw = args.iter().map(|arg| arg.as_u64().unwrap_or(0)).collect::<Vec<_>>();
+        let mut values = args.iter().map(|&arg| Value::from(arg)).collect::<Vec<_>>();
+
+        self.buffers.update(name, &raw);
+
+        if name == "glPixelStorei" && raw[0] as u32 == gl::UNPACK_ALIGNMENT {
+            self.unpack_alignment = raw[1].max(1) as usize;
+        }
+
+        let last = args.len().wrapping_sub(1);
+        let size = |num: usize| raw[num] as i64;
+
+        match name {
+            "glBufferData" | "glBufferDataARB" | "glBufferStorage" | "glBufferStorageEXT" |
+            "glNamedBufferData" | "glNamedBufferDataEXT" | "glNamedBufferStorage" |
+            "glNamedBufferStorageEXT" => {
+                values[2] = capture_data(raw[2], size(1));
+            },
+
+            "glBufferSubData" | "glBufferSubDataARB" | "glNamedBufferSubData" |
+            "glNamedBufferSubDataEXT" => {
+                values[3] = capture_data(raw[3], size(2));
+            },
+
+            "glProgramBinary" => {
+                values[2] = capture_data(raw[2], size(3));
+            },
+
+            "glShaderSource" | "glShaderSourceARB" | "glCreateShaderProgramv" => {
+                // the strings are concatenated into a single null-terminated string
+                let lengths = if name == "glCreateShaderProgramv" { 0 } else { raw[3] };
+                let source = capture_strings(size(1), raw[2], lengths).concat();
+                values[1] = Value::Int(1);
+                values[2] = Value::Data(nul_terminated(source));
+                if name != "glCreateShaderProgramv" {
+                    values[3] = Value::Pointer(0);
+                }
+            },
+
+            "glTransformFeedbackVaryings" | "glTransformFeedbackVaryingsEXT" => {
+                let varyings = capture_strings(size(1), raw[2], 0);
+                values[2] = Value::Data(varyings.into_iter().flat_map(nul_terminated).collect());
+            },
+
+            "glGetUniformLocation" | "glGetUniformLocationARB" | "glGetAttribLocation" |
+            "glGetAttribLocationARB" | "glGetUniformBlockIndex" | "glGetFragDataLocation" |
+            "glGetFragDataIndex" => {
+                values[1] = capture_string(raw[1], -1);
+            },
+
+            "glBindAttribLocation" | "glBindAttribLocationARB" | "glBindFragDataLocation" |
+            "glGetProgramResourceIndex" | "glGetProgramResourceLocation" |
+            "glGetSubroutineIndex" | "glGetSubroutineUniformLocation" => {
+                values[2] = capture_string(raw[2], -1);
+            },
+
+            "glBindFragDataLocationIndexed" => {
+                values[3] = capture_string(raw[3], -1);
+            },
+
+            "glObjectLabel" | "glObjectLabelKHR" | "glPushDebugGroup" | "glPushDebugGroupKHR" => {
+                values[3] = capture_string(raw[3], size(2));
+            },
+
+            "glObjectPtrLabel" | "glObjectPtrLabelKHR" => {
+                values[2] = capture_string(raw[2], size(1));
+            },
+
+            "glDebugMessageInsert" | "glDebugMessageInsertARB" | "glDebugMessageInsertKHR" => {
+                values[5] = capture_string(raw[5], size(4));
+            },
+
+            "glInsertEventMarkerEXT" | "glPushGroupMarkerEXT" | "glStringMarkerGREMEDY" => {
+                // a length of zero means that the string is null-terminated
+                let length = if raw[0] == 0 { -1 } else { size(0) };
+                values[1] = capture_string(raw[1], length);
+            },
+
+            "glDebugMessageControl" | "glDebugMessageControlARB" | "glDebugMessageControlKHR" => {
+                values[4] = capture_data(raw[4], size(3) * 4);
+            },
+
+            "glDrawBuffers" | "glDrawBuffersARB" | "glDrawBuffersATI" | "glDrawBuffersEXT" => {
+                values[1] = capture_data(raw[1], size(0) * 4);
+            },
+
+            "glNamedFramebufferDrawBuffers" | "glInvalidateFramebuffer" |
+            "glInvalidateSubFramebuffer" | "glInvalidateNamedFramebufferData" |
+            "glInvalidateNamedFramebufferSubData" | "glDiscardFramebufferEXT" => {
+                values[2] = capture_data(raw[2], size(1) * 4);
+            },
+
+            "glUniformSubroutinesuiv" => {
+                values[2] = capture_data(raw[2], size(1) * 4);
+            },
+
+            "glClearBufferfv" | "glClearBufferiv" | "glClearBufferuiv" => {
+                let length = if raw[0] as u32 == gl::COLOR { 16 } else { 4 };
+                values[2] = capture_data(raw[2], length);
+            },
+
+            "glClearNamedFramebufferfv" | "glClearNamedFramebufferiv" |
+            "glClearNamedFramebufferuiv" => {
+                let length = if raw[1] as u32 == gl::COLOR { 16 } else { 4 };
+                values[3] = capture_data(raw[3], length);
+            },
+
+            "glClearTexImage" => {
+                values[4] = capture_data(raw[4], pixel_size(raw[2] as u32, raw[3] as u32) as i64);
+            },
+
+            "glClearTexSubImage" => {
+                values[10] = capture_data(raw[10], pixel_size(raw[8] as u32, raw[9] as u32) as i64);
+            },
+
+            "glMapBufferRange" | "glMapBufferRangeEXT" | "glMapNamedBufferRange" |
+            "glMapNamedBufferRangeEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                let access = raw[3] as u32;
+                self.mappings.insert(buffer, Mapping {
+                    ptr: ret.and_then(|ret| ret.as_u64()).unwrap_or(0) as usize,
+                    length: raw[2] as usize,
+                    capture_on_unmap: (access & gl::MAP_WRITE_BIT) != 0 &&
+                                      (access & gl::MAP_FLUSH_EXPLICIT_BIT) == 0,
+                });
+            },
+
+            "glMapBuffer" | "glMapBufferARB" | "glMapBufferOES" | "glMapNamedBuffer" |
+            "glMapNamedBufferEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                let mut length = 0;
+                if name.contains("Named") {
+                    gl.GetNamedBufferParameteriv(buffer, gl::BUFFER_SIZE, &mut length);
+                } else {
+                    gl.GetBufferParameteriv(raw[0] as u32, gl::BUFFER_SIZE, &mut length);
+                }
+
+                self.mappings.insert(buffer, Mapping {
+                    ptr: ret.and_then(|ret| ret.as_u64()).unwrap_or(0) as usize,
+                    length: length.max(0) as usize,
+                    capture_on_unmap: raw[1] as u32 != gl::READ_ONLY,
+                });
+            },
+
+            "glFlushMappedBufferRange" | "glFlushMappedBufferRangeEXT" |
+            "glFlushMappedNamedBufferRange" | "glFlushMappedNamedBufferRangeEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                if let Some(mapping) = self.mappings.get(&buffer) {
+                    values.push(capture_data(mapping.ptr as u64 + raw[1], size(2)));
+                }
+            },
+
+            "glUnmapBuffer" | "glUnmapBufferARB" | "glUnmapBufferOES" | "glUnmapNamedBuffer" |
+            "glUnmapNamedBufferEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                if let Some(mapping) = self.mappings.remove(&buffer) {
+                    if mapping.capture_on_unmap {
+                        values.push(capture_data(mapping.ptr as u64, mapping.length as i64));
+                    }
+                }
+            },
+
+            // functions that create objects, like `glGenBuffers(n, buffers)` or
+            // `glCreateQueries(target, n, ids)`
+            _ if (name.starts_with("glGen") || name.starts_with("glCreate")) && args.len() >= 2 &&
+                 matches!(args[last], Arg::Pointer(_)) =>
+            {
+                values[last] = capture_data(raw[last], size(last - 1) * 4);
+            },
+
+            // functions that delete objects, like `glDeleteBuffers(n, buffers)`
+            _ if name.starts_with("glDelete") && args.len() == 2 &&
+                 matches!(args[1], Arg::Pointer(_)) =>
+            {
+                values[1] = capture_data(raw[1], size(0) * 4);
+            },
+
+            _ if is_parameter_array(name) => {
+                let length = match raw[last - 1] as u32 {
+                    gl::TEXTURE_BORDER_COLOR | gl::TEXTURE_SWIZZLE_RGBA => 16,
+                    _ => 4,
+                };
+                values[last] = capture_data(raw[last], length);
+            },
+
+            _ => {
+                if let Some((count, element_size)) = uniform_array(name) {
+                    values[last] = capture_data(raw[last], size(count) * element_size as i64);
+
+                } else if name.starts_with("glCompressedTex") {
+                    if self.buffers.bound(gl::PIXEL_UNPACK_BUFFER) == 0 {
+                        values[last] = capture_data(raw[last], size(last - 1));
+                    }
+
+                } else if let Some(upload) = texture_upload(name) {
+                    if self.buffers.bound(gl::PIXEL_UNPACK_BUFFER) == 0 {
+                        let dimension = |num: Option<usize>| num.map_or(1, |num| raw[num] as usize);
+                        let length = image_size(raw[upload.width] as usize,
+                                                dimension(upload.height),
+                                                dimension(upload.depth),
+                                                pixel_size(raw[upload.format] as u32,
+                                                           raw[upload.ty] as u32),
+                                                self.unpack_alignment);
+                        values[upload.pixels] = capture_data(raw[upload.pixels], length as i64);
+                    }
+                }
+            },
+        }
+
+        values
+    }
+}
+
+/// Copies `length` bytes at `ptr`. Returns a null pointer if `ptr` is null.
+unsafe fn capture_data(ptr: u64, length: i64) -> Value {
+    if ptr == 0 {
+        return Value::Pointer(0);
+    }
+
+    Value::Data(slice::from_raw_parts(ptr as usize as *const u8, length.max(0) as usize).to_vec())
+}
+
+/// Copies a string and adds a null terminator. A negative `length` means that the string is
+/// already null-terminated.
+unsafe fn capture_string(ptr: u64, length: i64) -> Value {
+    if ptr == 0 {
+        return Value::Pointer(0);
+    }
+
+    let string = if length < 0 {
+        CStr::from_ptr(ptr as usize as *const c_char).to_bytes().to_vec()
+    } else {
+        slice::from_raw_parts(ptr as usize as *const u8, length as usize).to_vec()
+    };
+
+    Value::Data(nul_terminated(string))
+}
+
+/// Copies an array of `count` strings. `lengths` is either null or an array of lengths where
+/// negative values mean that the string is null-terminated.
+unsafe fn capture_strings(count: i64, strings: u64, lengths: u64) -> Vec<Vec<u8>> {
+    let strings = strings as usize as *const *const c_char;
+    let lengths = lengths as usize as *const i32;
+
+    (0 .. count.max(0) as usize).map(|num| {
+        let string = *strings.add(num);
+        let length = if lengths.is_null() { -1 } else { *lengths.add(num) };
+
+        if length < 0 {
+            CStr::from_ptr(string).to_bytes().to_vec()
+        } else {
+            slice::from_raw_parts(string as *const u8, length as usize).to_vec()
+        }
+    }).collect()
+}
+
+fn nul_terminated(mut string: Vec<u8>) -> Vec<u8> {
+    string.push(0);
+    string
+}
+
+/// Returns true for functions like `glTexParameterfv` or `glSamplerParameterIiv`.
+fn is_parameter_array(name: &str) -> bool {
+    (name.starts_with("glTexParameter") || name.starts_with("glTextureParameter") ||
+     name.starts_with("glSamplerParameter")) && name.trim_end_matches("EXT").ends_with('v')
+}
+
+/// For functions like `glUniform4fv` or `glProgramUniformMatrix3x2dv`, returns the index of the
+/// `count` argument and the size in bytes of each element of the array.
+fn uniform_array(name: &str) -> Option<(usize, usize)> {
+    let (rest, count) = if let Some(rest) = name.strip_prefix("glProgramUniform") {
+        (rest, 2)
+    } else if let Some(rest) = name.strip_prefix("glUniform") {
+        (rest, 1)
+    } else {
+        return None;
+    };
+
+    let rest = rest.trim_end_matches("ARB").trim_end_matches("EXT").strip_suffix('v')?;
+
+    let (components, ty) = if let Some(rest) = rest.strip_prefix("Matrix") {
+        let dimensions = rest.chars().take_while(|c| c.is_ascii_digit() || *c == 'x')
+                             .collect::<String>();
+        let components = match dimensions.split_once('x') {
+            Some((columns, rows)) => columns.parse::<usize>().ok()? * rows.parse::<usize>().ok()?,
+            None => dimensions.parse::<usize>().ok()?.pow(2),
+        };
+        (components, &rest[dimensions.len() ..])
+
+    } else if let Some(ty) = rest.strip_prefix("Handle") {
+        (1, ty)
+
+    } else {
+        let components = rest.get(.. 1)?.parse::<usize>().ok()?;
+        (components, &rest[1 ..])
+    };
+
+    let size = match ty {
+        "f" | "i" | "ui" => 4,
+        "d" | "i64" | "ui64" => 8,
+        _ => return None,
+    };
+
+    Some((count, components * size))
+}
+
+/// Position of the arguments of a function that uploads an uncompressed image.
+struct TextureUpload {
+    width: usize,
+    height: Option<usize>,
+    depth: Option<usize>,
+    format: usize,
+    ty: usize,
+    pixels: usize,
+}
+
+fn texture_upload(name: &str) -> Option<TextureUpload> {
+    let upload = |width, height, depth, format| TextureUpload {
+        width, height, depth, format, ty: format + 1, pixels: format + 2,
+    };
+
+    // the EXT direct state access functions have an additional `texture` argument
+    let (name, offset) = match name.strip_suffix("EXT") {
+        Some(name) if name.starts_with("glTexture") => (name, 1),
+        _ => (name, 0),
+    };
+
+    let upload = match name {
+        "glTexImage1D" | "glTextureImage1D" => upload(3, None, None, 5),
+        "glTexImage2D" | "glTextureImage2D" => upload(3, Some(4), None, 6),
+        "glTexImage3D" | "glTextureImage3D" => upload(3, Some(4), Some(5), 7),
+        "glTexSubImage1D" | "glTextureSubImage1D" => upload(3, None, None, 4),
+        "glTexSubImage2D" | "glTextureSubImage2D" => upload(4, Some(5), None, 6),
+        "glTexSubImage3D" | "glTextureSubImage3D" => upload(5, Some(6), Some(7), 8),
+        _ => return None,
+    };
+
+    Some(TextureUpload {
+        width: upload.width + offset,
+        height: upload.height.map(|num| num + offset),
+        depth: upload.depth.map(|num| num + offset),
+        format: upload.format + offset,
+        ty: upload.ty + offset,
+        pixels: upload.pixels + offset,
+    })
+}
+
+/// Returns the number of bytes of an image, following the `GL_UNPACK_ALIGNMENT` rules.
+fn image_size(width: usize, height: usize, depth: usize, pixel_size: usize, alignment: usize)
+              -> usize
+{
+    if width == 0 || height == 0 || depth == 0 {
+        return 0;
+    }
+
+    let row = width * pixel_size;
+    let aligned_row = row.div_ceil(alignment) * alignment;
+    aligned_row * (height * depth - 1) + row
+}
+
+/// Returns the number of bytes of a pixel of the given client format and type.
+fn pixel_size(format: u32, ty: u32) -> usize {
+    match ty {
+        gl::UNSIGNED_BYTE_3_3_2 | gl::UNSIGNED_BYTE_2_3_3_REV => return 1,
+        gl::UNSIGNED_SHORT_5_6_5 | gl::UNSIGNED_SHORT_5_6_5_REV | gl::UNSIGNED_SHORT_4_4_4_4 |
+        gl::UNSIGNED_SHORT_4_4_4_4_REV | gl::UNSIGNED_SHORT_5_5_5_1 |
+        gl::UNSIGNED_SHORT_1_5_5_5_REV => return 2,
+        gl::UNSIGNED_INT_8_8_8_8 | gl::UNSIGNED_INT_8_8_8_8_REV | gl::UNSIGNED_INT_10_10_10_2 |
+        gl::UNSIGNED_INT_2_10_10_10_REV | gl::UNSIGNED_INT_24_8 |
+        gl::UNSIGNED_INT_10F_11F_11F_REV | gl::UNSIGNED_INT_5_9_9_9_REV => return 4,
+        gl::FLOAT_32_UNSIGNED_INT_24_8_REV => return 8,
+        _ => (),
+    }
+
+    let components = match format {
+        gl::RG | gl::RG_INTEGER | gl::LUMINANCE_ALPHA | gl::DEPTH_STENCIL => 2,
+        gl::RGB | gl::BGR | gl::RGB_INTEGER | gl::BGR_INTEGER => 3,
+        gl::RGBA | gl::BGRA | gl::RGBA_INTEGER | gl::BGRA_INTEGER => 4,
+        _ => 1,
+    };
+
+    let component_size = match ty {
+        gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => 2,
+        gl::INT | gl::UNSIGNED_INT | gl::FLOAT => 4,
+        gl::DOUBLE => 8,
+        _ => 1,
+    };
+
+    components * component_size
+}
+
+/// Returns false for the functions that only query the state of the implementation.
+fn is_replayed(name: &str) -> bool {
+    match name {
+        // these queries return values that are remapped or checked
+        "glGetUniformLocation" | "glGetUniformLocationARB" | "glGetAttribLocation" |
+        "glGetAttribLocationARB" | "glGetUniformBlockIndex" | "glGetFragDataLocation" |
+        "glGetFragDataIndex" | "glGetProgramResourceIndex" | "glGetProgramResourceLocation" |
+        "glGetSubroutineIndex" | "glGetSubroutineUniformLocation" | "glGetTextureHandleARB" |
+        "glGetTextureSamplerHandleARB" | "glGetImageHandleARB" => true,
+
+        _ if name.starts_with("glDebugMessageCallback") => false,
+
+        _ => !(name.starts_with("glGet") || name.starts_with("glIs") ||
+               name.starts_with("glAre") || name.starts_with("glReadPixels") ||
+               name.starts_with("glReadnPixels")),
+    }
+}
+
+/// State of the replay.
+#[derive(Default)]
+struct Replayer {
+    buffers: BufferBindings,
+    mappings: HashMap<u32, Mapping>,
+
+    /// The program passed to the latest `glUseProgram`.
+    current_program: u64,
+
+    /// Recorded uniform locations of each program, and their replayed value.
+    uniform_locations: HashMap<(u64, u64), u64>,
+
+    /// Recorded sync objects and bindless handles, and their replayed value.
+    handles: HashMap<u64, u64>,
+}
+
+impl Replayer {
+    unsafe fn replay_call(&mut self, gl: &gl::Gl, index: usize, call: &TracedCall)
+                          -> Result<(), ReplayError>
+    {
+        let name = call.name.as_str();
+        let mut args = call.args.clone();
+        let raw = args.iter().map(|arg| arg.as_u64().unwrap_or(0)).collect::<Vec<_>>();
+
+        self.buffers.update(name, &raw);
+
+        let mismatch = |recorded: u64, obtained: u64| ReplayError::NameMismatch {
+            index, name: call.name.clone(), recorded, obtained,
+        };
+
+        // the pointers to the strings must stay alive until the call
+        let mut strings: Vec<CString> = Vec::new();
+        let mut string_pointers: Vec<*const c_char> = Vec::new();
+
+        match name {
+            "glUseProgram" | "glUseProgramObjectARB" => {
+                self.current_program = raw[0];
+            },
+
+            "glShaderSource" | "glShaderSourceARB" | "glCreateShaderProgramv" |
+            "glTransformFeedbackVaryings" | "glTransformFeedbackVaryingsEXT" => {
+                if let Value::Data(ref data) = args[2] {
+                    strings = data.split(|&c| c == 0).take(raw[1] as usize)
+                                  .map(|s| CString::new(s).unwrap()).collect();
+                    string_pointers = strings.iter().map(|s| s.as_ptr()).collect();
+                    args[2] = Value::Pointer(string_pointers.as_ptr() as u64);
+                }
+            },
+
+            "glClientWaitSync" | "glClientWaitSyncAPPLE" | "glWaitSync" | "glWaitSyncAPPLE" |
+            "glDeleteSync" | "glDeleteSyncAPPLE" | "glMakeTextureHandleResidentARB" |
+            "glMakeTextureHandleNonResidentARB" | "glMakeImageHandleResidentARB" |
+            "glMakeImageHandleNonResidentARB" => {
+                self.remap_handle(&mut args[0]);
+            },
+
+            "glUniformHandleui64ARB" => self.remap_handle(&mut args[1]),
+            "glProgramUniformHandleui64ARB" => self.remap_handle(&mut args[2]),
+
+            "glFlushMappedBufferRange" | "glFlushMappedBufferRangeEXT" |
+            "glFlushMappedNamedBufferRange" | "glFlushMappedNamedBufferRangeEXT" |
+            "glUnmapBuffer" | "glUnmapBufferARB" | "glUnmapBufferOES" | "glUnmapNamedBuffer" |
+            "glUnmapNamedBufferEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                let written = if args.len() == 4 || args.len() == 2 { args.pop() } else { None };
+                let offset = if name.starts_with("glFlush") { raw[1] as usize } else { 0 };
+
+                if let (Some(Value::Data(data)), Some(mapping)) = (written, self.mappings.get(&buffer)) {
+                    if offset + data.len() <= mapping.length {
+                        ptr::copy_nonoverlapping(data.as_ptr(), (mapping.ptr + offset) as *mut u8,
+                                                 data.len());
+                    }
+                }
+
+                if name.starts_with("glUnmap") {
+                    self.mappings.remove(&buffer);
+                }
+            },
+
+            _ => {
+                if name.starts_with("glProgramUniform") {
+                    self.remap_location(raw[0], &mut args[1]);
+                } else if name.starts_with("glUniform") && name != "glUniformBlockBinding" &&
+                          name != "glUniformSubroutinesuiv"
+                {
+                    self.remap_location(self.current_program, &mut args[0]);
+                }
+            },
+        }
+
+        let ret = stubs::invoke(gl, name, &mut args)
+            .ok_or_else(|| ReplayError::UnknownFunction { index, name: call.name.clone() })?;
+        let ret = ret.and_then(|ret| ret.as_u64()).unwrap_or(0);
+        let recorded_ret = call.ret.as_ref().and_then(Value::as_u64).unwrap_or(0);
+
+        drop(string_pointers);
+        drop(strings);
+
+        match name {
+            "glGetUniformLocation" | "glGetUniformLocationARB" => {
+                self.uniform_locations.insert((raw[0], recorded_ret), ret);
+            },
+
+            "glCreateShader" | "glCreateProgram" | "glCreateShaderObjectARB" |
+            "glCreateProgramObjectARB" | "glCreateShaderProgramv" | "glGetAttribLocation" |
+            "glGetAttribLocationARB" | "glGetUniformBlockIndex" | "glGetFragDataLocation" |
+            "glGetFragDataIndex" | "glGetProgramResourceIndex" |
+            "glGetProgramResourceLocation" | "glGetSubroutineIndex" |
+            "glGetSubroutineUniformLocation" if ret != recorded_ret => {
+                return Err(mismatch(recorded_ret, ret));
+            },
+
+            "glFenceSync" | "glFenceSyncAPPLE" | "glGetTextureHandleARB" |
+            "glGetTextureSamplerHandleARB" | "glGetImageHandleARB" => {
+                self.handles.insert(recorded_ret, ret);
+            },
+
+            "glMapBufferRange" | "glMapBufferRangeEXT" | "glMapNamedBufferRange" |
+            "glMapNamedBufferRangeEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                self.mappings.insert(buffer, Mapping {
+                    ptr: ret as usize, length: raw[2] as usize, capture_on_unmap: false,
+                });
+            },
+
+            "glMapBuffer" | "glMapBufferARB" | "glMapBufferOES" | "glMapNamedBuffer" |
+            "glMapNamedBufferEXT" => {
+                let buffer = self.buffers.designated(name, raw[0]);
+                let mut length = 0;
+                if name.contains("Named") {
+                    gl.GetNamedBufferParameteriv(buffer, gl::BUFFER_SIZE, &mut length);
+                } else {
+                    gl.GetBufferParameteriv(raw[0] as u32, gl::BUFFER_SIZE, &mut length);
+                }
+
+                self.mappings.insert(buffer, Mapping {
+                    ptr: ret as usize, length: length.max(0) as usize, capture_on_unmap: false,
+                });
+            },
+
+            _ if name.starts_with("glGen") || name.starts_with("glCreate") => {
+                if let (Some(Value::Data(obtained)), Some(Value::Data(recorded))) =
+                       (args.last(), call.args.last())
+                {
+                    for (obtained, recorded) in obtained.chunks(4).zip(recorded.chunks(4)) {
+                        if obtained != recorded {
+                            return Err(mismatch(read_name(recorded), read_name(obtained)));
+                        }
+                    }
+                }
+            },
+
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn remap_handle(&self, value: &mut Value) {
+        if let Some(&replayed) = value.as_u64().and_then(|recorded| self.handles.get(&recorded)) {
+            *value = match *value {
+                Value::Pointer(_) => Value::Pointer(replayed),
+                _ => Value::UInt(replayed),
+            };
+        }
+    }
+
+    fn remap_location(&self, program: u64, value: &mut Value) {
+        let recorded = match value.as_u64() {
+            Some(recorded) => recorded,
+            None => return,
+        };
+
+        if let Some(&replayed) = self.uniform_locations.get(&(program, recorded)) {
+            *value = Value::Int(replayed as i32 as i64);
+        }
+    }
+}
+
+fn read_name(bytes: &[u8]) -> u64 {
+    let mut name = [0; 4];
+    name[.. bytes.len()].copy_from_slice(bytes);
+    u32::from_ne_bytes(name) as u64
+}
+
+/// Writes commands in the binary format of traces.
+///
+/// After the header, the file is a list of records that start with a one-byte tag:
+///
+///  - `0`: defines the name of a function, with a `u16` identifier, then the length of the name
+///    as a `u16` and the name itself.
+///  - `1`: a call, with the `u16` identifier of the function, the number of arguments as a `u8`,
+///    the arguments, a `u8` set to 1 if the function returned a value, and the return value.
+///  - `2`: a swap of the default framebuffer.
+///
+/// Values are a one-byte tag followed by either 8 bytes, or a `u32` length and the data for
+/// `Value::Data`. All numbers are little-endian.
+struct Encoder<W> {
+    output: W,
+    names: HashMap<String, u16>,
+}
+
+impl<W> Encoder<W> where W: Write {
+    fn new(mut output: W) -> io::Result<Encoder<W>> {
+        output.write_all(MAGIC)?;
+        output.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        Ok(Encoder { output, names: HashMap::new() })
+    }
+
+    fn write_command(&mut self, command: &Command) -> io::Result<()> {
+        let call = match *command {
+            Command::Call(ref call) => call,
+            Command::SwapBuffers => return self.output.write_all(&[2]),
+        };
+
+        let id = match self.names.get(&call.name) {
+            Some(&id) => id,
+            None => {
+                let id = self.names.len() as u16;
+                self.output.write_all(&[0])?;
+                self.output.write_all(&id.to_le_bytes())?;
+                self.output.write_all(&(call.name.len() as u16).to_le_bytes())?;
+                self.output.write_all(call.name.as_bytes())?;
+                self.names.insert(call.name.clone(), id);
+                id
+            },
+        };
+
+        self.output.write_all(&[1])?;
+        self.output.write_all(&id.to_le_bytes())?;
+        self.output.write_all(&[call.args.len() as u8])?;
+        for arg in &call.args {
+            self.write_value(arg)?;
+        }
+
+        match call.ret {
+            Some(ref ret) => {
+                self.output.write_all(&[1])?;
+                self.write_value(ret)
+            },
+            None => self.output.write_all(&[0]),
+        }
+    }
+
+    fn write_value(&mut self, value: &Value) -> io::Result<()> {
+        match *value {
+            Value::Int(val) => {
+                self.output.write_all(&[0])?;
+                self.output.write_all(&val.to_le_bytes())
+            },
+            Value::UInt(val) => {
+                self.output.write_all(&[1])?;
+                self.output.write_all(&val.to_le_bytes())
+            },
+            Value::Float(val) => {
+                self.output.write_all(&[2])?;
+                self.output.write_all(&val.to_bits().to_le_bytes())
+            },
+            Value::Pointer(val) => {
+                self.output.write_all(&[3])?;
+                self.output.write_all(&val.to_le_bytes())
+            },
+            Value::Data(ref data) => {
+                self.output.write_all(&[4])?;
+                self.output.write_all(&(data.len() as u32).to_le_bytes())?;
+                self.output.write_all(data)
+            },
+        }
+    }
+}
+
+/// Reads commands written by an `Encoder`.
+struct Decoder<R> {
+    input: R,
+    names: Vec<String>,
+}
+
+impl<R> Decoder<R> where R: Read {
+    /// Reads the next command, or returns `None` at the end of the trace.
+    fn read_command(&mut self) -> Result<Option<Command>, TraceReadError> {
+        loop {
+            let mut tag = [0];
+            if self.input.read(&mut tag)? == 0 {
+                return Ok(None);
+            }
+
+            match tag[0] {
+                0 => {
+                    let id = self.read_u16()? as usize;
+                    let length = self.read_u16()? as usize;
+                    let name = String::from_utf8(self.read_bytes(length)?)
+                                      .map_err(|_| TraceReadError::Corrupted)?;
+                    if id != self.names.len() {
+                        return Err(TraceReadError::Corrupted);
+                    }
+                    self.names.push(name);
+                },
+
+                1 => {
+                    let id = self.read_u16()? as usize;
+                    let name = self.names.get(id).ok_or(TraceReadError::Corrupted)?.clone();
+
+                    let num_args = self.read_u8()?;
+                    let args = (0 .. num_args).map(|_| self.read_value())
+                                              .collect::<Result<Vec<_>, _>>()?;
+
+                    let ret = match self.read_u8()? {
+                        0 => None,
+                        1 => Some(self.read_value()?),
+                        _ => return Err(TraceReadError::Corrupted),
+                    };
+
+                    return Ok(Some(Command::Call(TracedCall { name, args, ret })));
+                },
+
+                2 => return Ok(Some(Command::SwapBuffers)),
+
+                _ => return Err(TraceReadError::Corrupted),
+            }
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Value, TraceReadError> {
+        Ok(match self.read_u8()? {
+            0 => Value::Int(self.read_u64()? as i64),
+            1 => Value::UInt(self.read_u64()?),
+            2 => Value::Float(f64::from_bits(self.read_u64()?)),
+            3 => Value::Pointer(self.read_u64()?),
+            4 => {
+                let length = self.read_u32()? as usize;
+                Value::Data(self.read_bytes(length)?)
+            },
+            _ => return Err(TraceReadError::Corrupted),
+        })
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>, TraceReadError> {
+        let mut data = Vec::new();
+        (&mut self.input).take(length as u64).read_to_end(&mut data)?;
+        if data.len() != length {
+            return Err(TraceReadError::Corrupted);
+        }
+        Ok(data)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, TraceReadError> {
+        let mut value = [0; 1];
+        self.input.read_exact(&mut value)?;
+        Ok(value[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, TraceReadError> {
+        let mut value = [0; 2];
+        self.input.read_exact(&mut value)?;
+        Ok(u16::from_le_bytes(value))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, TraceReadError> {
+        let mut value = [0; 4];
+        self.input.read_exact(&mut value)?;
+        Ok(u32::from_le_bytes(value))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, TraceReadError> {
+        let mut value = [0; 8];
+        self.input.read_exact(&mut value)?;
+        Ok(u64::from_le_bytes(value))
+    }
+}
@@ -0,0 +1,165 @@
+/*!
+Moving buffers and textures between the contexts of a share group.
+
+A `Context` can't leave the thread that created it, but several contexts can share their objects.
+This makes it possible to create a second context on a worker thread with `Context::new_shared`
+(or `EglSurfaceless::shared_builder`), to create and fill buffers and textures with it, and to
+hand them over to the main context without stalling its frame loop.
+
+`Buffer::into_transfer` and `TextureAny::into_transfer` (and the same functions of the typed
+textures) turn an object into a `Transfer`, which can be sent to another thread. A fence is
+inserted after the commands that filled the object, and the main context adopts the object with
+`Transfer::adopt` once `Transfer::is_ready` returns true.
+
+```no_run
+# use std::rc::Rc;
+# use glium::backend::{Context, ShareGroup};
+# fn build_worker_context(share_group: ShareGroup) -> Rc<Context> { unimplemented!() }
+# fn example(display: glium::Display, image: Vec<Vec<(u8, u8, u8, u8)>>) {
+use std::sync::mpsc;
+
+let (sender, receiver) = mpsc::channel();
+let share_group = display.share_group();
+
+std::thread::spawn(move || {
+    // for example with `Context::new_shared`
+    let worker = build_worker_context(share_group);
+    let texture = glium::Texture2d::new(&worker, image).unwrap();
+    sender.send(texture.into_transfer()).unwrap();
+});
+
+let mut pending = Vec::new();
+loop {
+    pending.extend(receiver.try_iter());
+
+    // adopting the textures whose upload has finished, without blocking
+    let (ready, waiting) = pending.into_iter().partition::<Vec<_>, _>(|t| t.is_ready(&display));
+    pending = waiting;
+    for transfer in ready {
+        let texture: glium::Texture2d = transfer.adopt(&display);
+        // ...
+    }
+}
+# }
+```
+
+## Lifetime of the objects
+
+Between `into_transfer` and `adopt`, the object doesn't belong to any context. A `Transfer` that
+is dropped without being adopted leaks its object. The context that created the object can be
+destroyed before the object is adopted, as long as another context of the share group is alive.
+
+*/
+use crate::backend::Facade;
+use crate::context::{Context, ShareGroup};
+use crate::sync::SharedSyncFence;
+use crate::ContextExt;
+
+use std::fmt;
+use std::rc::Rc;
+
+/// An object that has been removed from its context and that can be sent to another thread,
+/// in order to be adopted by another context of the same share group. See the module-level
+/// documentation.
+pub struct Transfer<T> {
+    /// Builds the object in the adopting context.
+    adopt: Box<dyn FnOnce(&Rc<Context>) -> T + Send>,
+
+    /// Signaled when the commands that created the object have been executed. `None` if
+    /// fences aren't supported, in which case the commands have been waited for already.
+    fence: Option<SharedSyncFence>,
+
+    /// The share group of the context that created the object.
+    share_group: ShareGroup,
+}
+
+impl<T> Transfer<T> {
+    /// Inserts a fence in the context that created the object.
+    ///
+    /// `adopt` is called with the context that adopts the object, which shares its objects
+    /// with `context`.
+    pub(crate) fn new<A>(context: &Rc<Context>, adopt: A) -> Transfer<T>
+        where A: FnOnce(&Rc<Context>) -> T + Send + 'static
+    {
+        let mut ctxt = context.make_current();
+
+        let fence = match unsafe { SharedSyncFence::new(&mut ctxt) } {
+            Ok(fence) => Some(fence),
+            Err(_) => {
+                unsafe { ctxt.gl.Finish() };
+                None
+            },
+        };
+
+        Transfer {
+            adopt: Box::new(adopt),
+            fence,
+            share_group: context.share_group(),
+        }
+    }
+
+    /// Changes the type of the object that is adopted.
+    pub(crate) fn map<U>(self, map: fn(T) -> U) -> Transfer<U> where T: 'static, U: 'static {
+        let adopt = self.adopt;
+        Transfer {
+            adopt: Box::new(move |context| map(adopt(context))),
+            fence: self.fence,
+            share_group: self.share_group,
+        }
+    }
+
+    /// Returns the share group of the context that created the object.
+    #[inline]
+    pub fn share_group(&self) -> ShareGroup {
+        self.share_group
+    }
+
+    /// Returns true if the commands that created the object have been executed. Doesn't block.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the context of the facade isn't part of the share group of the object.
+    pub fn is_ready<F>(&self, facade: &F) -> bool where F: Facade + ?Sized {
+        let context = self.check_share_group(facade);
+
+        match self.fence {
+            Some(ref fence) => unsafe { fence.is_signaled(&mut context.make_current()) },
+            None => true,
+        }
+    }
+
+    /// Adopts the object in the context of the facade.
+    ///
+    /// If the object isn't ready yet, the following commands of the context wait for it on the
+    /// GPU. This function never blocks.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the context of the facade isn't part of the share group of the object.
+    pub fn adopt<F>(self, facade: &F) -> T where F: Facade + ?Sized {
+        let context = self.check_share_group(facade);
+
+        if let Some(fence) = self.fence {
+            unsafe { fence.wait_server_and_drop(&mut context.make_current()) };
+        }
+
+        (self.adopt)(context)
+    }
+
+    fn check_share_group<'a, F>(&self, facade: &'a F) -> &'a Rc<Context>
+        where F: Facade + ?Sized
+    {
+        let context = facade.get_context();
+        assert!(context.share_group() == self.share_group,
+                "The context doesn't share its objects with the context that created the object");
+        context
+    }
+}
+
+impl<T> fmt::Debug for Transfer<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Transfer")
+           .field("share_group", &self.share_group)
+           .finish()
+    }
+}
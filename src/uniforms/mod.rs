@@ -19,6 +19,11 @@ let uniforms = uniform! {
 
 Each field must implement the `UniformValue` trait for this to work.
 
+With the `derive` feature, a struct can implement the `Uniforms` trait with
+`#[derive(glium::uniforms::Uniforms)]`, and the `UniformBlock` trait with
+`#[derive(glium::uniforms::UniformBlock)]`. Each field of the struct is then a uniform or a
+member of the block, and can be renamed with `#[glium(name = "...")]`.
+
 ## Samplers
 
 In order to customize the way a texture is being sampled, you must use a `Sampler`.
@@ -130,6 +135,11 @@ pub use self::image_unit::{ImageUnit, ImageUnitBehavior};
 pub use self::uniforms::{EmptyUniforms, UniformsStorage};
 pub use self::value::{UniformValue, UniformType};
 
+/// Derive macros that implement `Uniforms` and `UniformBlock`. See the documentation of the
+/// `glium_derive` crate.
+#[cfg(feature = "derive")]
+pub use glium_derive::{Uniforms, UniformBlock};
+
 use std::error::Error;
 use std::fmt;
 
@@ -299,6 +309,83 @@ pub trait UniformBlock {        // TODO: `: Copy`, but unsized structs don't imp
 
     /// Builds the `BlockLayout` corresponding to the current object.
     fn build_layout(base_offset: usize) -> BlockLayout;
+
+    /// Alignment of this type in a block that uses the `std140` layout, or `None` if it is
+    /// unknown.
+    ///
+    /// `#[derive(UniformBlock)]` uses it to check the offsets of the fields at compile time.
+    const STD140_ALIGNMENT: Option<usize> = None;
+
+    /// Size of this type in a block that uses the `std140` layout, or `None` if it is unknown.
+    ///
+    /// `#[derive(UniformBlock)]` compares it with the size of the fields at compile time, which
+    /// catches arrays and matrices whose elements or columns aren't padded.
+    const STD140_SIZE: Option<usize> = None;
+}
+
+/// Returns the `std140` alignment of an array whose elements have the given alignment, or of a
+/// struct whose members have the given alignments. It is the largest alignment, rounded up to
+/// the alignment of a `vec4`.
+#[doc(hidden)]
+pub const fn __std140_aggregate_alignment(members: &[Option<usize>]) -> Option<usize> {
+    let mut alignment = 16;
+    let mut i = 0;
+
+    while i < members.len() {
+        match members[i] {
+            Some(member) if member > alignment => alignment = member,
+            Some(_) => (),
+            None => return None,
+        }
+
+        i += 1;
+    }
+
+    Some(alignment)
+}
+
+/// Returns the `std140` size of an array of `length` elements with the given alignment and
+/// size. With a length of 1, this is the stride of the array.
+#[doc(hidden)]
+pub const fn __std140_array_size(alignment: Option<usize>, size: Option<usize>, length: usize)
+                                 -> Option<usize>
+{
+    match (__std140_aggregate_alignment(&[alignment]), size) {
+        (Some(alignment), Some(size)) => Some(std140_round_up(size, alignment) * length),
+        _ => None,
+    }
+}
+
+/// Returns the `std140` size of a struct whose members have the given alignments and sizes, in
+/// the order of their declaration.
+#[doc(hidden)]
+pub const fn __std140_struct_size(members: &[(Option<usize>, Option<usize>)]) -> Option<usize> {
+    let mut alignment = 16;
+    let mut size = 0;
+    let mut i = 0;
+
+    while i < members.len() {
+        match members[i] {
+            (Some(member_alignment), Some(member_size)) => {
+                size = std140_round_up(size, member_alignment) + member_size;
+                if member_alignment > alignment {
+                    alignment = member_alignment;
+                }
+            },
+            _ => return None,
+        }
+
+        i += 1;
+    }
+
+    Some(std140_round_up(size, alignment))
+}
+
+const fn std140_round_up(value: usize, alignment: usize) -> usize {
+    match value % alignment {
+        0 => value,
+        rest => value + alignment - rest,
+    }
 }
 
 impl<T> UniformBlock for [T] where T: UniformBlock {
@@ -343,6 +430,8 @@ impl<T> UniformBlock for [T] where T: UniformBlock {
             content: Box::new(<T as UniformBlock>::build_layout(base_offset)),
         }
     }
+
+    const STD140_ALIGNMENT: Option<usize> = __std140_aggregate_alignment(&[T::STD140_ALIGNMENT]);
 }
 
 macro_rules! impl_uniform_block_array {
@@ -389,6 +478,12 @@ macro_rules! impl_uniform_block_array {
                     length: $len,
                 }
             }
+
+            const STD140_ALIGNMENT: Option<usize> =
+                __std140_aggregate_alignment(&[T::STD140_ALIGNMENT]);
+
+            const STD140_SIZE: Option<usize> =
+                __std140_array_size(T::STD140_ALIGNMENT, T::STD140_SIZE, $len);
         }
     );
 }
@@ -442,10 +442,76 @@ macro_rules! impl_uniform_block_basic {
                     offset_in_buffer: base_offset,
                 }
             }
+
+            const STD140_ALIGNMENT: Option<usize> = std140_alignment($uniform_ty);
+
+            const STD140_SIZE: Option<usize> = std140_size($uniform_ty);
         }
     );
 }
 
+/// Returns the alignment of a non-opaque type in a block that uses the `std140` layout.
+const fn std140_alignment(ty: UniformType) -> Option<usize> {
+    let alignment = match ty {
+        UniformType::Float | UniformType::Int | UniformType::UnsignedInt |
+        UniformType::Bool => 4,
+        UniformType::FloatVec2 | UniformType::IntVec2 | UniformType::UnsignedIntVec2 |
+        UniformType::BoolVec2 | UniformType::Double | UniformType::Int64 |
+        UniformType::UnsignedInt64 => 8,
+        UniformType::FloatVec3 | UniformType::FloatVec4 | UniformType::IntVec3 |
+        UniformType::IntVec4 | UniformType::UnsignedIntVec3 | UniformType::UnsignedIntVec4 |
+        UniformType::BoolVec3 | UniformType::BoolVec4 | UniformType::DoubleVec2 |
+        UniformType::Int64Vec2 | UniformType::UnsignedInt64Vec2 => 16,
+        UniformType::DoubleVec3 | UniformType::DoubleVec4 | UniformType::Int64Vec3 |
+        UniformType::Int64Vec4 | UniformType::UnsignedInt64Vec3 |
+        UniformType::UnsignedInt64Vec4 => 32,
+
+        // matrices are arrays of column vectors
+        UniformType::FloatMat2 | UniformType::FloatMat3 | UniformType::FloatMat4 |
+        UniformType::FloatMat2x3 | UniformType::FloatMat2x4 | UniformType::FloatMat3x2 |
+        UniformType::FloatMat3x4 | UniformType::FloatMat4x2 | UniformType::FloatMat4x3 |
+        UniformType::DoubleMat2 | UniformType::DoubleMat3x2 | UniformType::DoubleMat4x2 => 16,
+        UniformType::DoubleMat3 | UniformType::DoubleMat4 | UniformType::DoubleMat2x3 |
+        UniformType::DoubleMat2x4 | UniformType::DoubleMat3x4 | UniformType::DoubleMat4x3 => 32,
+
+        _ => return None,
+    };
+
+    Some(alignment)
+}
+
+/// Returns the size of a non-opaque type in a block that uses the `std140` layout.
+const fn std140_size(ty: UniformType) -> Option<usize> {
+    let size = match ty {
+        UniformType::Float | UniformType::Int | UniformType::UnsignedInt |
+        UniformType::Bool => 4,
+        UniformType::FloatVec2 | UniformType::IntVec2 | UniformType::UnsignedIntVec2 |
+        UniformType::BoolVec2 | UniformType::Double | UniformType::Int64 |
+        UniformType::UnsignedInt64 => 8,
+        UniformType::FloatVec3 | UniformType::IntVec3 | UniformType::UnsignedIntVec3 |
+        UniformType::BoolVec3 => 12,
+        UniformType::FloatVec4 | UniformType::IntVec4 | UniformType::UnsignedIntVec4 |
+        UniformType::BoolVec4 | UniformType::DoubleVec2 | UniformType::Int64Vec2 |
+        UniformType::UnsignedInt64Vec2 => 16,
+        UniformType::DoubleVec3 | UniformType::Int64Vec3 | UniformType::UnsignedInt64Vec3 => 24,
+        UniformType::DoubleVec4 | UniformType::Int64Vec4 | UniformType::UnsignedInt64Vec4 => 32,
+
+        // matrices are arrays of column vectors, whose stride is rounded up to the one of a `vec4`
+        UniformType::FloatMat2 | UniformType::FloatMat2x3 | UniformType::FloatMat2x4 |
+        UniformType::DoubleMat2 => 32,
+        UniformType::FloatMat3 | UniformType::FloatMat3x2 | UniformType::FloatMat3x4 |
+        UniformType::DoubleMat3x2 => 48,
+        UniformType::FloatMat4 | UniformType::FloatMat4x2 | UniformType::FloatMat4x3 |
+        UniformType::DoubleMat4x2 | UniformType::DoubleMat2x3 | UniformType::DoubleMat2x4 => 64,
+        UniformType::DoubleMat3 | UniformType::DoubleMat3x4 => 96,
+        UniformType::DoubleMat4 | UniformType::DoubleMat4x3 => 128,
+
+        _ => return None,
+    };
+
+    Some(size)
+}
+
 impl AsUniformValue for i8 {
     #[inline]
     fn as_uniform_value(&self) -> UniformValue<'_> {
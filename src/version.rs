@@ -8,10 +8,12 @@ use crate::gl;
 /// For example, both `Version(Gl, 3, 0) >= Version(GlEs, 3, 0)` and `Version(GlEs, 3, 0) >=
 /// Version(Gl, 3, 0)` return `false`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version(pub Api, pub u8, pub u8);
 
 /// Describes an OpenGL-related API.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Api {
     /// Regular OpenGL.
     Gl,
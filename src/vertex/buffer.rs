@@ -68,12 +68,14 @@ impl<T: Copy> GlObject for VertexBuffer<T> {
 pub struct VertexBuffer<T> where T: Copy {
     buffer: Buffer<[T]>,
     bindings: VertexFormat,
+    divisor: u32,
 }
 
 /// Represents a slice of a `VertexBuffer`.
 pub struct VertexBufferSlice<'b, T> where T: Copy {
     buffer: BufferSlice<'b, [T]>,
     bindings: &'b VertexFormat,
+    divisor: u32,
 }
 
 impl<'b, T: 'b> VertexBufferSlice<'b, T> where T: Copy + Content {
@@ -84,6 +86,9 @@ impl<'b, T: 'b> VertexBufferSlice<'b, T> where T: Copy + Content {
     /// This will draw one instance of the geometry for each element in this buffer slice.
     /// The attributes are still passed to the vertex shader, but each entry is passed
     /// for each different instance.
+    ///
+    /// If `Vertex::instance_divisor` of the vertex type is greater than 1, each element is used
+    /// for this number of consecutive instances.
     #[inline]
     pub fn per_instance(&'b self) -> Result<PerInstance<'_>, InstancingNotSupported> {
         // TODO: don't check this here
@@ -94,7 +99,7 @@ impl<'b, T: 'b> VertexBufferSlice<'b, T> where T: Copy + Content {
             return Err(InstancingNotSupported);
         }
 
-        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings))
+        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings, self.divisor))
     }
 }
 
@@ -226,6 +231,28 @@ impl<T> VertexBuffer<T> where T: Vertex {
         let buffer = Buffer::empty_array(facade, BufferType::ArrayBuffer, elements, mode)?;
         Ok(buffer.into())
     }
+
+    /// Builds a vertex buffer from an existing, externally created OpenGL buffer. The number of
+    /// elements is deduced from the size of the buffer.
+    ///
+    /// If `owned` is true, this vertex buffer will take ownership of the OpenGL buffer and be
+    /// responsible for cleaning it up. Otherwise, the buffer must be cleaned up externally, but
+    /// only after this vertex buffer's lifetime has ended.
+    ///
+    /// # Safety
+    ///
+    /// See `Buffer::from_id`.
+    pub unsafe fn from_id<F>(facade: &F, id: gl::types::GLuint, owned: bool)
+                             -> Result<VertexBuffer<T>, CreationError>
+                             where F: Facade + ?Sized
+    {
+        if !T::is_supported(facade) {
+            return Err(CreationError::FormatNotSupported);
+        }
+
+        let buffer = Buffer::from_id(facade, id, BufferType::ArrayBuffer, owned)?;
+        Ok(buffer.into())
+    }
 }
 
 impl<T> VertexBuffer<T> where T: Copy {
@@ -271,6 +298,7 @@ impl<T> VertexBuffer<T> where T: Copy {
             buffer: Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Default)?,
             bindings,
+            divisor: 1,
         })
     }
 
@@ -287,6 +315,7 @@ impl<T> VertexBuffer<T> where T: Copy {
             buffer: Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Dynamic)?,
             bindings,
+            divisor: 1,
         })
     }
 
@@ -303,6 +332,7 @@ impl<T> VertexBuffer<T> where T: Copy {
         Some(VertexBufferSlice {
             buffer: slice,
             bindings: &self.bindings,
+            divisor: self.divisor,
         })
     }
 
@@ -318,6 +348,9 @@ impl<T> VertexBuffer<T> where T: Copy {
     /// `surface.draw(vertex_buffer.per_instance(), ...)`. This will draw one instance of the
     /// geometry for each element in this buffer. The attributes are still passed to the
     /// vertex shader, but each entry is passed for each different instance.
+    ///
+    /// If `Vertex::instance_divisor` of the vertex type is greater than 1, each element is used
+    /// for this number of consecutive instances.
     #[inline]
     pub fn per_instance(&self) -> Result<PerInstance<'_>, InstancingNotSupported> {
         // TODO: don't check this here
@@ -328,7 +361,7 @@ impl<T> VertexBuffer<T> where T: Copy {
             return Err(InstancingNotSupported);
         }
 
-        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings))
+        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings, self.divisor))
     }
 }
 
@@ -344,6 +377,7 @@ impl<T> VertexBuffer<T> where T: Copy + Send + 'static {
         VertexBufferAny {
             buffer: self.buffer.into(),
             bindings: self.bindings,
+            divisor: self.divisor,
         }
     }
 }
@@ -358,6 +392,7 @@ impl<T> From<Buffer<[T]>> for VertexBuffer<T> where T: Vertex + Copy {
         VertexBuffer {
             buffer,
             bindings,
+            divisor: <T as Vertex>::instance_divisor(),
         }
     }
 }
@@ -442,6 +477,7 @@ impl<'a, T> From<VertexBufferSlice<'a, T>> for VerticesSource<'a> where T: Copy
 pub struct VertexBufferAny {
     buffer: BufferAny,
     bindings: VertexFormat,
+    divisor: u32,
 }
 
 impl VertexBufferAny {
@@ -475,6 +511,9 @@ impl VertexBufferAny {
     /// `surface.draw(vertex_buffer.per_instance(), ...)`. This will draw one instance of the
     /// geometry for each element in this buffer. The attributes are still passed to the
     /// vertex shader, but each entry is passed for each different instance.
+    ///
+    /// If `Vertex::instance_divisor` of the vertex type is greater than 1, each element is used
+    /// for this number of consecutive instances.
     #[inline]
     pub fn per_instance(&self) -> Result<PerInstance<'_>, InstancingNotSupported> {
         // TODO: don't check this here
@@ -485,7 +524,7 @@ impl VertexBufferAny {
             return Err(InstancingNotSupported);
         }
 
-        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings))
+        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings, self.divisor))
     }
 }
 
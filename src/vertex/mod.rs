@@ -26,6 +26,10 @@ implement_vertex!(MyVertex, position, texcoords);
 # }
 ```
 
+With the `derive` feature, you can write `#[derive(Copy, Clone, glium::Vertex)]` instead. The
+derive macro supports generic structs, and `#[glium(...)]` attributes that rename an attribute,
+normalize it, give its location or set the instancing divisor of the struct.
+
 ## Vertex buffer
 
 Once you have a struct that implements the `Vertex` trait, you can build an array of vertices and
@@ -113,7 +117,9 @@ Note that if you use `index::EmptyIndices` as indices the length of all vertex s
 be the same, or a `DrawError::VerticesSourcesLengthMismatch` will be produced.
 
 In all situation, the length of all per-instance sources must match, or
-`DrawError::InstancesCountMismatch` will be returned.
+`DrawError::InstancesCountMismatch` will be returned. The length of a per-instance source whose
+vertex type has an `instance_divisor` greater than 1 is its number of elements multiplied by the
+divisor.
 
 # Transform feedback
 
@@ -136,6 +142,10 @@ pub use self::buffer::CreationError as BufferCreationError;
 pub use self::format::{AttributeType, VertexFormat};
 pub use self::transform_feedback::{is_transform_feedback_supported, TransformFeedbackSession};
 
+/// Derive macro that implements `Vertex`. See the documentation of the `glium_derive` crate.
+#[cfg(feature = "derive")]
+pub use glium_derive::Vertex;
+
 use crate::buffer::BufferAnySlice;
 use crate::CapabilitiesSource;
 
@@ -145,6 +155,7 @@ mod transform_feedback;
 
 /// Describes the source to use for the vertices when drawing.
 #[derive(Clone)]
+#[non_exhaustive]
 pub enum VerticesSource<'a> {
     /// A buffer uploaded in the video memory.
     ///
@@ -154,6 +165,14 @@ pub enum VerticesSource<'a> {
     /// "per vertex" (false).
     VertexBuffer(BufferAnySlice<'a>, &'a VertexFormat, bool),
 
+    /// A buffer uploaded in the video memory that is "per instance", and whose elements are each
+    /// used for several consecutive instances.
+    ///
+    /// The third parameter is the number of consecutive instances that use the same element.
+    /// `PerInstance` produces this source instead of `VertexBuffer` when the
+    /// `Vertex::instance_divisor` of the vertex type is greater than 1.
+    VertexBufferWithDivisor(BufferAnySlice<'a>, &'a VertexFormat, u32),
+
     /// A marker indicating a "phantom list of attributes".
     Marker {
         /// Number of attributes.
@@ -191,12 +210,16 @@ impl<'a> From<EmptyInstanceAttributes> for VerticesSource<'a> {
 }
 
 /// Marker that instructs glium that the buffer is to be used per instance.
-pub struct PerInstance<'a>(BufferAnySlice<'a>, &'a VertexFormat);
+pub struct PerInstance<'a>(BufferAnySlice<'a>, &'a VertexFormat, u32);
 
 impl<'a> From<PerInstance<'a>> for VerticesSource<'a> {
     #[inline]
     fn from(this: PerInstance<'a>) -> VerticesSource<'a> {
-        VerticesSource::VertexBuffer(this.0, this.1, true)
+        if this.2 == 1 {
+            VerticesSource::VertexBuffer(this.0, this.1, true)
+        } else {
+            VerticesSource::VertexBufferWithDivisor(this.0, this.1, this.2)
+        }
     }
 }
 
@@ -280,7 +303,7 @@ impl_for_tuple!(A, B, C, D, E, F, G);
 /// Trait for structures that represent a vertex.
 ///
 /// Instead of implementing this trait yourself, it is recommended to use the `implement_vertex!`
-/// macro instead.
+/// macro or, with the `derive` feature, `#[derive(Vertex)]` instead.
 // TODO: this should be `unsafe`, but that would break the syntax extension
 pub trait Vertex: Copy + Sized {
     /// Builds the `VertexFormat` representing the layout of this element.
@@ -298,6 +321,13 @@ pub trait Vertex: Copy + Sized {
 
         true
     }
+
+    /// Returns the number of consecutive instances that use the same element when a buffer of
+    /// this type is drawn with `per_instance`. The default value is 1.
+    #[inline]
+    fn instance_divisor() -> u32 {
+        1
+    }
 }
 
 /// Trait for types that can be used as vertex attributes.
@@ -15,6 +15,7 @@ use crate::BufferExt;
 
 use crate::gl;
 use crate::context::CommandContext;
+use crate::context::ResourceKind;
 use crate::version::Api;
 use crate::version::Version;
 
@@ -331,6 +332,7 @@ impl VertexArrayObject {
             };
             id
         };
+        ctxt.resources.created(ResourceKind::VertexArray, Handle::Id(id), 0);
 
         // we don't use DSA as we're going to make multiple calls for this VAO
         // and we're likely going to use the VAO right after it's been created
@@ -379,6 +381,7 @@ impl VertexArrayObject {
     /// measure).
     fn destroy(mut self, ctxt: &mut CommandContext<'_>) {
         self.destroyed = true;
+        ctxt.resources.destroyed(ResourceKind::VertexArray, Handle::Id(self.id));
 
         // unbinding
         if ctxt.state.vertex_array == self.id {
@@ -0,0 +1,61 @@
+#[macro_use]
+extern crate glium;
+
+use glium::backend::recording::DriverProfile;
+use glium::{Api, Version};
+
+mod support;
+
+#[test]
+fn report_contents() {
+    let display = support::build_display();
+    let report = display.capabilities_report();
+
+    assert_eq!(report.version, *display.get_opengl_version());
+    assert_eq!(report.renderer, display.get_opengl_renderer_string());
+    assert!(report.limits["GL_MAX_TEXTURE_SIZE"][0] >= 1024);
+    assert_eq!(report.limits["GL_MAX_VIEWPORT_DIMS"].len(), 2);
+    assert!(report.extensions.contains_key("GL_ARB_buffer_storage"));
+    assert!(!report.supported_glsl_versions.is_empty());
+}
+
+#[test]
+fn report_extensions_follow_profile() {
+    let mut profile = DriverProfile::new(Version(Api::Gl, 3, 3));
+    profile.extensions.push("GL_ARB_buffer_storage".to_owned());
+    let (context, _) = support::build_recording_context(profile);
+
+    let report = context.capabilities_report();
+    assert!(report.extensions["GL_ARB_buffer_storage"]);
+    assert!(!report.extensions["GL_ARB_direct_state_access"]);
+    assert_eq!(report.supported_extensions().collect::<Vec<_>>(), vec!["GL_ARB_buffer_storage"]);
+    assert_eq!(report.limits["GL_MAX_TEXTURE_SIZE"], vec![1024]);
+}
+
+#[test]
+fn report_reproduced_by_recording_backend() {
+    let display = support::build_display();
+    let report = display.capabilities_report();
+
+    let (context, _) = support::build_recording_context(DriverProfile::from_report(&report));
+    let reproduced = context.capabilities_report();
+
+    assert_eq!(reproduced.version, report.version);
+    assert_eq!(reproduced.profile, report.profile);
+    assert_eq!(reproduced.vendor, report.vendor);
+    assert_eq!(reproduced.renderer, report.renderer);
+    assert_eq!(reproduced.extensions, report.extensions);
+    assert_eq!(reproduced.limits, report.limits);
+    assert_eq!(reproduced.supported_glsl_versions, report.supported_glsl_versions);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn report_serialization() {
+    let display = support::build_display();
+    let report = display.capabilities_report();
+
+    let json = serde_json::to_string(&report).unwrap();
+    let deserialized: glium::backend::CapabilitiesReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, report);
+}
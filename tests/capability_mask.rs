@@ -0,0 +1,176 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::{Api, CapabilitiesSource, Profile, Version};
+use glium::backend::{CapabilityMask, Context};
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::debug::DebugCallbackBehavior;
+
+mod support;
+
+fn driver(version: Version, profile: Option<Profile>) -> Rc<RecordingBackend> {
+    let mut driver = DriverProfile::new(version);
+    driver.profile = profile;
+    driver.extensions = vec![
+        "GL_ARB_ES2_compatibility".to_owned(),
+        "GL_ARB_buffer_storage".to_owned(),
+        "GL_ARB_direct_state_access".to_owned(),
+        "GL_ARB_framebuffer_object".to_owned(),
+        "GL_ARB_vertex_array_object".to_owned(),
+        "GL_EXT_framebuffer_blit".to_owned(),
+    ];
+    Rc::new(RecordingBackend::new(driver, (800, 600)))
+}
+
+fn build(backend: &Rc<RecordingBackend>, mask: CapabilityMask)
+         -> Result<Rc<Context>, glium::IncompatibleOpenGl>
+{
+    unsafe {
+        Context::with_capability_mask(backend.clone(), true, DebugCallbackBehavior::Ignore, mask)
+    }
+}
+
+#[test]
+fn gl21_vertex_array_object() {
+    let backend = driver(Version(Api::Gl, 4, 5), Some(Profile::Compatibility));
+    let context = build(&backend, CapabilityMask::gl21_vertex_array_object()).unwrap();
+
+    assert_eq!(*context.get_opengl_version(), Version(Api::Gl, 2, 1));
+    assert_eq!(context.get_opengl_profile(), None);
+    assert!(context.get_extensions().gl_arb_vertex_array_object);
+    assert!(context.get_extensions().gl_arb_framebuffer_object);
+    assert!(!context.get_extensions().gl_arb_buffer_storage);
+    assert!(!context.get_extensions().gl_arb_direct_state_access);
+    assert_eq!(context.get_supported_glsl_version(), Version(Api::Gl, 1, 2));
+
+    // the buffer is created with the pre-DSA functions
+    backend.clear_calls();
+    glium::buffer::Buffer::new(&context, &[1u8, 2, 3][..], glium::buffer::BufferType::ArrayBuffer,
+                               glium::buffer::BufferMode::Default).unwrap();
+    assert!(support::count_calls(&backend, "glBufferData") >= 1);
+    assert!(!backend.calls().iter().any(|c| c.name.starts_with("glNamedBuffer") ||
+                                            c.name == "glBufferStorage"));
+}
+
+#[test]
+fn gl33_core() {
+    let backend = driver(Version(Api::Gl, 4, 5), Some(Profile::Compatibility));
+    let context = build(&backend, CapabilityMask::gl33_core()).unwrap();
+
+    assert_eq!(*context.get_opengl_version(), Version(Api::Gl, 3, 3));
+    assert_eq!(context.get_opengl_profile(), Some(Profile::Core));
+    assert!(!context.get_extensions().gl_arb_vertex_array_object);
+    assert_eq!(context.get_capabilities().supported_glsl_versions.last(),
+               Some(&Version(Api::Gl, 3, 3)));
+}
+
+#[test]
+fn gles20_on_desktop() {
+    let backend = driver(Version(Api::Gl, 4, 5), Some(Profile::Core));
+    let context = build(&backend, CapabilityMask::gles20()).unwrap();
+
+    assert_eq!(*context.get_opengl_version(), Version(Api::GlEs, 2, 0));
+    assert_eq!(context.get_opengl_profile(), None);
+    assert_eq!(context.get_supported_glsl_version(), Version(Api::GlEs, 1, 0));
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    let backend = driver(Version(Api::Gl, 3, 3), Some(Profile::Core));
+    assert!(build(&backend, CapabilityMask::new(Version(Api::Gl, 4, 0))).is_err());
+}
+
+#[test]
+fn compatibility_on_core_is_rejected() {
+    let backend = driver(Version(Api::Gl, 4, 5), Some(Profile::Core));
+    let mask = CapabilityMask {
+        profile: Some(Profile::Compatibility),
+        .. CapabilityMask::new(Version(Api::Gl, 3, 3))
+    };
+    assert!(build(&backend, mask).is_err());
+}
+
+#[test]
+fn gles_without_compatibility_extension_is_rejected() {
+    let backend = Rc::new(RecordingBackend::new(DriverProfile::default(), (800, 600)));
+    assert!(build(&backend, CapabilityMask::gles20()).is_err());
+}
+
+#[cfg(feature = "test_headless")]
+fn draw_red_triangle(mask: CapabilityMask) -> (u8, u8, u8, u8) {
+    use glium::Surface;
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+    use glium::index::{NoIndices, PrimitiveType};
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let display = EglSurfaceless::with_capability_mask((64, 64), mask,
+                                                       DebugCallbackBehavior::DebugMessageOnError)
+                                 .unwrap();
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0, -1.0] },
+        Vertex { position: [-1.0, 3.0] },
+        Vertex { position: [3.0, -1.0] },
+    ]).unwrap();
+
+    let program = program!(&display,
+        120 => {
+            vertex: "
+                #version 120
+                attribute vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 120
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+                attribute lowp vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 64, 64).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &NoIndices(PrimitiveType::TrianglesList), &program,
+                              &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    data[32][32]
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn draw_with_gl21_on_real_driver() {
+    assert_eq!(draw_red_triangle(CapabilityMask::gl21_vertex_array_object()), (255, 0, 0, 255));
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn draw_with_gles20_on_real_driver() {
+    assert_eq!(draw_red_triangle(CapabilityMask::gles20()), (255, 0, 0, 255));
+}
@@ -0,0 +1,239 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::{Api, Version};
+use glium::backend::{Backend, Context};
+use glium::backend::recording::{Arg, DriverProfile, RecordingBackend};
+use glium::debug::{DebugMessageLog, MessageType, Severity, Source};
+
+mod support;
+
+/// Simulates a message of the debug output by calling `glDebugMessageInsert`.
+fn insert_message(context: &Context, backend: &RecordingBackend, ty: u32, severity: u32,
+                  message: &str)
+{
+    type InsertFn = extern "system" fn(u32, u32, u32, u32, i32, *const u8);
+
+    unsafe {
+        let insert = backend.get_proc_address("glDebugMessageInsert");
+        let insert: InsertFn = std::mem::transmute(insert);
+        context.exec_in_context(|| {
+            insert(0x8246 /* GL_DEBUG_SOURCE_API */, ty, 7, severity, message.len() as i32,
+                   message.as_ptr());
+        });
+    }
+}
+
+const GL_DEBUG_TYPE_ERROR: u32 = 0x824C;
+const GL_DEBUG_TYPE_PERFORMANCE: u32 = 0x8250;
+const GL_DEBUG_SEVERITY_HIGH: u32 = 0x9146;
+const GL_DEBUG_SEVERITY_MEDIUM: u32 = 0x9147;
+const GL_DEBUG_SEVERITY_NOTIFICATION: u32 = 0x826B;
+
+fn build_program(context: &Rc<Context>) -> glium::Program {
+    program!(context,
+        140 => {
+            vertex: "
+                #version 140
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap()
+}
+
+#[test]
+fn object_labels() {
+    let (context, backend) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 4, 5)));
+
+    let buffer = glium::buffer::Buffer::new(&context, &[1u8, 2, 3][..],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+    let texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+    let render_buffer = glium::framebuffer::RenderBuffer::new(
+        &context, glium::texture::UncompressedFloatFormat::U8U8U8U8, 4, 4).unwrap();
+    let program = build_program(&context);
+    let query = glium::draw_parameters::SamplesPassedQuery::new(&context).unwrap();
+
+    backend.clear_calls();
+    buffer.set_label("vertices");
+    texture.set_label("albedo");
+    render_buffer.set_label("depth");
+    program.set_label("shading");
+    query.set_label("occlusion");
+
+    let calls = backend.take_calls();
+    let identifiers = calls.iter().filter(|c| c.name == "glObjectLabel")
+                           .map(|c| c.args[0]).collect::<Vec<_>>();
+    assert_eq!(identifiers, vec![Arg::UInt(0x82E0), Arg::UInt(0x1702), Arg::UInt(0x8D41),
+                                 Arg::UInt(0x82E2), Arg::UInt(0x82E3)]);
+
+    let label = calls.iter().find(|c| c.name == "glObjectLabel").unwrap();
+    assert_eq!(label.args[2], Arg::Int(8));
+}
+
+#[test]
+fn long_labels_are_truncated() {
+    let (context, backend) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 4, 5)));
+    let texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+
+    backend.clear_calls();
+    texture.set_label(&"é".repeat(200));
+
+    let label = backend.calls().into_iter().find(|c| c.name == "glObjectLabel").unwrap();
+    assert_eq!(label.args[2], Arg::Int(254));
+}
+
+#[test]
+fn labels_without_khr_debug() {
+    let (context, backend) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+
+    backend.clear_calls();
+    texture.set_label("albedo");
+    {
+        let _group = context.debug_group("shadow pass");
+    }
+
+    assert!(backend.calls().is_empty());
+}
+
+#[test]
+fn debug_groups() {
+    let (context, backend) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 4, 5)));
+
+    backend.clear_calls();
+    {
+        let _outer = context.debug_group("shadow pass");
+        let _inner = context.debug_group("cascade 0");
+        context.finish();
+    }
+
+    let calls = backend.take_calls().into_iter().map(|c| c.name).collect::<Vec<_>>();
+    assert_eq!(calls, vec!["glPushDebugGroup", "glPushDebugGroup", "glFinish",
+                           "glPopDebugGroup", "glPopDebugGroup"]);
+}
+
+#[test]
+fn message_log() {
+    let log = DebugMessageLog::new();
+    let backend = Rc::new(RecordingBackend::new(DriverProfile::new(Version(Api::Gl, 4, 5)),
+                                                (800, 600)));
+    let context = unsafe { Context::new(backend.clone(), true, log.behavior()) }.unwrap();
+    log.clear();
+
+    insert_message(&context, &backend, GL_DEBUG_TYPE_PERFORMANCE, GL_DEBUG_SEVERITY_MEDIUM,
+                   "buffer is slow");
+    insert_message(&context, &backend, GL_DEBUG_TYPE_ERROR, GL_DEBUG_SEVERITY_HIGH,
+                   "invalid enum");
+
+    assert_eq!(log.len(), 2);
+    let message = &log.messages()[0];
+    assert_eq!(message.source, Source::Api);
+    assert_eq!(message.ty, MessageType::Performance);
+    assert_eq!(message.severity, Severity::Medium);
+    assert_eq!(message.id, 7);
+    assert_eq!(message.message, "buffer is slow");
+
+    let errors = log.filter(|m| m.is_error());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "invalid enum");
+
+    let performance = log.drain_filter(|m| m.ty == MessageType::Performance);
+    assert_eq!(performance.len(), 1);
+    assert_eq!(log.len(), 1);
+
+    assert_eq!(log.drain().len(), 1);
+    assert!(log.is_empty());
+}
+
+#[test]
+fn assert_no_errors_ignores_warnings() {
+    let log = DebugMessageLog::new();
+    let backend = Rc::new(RecordingBackend::new(DriverProfile::new(Version(Api::Gl, 4, 5)),
+                                                (800, 600)));
+    let context = unsafe { Context::new(backend.clone(), true, log.behavior()) }.unwrap();
+
+    let value = log.assert_no_errors(|| {
+        insert_message(&context, &backend, GL_DEBUG_TYPE_PERFORMANCE,
+                       GL_DEBUG_SEVERITY_NOTIFICATION, "buffer moved to video memory");
+        5
+    });
+
+    assert_eq!(value, 5);
+}
+
+#[test]
+#[should_panic(expected = "invalid enum")]
+fn assert_no_errors_panics_on_error() {
+    let log = DebugMessageLog::new();
+    let backend = Rc::new(RecordingBackend::new(DriverProfile::new(Version(Api::Gl, 4, 5)),
+                                                (800, 600)));
+    let context = unsafe { Context::new(backend.clone(), true, log.behavior()) }.unwrap();
+
+    log.assert_no_errors(|| {
+        insert_message(&context, &backend, GL_DEBUG_TYPE_ERROR, GL_DEBUG_SEVERITY_HIGH,
+                       "invalid enum");
+    });
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn labels_on_real_driver() {
+    use std::cell::RefCell;
+    use glium::Surface;
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+    use glium::debug::{DebugCallbackBehavior, MessageType, Severity};
+
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let callback = {
+        let errors = errors.clone();
+        Box::new(move |_, ty, severity, _, _, message: &str| {
+            if let (MessageType::Error, Severity::High) = (ty, severity) {
+                errors.borrow_mut().push(message.to_owned());
+            }
+        })
+    };
+
+    let display = EglSurfaceless::with_debug((64, 64), DebugCallbackBehavior::Custom {
+        callback,
+        synchronous: true,
+    }).unwrap();
+    let context = glium::backend::Facade::get_context(&display).clone();
+
+    let buffer = glium::buffer::Buffer::new(&context, &[1u8, 2, 3][..],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+    let texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+    let render_buffer = glium::framebuffer::RenderBuffer::new(
+        &context, glium::texture::UncompressedFloatFormat::U8U8U8U8, 4, 4).unwrap();
+    let program = build_program(&context);
+    let query = glium::draw_parameters::SamplesPassedQuery::new(&context).unwrap();
+
+    {
+        let _group = context.debug_group("labels");
+        buffer.set_label("vertices");
+        texture.set_label("albedo");
+        render_buffer.set_label("depth");
+        program.set_label("shading");
+        query.set_label("occlusion");
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    }
+
+    context.finish();
+    assert!(errors.borrow().is_empty(), "{:?}", errors.borrow());
+}
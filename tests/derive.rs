@@ -0,0 +1,370 @@
+#![cfg(feature = "derive")]
+
+#[macro_use]
+extern crate glium;
+
+use std::borrow::Cow;
+
+use glium::Surface;
+use glium::program::BlockLayout;
+use glium::uniforms::{UniformBlock, UniformBuffer, UniformType, UniformValue, Uniforms};
+use glium::vertex::AttributeType;
+use glium::Vertex;
+
+mod support;
+
+#[derive(Copy, Clone, Vertex)]
+struct Position {
+    position: [f32; 2],
+}
+
+#[derive(Copy, Clone, Vertex)]
+#[repr(C)]
+struct Extended<T: Copy> {
+    #[glium(flatten)]
+    base: T,
+    #[glium(name = "a_color", normalize, location = 3)]
+    color: [u8; 4],
+    #[glium(skip)]
+    _padding: u32,
+}
+
+#[test]
+fn vertex_bindings() {
+    let bindings = <Extended<Position> as Vertex>::build_bindings();
+
+    assert_eq!(bindings.len(), 2);
+    assert_eq!(bindings[0], (Cow::Borrowed("position"), 0, -1, AttributeType::F32F32, false));
+    assert_eq!(bindings[1], (Cow::Borrowed("a_color"), 8, 3, AttributeType::U8U8U8U8, true));
+
+    assert_eq!(<Extended<Position> as Vertex>::instance_divisor(), 1);
+}
+
+#[test]
+fn instance_divisor() {
+    #[derive(Copy, Clone, Vertex)]
+    #[glium(divisor = 2)]
+    struct Instance {
+        #[glium(name = "i_color", normalize)]
+        color: [u8; 4],
+    }
+
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let instances = glium::VertexBuffer::new(&display, &[
+        Instance { color: [255, 0, 0, 255] },
+        Instance { color: [0, 255, 0, 255] },
+    ]).unwrap();
+
+    // each instance covers a quarter of the width of the texture
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec4 i_color;
+                out vec4 v_color;
+
+                void main() {
+                    float x = (position.x + 1.0) * 0.5 + float(gl_InstanceID);
+                    gl_Position = vec4(x * 0.5 - 1.0, position.y, 0.0, 1.0);
+                    v_color = i_color;
+                }
+            ",
+            fragment: "
+                #version 140
+
+                in vec4 v_color;
+                out vec4 color;
+
+                void main() {
+                    color = v_color;
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 1).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&vertex_buffer, instances.per_instance().unwrap()), &index_buffer,
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(255, 0, 0, 255), (255, 0, 0, 255),
+                                 (0, 255, 0, 255), (0, 255, 0, 255)]]);
+
+    display.assert_no_error(None);
+}
+
+#[derive(Uniforms)]
+struct Light {
+    #[glium(name = "u_intensity")]
+    intensity: f32,
+}
+
+#[derive(Uniforms)]
+struct Material<'a> {
+    #[glium(name = "u_texture")]
+    texture: &'a glium::Texture2d,
+    #[glium(flatten)]
+    light: Light,
+    #[glium(skip)]
+    _unused: String,
+}
+
+#[test]
+fn uniforms_visit_values() {
+    let uniforms = Light { intensity: 0.5 };
+
+    let mut values = Vec::new();
+    uniforms.visit_values(|name, value| {
+        match value {
+            UniformValue::Float(value) => values.push((name.to_owned(), value)),
+            _ => panic!(),
+        }
+    });
+
+    assert_eq!(values, vec![("u_intensity".to_owned(), 0.5)]);
+}
+
+#[test]
+fn uniforms_draw() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                uniform sampler2D u_texture;
+                uniform float u_intensity;
+                out vec4 color;
+
+                void main() {
+                    color = texture(u_texture, vec2(0.5, 0.5)) * u_intensity;
+                }
+            ",
+        },
+    ).unwrap();
+
+    let source = support::build_unicolor_texture2d(&display, 1.0, 1.0, 0.0);
+    let uniforms = Material {
+        texture: &source,
+        light: Light { intensity: 0.2 },
+        _unused: String::new(),
+    };
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniforms,
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels[0][0], (51, 51, 0, 51));
+
+    display.assert_no_error(None);
+}
+
+#[derive(Copy, Clone, UniformBlock)]
+#[repr(C)]
+#[glium(std140)]
+struct Parameters {
+    intensity: f32,
+    #[glium(skip)]
+    _padding: [f32; 3],
+    #[glium(name = "tint")]
+    color: [f32; 4],
+}
+
+#[test]
+fn uniform_block_layout() {
+    let members = match <Parameters as UniformBlock>::build_layout(16) {
+        BlockLayout::Struct { members } => members,
+        _ => panic!(),
+    };
+
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[1].0, "tint");
+    match members[1].1 {
+        BlockLayout::BasicType { ty: UniformType::FloatVec4, offset_in_buffer: 32 } => (),
+        _ => panic!(),
+    };
+
+    assert_eq!(<Parameters as UniformBlock>::STD140_ALIGNMENT, Some(16));
+    assert_eq!(<[f32; 2] as UniformBlock>::STD140_ALIGNMENT, Some(8));
+    assert_eq!(<[f64; 3] as UniformBlock>::STD140_ALIGNMENT, Some(32));
+    assert_eq!(<[f32; 5] as UniformBlock>::STD140_ALIGNMENT, Some(16));
+
+    assert_eq!(<Parameters as UniformBlock>::STD140_SIZE, Some(32));
+    assert_eq!(<[f32; 3] as UniformBlock>::STD140_SIZE, Some(12));
+    assert_eq!(<[[f32; 3]; 3] as UniformBlock>::STD140_SIZE, Some(48));
+    assert_eq!(<[[f64; 3]; 3] as UniformBlock>::STD140_SIZE, Some(96));
+    assert_eq!(<[f32; 8] as UniformBlock>::STD140_SIZE, Some(128));
+    assert_eq!(<[[f32; 4]; 5] as UniformBlock>::STD140_SIZE, Some(80));
+    assert_eq!(<[Parameters; 5] as UniformBlock>::STD140_SIZE, Some(160));
+    assert_eq!(<[f32] as UniformBlock>::STD140_SIZE, None);
+}
+
+#[derive(Copy, Clone, UniformBlock)]
+#[repr(C)]
+#[glium(std140)]
+struct Palette {
+    index: i32,
+    #[glium(skip)]
+    _padding: [i32; 3],
+    colors: [[f32; 4]; 5],
+    scale: [f32; 3],
+    alpha: f32,
+}
+
+#[test]
+fn uniform_block_array_member() {
+    assert_eq!(<Palette as UniformBlock>::STD140_SIZE, Some(112));
+
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 140
+
+            layout(std140) uniform Block {
+                int index;
+                vec4 colors[5];
+                vec3 scale;
+                float alpha;
+            };
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(colors[index].rgb * scale, alpha);
+            }
+        ",
+        None)
+    {
+        Err(glium::CompilationError(..)) => return,
+        p => p.unwrap(),
+    };
+
+    let mut colors = [[0.0; 4]; 5];
+    colors[3] = [1.0, 1.0, 0.0, 0.0];
+
+    let buffer = UniformBuffer::new(&display, Palette {
+        index: 3,
+        _padding: [0; 3],
+        colors,
+        scale: [1.0, 0.5, 1.0],
+        alpha: 1.0,
+    }).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{ Block: &buffer },
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels[0][0], (255, 128, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn uniform_block_draw() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 140
+
+            layout(std140) uniform Block {
+                float intensity;
+                vec4 tint;
+            };
+
+            out vec4 color;
+
+            void main() {
+                color = tint * intensity;
+            }
+        ",
+        None)
+    {
+        Err(glium::CompilationError(..)) => return,
+        p => p.unwrap(),
+    };
+
+    let buffer = UniformBuffer::new(&display, Parameters {
+        intensity: 0.5,
+        _padding: [0.0; 3],
+        color: [1.0, 0.0, 1.0, 1.0],
+    }).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{ Block: &buffer },
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels[0][0], (128, 0, 128, 128));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn nested_uniform_block() {
+    #[derive(Copy, Clone, UniformBlock)]
+    struct Outer {
+        scale: f32,
+        inner: Parameters,
+    }
+
+    let members = match <Outer as UniformBlock>::build_layout(0) {
+        BlockLayout::Struct { members } => members,
+        _ => panic!(),
+    };
+
+    let offset = std::mem::offset_of!(Outer, inner);
+    let inner = members.iter().find(|&&(ref name, _)| name == "inner").unwrap();
+    assert!(<Parameters as UniformBlock>::matches(&inner.1, offset).is_ok());
+    assert!(<Outer as UniformBlock>::matches(&BlockLayout::Struct { members }, 0).is_ok());
+
+    let layout = BlockLayout::Struct {
+        members: vec![("scale".to_owned(), BlockLayout::BasicType {
+            ty: UniformType::Float,
+            offset_in_buffer: 0,
+        })],
+    };
+    assert!(<Outer as UniformBlock>::matches(&layout, 0).is_err());
+}
@@ -149,3 +149,76 @@ fn is_context_lost() {
     display.is_context_lost();
     display.assert_no_error(None);
 }
+
+/// Returns the `GL_VERSION` string and the `GL_CONTEXT_FLAGS` of the current OpenGL context.
+#[cfg(feature = "test_headless")]
+fn raw_version_and_flags(display: &glium::backend::egl_surfaceless::EglSurfaceless)
+                         -> (String, glium::gl::types::GLint)
+{
+    use std::ffi::CStr;
+
+    unsafe {
+        display.exec_raw(&[], |gl| {
+            let version = CStr::from_ptr(gl.GetString(glium::gl::VERSION) as *const _);
+            let mut flags = 0;
+            gl.GetIntegerv(glium::gl::CONTEXT_FLAGS, &mut flags);
+            (version.to_string_lossy().into_owned(), flags)
+        })
+    }
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn headless_debug_context_is_opt_in() {
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+    use glium::debug::DebugCallbackBehavior;
+
+    let is_debug = |display: &EglSurfaceless| {
+        raw_version_and_flags(display).1 as glium::gl::types::GLenum &
+            glium::gl::CONTEXT_FLAG_DEBUG_BIT != 0
+    };
+
+    let display = EglSurfaceless::with_version((64, 64), None,
+                                               DebugCallbackBehavior::DebugMessageOnError).unwrap();
+    assert!(!is_debug(&display));
+
+    let display = EglSurfaceless::with_debug_context((64, 64), None,
+                                                     DebugCallbackBehavior::Ignore).unwrap();
+    assert!(!is_debug(&display));
+
+    let display = EglSurfaceless::with_debug_context((64, 64), None,
+                                                     DebugCallbackBehavior::DebugMessageOnError)
+                                 .unwrap();
+    assert!(is_debug(&display));
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn headless_gles_capability_mask() {
+    use glium::backend::CapabilityMask;
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+
+    let display = EglSurfaceless::with_capability_mask((64, 64), CapabilityMask::gles20(),
+                                                       Default::default()).unwrap();
+    let (version, _) = raw_version_and_flags(&display);
+    assert!(version.starts_with("OpenGL ES"), "{}", version);
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn headless_rebuild_keeps_version() {
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+
+    let display = EglSurfaceless::with_version((64, 64), Some(glium::Version(glium::Api::Gl, 3, 1)),
+                                               Default::default()).unwrap();
+    let (version, _) = raw_version_and_flags(&display);
+
+    display.rebuild().unwrap();
+    assert_eq!(raw_version_and_flags(&display).0, version);
+
+    display.recover().unwrap();
+    assert_eq!(raw_version_and_flags(&display).0, version);
+    display.assert_no_error(None);
+}
@@ -0,0 +1,101 @@
+#[macro_use]
+extern crate glium;
+
+use glium::backend::recording::DriverProfile;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{DepthFormat, MipmapsOption, TextureFormat};
+use glium::texture::{UncompressedFloatFormat, UncompressedUintFormat};
+use glium::{Api, Version};
+
+mod support;
+
+#[test]
+fn queried_from_driver() {
+    let display = support::build_display();
+
+    let rgba8 = display.get_format_support(
+        TextureFormat::UncompressedFloat(UncompressedFloatFormat::U8U8U8U8));
+
+    if !rgba8.queried {
+        return;
+    }
+
+    assert!(rgba8.textures);
+    assert!(rgba8.render_buffers);
+    assert!(rgba8.filterable);
+    assert!(rgba8.color_renderable);
+    assert!(!rgba8.depth_renderable);
+    assert!(rgba8.blendable);
+    assert!(rgba8.mipmap_generation);
+    assert!(rgba8.preferred_upload_format.is_some());
+
+    let r32ui = display.get_format_support(
+        TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U32));
+    assert!(r32ui.color_renderable);
+    assert!(!r32ui.filterable);
+    assert!(!r32ui.blendable);
+
+    let depth = display.get_format_support(TextureFormat::DepthFormat(DepthFormat::I24));
+    assert!(depth.depth_renderable);
+    assert!(!depth.color_renderable);
+}
+
+#[test]
+fn color_renderable_formats_can_be_attached() {
+    let display = support::build_display();
+
+    for format in UncompressedFloatFormat::get_formats_list() {
+        let support = display.get_format_support(TextureFormat::UncompressedFloat(format));
+        if !support.textures || !support.color_renderable {
+            continue;
+        }
+
+        let texture = glium::Texture2d::empty_with_format(&display, format,
+                                                          MipmapsOption::NoMipmap, 4, 4)
+                                                          .unwrap();
+        SimpleFrameBuffer::new(&display, &texture).unwrap();
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn conservative_fallback() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let support = context.get_format_support(
+        TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32F32F32F32));
+    assert!(!support.queried);
+    assert!(support.textures);
+    assert!(support.filterable);
+    assert!(support.color_renderable);
+    assert!(support.blendable);
+    assert!(!support.image_load_store);
+    assert_eq!(support.preferred_upload_format, None);
+    assert!(!support.preferred_upload_inverted);
+
+    let support = context.get_format_support(
+        TextureFormat::UncompressedUnsigned(UncompressedUintFormat::U8U8U8U8));
+    assert!(support.color_renderable);
+    assert!(!support.filterable);
+    assert!(!support.blendable);
+    assert!(!support.mipmap_generation);
+}
+
+#[test]
+fn conservative_fallback_gles() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::GlEs, 3, 1)));
+
+    let support = context.get_format_support(
+        TextureFormat::UncompressedFloat(UncompressedFloatFormat::F32F32F32F32));
+    assert!(support.textures);
+    assert!(!support.filterable);
+    assert!(!support.blendable);
+    assert!(support.image_load_store);
+
+    let support = context.get_format_support(
+        TextureFormat::UncompressedFloat(UncompressedFloatFormat::F16F16));
+    assert!(support.filterable);
+    assert!(!support.image_load_store);
+}
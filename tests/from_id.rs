@@ -0,0 +1,246 @@
+#[macro_use]
+extern crate glium;
+
+use std::ffi::CString;
+use std::ptr;
+use std::rc::Rc;
+
+use glium::{Surface, GlObject};
+use glium::backend::{Context, StateGroup};
+use glium::backend::recording::DriverProfile;
+use glium::buffer::{Buffer, BufferCreationError, BufferType};
+use glium::framebuffer::{RenderBuffer, SimpleFrameBuffer};
+use glium::gl;
+use glium::index::PrimitiveType;
+
+mod support;
+
+fn create_raw_buffer(context: &Rc<Context>, target: gl::types::GLenum, data: &[u8])
+                     -> gl::types::GLuint
+{
+    unsafe {
+        context.exec_raw(&[StateGroup::Buffers], |gl| {
+            let mut id = 0;
+            gl.GenBuffers(1, &mut id);
+            gl.BindBuffer(target, id);
+            gl.BufferData(target, data.len() as gl::types::GLsizeiptr,
+                          data.as_ptr() as *const _, gl::STATIC_DRAW);
+            id
+        })
+    }
+}
+
+#[test]
+fn buffer_size_is_queried() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    let id = create_raw_buffer(&context, gl::ARRAY_BUFFER, &[1, 0, 0, 0, 2, 0, 0, 0]);
+
+    let buffer = unsafe {
+        Buffer::<[u32]>::from_id(&context, id, BufferType::ArrayBuffer, false)
+    }.unwrap();
+
+    assert_eq!(buffer.get_id(), id);
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.read().unwrap(), vec![1, 2]);
+    assert_eq!(context.resource_stats().buffers.count, 0);
+
+    backend.clear_calls();
+    drop(buffer);
+    assert_eq!(support::count_calls(&backend, "glDeleteBuffers"), 0);
+}
+
+#[test]
+fn owned_buffer_is_destroyed() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    let id = create_raw_buffer(&context, gl::ARRAY_BUFFER, &[0; 16]);
+
+    let buffer = unsafe {
+        Buffer::<[u32]>::from_id(&context, id, BufferType::ArrayBuffer, true)
+    }.unwrap();
+
+    assert_eq!(context.resource_stats().buffers.count, 1);
+    assert_eq!(context.resource_stats().buffers.bytes, 16);
+
+    backend.clear_calls();
+    drop(buffer);
+    assert_eq!(support::count_calls(&backend, "glDeleteBuffers"), 1);
+    assert_eq!(context.resource_stats().buffers.count, 0);
+}
+
+#[test]
+fn buffer_size_mismatch() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    let id = create_raw_buffer(&context, gl::ARRAY_BUFFER, &[0; 16]);
+
+    backend.clear_calls();
+    let result = unsafe {
+        Buffer::<[[u8; 3]]>::from_id(&context, id, BufferType::ArrayBuffer, true)
+    };
+
+    match result {
+        Err(BufferCreationError::SizeMismatch) => (),
+        _ => panic!()
+    };
+
+    assert_eq!(support::count_calls(&backend, "glDeleteBuffers"), 0);
+    assert_eq!(context.resource_stats().buffers.count, 0);
+}
+
+#[test]
+fn buffer_size_mismatch_then_draw() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    // the raw buffer stays bound to `GL_ARRAY_BUFFER`, which the state cache knows about
+    let id = unsafe {
+        display.exec_raw(&[StateGroup::Buffers], |gl| {
+            let mut id = 0;
+            gl.GenBuffers(1, &mut id);
+            gl.BindBuffer(gl::ARRAY_BUFFER, id);
+            gl.BufferData(gl::ARRAY_BUFFER, 16, ptr::null(), gl::STATIC_DRAW);
+            id
+        })
+    };
+
+    let result = unsafe {
+        Buffer::<[[u8; 3]]>::from_id(&display, id, BufferType::ArrayBuffer, true)
+    };
+
+    match result {
+        Err(BufferCreationError::SizeMismatch) => (),
+        _ => panic!()
+    };
+
+    let (vertex_buffer, index_buffer, program) = support::build_fullscreen_red_pipeline(&display);
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(255, 0, 0, 255); 4]; 4]);
+
+    unsafe { display.exec_raw(&[StateGroup::Buffers], |gl| gl.DeleteBuffers(1, &id)) };
+    display.assert_no_error(None);
+}
+
+#[test]
+fn program_owned_flag() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+
+    let id = unsafe { context.exec_raw(&[], |gl| gl.CreateProgram()) };
+    let program = unsafe { glium::Program::from_id(&context, id, false) }.unwrap();
+    assert_eq!(context.resource_stats().programs.count, 0);
+
+    backend.clear_calls();
+    drop(program);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 0);
+
+    let program = unsafe { glium::Program::from_id(&context, id, true) }.unwrap();
+    assert_eq!(context.resource_stats().programs.count, 1);
+
+    backend.clear_calls();
+    drop(program);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 1);
+    assert_eq!(context.resource_stats().programs.count, 0);
+}
+
+unsafe fn compile_raw_shader(gl: &gl::Gl, ty: gl::types::GLenum, source: &str)
+                             -> gl::types::GLuint
+{
+    let source = CString::new(source).unwrap();
+    let id = gl.CreateShader(ty);
+    gl.ShaderSource(id, 1, &source.as_ptr(), ptr::null());
+    gl.CompileShader(id);
+    id
+}
+
+#[test]
+fn adopted_objects_draw() {
+    let display = support::build_display();
+
+    let vertices: [f32; 8] = [-1.0, 1.0, 1.0, 1.0, -1.0, -1.0, 1.0, -1.0];
+    let indices: [u16; 4] = [0, 1, 2, 3];
+
+    let (vb_id, ib_id, program_id, rb_id) = unsafe {
+        display.exec_raw(&[StateGroup::Buffers, StateGroup::Framebuffers], |gl| {
+            let mut buffers = [0; 2];
+            gl.GenBuffers(2, buffers.as_mut_ptr());
+            gl.BindBuffer(gl::ARRAY_BUFFER, buffers[0]);
+            gl.BufferData(gl::ARRAY_BUFFER, 32, vertices.as_ptr() as *const _, gl::STATIC_DRAW);
+            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffers[1]);
+            gl.BufferData(gl::ELEMENT_ARRAY_BUFFER, 8, indices.as_ptr() as *const _,
+                          gl::DYNAMIC_DRAW);
+
+            let vs = compile_raw_shader(gl, gl::VERTEX_SHADER, "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ");
+            let fs = compile_raw_shader(gl, gl::FRAGMENT_SHADER, "
+                #version 140
+                uniform vec4 color;
+                out vec4 f_color;
+                void main() {
+                    f_color = color;
+                }
+            ");
+            let program = gl.CreateProgram();
+            gl.AttachShader(program, vs);
+            gl.AttachShader(program, fs);
+            gl.LinkProgram(program);
+            gl.DeleteShader(vs);
+            gl.DeleteShader(fs);
+
+            let mut renderbuffer = 0;
+            gl.GenRenderbuffers(1, &mut renderbuffer);
+            gl.BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+            gl.RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, 4, 4);
+
+            (buffers[0], buffers[1], program, renderbuffer)
+        })
+    };
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let vertex_buffer = unsafe {
+        glium::VertexBuffer::<Vertex>::from_id(&display, vb_id, true)
+    }.unwrap();
+    assert_eq!(vertex_buffer.len(), 4);
+
+    let index_buffer = unsafe {
+        glium::IndexBuffer::<u16>::from_id(&display, ib_id, PrimitiveType::TriangleStrip, true)
+    }.unwrap();
+    assert_eq!(index_buffer.len(), 4);
+
+    let program = unsafe { glium::Program::from_id(&display, program_id, true) }.unwrap();
+    assert!(program.get_uniform("color").is_some());
+    assert!(program.get_attribute("position").is_some());
+    assert!(!program.has_geometry_shader());
+
+    let renderbuffer = unsafe { RenderBuffer::from_id(&display, rb_id, true) };
+    assert_eq!(renderbuffer.get_dimensions(), (4, 4));
+    assert_eq!(renderbuffer.get_samples(), None);
+
+    let mut framebuffer = SimpleFrameBuffer::new(&display, &renderbuffer).unwrap();
+    framebuffer.clear_color(0.0, 0.0, 0.0, 0.0);
+    framebuffer.draw(&vertex_buffer, &index_buffer, &program,
+                     &uniform!{ color: [0.0f32, 1.0, 0.0, 1.0] },
+                     &Default::default()).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    framebuffer.blit_whole_color_to(&texture.as_surface(), &glium::BlitTarget {
+        left: 0, bottom: 0, width: 4, height: 4,
+    }, glium::uniforms::MagnifySamplerFilter::Nearest);
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
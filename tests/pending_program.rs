@@ -0,0 +1,173 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::Context;
+use glium::backend::recording::{Arg, DriverProfile, RecordingBackend};
+use glium::program::{ProgramCreationError, ShaderType, SourceCode};
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 140
+
+    in vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+const FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.0, 1.0, 0.0, 1.0);
+    }
+";
+
+/// `GL_COMPLETION_STATUS_KHR`
+const COMPLETION_STATUS: u64 = 0x91B1;
+
+/// `GL_LINK_STATUS`
+const LINK_STATUS: u64 = 0x8B82;
+
+fn source_code(fragment_shader: &str) -> SourceCode<'_> {
+    SourceCode {
+        vertex_shader: VERTEX_SHADER,
+        fragment_shader,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    }
+}
+
+fn build_context(parallel_compile: bool) -> (Rc<Context>, Rc<RecordingBackend>) {
+    let mut profile = DriverProfile::default();
+    if parallel_compile {
+        profile.extensions.push("GL_KHR_parallel_shader_compile".to_owned());
+    }
+
+    support::build_recording_context(profile)
+}
+
+fn count_queries(backend: &RecordingBackend, name: &str, pname: u64) -> usize {
+    backend.calls().iter()
+           .filter(|call| call.name == name && call.args.get(1) == Some(&Arg::UInt(pname)))
+           .count()
+}
+
+#[test]
+fn checks_are_deferred() {
+    let (context, backend) = build_context(true);
+
+    backend.clear_calls();
+    let pending = glium::Program::new_async(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    let calls = backend.calls();
+    assert!(calls.iter().any(|call| call.name == "glLinkProgram"));
+    assert!(!calls.iter().any(|call| call.name == "glGetShaderiv" ||
+                                     call.name == "glGetProgramiv"));
+
+    backend.clear_calls();
+    assert!(pending.is_ready());
+    assert_eq!(count_queries(&backend, "glGetProgramiv", COMPLETION_STATUS), 1);
+    assert_eq!(count_queries(&backend, "glGetProgramiv", LINK_STATUS), 0);
+
+    backend.clear_calls();
+    let program = pending.wait().unwrap();
+    assert_eq!(count_queries(&backend, "glGetProgramiv", LINK_STATUS), 1);
+    assert!(!program.has_geometry_shader());
+}
+
+#[test]
+fn ready_without_extension() {
+    let (context, backend) = build_context(false);
+
+    let pending = glium::Program::new_async(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    backend.clear_calls();
+    assert!(pending.is_ready());
+    assert!(backend.calls().is_empty());
+
+    pending.wait().unwrap();
+}
+
+#[test]
+fn dropping_cancels() {
+    let (context, backend) = build_context(true);
+
+    let pending = glium::Program::new_async(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    backend.clear_calls();
+    drop(pending);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 1);
+    assert_eq!(support::count_calls(&backend, "glDeleteShader"), 2);
+    assert_eq!(count_queries(&backend, "glGetProgramiv", LINK_STATUS), 0);
+}
+
+#[test]
+fn pending_program_draws() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let pending = glium::Program::new_async(&display, source_code(FRAGMENT_SHADER)).unwrap();
+    while !pending.is_ready() {
+        std::thread::yield_now();
+    }
+    let program = pending.wait().unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn errors_are_reported_by_wait() {
+    let display = support::build_display();
+
+    let pending = glium::Program::new_async(&display, source_code("
+        #version 140
+
+        out vec4 color;
+
+        void main() {
+            color = this is an error;
+        }
+    ")).unwrap();
+
+    match pending.wait() {
+        Err(ProgramCreationError::CompilationError(_, ShaderType::Fragment)) => (),
+        _ => panic!(),
+    };
+
+    // `foo` is declared but never defined, which is a linking error
+    let pending = glium::Program::new_async(&display, source_code("
+        #version 140
+
+        out vec4 color;
+
+        vec4 foo();
+
+        void main() {
+            color = foo();
+        }
+    ")).unwrap();
+
+    match pending.wait() {
+        Err(ProgramCreationError::LinkingError(_)) => (),
+        _ => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,217 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::Context;
+use glium::backend::recording::DriverProfile;
+use glium::profiler::Profiler;
+use glium::{Api, Version};
+
+mod support;
+
+/// Makes a few OpenGL calls, which advances the clock of the recording backend.
+fn work(context: &Rc<Context>, calls: usize) {
+    for _ in 0 .. calls {
+        context.flush();
+    }
+}
+
+#[test]
+fn not_supported() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 0)));
+    assert!(Profiler::new(&context, 2).is_none());
+}
+
+#[test]
+fn nested_scopes() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let mut profiler = Profiler::new(&context, 2).unwrap();
+
+    profiler.begin_frame();
+    {
+        let _shadows = profiler.scope("shadows");
+        work(&context, 5);
+    }
+    {
+        let _scene = profiler.scope("scene");
+        let _opaque = profiler.scope("opaque");
+        work(&context, 10);
+    }
+    profiler.end_frame();
+    profiler.flush();
+
+    let frame = profiler.latest_frame().unwrap();
+    assert_eq!(frame.number, 0);
+
+    let paths = frame.scopes.iter().map(|s| (s.path.as_str(), s.depth)).collect::<Vec<_>>();
+    assert_eq!(paths, vec![("shadows", 0), ("scene", 0), ("scene/opaque", 1)]);
+    assert_eq!(frame.scopes[2].name, "opaque");
+
+    let (shadows, scene, opaque) = (&frame.scopes[0], &frame.scopes[1], &frame.scopes[2]);
+    assert!(shadows.end <= scene.start);
+    assert!(scene.start <= opaque.start && opaque.end <= scene.end);
+    assert!(opaque.duration() > shadows.duration());
+}
+
+#[test]
+fn statistics() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let mut profiler = Profiler::new(&context, 2).unwrap();
+
+    for calls in &[10, 30, 20] {
+        profiler.begin_frame();
+        {
+            let _scope = profiler.scope("draw");
+            work(&context, *calls);
+        }
+        profiler.end_frame();
+    }
+    profiler.flush();
+
+    let durations = profiler.frames().map(|f| f.scopes[0].duration()).collect::<Vec<_>>();
+    assert_eq!(durations.len(), 3);
+
+    let stats = profiler.scope_stats("draw").unwrap();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.min, *durations.iter().min().unwrap());
+    assert_eq!(stats.max, *durations.iter().max().unwrap());
+    assert_eq!(stats.max, durations[1]);
+    assert_eq!(stats.total, durations.iter().sum());
+    assert!(stats.min < stats.average() && stats.average() < stats.max);
+
+    profiler.reset();
+    assert!(profiler.stats().is_empty());
+    assert!(profiler.latest_frame().is_none());
+}
+
+#[test]
+fn queries_are_reused() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let mut profiler = Profiler::new(&context, 2).unwrap();
+
+    for _ in 0 .. 10 {
+        profiler.begin_frame();
+        {
+            let _first = profiler.scope("first");
+        }
+        {
+            let _second = profiler.scope("second");
+        }
+        profiler.end_frame();
+    }
+
+    // the results of the recording backend are always available, so two queries per scope
+    assert_eq!(context.resource_stats().queries.count, 4);
+    assert_eq!(profiler.frames().count(), 10);
+
+    drop(profiler);
+    assert_eq!(context.resource_stats().queries.count, 0);
+}
+
+#[test]
+fn history_len() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let mut profiler = Profiler::new(&context, 0).unwrap();
+    profiler.set_history_len(2);
+
+    for _ in 0 .. 5 {
+        profiler.begin_frame();
+        drop(profiler.scope("frame"));
+        profiler.end_frame();
+    }
+
+    let numbers = profiler.frames().map(|f| f.number).collect::<Vec<_>>();
+    assert_eq!(numbers, vec![3, 4]);
+    assert_eq!(profiler.scope_stats("frame").unwrap().count, 5);
+}
+
+#[test]
+#[should_panic(expected = "outside of a frame")]
+fn scope_outside_frame() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let profiler = Profiler::new(&context, 2).unwrap();
+    let _scope = profiler.scope("scope");
+}
+
+#[test]
+fn chrome_trace() {
+    let (context, _) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 3, 3)));
+    let mut profiler = Profiler::new(&context, 2).unwrap();
+
+    profiler.begin_frame();
+    {
+        let _scope = profiler.scope("say \"hello\"");
+        let _inner = profiler.scope("inner");
+    }
+    profiler.end_frame();
+    profiler.flush();
+
+    let mut output = Vec::new();
+    profiler.write_chrome_trace(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.starts_with("{\"traceEvents\":["));
+    assert!(output.trim_end().ends_with("],\"displayTimeUnit\":\"ms\"}"));
+    assert!(output.contains("{\"name\":\"say \\\"hello\\\"\",\"cat\":\"gpu\",\"ph\":\"X\",\
+                             \"ts\":0.000,"));
+    assert!(output.contains("\"path\":\"say \\\"hello\\\"/inner\""));
+    assert_eq!(output.matches("\"ph\":\"X\"").count(), 2);
+}
+
+#[test]
+fn real_driver() {
+    let display = support::build_display();
+    let mut profiler = match Profiler::new(&display, 2) {
+        Some(profiler) => profiler,
+        None => return,
+    };
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+                attribute vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    for _ in 0 .. 4 {
+        profiler.begin_frame();
+        let mut target = display.draw();
+        {
+            let _scope = profiler.scope("draw");
+            target.clear_color(0.0, 0.0, 0.0, 0.0);
+            target.draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                        &Default::default()).unwrap();
+        }
+        target.finish().unwrap();
+        profiler.end_frame();
+    }
+    profiler.flush();
+
+    assert_eq!(profiler.frames().count(), 4);
+    for frame in profiler.frames() {
+        assert!(frame.scopes[0].end >= frame.scopes[0].start);
+    }
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,182 @@
+#[macro_use]
+extern crate glium;
+
+use std::fs;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::Context;
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::program::{ProgramCache, SourceCode};
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 140
+
+    in vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+const FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.0, 1.0, 0.0, 1.0);
+    }
+";
+
+fn source_code(fragment_shader: &str) -> SourceCode<'_> {
+    SourceCode {
+        vertex_shader: VERTEX_SHADER,
+        fragment_shader,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    }
+}
+
+/// A cache in a temporary directory, which is removed when the cache is dropped.
+struct TemporaryCache(ProgramCache);
+
+impl Deref for TemporaryCache {
+    type Target = ProgramCache;
+
+    fn deref(&self) -> &ProgramCache {
+        &self.0
+    }
+}
+
+impl Drop for TemporaryCache {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0.directory());
+    }
+}
+
+fn build_cache(name: &str) -> TemporaryCache {
+    let directory = std::env::temp_dir()
+        .join(format!("glium-program-cache-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&directory);
+    TemporaryCache(ProgramCache::new(directory).unwrap())
+}
+
+fn cached_files(cache: &ProgramCache) -> Vec<PathBuf> {
+    fs::read_dir(cache.directory()).unwrap().map(|entry| entry.unwrap().path()).collect()
+}
+
+fn build_context(renderer: &str, binaries: bool) -> (Rc<Context>, Rc<RecordingBackend>) {
+    let mut profile = DriverProfile::default();
+    profile.renderer = renderer.to_owned();
+    if binaries {
+        profile.extensions.push("GL_ARB_get_program_binary".to_owned());
+    }
+
+    support::build_recording_context(profile)
+}
+
+#[test]
+fn binary_is_reused() {
+    let cache = build_cache("reused");
+    let (context, backend) = build_context("renderer", true);
+
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 2);
+    assert_eq!(support::count_calls(&backend, "glProgramBinary"), 0);
+    assert_eq!(cached_files(&cache).len(), 1);
+
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 0);
+    assert_eq!(support::count_calls(&backend, "glProgramBinary"), 1);
+
+    // another source code is another entry
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code("#version 140\nvoid main() {}")).unwrap();
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 2);
+    assert_eq!(cached_files(&cache).len(), 2);
+
+    cache.clear().unwrap();
+    assert!(cached_files(&cache).is_empty());
+}
+
+#[test]
+fn driver_is_part_of_the_key() {
+    let cache = build_cache("driver");
+
+    let (context, _) = build_context("renderer", true);
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    let (context, backend) = build_context("updated renderer", true);
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glProgramBinary"), 0);
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 2);
+    assert_eq!(cached_files(&cache).len(), 2);
+}
+
+#[test]
+fn rejected_binary_is_refreshed() {
+    let cache = build_cache("rejected");
+    let (context, backend) = build_context("renderer", true);
+
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    // corrupting the binary, which is at the end of the file
+    let path = cached_files(&cache).remove(0);
+    let mut data = fs::read(&path).unwrap();
+    *data.last_mut().unwrap() ^= 0xff;
+    fs::write(&path, data).unwrap();
+
+    backend.clear_calls();
+    let program = cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glProgramBinary"), 1);
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 2);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 1);
+    drop(program);
+
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glProgramBinary"), 1);
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 0);
+}
+
+#[test]
+fn unsupported_binaries() {
+    let cache = build_cache("unsupported");
+    let (context, backend) = build_context("renderer", false);
+
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+    backend.clear_calls();
+    cache.get_or_create(&context, source_code(FRAGMENT_SHADER)).unwrap();
+
+    assert_eq!(support::count_calls(&backend, "glCompileShader"), 2);
+    assert!(cached_files(&cache).is_empty());
+}
+
+#[test]
+fn cached_program_draws() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let cache = build_cache("draws");
+
+    cache.get_or_create(&display, source_code(FRAGMENT_SHADER)).unwrap();
+    let program = cache.get_or_create(&display, source_code(FRAGMENT_SHADER)).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,262 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::{GlObject, Surface};
+use glium::backend::Context;
+use glium::backend::recording::{Arg, DriverProfile, RecordingBackend};
+use glium::index::{NoIndices, PrimitiveType};
+use glium::program::{PipelineStages, ProgramCreationError, ProgramPipeline, ShaderStageProgram};
+use glium::program::ShaderType;
+use glium::uniforms::UniformType;
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 410
+
+    uniform float scale;
+
+    in vec2 position;
+
+    out gl_PerVertex {
+        vec4 gl_Position;
+    };
+
+    void main() {
+        gl_Position = vec4(position * scale, 0.0, 1.0);
+    }
+";
+
+const GREEN_FRAGMENT_SHADER: &str = "
+    #version 410
+
+    uniform float scale;
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.0, scale, 0.0, 1.0);
+    }
+";
+
+const TINTED_FRAGMENT_SHADER: &str = "
+    #version 410
+
+    uniform vec4 tint;
+
+    out vec4 color;
+
+    void main() {
+        color = tint;
+    }
+";
+
+fn stages<'a>(vertex: &'a ShaderStageProgram, fragment: &'a ShaderStageProgram)
+              -> PipelineStages<'a>
+{
+    PipelineStages {
+        vertex_shader: vertex,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+        fragment_shader: fragment,
+        outputs_srgb: false,
+        uses_point_size: false,
+    }
+}
+
+fn build_context(separate_shader_objects: bool) -> (Rc<Context>, Rc<RecordingBackend>) {
+    let mut profile = DriverProfile::default();
+    if separate_shader_objects {
+        profile.extensions.push("GL_ARB_separate_shader_objects".to_owned());
+    }
+
+    support::build_recording_context(profile)
+}
+
+#[test]
+fn stages_are_mixed_and_matched() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let vertex = ShaderStageProgram::new(&display, ShaderType::Vertex, VERTEX_SHADER).unwrap();
+    let green = ShaderStageProgram::new(&display, ShaderType::Fragment,
+                                        GREEN_FRAGMENT_SHADER).unwrap();
+    let tinted = ShaderStageProgram::new(&display, ShaderType::Fragment,
+                                         TINTED_FRAGMENT_SHADER).unwrap();
+
+    let green_pipeline = ProgramPipeline::new(&display, stages(&vertex, &green)).unwrap();
+    let tinted_pipeline = ProgramPipeline::new(&display, stages(&vertex, &tinted)).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &green_pipeline,
+                              &uniform!{ scale: 1.0f32 }, &Default::default()).unwrap();
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &tinted_pipeline,
+                              &uniform!{ scale: 1.0f32, tint: [1.0f32, 0.0, 1.0, 1.0] },
+                              &Default::default()).unwrap();
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(255, 0, 255, 255); 4]; 4]);
+
+    // a regular program still works after a pipeline has been bound
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                out vec4 color;
+                void main() {
+                    color = vec4(0.0, 0.0, 1.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 0, 255, 255); 4]; 4]);
+
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &green_pipeline,
+                              &uniform!{ scale: 1.0f32 }, &Default::default()).unwrap();
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn reflection_is_merged() {
+    let display = support::build_display();
+
+    let vertex = ShaderStageProgram::new(&display, ShaderType::Vertex, VERTEX_SHADER).unwrap();
+    let fragment = ShaderStageProgram::new(&display, ShaderType::Fragment,
+                                           TINTED_FRAGMENT_SHADER).unwrap();
+    assert_eq!(vertex.get_stage(), ShaderType::Vertex);
+    assert!(vertex.get_attribute("position").is_some());
+    assert!(fragment.get_uniform("tint").is_some());
+
+    let pipeline = ProgramPipeline::new(&display, stages(&vertex, &fragment)).unwrap();
+
+    assert_eq!(pipeline.uniforms().count(), 2);
+    assert_eq!(pipeline.get_uniform("scale").unwrap().ty, UniformType::Float);
+    assert_eq!(pipeline.get_uniform("tint").unwrap().ty, UniformType::FloatVec4);
+    assert!(pipeline.get_attribute("position").is_some());
+    assert!(!pipeline.has_geometry_shader());
+    assert!(pipeline.get_binary().is_err());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn mismatched_stages_are_rejected() {
+    let display = support::build_display();
+
+    let vertex = ShaderStageProgram::new(&display, ShaderType::Vertex, VERTEX_SHADER).unwrap();
+    let fragment = ShaderStageProgram::new(&display, ShaderType::Fragment, "
+        #version 410
+
+        uniform vec2 scale;
+
+        out vec4 color;
+
+        void main() {
+            color = vec4(scale, 0.0, 1.0);
+        }
+    ").unwrap();
+
+    match ProgramPipeline::new(&display, stages(&vertex, &fragment)) {
+        Err(ProgramCreationError::LinkingError(_)) => (),
+        _ => panic!(),
+    };
+
+    match ProgramPipeline::new(&display, stages(&fragment, &vertex)) {
+        Err(ProgramCreationError::LinkingError(_)) => (),
+        _ => panic!(),
+    };
+
+    match ShaderStageProgram::new(&display, ShaderType::Compute, "") {
+        Err(ProgramCreationError::ShaderTypeNotSupported) => (),
+        _ => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn pipeline_is_bound_instead_of_program() {
+    let (context, backend) = build_context(true);
+
+    backend.clear_calls();
+    let vertex = ShaderStageProgram::new(&context, ShaderType::Vertex, VERTEX_SHADER).unwrap();
+    let fragment = ShaderStageProgram::new(&context, ShaderType::Fragment,
+                                           GREEN_FRAGMENT_SHADER).unwrap();
+    assert_eq!(support::count_calls(&backend, "glProgramParameteri"), 2);
+
+    backend.clear_calls();
+    let pipeline = ProgramPipeline::new(&context, stages(&vertex, &fragment)).unwrap();
+    assert_eq!(support::count_calls(&backend, "glGenProgramPipelines"), 1);
+    assert_eq!(support::count_calls(&backend, "glUseProgramStages"), 2);
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let vertex_buffer = glium::VertexBuffer::new(&context, &[
+        Vertex { position: [-0.5, -0.5] },
+        Vertex { position: [0.0, 0.5] },
+        Vertex { position: [0.5, -0.5] },
+    ]).unwrap();
+
+    backend.clear_calls();
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    for _ in 0 .. 2 {
+        frame.draw(&vertex_buffer, NoIndices(PrimitiveType::TrianglesList), &pipeline,
+                   &uniform!{}, &Default::default()).unwrap();
+    }
+    frame.finish().unwrap();
+
+    let bindings = backend.calls().into_iter()
+                          .filter(|call| call.name == "glBindProgramPipeline")
+                          .collect::<Vec<_>>();
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].args, vec![Arg::UInt(pipeline.get_id() as u64)]);
+    assert_eq!(support::count_calls(&backend, "glUseProgram"), 0);
+    assert_eq!(support::count_calls(&backend, "glDrawArrays"), 2);
+
+    backend.clear_calls();
+    drop(pipeline);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgramPipelines"), 1);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 0);
+    backend.clear_calls();
+
+    drop(vertex);
+    drop(fragment);
+    assert_eq!(support::count_calls(&backend, "glDeleteProgram"), 2);
+}
+
+#[test]
+fn unsupported_separate_shader_objects() {
+    let (context, _) = build_context(false);
+
+    match ShaderStageProgram::new(&context, ShaderType::Vertex, VERTEX_SHADER) {
+        Err(ProgramCreationError::SeparateShaderObjectsNotSupported) => (),
+        _ => panic!(),
+    };
+}
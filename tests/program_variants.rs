@@ -0,0 +1,168 @@
+#[macro_use]
+extern crate glium;
+
+use glium::{GlObject, Surface};
+use glium::program::{ProgramChooserCreationError, ProgramCreationError, ProgramVariants};
+use glium::program::{ShaderDefines, ShaderType, SourceCode};
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 140
+
+    in vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+const FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+    #ifdef RED
+        color = vec4(1.0, 0.0, BLUE, 1.0);
+    #else
+        color = vec4(0.0, 1.0, BLUE, 1.0);
+    #endif
+    }
+";
+
+fn build_variants<F>(facade: &F) -> ProgramVariants where F: glium::backend::Facade + ?Sized {
+    ProgramVariants::new(facade, SourceCode {
+        vertex_shader: VERTEX_SHADER,
+        fragment_shader: FRAGMENT_SHADER,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    })
+}
+
+#[test]
+fn defines_are_injected_after_version() {
+    let defines = ShaderDefines::new().define("B").define_value("A", 2);
+    assert_eq!(defines.inject("\n  // comment\n  #version 330\nvoid main() {}"),
+               "\n  // comment\n  #version 330\n#define A 2\n#define B\n#line 4 0\nvoid main() {}\n");
+    assert_eq!(defines.inject("void main() {}"),
+               "#define A 2\n#define B\n#line 0 0\nvoid main() {}\n");
+}
+
+#[test]
+fn defines_are_unordered() {
+    let a = ShaderDefines::new().define("RED").define_value("BLUE", 1);
+    let b = vec![("BLUE", "1"), ("RED", "")].into_iter().collect::<ShaderDefines>();
+    assert_eq!(a, b);
+    assert_eq!(b.get("RED"), Some(""));
+    assert_eq!(b.len(), 2);
+}
+
+#[test]
+fn variants_are_compiled_once() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let mut variants = build_variants(&display);
+    assert!(variants.is_empty());
+
+    let red = ShaderDefines::new().define("RED").define_value("BLUE", "1.0");
+    let green = ShaderDefines::new().define_value("BLUE", "0.0");
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+
+    for &(ref defines, expected) in &[(&red, (255, 0, 255, 255)), (&green, (0, 255, 0, 255))] {
+        let program = variants.get(defines).unwrap();
+
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vertex_buffer, &index_buffer, program, &uniform!{},
+                                  &Default::default()).unwrap();
+
+        let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(pixels, vec![vec![expected; 4]; 4]);
+    }
+
+    assert_eq!(variants.len(), 2);
+
+    let id = variants.get(&red).unwrap().get_id();
+    let same = ShaderDefines::new().define_value("BLUE", "1.0").define("RED");
+    assert_eq!(variants.get(&same).unwrap().get_id(), id);
+    assert_eq!(variants.len(), 2);
+
+    assert!(variants.evict(&red).is_some());
+    assert!(!variants.contains(&red));
+    variants.clear();
+    assert!(variants.is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn least_recently_used_variant_is_evicted() {
+    let display = support::build_display();
+    let mut variants = build_variants(&display).max_variants(Some(2));
+
+    let defines = (0 .. 3).map(|blue| ShaderDefines::new().define_value("BLUE", blue))
+                          .collect::<Vec<_>>();
+
+    variants.get(&defines[0]).unwrap();
+    variants.get(&defines[1]).unwrap();
+    variants.get(&defines[0]).unwrap();
+    variants.get(&defines[2]).unwrap();
+
+    assert_eq!(variants.len(), 2);
+    assert!(variants.contains(&defines[0]));
+    assert!(!variants.contains(&defines[1]));
+    assert!(variants.contains(&defines[2]));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn errors_are_not_cached() {
+    let display = support::build_display();
+    let mut variants = build_variants(&display);
+
+    // `BLUE` isn't defined
+    match variants.get(&ShaderDefines::new()) {
+        Err(ProgramCreationError::CompilationError(_, ShaderType::Fragment)) => (),
+        _ => panic!(),
+    };
+
+    assert!(variants.is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn macro_chooses_version() {
+    let display = support::build_display();
+
+    let mut variants = program_variants!(&display,
+        9000 => {
+            vertex: "#version 9000",
+            fragment: "#version 9000",
+        },
+        140 => {
+            vertex: VERTEX_SHADER,
+            fragment: FRAGMENT_SHADER,
+            outputs_srgb: true,
+        },
+    ).unwrap();
+
+    assert!(variants.get(&ShaderDefines::new().define_value("BLUE", 1)).is_ok());
+
+    let result = program_variants!(&display,
+        9000 => {
+            vertex: "#version 9000",
+            fragment: "#version 9000",
+        }
+    );
+
+    match result {
+        Err(ProgramChooserCreationError::NoVersion) => (),
+        _ => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
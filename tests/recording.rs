@@ -0,0 +1,116 @@
+#[macro_use]
+extern crate glium;
+
+use glium::{CapabilitiesSource, Surface};
+use glium::backend::recording::{Arg, DriverProfile};
+
+mod support;
+
+#[test]
+fn context_uses_profile() {
+    let mut profile = DriverProfile::default();
+    profile.renderer = "Fake renderer".to_owned();
+    profile.extensions.push("GL_EXT_texture_filter_anisotropic".to_owned());
+    profile.limits.insert(0x84FF, vec![16]);     // GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT
+
+    let (context, backend) = support::build_recording_context(profile);
+
+    assert_eq!(*context.get_opengl_version(), glium::Version(glium::Api::Gl, 3, 3));
+    assert_eq!(context.get_opengl_renderer_string(), "Fake renderer");
+    assert!(context.get_extensions().gl_ext_texture_filter_anisotropic);
+    assert!(!context.get_extensions().gl_arb_buffer_storage);
+    assert_eq!(context.get_capabilities().max_texture_max_anisotropy, Some(16.0));
+    assert!(support::count_calls(&backend, "glGetStringi") >= 1);
+}
+
+#[test]
+fn gles_profile() {
+    let mut profile = DriverProfile::new(glium::Version(glium::Api::GlEs, 2, 0));
+    profile.profile = None;
+
+    let (context, _) = support::build_recording_context(profile);
+    assert_eq!(*context.get_opengl_version(), glium::Version(glium::Api::GlEs, 2, 0));
+}
+
+#[test]
+fn redundant_state_changes_are_filtered() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    backend.clear_calls();
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    frame.clear_color(0.0, 0.0, 1.0, 1.0);
+    frame.clear_color(0.0, 0.0, 1.0, 1.0);
+    frame.finish().unwrap();
+
+    assert_eq!(support::count_calls(&backend, "glClearColor"), 1);
+    assert_eq!(support::count_calls(&backend, "glClear"), 2);
+
+    let clear_color = backend.calls().into_iter().find(|c| c.name == "glClearColor").unwrap();
+    assert_eq!(clear_color.args, vec![Arg::Float(0.0), Arg::Float(0.0), Arg::Float(1.0),
+                                      Arg::Float(1.0)]);
+}
+
+#[test]
+fn buffer_content_is_stored() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let buffer = glium::buffer::Buffer::new(&context, &[1u8, 2, 3, 4][..],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+    buffer.slice(1 .. 3).unwrap().write(&[5, 6]);
+
+    assert_eq!(buffer.read().unwrap(), vec![1, 5, 6, 4]);
+}
+
+#[test]
+fn draw_calls() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let vertex_buffer = glium::VertexBuffer::new(&context, &[
+        Vertex { position: [-0.5, -0.5] },
+        Vertex { position: [0.0, 0.5] },
+        Vertex { position: [0.5, -0.5] },
+    ]).unwrap();
+
+    let program = program!(&context,
+        330 => {
+            vertex: "
+                #version 330
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 330
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    backend.clear_calls();
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    frame.draw(&vertex_buffer, &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+               &program, &uniform!{}, &Default::default()).unwrap();
+    frame.finish().unwrap();
+
+    let calls = backend.take_calls();
+    assert_eq!(calls.iter().filter(|c| c.name == "glUseProgram").count(), 1);
+    assert_eq!(calls.iter().filter(|c| c.name == "glBindVertexArray").count(), 1);
+
+    let draw = calls.iter().find(|c| c.name == "glDrawArrays").unwrap();
+    assert_eq!(draw.args, vec![Arg::UInt(0x0004), Arg::Int(0), Arg::Int(3)]);
+
+    assert!(backend.calls().is_empty());
+}
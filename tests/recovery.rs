@@ -0,0 +1,158 @@
+#[macro_use]
+extern crate glium;
+
+use std::cell::Cell;
+use std::io;
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::Context;
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::recovery::{self, Recoverable, RecoveryError};
+use glium::texture::{UncompressedFloatFormat, MipmapsOption};
+use glium::{Api, Version};
+
+mod support;
+
+fn build_backend() -> RecordingBackend {
+    RecordingBackend::new(DriverProfile::default(), (800, 600))
+}
+
+fn build_texture(context: &Rc<Context>) -> Recoverable<glium::Texture2d> {
+    Recoverable::new(context, |context| {
+        glium::Texture2d::empty_with_format(context, UncompressedFloatFormat::U8U8U8U8,
+                                            MipmapsOption::NoMipmap, 16, 16)
+    }).unwrap()
+}
+
+#[test]
+fn resources_are_recreated() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let texture = build_texture(&context);
+    let buffer = Recoverable::with_source(&context, vec![1u32, 2, 3, 4], |context, data| {
+        glium::buffer::Buffer::new(context, &data[..], glium::buffer::BufferType::ArrayBuffer,
+                                   glium::buffer::BufferMode::Default)
+    }).unwrap();
+    let stats = context.resource_stats();
+
+    unsafe { recovery::recover(&context, build_backend()) }.unwrap();
+
+    assert!(texture.is_available() && buffer.is_available());
+    assert_eq!(texture.get().width(), 16);
+    assert_eq!(buffer.get().read().unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(context.resource_stats().total, stats.total);
+    assert!(!context.is_context_lost());
+}
+
+#[test]
+fn dropped_resources_are_not_recreated() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+    let reloads = Rc::new(Cell::new(0));
+
+    let texture = {
+        let reloads = reloads.clone();
+        Recoverable::new(&context, move |context| {
+            reloads.set(reloads.get() + 1);
+            glium::Texture2d::empty(context, 4, 4)
+        }).unwrap()
+    };
+
+    unsafe { recovery::recover(&context, build_backend()) }.unwrap();
+    assert_eq!(reloads.get(), 2);
+
+    drop(texture);
+    unsafe { recovery::recover(&context, build_backend()) }.unwrap();
+    assert_eq!(reloads.get(), 2);
+    assert_eq!(context.resource_stats().total.count, 0);
+}
+
+#[test]
+fn objects_still_alive() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let recoverable = build_texture(&context);
+    let texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+
+    match unsafe { recovery::recover(&context, build_backend()) } {
+        Err(RecoveryError::ObjectsStillAlive(report)) => assert_eq!(report.len(), 1),
+        _ => panic!()
+    }
+    assert!(!recoverable.is_available());
+
+    drop(texture);
+    unsafe { recovery::recover(&context, build_backend()) }.unwrap();
+    assert!(recoverable.is_available());
+}
+
+#[test]
+fn incompatible_version() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+    let _texture = build_texture(&context);
+
+    let backend = RecordingBackend::new(DriverProfile::new(Version(Api::Gl, 3, 0)), (800, 600));
+    match unsafe { recovery::recover(&context, backend) } {
+        Err(RecoveryError::IncompatibleOpenGl(_)) => (),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn reload_failed() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let texture = build_texture(&context);
+    let first = Rc::new(Cell::new(true));
+    let failing = Recoverable::new(&context, move |_| {
+        if first.replace(false) {
+            Ok(())
+        } else {
+            Err(io::Error::other("source file removed"))
+        }
+    }).unwrap();
+
+    match unsafe { recovery::recover(&context, build_backend()) } {
+        Err(RecoveryError::ReloadFailed(errors)) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].to_string(), "source file removed");
+        },
+        _ => panic!()
+    }
+
+    assert!(texture.is_available());
+    assert!(!failing.is_available());
+}
+
+#[test]
+#[should_panic(expected = "couldn't be recreated")]
+fn unavailable_resource() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let recoverable = build_texture(&context);
+    let _texture = glium::Texture2d::empty(&context, 4, 4).unwrap();
+    assert!(unsafe { recovery::recover(&context, build_backend()) }.is_err());
+
+    recoverable.get();
+}
+
+#[test]
+fn real_driver() {
+    let display = support::build_display();
+
+    let data = vec![vec![(255u8, 0u8, 0u8, 255u8); 8]; 8];
+    let texture = Recoverable::with_source(&display, data, |context, data| {
+        glium::Texture2d::new(context, data.clone())
+    }).unwrap();
+
+    support::recover_display(&display).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.get().read();
+    assert_eq!(pixels, vec![vec![(255, 0, 0, 255); 8]; 8]);
+
+    // the default framebuffer and the state cache of the new context work
+    let mut target = display.draw();
+    target.clear_color(0.0, 1.0, 0.0, 1.0);
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
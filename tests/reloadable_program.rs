@@ -0,0 +1,203 @@
+#[macro_use]
+extern crate glium;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use glium::Surface;
+use glium::program::{ProgramCreationError, ReloadError, ReloadableProgram, ShaderType};
+use glium::program::SourcePaths;
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 140
+
+    in vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+const GREEN_FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.0, 1.0, 0.0, 1.0);
+    }
+";
+
+const RED_FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(1.0, 0.0, 0.0, 1.0);
+    }
+";
+
+const INVALID_FRAGMENT_SHADER: &str = "
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = this is an error;
+    }
+";
+
+/// A directory that contains the shader files, which is removed when dropped.
+struct ShaderDirectory(PathBuf);
+
+impl ShaderDirectory {
+    fn new(name: &str) -> ShaderDirectory {
+        let directory = std::env::temp_dir()
+            .join(format!("glium-reloadable-program-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        let directory = ShaderDirectory(directory);
+        directory.write("shader.vert", VERTEX_SHADER, 0);
+        directory.write("shader.frag", GREEN_FRAGMENT_SHADER, 0);
+        directory
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+
+    /// Writes a file whose modification time is `seconds` after the epoch, so that the tests
+    /// don't depend on the resolution of the file system's timestamps.
+    fn write(&self, name: &str, content: &str, seconds: u64) {
+        fs::write(self.path(name), content).unwrap();
+        fs::File::options().write(true).open(self.path(name)).unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)).unwrap();
+    }
+
+    fn paths(&self) -> (PathBuf, PathBuf) {
+        (self.path("shader.vert"), self.path("shader.frag"))
+    }
+}
+
+impl Drop for ShaderDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn source_paths<'a>(vertex_shader: &'a Path, fragment_shader: &'a Path) -> SourcePaths<'a> {
+    SourcePaths {
+        vertex_shader,
+        fragment_shader,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    }
+}
+
+fn draw_color<F>(facade: &F, program: &glium::Program) -> (u8, u8, u8, u8)
+                 where F: glium::backend::Facade + ?Sized
+{
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(facade);
+    let texture = glium::Texture2d::empty(facade, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    pixels[0][0]
+}
+
+#[test]
+fn reloads_modified_files() {
+    let display = support::build_display();
+    let directory = ShaderDirectory::new("reload");
+    let (vertex_shader, fragment_shader) = directory.paths();
+
+    let mut program = ReloadableProgram::new(&display,
+                                             source_paths(&vertex_shader, &fragment_shader))
+                                        .unwrap();
+    assert_eq!(draw_color(&display, &program), (0, 255, 0, 255));
+    assert_eq!(program.path(ShaderType::Fragment), Some(fragment_shader.as_path()));
+    assert_eq!(program.path(ShaderType::Geometry), None);
+
+    // nothing has changed
+    assert!(!program.reload_if_changed());
+
+    directory.write("shader.frag", RED_FRAGMENT_SHADER, 1);
+    assert!(program.reload_if_changed());
+    assert!(program.last_error().is_none());
+    assert_eq!(draw_color(&display, &program), (255, 0, 0, 255));
+    assert!(!program.reload_if_changed());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn keeps_last_good_program() {
+    let display = support::build_display();
+    let directory = ShaderDirectory::new("last-good");
+    let (vertex_shader, fragment_shader) = directory.paths();
+
+    let mut program = ReloadableProgram::new(&display,
+                                             source_paths(&vertex_shader, &fragment_shader))
+                                        .unwrap();
+
+    directory.write("shader.frag", INVALID_FRAGMENT_SHADER, 1);
+    assert!(program.reload_if_changed());
+
+    match program.last_error() {
+        Some(ReloadError::Program(ProgramCreationError::CompilationError(_,
+                                                                         ShaderType::Fragment))) => (),
+        _ => panic!(),
+    };
+    assert!(!program.last_error().unwrap().diagnostics().is_empty());
+    assert_eq!(draw_color(&display, &program), (0, 255, 0, 255));
+
+    // the broken file isn't compiled again until it changes
+    assert!(!program.reload_if_changed());
+
+    directory.write("shader.frag", RED_FRAGMENT_SHADER, 2);
+    assert!(program.reload_if_changed());
+    assert!(program.last_error().is_none());
+    assert_eq!(draw_color(&display, &program), (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn missing_files() {
+    let display = support::build_display();
+    let directory = ShaderDirectory::new("missing");
+    let (vertex_shader, fragment_shader) = directory.paths();
+
+    let missing = directory.path("missing.frag");
+    match ReloadableProgram::new(&display, source_paths(&vertex_shader, &missing)) {
+        Err(ReloadError::Io { ref path, .. }) if *path == missing => (),
+        _ => panic!(),
+    };
+
+    let mut program = ReloadableProgram::new(&display,
+                                             source_paths(&vertex_shader, &fragment_shader))
+                                        .unwrap();
+
+    // editors sometimes remove the file before writing the new one
+    fs::remove_file(&fragment_shader).unwrap();
+    assert!(program.reload_if_changed());
+    match program.last_error() {
+        Some(ReloadError::Io { .. }) => (),
+        _ => panic!(),
+    };
+    assert_eq!(draw_color(&display, &program), (0, 255, 0, 255));
+
+    directory.write("shader.frag", RED_FRAGMENT_SHADER, 0);
+    assert!(program.reload_if_changed());
+    assert_eq!(draw_color(&display, &program), (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,231 @@
+#[macro_use]
+extern crate glium;
+
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::{Context, ResourceKind, ResourceUsage};
+use glium::backend::recording::DriverProfile;
+use glium::texture::{MipmapsOption, UncompressedFloatFormat};
+
+mod support;
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+fn build_program(context: &Rc<Context>) -> glium::Program {
+    program!(context,
+        330 => {
+            vertex: "
+                #version 330
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 330
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap()
+}
+
+#[test]
+fn empty_context() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+    let stats = context.resource_stats();
+
+    assert_eq!(stats.total, ResourceUsage::default());
+    assert_eq!(stats.peak, ResourceUsage::default());
+    assert_eq!(stats.free_video_memory, None);
+}
+
+#[test]
+fn buffers() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let buffer = glium::buffer::Buffer::<[u8]>::empty_unsized(&context,
+                                                             glium::buffer::BufferType::ArrayBuffer,
+                                                             1000,
+                                                             glium::buffer::BufferMode::Default)
+                                                             .unwrap();
+    let index_buffer = glium::IndexBuffer::new(&context, glium::index::PrimitiveType::Points,
+                                               &[0u16, 1, 2]).unwrap();
+
+    let stats = context.resource_stats();
+    assert_eq!(stats.buffers, ResourceUsage { count: 2, bytes: 1006 });
+    assert_eq!(stats.get(ResourceKind::Buffer), stats.buffers);
+    assert_eq!(stats.total, stats.buffers);
+
+    drop(buffer);
+    assert_eq!(context.resource_stats().buffers, ResourceUsage { count: 1, bytes: 6 });
+
+    drop(index_buffer);
+    let stats = context.resource_stats();
+    assert_eq!(stats.buffers, ResourceUsage::default());
+    assert_eq!(stats.peak, ResourceUsage { count: 2, bytes: 1006 });
+}
+
+#[test]
+fn textures_and_render_buffers() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let texture = glium::Texture2d::empty_with_format(&context, UncompressedFloatFormat::U8U8U8U8,
+                                                      MipmapsOption::NoMipmap, 64, 32).unwrap();
+    let mipmapped = glium::Texture2d::empty_with_format(&context,
+                                                        UncompressedFloatFormat::F32,
+                                                        MipmapsOption::EmptyMipmaps, 4, 4)
+                                                        .unwrap();
+    let render_buffer = glium::framebuffer::RenderBuffer::new(&context,
+                                                              UncompressedFloatFormat::U8U8U8U8,
+                                                              16, 16).unwrap();
+
+    let stats = context.resource_stats();
+    // 4x4 + 2x2 + 1x1 texels of 4 bytes
+    assert_eq!(stats.textures, ResourceUsage { count: 2, bytes: 64 * 32 * 4 + 21 * 4 });
+    assert_eq!(stats.render_buffers, ResourceUsage { count: 1, bytes: 16 * 16 * 4 });
+    assert_eq!(stats.total.count, 3);
+
+    drop(texture);
+    drop(mipmapped);
+    drop(render_buffer);
+
+    let stats = context.resource_stats();
+    assert_eq!(stats.total, ResourceUsage::default());
+    assert_eq!(stats.peak.bytes, 64 * 32 * 4 + 21 * 4 + 16 * 16 * 4);
+}
+
+#[test]
+fn programs_and_queries() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let program = build_program(&context);
+    let query = glium::draw_parameters::SamplesPassedQuery::new(&context).unwrap();
+
+    let stats = context.resource_stats();
+    assert_eq!(stats.programs, ResourceUsage { count: 1, bytes: 0 });
+    assert_eq!(stats.queries, ResourceUsage { count: 1, bytes: 0 });
+
+    drop(program);
+    drop(query);
+    assert_eq!(context.resource_stats().total.count, 0);
+}
+
+#[test]
+fn cached_objects() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let vertex_buffer = glium::VertexBuffer::new(&context, &[
+        Vertex { position: [-0.5, -0.5] },
+        Vertex { position: [0.0, 0.5] },
+        Vertex { position: [0.5, -0.5] },
+    ]).unwrap();
+    let program = build_program(&context);
+    let texture = glium::Texture2d::empty(&context, 16, 16).unwrap();
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&context, &texture).unwrap();
+    framebuffer.draw(&vertex_buffer,
+                     &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+                     &program, &uniform!{}, &Default::default()).unwrap();
+
+    let stats = context.resource_stats();
+    assert_eq!(stats.framebuffers.count, 1);
+    assert_eq!(stats.vertex_arrays.count, 1);
+
+    // destroying the texture and the program purges the FBO and the VAO
+    drop(framebuffer);
+    drop(texture);
+    drop(program);
+
+    let stats = context.resource_stats();
+    assert_eq!(stats.framebuffers.count, 0);
+    assert_eq!(stats.vertex_arrays.count, 0);
+    assert_eq!(stats.buffers.count, 1);
+}
+
+#[inline(never)]
+fn load_level(context: &Rc<Context>) -> glium::Texture2d {
+    glium::Texture2d::empty_with_format(context, UncompressedFloatFormat::U8U8U8U8,
+                                        MipmapsOption::NoMipmap, 8, 8).unwrap()
+}
+
+#[test]
+fn leak_report() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+    let before = glium::VertexBuffer::<Vertex>::empty(&context, 4).unwrap();
+
+    context.set_leak_tracking(true);
+    let texture = load_level(&context);
+
+    let report = context.leak_report();
+    assert_eq!(report.len(), 2);
+
+    let buffer = &report.resources[0];
+    assert_eq!(buffer.kind, ResourceKind::Buffer);
+    assert!(buffer.creation_site.is_none() && buffer.backtrace.is_none());
+
+    let leaked = &report.resources[1];
+    assert_eq!(leaked.kind, ResourceKind::Texture);
+    assert_eq!(leaked.bytes, 8 * 8 * 4);
+    assert!(leaked.backtrace.is_some());
+    assert!(leaked.creation_site.as_ref().unwrap().contains("load_level"),
+            "{:?}", leaked.creation_site);
+    assert_eq!(report.created_at("load_level").count(), 1);
+
+    let text = report.to_string();
+    assert!(text.starts_with("2 object(s) alive, 288 bytes"), "{}", text);
+    assert!(text.contains("while leak tracking was disabled"), "{}", text);
+    assert!(text.contains(" (256 bytes), created at "), "{}", text);
+
+    drop(before);
+    drop(texture);
+    assert!(context.leak_report().is_empty());
+}
+
+#[test]
+fn leak_report_finds_cycles() {
+    use std::cell::RefCell;
+
+    struct Node {
+        _texture: glium::Texture2d,
+        next: RefCell<Option<Rc<Node>>>,
+    }
+
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+    context.set_leak_tracking(true);
+
+    {
+        let node = Rc::new(Node { _texture: load_level(&context), next: RefCell::new(None) });
+        *node.next.borrow_mut() = Some(node.clone());
+    }
+
+    let report = context.leak_report();
+    assert_eq!(report.created_at("load_level").count(), 1);
+}
+
+#[test]
+fn texture_leak_across_reloads() {
+    let display = support::build_display();
+
+    let mut textures = Vec::new();
+    for _ in 0 .. 3 {
+        // "leaking" one texture per reload
+        textures.push(glium::Texture2d::empty(&display, 32, 32).unwrap());
+        let _temporary = glium::Texture2d::empty(&display, 32, 32).unwrap();
+    }
+
+    let stats = display.resource_stats();
+    assert_eq!(stats.textures.count, 3);
+    assert!(stats.textures.bytes >= 3 * 32 * 32);
+    assert!(stats.peak.count >= 4);
+
+    display.assert_no_error(None);
+}
@@ -0,0 +1,143 @@
+#[macro_use]
+extern crate glium;
+
+use glium::program::{Diagnostic, Severity, ShaderIncludes, SourceCode};
+
+mod support;
+
+fn diagnostic(file: Option<&str>, line: Option<u32>, column: Option<u32>, severity: Severity,
+              message: &str) -> Diagnostic
+{
+    Diagnostic {
+        file: file.map(|file| file.to_owned()),
+        line,
+        column,
+        severity,
+        message: message.to_owned(),
+    }
+}
+
+#[test]
+fn parse_mesa_log() {
+    let log = "0:12(5): error: `foo' undeclared\n\
+               /lib/a.glsl:3(1): warning: unused variable\n\
+               error: vertex shader output `v' is not written\n";
+
+    assert_eq!(Diagnostic::parse_log(log), vec![
+        diagnostic(None, Some(12), Some(5), Severity::Error, "`foo' undeclared"),
+        diagnostic(Some("/lib/a.glsl"), Some(3), Some(1), Severity::Warning, "unused variable"),
+        diagnostic(None, None, None, Severity::Error, "vertex shader output `v' is not written"),
+    ]);
+}
+
+#[test]
+fn parse_nvidia_log() {
+    let log = "0(12) : error C1008: undefined variable \"foo\"\n\
+               2(3) : warning C7022: unrecognized profile specifier \"bar\"\n";
+
+    assert_eq!(Diagnostic::parse_log(log), vec![
+        diagnostic(None, Some(12), None, Severity::Error, "undefined variable \"foo\""),
+        diagnostic(Some("2"), Some(3), None, Severity::Warning,
+                   "unrecognized profile specifier \"bar\""),
+    ]);
+}
+
+#[test]
+fn parse_amd_log() {
+    let log = "ERROR: 0:12: 'foo' : undeclared identifier\n\
+               WARNING: /a.glsl:4: 'bar' : unused\n\
+               ERROR: 1 compilation errors.  No code generated.\n\n";
+
+    assert_eq!(Diagnostic::parse_log(log), vec![
+        diagnostic(None, Some(12), None, Severity::Error, "'foo' : undeclared identifier"),
+        diagnostic(Some("/a.glsl"), Some(4), None, Severity::Warning, "'bar' : unused"),
+    ]);
+}
+
+#[test]
+fn unrecognized_lines_are_kept() {
+    let log = "Fragment info\n-------------\n0:3(1): error: foo\n  more details\n";
+
+    assert_eq!(Diagnostic::parse_log(log), vec![
+        diagnostic(None, None, None, Severity::Info, "Fragment info\n-------------"),
+        diagnostic(None, Some(3), Some(1), Severity::Error, "foo\nmore details"),
+    ]);
+}
+
+#[test]
+fn format_with_carets() {
+    let source = "#version 140\nvoid main() {\n    foo = 1;\n}\n";
+
+    let formatted = diagnostic(None, Some(3), Some(5), Severity::Error, "`foo' undeclared")
+        .format_with_source(source, None);
+    assert_eq!(formatted, "error: `foo' undeclared\n  \
+                           --> <source>:3:5\n  \
+                           |\n\
+                           3 |     foo = 1;\n  \
+                           |     ^\n");
+
+    let mut includes = ShaderIncludes::new();
+    includes.insert("a.glsl", "float a;\n  bad line;  \n");
+
+    let formatted = diagnostic(Some("/a.glsl"), Some(2), None, Severity::Warning, "bad")
+        .format_with_source(source, Some(&includes));
+    assert_eq!(formatted, "warning: bad\n  \
+                           --> /a.glsl:2\n  \
+                           |\n\
+                           2 |   bad line;  \n  \
+                           |   ^^^^^^^^^\n");
+
+    // the line doesn't exist
+    let formatted = diagnostic(None, Some(30), None, Severity::Error, "foo")
+        .format_with_source(source, None);
+    assert_eq!(formatted, "error: foo\n  --> <source>:30\n");
+}
+
+#[test]
+fn program_creation_diagnostics() {
+    let display = support::build_display();
+
+    let mut includes = ShaderIncludes::new();
+    includes.insert("broken.glsl", "float a;\nthis is an error;\n");
+
+    let source = |fragment_shader| SourceCode {
+        vertex_shader: "
+            #version 140
+            void main() {
+                gl_Position = vec4(0.0);
+            }
+        ",
+        fragment_shader,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    };
+
+    let err = match glium::Program::with_includes(&display, source("
+        #version 140
+        #include \"broken.glsl\"
+        out vec4 f_color;
+        void main() { f_color = vec4(a); }
+    "), &includes) {
+        Err(err) => err,
+        Ok(_) => panic!(),
+    };
+
+    let diagnostics = err.diagnostics();
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics.iter().any(|diagnostic| {
+        diagnostic.file.as_deref() == Some("/broken.glsl") && diagnostic.line == Some(2) &&
+            diagnostic.severity == Severity::Error
+    }), "{:?}", diagnostics);
+
+    let err = match glium::Program::with_includes(&display, source("\n#include <missing.glsl>"),
+                                                  &includes)
+    {
+        Err(err) => err,
+        Ok(_) => panic!(),
+    };
+
+    assert_eq!(err.diagnostics(), vec![
+        diagnostic(None, Some(2), None, Severity::Error, "Included file `missing.glsl` not found"),
+    ]);
+}
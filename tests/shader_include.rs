@@ -0,0 +1,184 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::program::{IncludeError, ShaderIncludes, SourceCode};
+
+mod support;
+
+const VERTEX_SHADER: &str = "
+    #version 140
+
+    in vec2 position;
+
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+fn source_code(fragment_shader: &str) -> SourceCode<'_> {
+    SourceCode {
+        vertex_shader: VERTEX_SHADER,
+        fragment_shader,
+        geometry_shader: None,
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+    }
+}
+
+#[test]
+fn nested_includes_are_expanded() {
+    let mut includes = ShaderIncludes::new();
+    includes.insert("lib/color.glsl", "vec4 color() { return vec4(1.0); }");
+    includes.insert("lib/util.glsl", "#include \"color.glsl\"\nvec4 util() { return color(); }");
+
+    let expanded = includes.expand("#version 330\n#include <lib/util.glsl>\nvoid main() {}\n")
+                           .unwrap();
+
+    assert_eq!(expanded.files, vec!["/lib/util.glsl", "/lib/color.glsl"]);
+    assert_eq!(expanded.source, "#version 330\n\
+                                 #line 1 1\n\
+                                 #line 1 2\n\
+                                 vec4 color() { return vec4(1.0); }\n\
+                                 #line 2 1\n\
+                                 vec4 util() { return color(); }\n\
+                                 #line 3 0\n\
+                                 void main() {}\n");
+}
+
+#[test]
+fn line_directives_follow_version() {
+    let mut includes = ShaderIncludes::new();
+    includes.insert("a.glsl", "float a;");
+
+    // before GLSL 3.30, `#line N` sets the number of the directive's own line
+    let expanded = includes.expand("#version 140\n#include \"a.glsl\"\n").unwrap();
+    assert_eq!(expanded.source, "#version 140\n#line 0 1\nfloat a;\n#line 2 0\n");
+
+    let expanded = includes.expand("#version 300 es\n#include \"a.glsl\"\n").unwrap();
+    assert_eq!(expanded.source, "#version 300 es\n#line 1 1\nfloat a;\n#line 3 0\n");
+}
+
+#[test]
+fn include_guards() {
+    let mut includes = ShaderIncludes::new();
+    includes.insert("once.glsl", "#pragma once\n#include \"guarded.glsl\"\nfloat once;");
+    includes.insert("guarded.glsl", "// comment\n#ifndef GUARDED\n#define GUARDED\n\
+                                     #include \"once.glsl\"\nfloat guarded;\n#endif\n");
+
+    let expanded = includes.expand("#version 330\n#include \"once.glsl\"\n\
+                                    #include \"guarded.glsl\"\n#include \"once.glsl\"\n")
+                           .unwrap();
+
+    assert_eq!(expanded.source.matches("float once;").count(), 1);
+    assert_eq!(expanded.source.matches("float guarded;").count(), 1);
+    assert!(!expanded.source.contains("#pragma once"));
+}
+
+#[test]
+fn include_errors() {
+    let mut includes = ShaderIncludes::new();
+    includes.insert("a.glsl", "#include \"b.glsl\"");
+    includes.insert("b.glsl", "\n#include \"a.glsl\"");
+    includes.insert("c.glsl", "/* #include \"missing.glsl\" */\n#include \"../missing.glsl\"");
+
+    assert_eq!(includes.expand("#include \"a.glsl\"").unwrap_err(), IncludeError::Recursive {
+        path: "/a.glsl".to_owned(),
+        file: Some("/b.glsl".to_owned()),
+        line: 2,
+    });
+
+    assert_eq!(includes.expand("\n#include <c.glsl>").unwrap_err(), IncludeError::NotFound {
+        path: "../missing.glsl".to_owned(),
+        file: Some("/c.glsl".to_owned()),
+        line: 2,
+    });
+
+    assert_eq!(includes.expand("#include a.glsl").unwrap_err(), IncludeError::InvalidDirective {
+        file: None,
+        line: 1,
+    });
+}
+
+#[test]
+fn driver_logs_are_remapped() {
+    let mut includes = ShaderIncludes::new();
+    includes.insert("a.glsl", "float a;");
+    let expanded = includes.expand("#include \"a.glsl\"").unwrap();
+
+    assert_eq!(expanded.remap_log("1:3(10): error: foo\n0:4(1): error: bar\n"),
+               "/a.glsl:3(10): error: foo\n0:4(1): error: bar\n");
+    assert_eq!(expanded.remap_log("1(3) : error C0000: foo"), "/a.glsl(3) : error C0000: foo");
+    assert_eq!(expanded.remap_log("ERROR: 1:3: foo\nERROR: 1 compilation errors."),
+               "ERROR: /a.glsl:3: foo\nERROR: 1 compilation errors.");
+}
+
+#[test]
+fn program_with_includes() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+
+    let mut includes = ShaderIncludes::new();
+    includes.insert("colors/green.glsl", "#pragma once\nconst vec4 GREEN = vec4(0.0, 1.0, 0.0, 1.0);");
+    includes.insert("colors.glsl", "#include \"colors/green.glsl\"\n\
+                                    vec4 color() { return GREEN; }");
+
+    let program = glium::Program::with_includes(&display, source_code("
+        #version 140
+
+        #include \"colors/green.glsl\"
+        #include <colors.glsl>
+
+        out vec4 f_color;
+
+        void main() {
+            f_color = color();
+        }
+    "), &includes).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn compilation_errors_refer_to_files() {
+    let display = support::build_display();
+
+    let mut includes = ShaderIncludes::new();
+    includes.insert("broken.glsl", "float a;\nthis is an error;\n");
+
+    let fragment_shader = "
+        #version 140
+        #include \"broken.glsl\"
+        out vec4 f_color;
+        void main() { f_color = vec4(a); }
+    ";
+
+    match glium::Program::with_includes(&display, source_code(fragment_shader), &includes) {
+        Err(glium::CompilationError(log, _)) => assert!(log.contains("/broken.glsl"), "{}", log),
+        _ => panic!(),
+    };
+
+    // same thing with the includes expanded on the CPU
+    let expanded = includes.expand(fragment_shader).unwrap();
+    match glium::Program::new(&display, source_code(&expanded.source)) {
+        Err(glium::CompilationError(log, _)) => {
+            assert!(expanded.remap_log(&log).contains("/broken.glsl"), "{}", log);
+        },
+        _ => panic!(),
+    };
+
+    match glium::Program::with_includes(&display, source_code("#include \"missing.glsl\""),
+                                        &includes)
+    {
+        Err(glium::program::ProgramCreationError::IncludeError(IncludeError::NotFound { .. })) => (),
+        _ => panic!(),
+    };
+}
@@ -0,0 +1,249 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::backend::recording::{Arg, DriverProfile};
+use glium::program::{BlockLayout, ProgramCreationError, ProgramCreationInput};
+use glium::program::{SpirvEntryPoint, SpirvProgram};
+use glium::uniforms::UniformType;
+use glium::vertex::AttributeType;
+use glium::{Api, Version};
+
+mod support;
+
+/// Encodes an instruction.
+fn inst(opcode: u32, operands: &[u32]) -> Vec<u32> {
+    let mut words = vec![((operands.len() as u32 + 1) << 16) | opcode];
+    words.extend_from_slice(operands);
+    words
+}
+
+/// Encodes a nul-terminated literal string.
+fn string(s: &str) -> Vec<u32> {
+    let mut bytes = s.as_bytes().to_vec();
+    bytes.resize((bytes.len() / 4 + 1) * 4, 0);
+    bytes.chunks(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+fn name(id: u32, name: &str) -> Vec<u32> {
+    inst(5, &[&[id][..], &string(name)].concat())
+}
+
+fn member_name(ty: u32, member: u32, name: &str) -> Vec<u32> {
+    inst(6, &[&[ty, member][..], &string(name)].concat())
+}
+
+/// Builds a module equivalent to:
+///
+/// ```glsl
+/// // vertex shader
+/// layout(location = 0) in vec2 position;
+///
+/// void main() {
+///     gl_Position = vec4(position, 0.0, 1.0);
+/// }
+///
+/// // fragment shader
+/// layout(location = 2) uniform vec4 tint;
+/// layout(binding = 1) uniform Parameters {
+///     vec4 unused;
+///     vec4 offset;
+/// } params;
+/// layout(constant_id = 3) const float intensity = 0.0;
+///
+/// layout(location = 0) out vec4 color;
+///
+/// void main() {
+///     color = tint * intensity + params.offset;
+/// }
+/// ```
+fn build_module() -> Vec<u8> {
+    let module = [
+        vec![0x07230203, 0x00010000, 0, 35, 0],
+        inst(17, &[1]),                                     // OpCapability Shader
+        inst(14, &[0, 1]),                                  // OpMemoryModel Logical GLSL450
+        inst(15, &[&[0, 10][..], &string("main"), &[8, 9]].concat()),
+        inst(15, &[&[4, 23][..], &string("main"), &[13]].concat()),
+        inst(16, &[23, 8]),                                 // OpExecutionMode OriginLowerLeft
+
+        name(8, "position"),
+        name(13, "color"),
+        name(15, "tint"),
+        name(16, "Parameters"),
+        member_name(16, 0, "unused"),
+        member_name(16, 1, "offset"),
+        name(18, "params"),
+        name(19, "intensity"),
+
+        inst(71, &[8, 30, 0]),                              // Location 0
+        inst(71, &[9, 11, 0]),                              // BuiltIn Position
+        inst(71, &[13, 30, 0]),                             // Location 0
+        inst(71, &[15, 30, 2]),                             // Location 2
+        inst(71, &[16, 2]),                                 // Block
+        inst(72, &[16, 0, 35, 0]),                          // Offset 0
+        inst(72, &[16, 1, 35, 16]),                         // Offset 16
+        inst(71, &[18, 33, 1]),                             // Binding 1
+        inst(71, &[19, 1, 3]),                              // SpecId 3
+
+        inst(19, &[1]),                                     // void
+        inst(33, &[2, 1]),                                  // void()
+        inst(22, &[3, 32]),                                 // float
+        inst(23, &[4, 3, 2]),                               // vec2
+        inst(23, &[5, 3, 4]),                               // vec4
+        inst(32, &[6, 1, 4]),                               // Input vec2*
+        inst(32, &[7, 3, 5]),                               // Output vec4*
+        inst(59, &[6, 8, 1]),                               // position
+        inst(59, &[7, 9, 3]),                               // gl_Position
+        inst(43, &[3, 11, 0.0f32.to_bits()]),
+        inst(43, &[3, 12, 1.0f32.to_bits()]),
+        inst(59, &[7, 13, 3]),                              // color
+        inst(32, &[14, 0, 5]),                              // UniformConstant vec4*
+        inst(59, &[14, 15, 0]),                             // tint
+        inst(30, &[16, 5, 5]),                              // Parameters
+        inst(32, &[17, 2, 16]),                             // Uniform Parameters*
+        inst(59, &[17, 18, 2]),                             // params
+        inst(50, &[3, 19, 0.0f32.to_bits()]),               // intensity
+        inst(21, &[20, 32, 1]),                             // int
+        inst(43, &[20, 21, 1]),
+        inst(32, &[22, 2, 5]),                              // Uniform vec4*
+
+        // vertex shader
+        inst(54, &[1, 10, 0, 2]),
+        inst(248, &[24]),
+        inst(61, &[4, 25, 8]),
+        inst(81, &[3, 26, 25, 0]),
+        inst(81, &[3, 27, 25, 1]),
+        inst(80, &[5, 28, 26, 27, 11, 12]),
+        inst(62, &[9, 28]),
+        inst(253, &[]),
+        inst(56, &[]),
+
+        // fragment shader
+        inst(54, &[1, 23, 0, 2]),
+        inst(248, &[29]),
+        inst(61, &[5, 30, 15]),
+        inst(142, &[5, 31, 30, 19]),
+        inst(65, &[22, 32, 18, 21]),
+        inst(61, &[5, 33, 32]),
+        inst(129, &[5, 34, 31, 33]),
+        inst(62, &[13, 34]),
+        inst(253, &[]),
+        inst(56, &[]),
+    ].concat();
+
+    module.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[derive(Copy, Clone)]
+struct Parameters {
+    unused: [f32; 4],
+    offset: [f32; 4],
+}
+
+implement_uniform_block!(Parameters, unused, offset);
+
+/// Builds a program from the module, with `intensity` set to `intensity` if it is `Some`.
+fn build_program<F>(facade: &F, module: &[u8], intensity: Option<f32>)
+                    -> Result<glium::Program, ProgramCreationError>
+    where F: glium::backend::Facade + ?Sized
+{
+    let constants = intensity.map(|intensity| vec![(3, intensity.to_bits())]).unwrap_or_default();
+
+    glium::Program::new(facade, ProgramCreationInput::SpirV(SpirvProgram::from_vs_and_fs(
+        SpirvEntryPoint::new(module, "main"),
+        SpirvEntryPoint::new(module, "main").specialization_constants(&constants),
+    )))
+}
+
+#[test]
+fn spirv_reflection() {
+    let display = support::build_display();
+    let module = build_module();
+
+    // `tint` is optimized out if `intensity` is 0
+    let program = match build_program(&display, &module, Some(1.0)) {
+        Err(ProgramCreationError::CompilationNotSupported) => return,
+        p => p.unwrap(),
+    };
+
+    let tint = program.get_uniform("tint").unwrap();
+    assert_eq!(tint.location, 2);
+    assert_eq!(tint.ty, UniformType::FloatVec4);
+    assert!(program.get_uniform("intensity").is_none());
+
+    let attribute = program.get_attribute("position").unwrap();
+    assert_eq!(attribute.location, 0);
+    assert_eq!(attribute.ty, AttributeType::F32F32);
+
+    let block = program.get_uniform_blocks().get("Parameters").unwrap();
+    assert_eq!(block.initial_binding, 1);
+
+    let members = match block.layout {
+        BlockLayout::Struct { ref members } => members,
+        _ => panic!(),
+    };
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[1].0, "offset");
+    match members[1].1 {
+        BlockLayout::BasicType { ty: UniformType::FloatVec4, offset_in_buffer: 16 } => (),
+        _ => panic!(),
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn spirv_specialization_constants() {
+    let display = support::build_display();
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let module = build_module();
+
+    let programs = [None, Some(1.0)].iter()
+                                    .map(|&intensity| build_program(&display, &module, intensity))
+                                    .collect::<Result<Vec<_>, _>>();
+
+    let programs = match programs {
+        Err(ProgramCreationError::CompilationNotSupported) => return,
+        p => p.unwrap(),
+    };
+
+    let buffer = glium::uniforms::UniformBuffer::new(&display, Parameters {
+        unused: [0.0; 4],
+        offset: [0.0, 0.0, 1.0, 1.0],
+    }).unwrap();
+
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+
+    for (program, expected) in programs.iter().zip([(0, 0, 255, 255), (255, 0, 255, 255)]) {
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        texture.as_surface().draw(&vertex_buffer, &index_buffer, program,
+                                  &uniform!{ tint: [1.0f32, 0.0, 0.0, 0.0], Parameters: &buffer },
+                                  &Default::default()).unwrap();
+
+        let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(pixels, vec![vec![expected; 4]; 4]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn specialization_constants_are_passed_to_the_driver() {
+    let (context, backend) =
+        support::build_recording_context(DriverProfile::new(Version(Api::Gl, 4, 6)));
+
+    let module = build_module();
+
+    backend.clear_calls();
+    let program = build_program(&context, &module, Some(0.5)).unwrap();
+
+    let counts = backend.calls().iter().filter(|call| call.name == "glSpecializeShader")
+                        .map(|call| call.args[2].clone())
+                        .collect::<Vec<_>>();
+    assert_eq!(counts, vec![Arg::UInt(0), Arg::UInt(1)]);
+
+    // the backend doesn't report any variable, so the attributes come from the module and the
+    // uniforms are considered inactive
+    assert_eq!(program.get_attribute("position").unwrap().location, 0);
+    assert!(program.get_uniform("tint").is_none());
+}
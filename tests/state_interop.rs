@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::backend::StateGroup;
+use glium::backend::recording::DriverProfile;
+use glium::gl;
+
+mod support;
+
+#[test]
+fn exec_raw_calls_are_recorded() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    backend.clear_calls();
+
+    let value = unsafe {
+        context.exec_raw(&[StateGroup::Capabilities], |gl| {
+            gl.Enable(gl::BLEND);
+            5
+        })
+    };
+
+    assert_eq!(value, 5);
+    assert_eq!(support::count_calls(&backend, "glEnable"), 1);
+    assert!(support::count_calls(&backend, "glIsEnabled") >= 1);
+}
+
+#[test]
+fn exec_raw_only_queries_given_groups() {
+    let (context, backend) = support::build_recording_context(DriverProfile::default());
+    backend.clear_calls();
+
+    unsafe {
+        context.exec_raw(&[StateGroup::Clear], |gl| {
+            gl.ClearColor(0.0, 0.0, 1.0, 1.0);
+        });
+    }
+
+    assert_eq!(support::count_calls(&backend, "glIsEnabled"), 0);
+    assert_eq!(support::count_calls(&backend, "glGetIntegerv"), 1);
+    assert_eq!(support::count_calls(&backend, "glGetFloatv"), 2);
+}
+
+#[test]
+fn invalidated_state_is_queried() {
+    let mut profile = DriverProfile::default();
+    profile.limits.insert(gl::COLOR_CLEAR_VALUE, vec![0, 0, 1, 1]);
+    let (context, backend) = support::build_recording_context(profile);
+
+    context.invalidate_state_groups(&[StateGroup::Clear]);
+    backend.clear_calls();
+
+    // the cache contains the queried clear color
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    frame.clear_color(0.0, 0.0, 1.0, 1.0);
+    frame.finish().unwrap();
+
+    assert_eq!(support::count_calls(&backend, "glClearColor"), 0);
+    assert_eq!(support::count_calls(&backend, "glClear"), 1);
+}
+
+#[test]
+fn exec_raw_resynchronizes() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let texture = glium::Texture2d::empty(&display, 16, 16).unwrap();
+    texture.as_surface().clear_color(0.0, 1.0, 0.0, 1.0);
+
+    unsafe {
+        display.exec_raw(StateGroup::ALL, |gl| {
+            let mut buffer = 0;
+            gl.GenBuffers(1, &mut buffer);
+            gl.BindBuffer(gl::ARRAY_BUFFER, buffer);
+            gl.Enable(gl::BLEND);
+            gl.Enable(gl::SCISSOR_TEST);
+            gl.Scissor(0, 0, 1, 1);
+            gl.ClearColor(1.0, 0.0, 0.0, 1.0);
+            gl.ColorMask(gl::FALSE, gl::TRUE, gl::TRUE, gl::TRUE);
+            gl.ActiveTexture(gl::TEXTURE3);
+            gl.BindTexture(gl::TEXTURE_2D, 0);
+            gl.DeleteBuffers(1, &buffer);
+        });
+    }
+
+    // the state verification panics if the cache doesn't match
+    let mut target = display.draw();
+    target.clear_color(0.0, 0.0, 1.0, 1.0);
+    target.finish().unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 16]; 16]);
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                uniform sampler2D tex;
+                out vec4 color;
+                void main() {
+                    color = texture(tex, vec2(0.5, 0.5));
+                }
+            ",
+        },
+    ).unwrap();
+
+    let output = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    output.as_surface().draw(&vertex_buffer, &index_buffer, &program,
+                             &uniform!{ tex: &texture }, &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 4]; 4]);
+
+    display.assert_no_error(None);
+}
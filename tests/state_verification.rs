@@ -0,0 +1,219 @@
+#[macro_use]
+extern crate glium;
+
+use glium::Surface;
+use glium::index::PrimitiveType;
+
+mod support;
+
+#[test]
+fn draw_matches_state_cache() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 0.5);
+                }
+            ",
+        },
+        110 => {
+            vertex: "
+                #version 110
+                attribute vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 0.5);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let params = glium::DrawParameters {
+        blend: glium::Blend::alpha_blending(),
+        depth: glium::Depth {
+            test: glium::DepthTest::IfLessOrEqual,
+            write: true,
+            .. Default::default()
+        },
+        stencil: glium::draw_parameters::Stencil {
+            test_clockwise: glium::StencilTest::IfEqual { mask: 0xff },
+            reference_value_clockwise: 1,
+            .. Default::default()
+        },
+        viewport: Some(glium::Rect { left: 2, bottom: 2, width: 60, height: 60 }),
+        scissor: Some(glium::Rect { left: 4, bottom: 4, width: 20, height: 20 }),
+        .. Default::default()
+    };
+
+    let mut target = display.draw();
+    target.clear_all((0.0, 0.0, 0.0, 0.0), 1.0, 0);
+    target.draw(&vertex_buffer, &index_buffer, &program, &uniform!{}, &params).unwrap();
+    target.draw(&vertex_buffer, &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                &program, &uniform!{}, &Default::default()).unwrap();
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+/// Loads `glEnable` through EGL, to modify the state without glium knowing about it.
+#[cfg(feature = "test_headless")]
+fn raw_enable() -> extern "system" fn(u32) {
+    use std::ffi::CStr;
+
+    type GetProcAddressFn = unsafe extern "C" fn(*const libc::c_char) -> *const libc::c_void;
+
+    unsafe {
+        let egl = libc::dlopen(CStr::from_bytes_with_nul(b"libEGL.so.1\0").unwrap().as_ptr(),
+                               libc::RTLD_NOW);
+        assert!(!egl.is_null());
+        let get_proc_address = libc::dlsym(egl, b"eglGetProcAddress\0".as_ptr() as *const _);
+        let get_proc_address: GetProcAddressFn = std::mem::transmute(get_proc_address);
+        std::mem::transmute(get_proc_address(b"glEnable\0".as_ptr() as *const _))
+    }
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+#[should_panic(expected = "GL_BLEND: cached false, actual true")]
+fn desync_is_detected() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let enable = raw_enable();
+
+    // modifying the state behind glium's back
+    unsafe { display.exec_in_context(|| enable(0x0BE2 /* GL_BLEND */)) };
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+#[should_panic(expected = "panic during the operation")]
+fn desync_is_not_checked_while_panicking() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let enable = raw_enable();
+
+    // the state cache is out of date when the operation panics
+    unsafe {
+        display.exec_in_context::<(), _>(|| {
+            enable(0x0BE2 /* GL_BLEND */);
+            panic!("panic during the operation");
+        })
+    };
+}
+
+#[test]
+fn buffer_transfer_matches_state_cache() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+            in vec2 position;
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 140
+            uniform Block {
+                vec4 tint;
+            };
+            out vec4 color;
+            void main() {
+                color = tint;
+            }
+        ",
+        None)
+    {
+        Err(glium::CompilationError(..)) => return,
+        p => p.unwrap(),
+    };
+
+    #[derive(Copy, Clone)]
+    struct Block {
+        tint: [f32; 4],
+    }
+
+    implement_uniform_block!(Block, tint);
+
+    // binding the buffer to the uniform buffer bind points before removing it from the context
+    let buffer = glium::buffer::Buffer::new(&display, &Block { tint: [1.0, 0.0, 0.0, 1.0] },
+                                            glium::buffer::BufferType::UniformBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program,
+                              &uniform!{ Block: &buffer }, &Default::default()).unwrap();
+
+    let buffer = buffer.into_transfer().adopt(&display);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program,
+                              &uniform!{ Block: &buffer }, &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn texture_transfer_matches_state_cache() {
+    let display = support::build_display();
+    display.set_state_verification(true);
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                uniform sampler2D tex;
+                out vec4 color;
+                void main() {
+                    color = texture(tex, vec2(0.5, 0.5));
+                }
+            ",
+        },
+    ).unwrap();
+
+    // binding the texture to a texture unit before removing it from the context
+    let source = support::build_unicolor_texture2d(&display, 1.0, 0.0, 0.0);
+    let texture = glium::Texture2d::empty(&display, 4, 4).unwrap();
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{ tex: &source },
+                              &Default::default()).unwrap();
+
+    let source = source.into_transfer().adopt(&display);
+    texture.as_surface().draw(&vertex_buffer, &index_buffer, &program, &uniform!{ tex: &source },
+                              &Default::default()).unwrap();
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
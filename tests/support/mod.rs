@@ -6,10 +6,13 @@ Test supports module.
 #![allow(dead_code)]
 
 use glium::{self, glutin};
-use glium::backend::Facade;
+use glium::backend::{Context, Facade};
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::debug::DebugCallbackBehavior;
 use glium::index::PrimitiveType;
 
 use std::env;
+use std::rc::Rc;
 
 /// Builds a display for tests.
 #[cfg(not(feature = "test_headless"))]
@@ -24,21 +27,21 @@ pub fn build_display() -> glium::Display {
 }
 
 /// Builds a headless display for tests.
+///
+/// Doesn't require any window system, which allows running the tests on machines that only
+/// have a software rasterizer.
 #[cfg(feature = "test_headless")]
-pub fn build_display() -> glium::HeadlessRenderer {
-    let version = parse_version();
-    let hrb = glutin::HeadlessRendererBuilder::new(1024, 768)
-        .with_gl_debug_flag(true)
-        .with_gl(version)
-        .build()
-        .unwrap();
-    glium::HeadlessRenderer::new(hrb).unwrap()
+pub fn build_display() -> glium::backend::egl_surfaceless::EglSurfaceless {
+    let version = parse_headless_version();
+    glium::backend::egl_surfaceless::EglSurfaceless::with_debug_context((1024, 768), version,
+        glium::debug::DebugCallbackBehavior::DebugMessageOnError).unwrap()
 }
 
 /// Rebuilds an existing display.
 ///
 /// In real applications this is used for things such as switching to fullscreen. Some things are
 /// invalidated during a rebuild, and this has to be handled by glium.
+#[cfg(not(feature = "test_headless"))]
 pub fn rebuild_display(display: &glium::Display) {
     let version = parse_version();
     let event_loop = glutin::event_loop::EventLoop::new();
@@ -49,6 +52,61 @@ pub fn rebuild_display(display: &glium::Display) {
     display.rebuild(wb, cb, &event_loop).unwrap();
 }
 
+/// Rebuilds an existing headless display.
+#[cfg(feature = "test_headless")]
+pub fn rebuild_display(display: &glium::backend::egl_surfaceless::EglSurfaceless) {
+    display.rebuild().unwrap();
+}
+
+/// Replaces the OpenGL context of an existing display with a new one that doesn't share its
+/// objects, as after a context loss.
+#[cfg(not(feature = "test_headless"))]
+pub fn recover_display(display: &glium::Display) -> Result<(), glium::recovery::RecoveryError> {
+    let version = parse_version();
+    let event_loop = glutin::event_loop::EventLoop::new();
+    let wb = glutin::window::WindowBuilder::new().with_visible(false);
+    let cb = glutin::ContextBuilder::new()
+        .with_gl_debug_flag(true)
+        .with_gl(version);
+    display.recover(wb, cb, &event_loop)
+}
+
+/// Replaces the OpenGL context of an existing headless display with a new one that doesn't
+/// share its objects, as after a context loss.
+#[cfg(feature = "test_headless")]
+pub fn recover_display(display: &glium::backend::egl_surfaceless::EglSurfaceless)
+                       -> Result<(), glium::recovery::RecoveryError>
+{
+    display.recover()
+}
+
+/// Builds a context on top of a recording backend that emulates the given driver.
+///
+/// The backend is returned as well, so that tests can inspect the calls made by glium.
+pub fn build_recording_context(profile: DriverProfile) -> (Rc<Context>, Rc<RecordingBackend>) {
+    let backend = Rc::new(RecordingBackend::new(profile, (800, 600)));
+    let context = unsafe {
+        Context::new(backend.clone(), true, DebugCallbackBehavior::Ignore)
+    }.unwrap();
+    (context, backend)
+}
+
+/// Returns the number of calls to the OpenGL function `name` recorded by the backend.
+pub fn count_calls(backend: &RecordingBackend, name: &str) -> usize {
+    backend.calls().iter().filter(|call| call.name == name).count()
+}
+
+#[cfg(feature = "test_headless")]
+fn parse_headless_version() -> Option<glium::Version> {
+    match parse_version() {
+        glutin::GlRequest::Specific(glutin::Api::OpenGl, (major, minor)) =>
+            Some(glium::Version(glium::Api::Gl, major, minor)),
+        glutin::GlRequest::Specific(_, (major, minor)) =>
+            Some(glium::Version(glium::Api::GlEs, major, minor)),
+        _ => None,
+    }
+}
+
 fn parse_version() -> glutin::GlRequest {
     match env::var("GLIUM_GL_VERSION") {
         Ok(version) => {
@@ -0,0 +1,261 @@
+#[macro_use]
+extern crate glium;
+
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use glium::Surface;
+use glium::backend::{Context, Facade};
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::trace::{self, Command, Trace, TraceReadError, Value};
+
+mod support;
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+const VERTEX_SHADER: &str = "
+    #version 330
+    in vec2 position;
+    void main() {
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+";
+
+const FRAGMENT_SHADER: &str = "
+    #version 330
+    out vec4 color;
+    void main() {
+        color = vec4(1.0, 0.0, 0.0, 1.0);
+    }
+";
+
+fn draw_frame(context: &Rc<Context>) {
+    let vertex_buffer = glium::VertexBuffer::new(context, &[
+        Vertex { position: [-0.5, -0.5] },
+        Vertex { position: [0.0, 0.5] },
+        Vertex { position: [0.5, -0.5] },
+    ]).unwrap();
+
+    let program = glium::Program::from_source(context, VERTEX_SHADER, FRAGMENT_SHADER, None)
+                                 .unwrap();
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    frame.clear_color(0.0, 0.0, 0.0, 1.0);
+    frame.draw(&vertex_buffer, &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+               &program, &uniform!{}, &Default::default()).unwrap();
+    frame.finish().unwrap();
+}
+
+fn capture() -> Trace {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let path = std::env::temp_dir().join(format!("glium-trace-{}.bin", std::process::id()));
+    context.start_trace(File::create(&path).unwrap()).unwrap();
+    assert!(context.is_tracing());
+    draw_frame(&context);
+    context.stop_trace().unwrap();
+    assert!(!context.is_tracing());
+
+    let trace = Trace::read(File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    trace
+}
+
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn payloads_are_captured() {
+    let trace = capture();
+
+    let buffer_data = trace.calls().find(|c| c.name == "glBufferData").unwrap();
+    let vertices = [-0.5f32, -0.5, 0.0, 0.5, 0.5, -0.5].iter()
+                       .flat_map(|v| v.to_ne_bytes()).collect::<Vec<_>>();
+    assert_eq!(buffer_data.args[2], Value::Data(vertices));
+
+    let sources = trace.calls().filter(|c| c.name == "glShaderSource").collect::<Vec<_>>();
+    assert_eq!(sources.len(), 2);
+    match sources[0].args[2] {
+        Value::Data(ref data) => assert!(contains(data, b"gl_Position = vec4(position")),
+        ref value => panic!("{:?}", value),
+    }
+
+    assert!(trace.calls().any(|c| c.name == "glDrawArrays"));
+    assert_eq!(trace.commands.iter().filter(|c| **c == Command::SwapBuffers).count(), 1);
+}
+
+#[test]
+fn nothing_is_recorded_after_stop() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let path = std::env::temp_dir().join(format!("glium-trace-stop-{}.bin", std::process::id()));
+    context.start_trace(File::create(&path).unwrap()).unwrap();
+    context.stop_trace().unwrap();
+    draw_frame(&context);
+
+    let trace = Trace::read(File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(trace.commands.is_empty());
+}
+
+/// Output that sets a flag when it is dropped.
+struct DropFlag(Rc<Cell<bool>>);
+
+impl Write for DropFlag {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn output_is_closed_when_stopped() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let dropped = Rc::new(Cell::new(false));
+    context.start_trace(DropFlag(dropped.clone())).unwrap();
+    draw_frame(&context);
+    context.stop_trace().unwrap();
+    assert!(dropped.get());
+
+    // the context still works without the tracer
+    draw_frame(&context);
+}
+
+#[test]
+fn output_is_closed_when_context_is_dropped() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let dropped = Rc::new(Cell::new(false));
+    context.start_trace(DropFlag(dropped.clone())).unwrap();
+    draw_frame(&context);
+    drop(context);
+    assert!(dropped.get());
+}
+
+#[test]
+fn replay() {
+    let trace = capture();
+
+    let backend = RecordingBackend::new(DriverProfile::default(), (800, 600));
+    unsafe { trace::replay(&trace, &backend) }.unwrap();
+
+    let replayed = backend.calls().into_iter().map(|c| c.name).collect::<Vec<_>>();
+    let expected = trace.calls().map(|c| c.name.as_str())
+                        .filter(|name| !name.starts_with("glGet") || name.ends_with("Location"))
+                        .collect::<Vec<_>>();
+    assert_eq!(replayed, expected);
+
+    let draw = backend.calls().into_iter().find(|c| c.name == "glDrawArrays").unwrap();
+    assert_eq!(draw.args.len(), 3);
+}
+
+#[test]
+fn write_read_roundtrip() {
+    let trace = capture();
+
+    let mut data = Vec::new();
+    trace.write(&mut data).unwrap();
+    assert_eq!(Trace::read(&data[..]).unwrap(), trace);
+}
+
+#[test]
+fn invalid_data() {
+    match Trace::read(&b"not a trace at all"[..]) {
+        Err(TraceReadError::NotATrace) => (),
+        result => panic!("{:?}", result),
+    }
+
+    match Trace::read(&b"GLIUMTRC\x01\x00\x00\x00\x07"[..]) {
+        Err(TraceReadError::Corrupted) => (),
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn capture_real_context() {
+    let display = support::build_display();
+    let context = display.get_context();
+
+    let (vertex_buffer, index_buffer) = support::build_rectangle_vb_ib(&display);
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+                in vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                uniform vec4 color;
+                uniform sampler2D tex;
+                out vec4 f_color;
+                void main() {
+                    f_color = color + texture(tex, vec2(0.5, 0.5));
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+                attribute lowp vec2 position;
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+                uniform lowp vec4 color;
+                uniform lowp sampler2D tex;
+                void main() {
+                    gl_FragColor = color + texture2D(tex, vec2(0.5, 0.5));
+                }
+            ",
+        },
+    ).unwrap();
+
+    let path = std::env::temp_dir().join(format!("glium-trace-real-{}.bin", std::process::id()));
+    context.start_trace(File::create(&path).unwrap()).unwrap();
+
+    let texture = glium::texture::Texture2d::new(&display, vec![vec![(0u8, 0u8, 255u8, 0u8)]])
+                                                .unwrap();
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    output.as_surface().draw(&vertex_buffer, &index_buffer, &program,
+                             &uniform!{ color: [1.0f32, 0.0, 0.0, 1.0], tex: &texture },
+                             &Default::default()).unwrap();
+
+    context.stop_trace().unwrap();
+    let trace = Trace::read(File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // tracing must not change the result
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (255, 0, 255, 255));
+
+    let uniform = trace.calls().find(|c| c.name == "glUniform4fv").unwrap();
+    let color = [1.0f32, 0.0, 0.0, 1.0].iter().flat_map(|v| v.to_ne_bytes()).collect::<Vec<_>>();
+    assert_eq!(uniform.args[2], Value::Data(color));
+
+    assert!(trace.calls().any(|c| c.name.starts_with("glTex") && c.name.contains("Image2D") &&
+                                  c.args.contains(&Value::Data(vec![0, 0, 255, 0]))));
+}
@@ -0,0 +1,120 @@
+#[macro_use]
+extern crate glium;
+
+use glium::backend::Context;
+use glium::backend::recording::{DriverProfile, RecordingBackend};
+use glium::buffer::{Buffer, BufferMode, BufferType};
+use glium::debug::DebugCallbackBehavior;
+use glium::texture::{MipmapsOption, UncompressedFloatFormat};
+use glium::transfer::Transfer;
+
+mod support;
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn share_groups() {
+    let (first, _) = support::build_recording_context(DriverProfile::default());
+    let (second, _) = support::build_recording_context(DriverProfile::default());
+    assert!(first.share_group() != second.share_group());
+
+    let backend = RecordingBackend::new(DriverProfile::default(), (800, 600));
+    let shared = unsafe {
+        Context::new_shared(backend, true, DebugCallbackBehavior::Ignore, first.share_group())
+    }.unwrap();
+    assert_eq!(shared.share_group(), first.share_group());
+}
+
+#[test]
+fn buffer_transfer() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let buffer = Buffer::new(&context, &[1u32, 2, 3, 4][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+    let transfer = buffer.into_transfer();
+    assert_send(&transfer);
+    assert_eq!(context.resource_stats().buffers.count, 0);
+
+    let transfer = std::thread::spawn(move || transfer).join().unwrap();
+    assert!(transfer.is_ready(&context));
+
+    let buffer: Buffer<[u32]> = transfer.adopt(&context);
+    assert_eq!(buffer.read().unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(context.resource_stats().buffers.count, 1);
+}
+
+#[test]
+fn texture_transfer() {
+    let (context, _) = support::build_recording_context(DriverProfile::default());
+
+    let texture = glium::Texture2d::empty_with_format(&context, UncompressedFloatFormat::U8U8U8U8,
+                                                      MipmapsOption::NoMipmap, 16, 8).unwrap();
+    let stats = context.resource_stats();
+
+    let transfer: Transfer<glium::Texture2d> = texture.into_transfer();
+    assert_send(&transfer);
+    assert_eq!(context.resource_stats().textures.count, 0);
+
+    let texture = transfer.adopt(&context);
+    assert_eq!((texture.width(), texture.height()), (16, 8));
+    assert_eq!(context.resource_stats().textures, stats.textures);
+}
+
+#[test]
+#[should_panic(expected = "doesn't share its objects")]
+fn different_share_group() {
+    let (first, _) = support::build_recording_context(DriverProfile::default());
+    let (second, _) = support::build_recording_context(DriverProfile::default());
+
+    let texture = glium::Texture2d::empty(&first, 4, 4).unwrap();
+    let transfer = texture.into_transfer();
+    transfer.adopt(&second);
+}
+
+#[test]
+#[cfg(feature = "test_headless")]
+fn upload_from_worker_thread() {
+    use glium::backend::egl_surfaceless::EglSurfaceless;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    let display = EglSurfaceless::new((64, 64)).unwrap();
+    let builder = display.shared_builder();
+    let share_group = display.share_group();
+    assert_send(&builder);
+
+    let (sender, receiver) = mpsc::channel();
+    let worker = std::thread::spawn(move || {
+        let worker = builder.build().unwrap();
+        assert_eq!(worker.share_group(), share_group);
+
+        let data = vec![vec![(0u8, 255u8, 0u8, 255u8); 32]; 32];
+        let texture = glium::Texture2d::new(&worker, data).unwrap();
+        let buffer = Buffer::new(&worker, &[5u8, 6, 7][..], BufferType::ArrayBuffer,
+                                 BufferMode::Default).unwrap();
+        sender.send((texture.into_transfer(), buffer.into_transfer())).unwrap();
+
+        // the objects survive the destruction of the worker context
+        drop(worker);
+    });
+
+    let (texture, buffer) = receiver.recv().unwrap();
+    worker.join().unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !texture.is_ready(&display) || !buffer.is_ready(&display) {
+        assert!(Instant::now() < deadline);
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let texture = texture.adopt(&display);
+    let buffer = buffer.adopt(&display);
+
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(pixels, vec![vec![(0, 255, 0, 255); 32]; 32]);
+    assert_eq!(buffer.read().unwrap(), vec![5, 6, 7]);
+    let stats = display.resource_stats();
+    assert_eq!((stats.textures.count, stats.buffers.count), (1, 1));
+
+    display.assert_no_error(None);
+}